@@ -9,7 +9,7 @@
 
 //! Persistent metadata storage for the coordinator.
 
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::path::Path;
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{Duration, Instant};
@@ -34,9 +34,11 @@ use tracing::{info, trace};
 use mz_build_info::DUMMY_BUILD_INFO;
 use mz_dataflow_types::{
     sinks::{SinkConnector, SinkConnectorBuilder},
-    sources::{SourceConnector, Timeline},
+    sources::{SourceConnector, Timeline, WebhookSourceConnector},
+};
+use mz_expr::{
+    ExprHumanizer, GlobalId, Id, MirRelationExpr, MirScalarExpr, OptimizedMirRelationExpr,
 };
-use mz_expr::{ExprHumanizer, GlobalId, MirScalarExpr, OptimizedMirRelationExpr};
 use mz_repr::{RelationDesc, ScalarType};
 use mz_sql::ast::display::AstDisplay;
 use mz_sql::ast::{Expr, Raw};
@@ -244,9 +246,11 @@ impl CatalogState {
     pub fn uses_tables(&self, id: GlobalId) -> bool {
         match self.get_by_id(&id).item() {
             CatalogItem::Table(_) => true,
+            // Webhook sources are written by the coordinator, so reads must
+            // observe writes to them just like writes to tables.
+            CatalogItem::Source(source) => source.webhook().is_some(),
             item @ CatalogItem::View(_) => item.uses().iter().any(|id| self.uses_tables(*id)),
-            CatalogItem::Source(_)
-            | CatalogItem::Func(_)
+            CatalogItem::Func(_)
             | CatalogItem::Index(_)
             | CatalogItem::Sink(_)
             | CatalogItem::Type(_) => false,
@@ -368,6 +372,7 @@ impl CatalogState {
             CatalogItem::Source(source) => match &source.connector {
                 SourceConnector::External { connector, .. } => match &connector {
                     ExternalSourceConnector::PubNub(_) => Volatile,
+                    ExternalSourceConnector::Webhook(_) => Volatile,
                     ExternalSourceConnector::Kinesis(_) => Volatile,
                     _ => Unknown,
                 },
@@ -498,6 +503,18 @@ impl Source {
         // those would clash when trying to write to those collections.
         self.connector.requires_single_materialization() || self.persist_details.is_some()
     }
+
+    /// Returns the connector of a webhook source, whose data is written by the
+    /// coordinator rather than read by the dataflow workers.
+    pub fn webhook(&self) -> Option<&WebhookSourceConnector> {
+        match &self.connector {
+            SourceConnector::External {
+                connector: ExternalSourceConnector::Webhook(webhook),
+                ..
+            } => Some(webhook),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -505,7 +522,7 @@ pub struct Sink {
     pub create_sql: String,
     pub from: GlobalId,
     pub connector: SinkConnectorState,
-    pub envelope: SinkEnvelope,
+    pub envelope: Option<SinkEnvelope>,
     pub with_snapshot: bool,
     pub depends_on: Vec<GlobalId>,
 }
@@ -523,6 +540,9 @@ pub struct View {
     pub desc: RelationDesc,
     pub conn_id: Option<u32>,
     pub depends_on: Vec<GlobalId>,
+    /// Whether the unmatched rows of outer joins in the view were planned
+    /// with anti-joins.
+    pub outer_join_anti_joins: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -533,6 +553,8 @@ pub struct Index {
     pub conn_id: Option<u32>,
     pub depends_on: Vec<GlobalId>,
     pub enabled: bool,
+    pub spill: bool,
+    pub spill_threshold: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -958,6 +980,8 @@ impl Catalog {
                             conn_id: None,
                             depends_on: vec![log.id],
                             enabled: catalog.index_enabled_by_default(&log.index_id),
+                            spill: false,
+                            spill_threshold: None,
                         }),
                     );
                 }
@@ -1011,6 +1035,8 @@ impl Catalog {
                             conn_id: None,
                             depends_on: vec![table.id],
                             enabled: catalog.index_enabled_by_default(&table.index_id),
+                            spill: false,
+                            spill_threshold: None,
                         }),
                     );
                 }
@@ -1533,6 +1559,117 @@ impl Catalog {
         }
     }
 
+    /// Returns the [`Op`]s necessary to replace the definition of the source
+    /// `id` with `source`, whose description extends the existing source's
+    /// description with additional columns.
+    ///
+    /// Views are replanned from their definitions whenever the catalog is
+    /// opened, so the views that transitively depend on the source are
+    /// replanned here in the same way, against the refreshed source. That
+    /// fails if replanning would change the description of any view, e.g.
+    /// because the view selects `*` from the source, as anything that depends
+    /// on the view was planned against its current description.
+    ///
+    /// Temporary views do not survive restarts, and are instead rewritten to
+    /// project away the additional columns.
+    ///
+    /// # Panics
+    /// Panics if `id` is not the `id` of a [`CatalogItem::Source`].
+    pub fn refresh_source_schema_ops(
+        &self,
+        id: GlobalId,
+        source: Source,
+    ) -> Result<Vec<Op>, CoordError> {
+        let entry = self.get_by_id(&id);
+        let old_arity = match entry.item() {
+            CatalogItem::Source(old) => old.desc.arity(),
+            _ => unreachable!("cannot refresh the schema of non-sources"),
+        };
+        let typ = source.desc.typ().clone();
+
+        let mut refreshed = self.clone();
+        refreshed
+            .state
+            .by_id
+            .get_mut(&id)
+            .expect("source exists")
+            .item = CatalogItem::Source(source.clone());
+
+        let mut dependent_ids = BTreeSet::new();
+        let mut stack = entry.used_by().to_vec();
+        while let Some(dependent_id) = stack.pop() {
+            if dependent_ids.insert(dependent_id) {
+                stack.extend(self.get_by_id(&dependent_id).used_by());
+            }
+        }
+
+        let mut ops = vec![Op::UpdateItem {
+            id,
+            to_item: CatalogItem::Source(source),
+        }];
+        let mut changed_views = vec![];
+        for dependent_id in dependent_ids {
+            let dependent = self.get_by_id(&dependent_id);
+            let view = match dependent.item() {
+                CatalogItem::View(view) => view,
+                _ => continue,
+            };
+            let optimized_expr = if view.conn_id.is_some() {
+                if !dependent.uses().contains(&id) {
+                    continue;
+                }
+                let mut optimized_expr = view.optimized_expr.clone();
+                optimized_expr.as_inner_mut().visit_mut_post(&mut |expr| {
+                    if let MirRelationExpr::Get {
+                        id: Id::Global(get_id),
+                        ..
+                    } = expr
+                    {
+                        if *get_id == id {
+                            *expr = MirRelationExpr::global_get(id, typ.clone())
+                                .project((0..old_arity).collect());
+                        }
+                    }
+                });
+                optimized_expr
+            } else {
+                match refreshed.parse_item(
+                    dependent_id,
+                    view.create_sql.clone(),
+                    Some(&PlanContext {
+                        outer_join_anti_joins: view.outer_join_anti_joins,
+                        ..PlanContext::zero()
+                    }),
+                    None,
+                    None,
+                )? {
+                    CatalogItem::View(replanned) if replanned.desc == view.desc => {
+                        replanned.optimized_expr
+                    }
+                    CatalogItem::View(_) => {
+                        changed_views.push(dependent.name().to_string());
+                        continue;
+                    }
+                    _ => unreachable!("view replanned as non-view"),
+                }
+            };
+            ops.push(Op::UpdateItem {
+                id: dependent_id,
+                to_item: CatalogItem::View(View {
+                    optimized_expr,
+                    ..view.clone()
+                }),
+            });
+        }
+        if !changed_views.is_empty() {
+            return Err(CoordError::SchemaRefreshChangesViews {
+                source_name: entry.name().to_string(),
+                dependent_views: changed_views,
+            });
+        }
+        Ok(ops)
+    }
+
     /// Returns the [`Op`]s necessary to enable an index.
     ///
     /// # Panics
@@ -1620,6 +1757,48 @@ impl Catalog {
         Ok(())
     }
 
+    /// Loads the positions of all named `TAIL` subscriptions.
+    pub fn load_subscriptions(&self) -> Result<Vec<storage::Subscription>, Error> {
+        self.storage().load_subscriptions()
+    }
+
+    /// Records the position of a named `TAIL` subscription, replacing any
+    /// previously recorded position for a subscription of the same name.
+    pub fn upsert_subscription(
+        &mut self,
+        subscription: &storage::Subscription,
+    ) -> Result<(), Error> {
+        let mut storage = self.storage();
+        let tx = storage.transaction()?;
+        tx.upsert_subscription(subscription)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Removes the named `TAIL` subscription `name`.
+    pub fn remove_subscription(&mut self, name: &str) -> Result<(), Error> {
+        let mut storage = self.storage();
+        let tx = storage.transaction()?;
+        tx.remove_subscription(name)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Loads the latest timestamp that the sink `id` has recorded as
+    /// delivered, if any.
+    pub fn load_sink_progress(&self, id: GlobalId) -> Result<Option<Timestamp>, Error> {
+        self.storage().load_sink_progress(id)
+    }
+
+    /// Records `ts` as the latest timestamp that the sink `id` has delivered.
+    pub fn upsert_sink_progress(&mut self, id: GlobalId, ts: Timestamp) -> Result<(), Error> {
+        let mut storage = self.storage();
+        let tx = storage.transaction()?;
+        tx.upsert_sink_progress(id, ts)?;
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Read all available timestamp bindings for a source
     ///
     /// Returns its output sorted by (partition, timestamp)
@@ -1636,9 +1815,34 @@ impl Catalog {
         Ok(ret)
     }
 
+    /// Returns the earliest time from which a source can be replayed
+    /// faithfully using its restored timestamp bindings.
+    ///
+    /// This is the frontier to which the bindings were last compacted. Sources
+    /// whose bindings were compacted before that frontier was recorded fall
+    /// back to their earliest binding.
+    pub fn timestamp_bindings_since(&mut self, source_id: GlobalId) -> Result<Timestamp, Error> {
+        let mut storage = self.storage();
+        let tx = storage.transaction()?;
+
+        let since = match tx.load_timestamp_compaction(source_id)? {
+            Some(frontier) => frontier,
+            None => tx
+                .load_timestamp_bindings(source_id)?
+                .into_iter()
+                .map(|(_pid, ts, _offset)| ts)
+                .min()
+                .unwrap_or(0),
+        };
+        tx.commit()?;
+
+        Ok(since)
+    }
+
     /// Compact timestamp bindings for a source
     ///
-    /// In practice this ends up being "remove all bindings less than a given timestamp"
+    /// In practice this ends up being "remove all bindings less than a given timestamp",
+    /// save for the latest binding of each partition that is not beyond that timestamp,
     /// because all offsets are then assigned to the next available binding.
     pub fn compact_timestamp_bindings(
         &mut self,
@@ -1884,10 +2088,20 @@ impl Catalog {
                         CatalogItem::Source(_) => {
                             tx.delete_timestamp_bindings(id)?;
                         }
+                        // Pending sinks are dropped and recreated once their
+                        // connector is ready, which must not forget the
+                        // progress of a previous incarnation of the sink.
+                        CatalogItem::Sink(Sink {
+                            connector: SinkConnectorState::Ready(_),
+                            ..
+                        }) => {
+                            tx.remove_sink_progress(id)?;
+                        }
                         _ => {}
                     }
                     if !entry.item().is_temporary() {
                         tx.remove_item(id)?;
+                        tx.remove_subscriptions_on(id)?;
                     }
                     builtin_table_updates.extend(self.state.pack_item_update(id, -1));
                     vec![Action::DropItem(id)]
@@ -2159,7 +2373,11 @@ impl Catalog {
             },
             CatalogItem::View(view) => SerializedCatalogItem::V1 {
                 create_sql: view.create_sql.clone(),
-                eval_env: None,
+                eval_env: Some(SerializedPlanContext {
+                    logical_time: None,
+                    wall_time: None,
+                    outer_join_anti_joins: Some(view.outer_join_anti_joins),
+                }),
                 table_persist_name: None,
                 source_persist_details: None,
             },
@@ -2189,14 +2407,23 @@ impl Catalog {
     fn deserialize_item(&self, id: GlobalId, bytes: Vec<u8>) -> Result<CatalogItem, anyhow::Error> {
         let SerializedCatalogItem::V1 {
             create_sql,
-            eval_env: _,
+            eval_env,
             table_persist_name,
             source_persist_details,
         } = serde_json::from_slice(&bytes)?;
+        // Replan views the way they were planned when they were created, so
+        // that their plans don't change across restarts. Views serialized
+        // before the flag was recorded were planned without anti-joins.
+        let pcx = PlanContext {
+            outer_join_anti_joins: eval_env
+                .and_then(|cx| cx.outer_join_anti_joins)
+                .unwrap_or(false),
+            ..PlanContext::zero()
+        };
         self.parse_item(
             id,
             create_sql,
-            Some(&PlanContext::zero()),
+            Some(&pcx),
             table_persist_name,
             source_persist_details,
         )
@@ -2253,6 +2480,7 @@ impl Catalog {
                     desc,
                     conn_id: None,
                     depends_on: view.depends_on,
+                    outer_join_anti_joins: view.outer_join_anti_joins,
                 })
             }
             Plan::CreateIndex(CreateIndexPlan { index, .. }) => CatalogItem::Index(Index {
@@ -2262,6 +2490,8 @@ impl Catalog {
                 conn_id: None,
                 depends_on: index.depends_on,
                 enabled: self.index_enabled_by_default(&id),
+                spill: index.spill,
+                spill_threshold: index.spill_threshold,
             }),
             Plan::CreateSink(CreateSinkPlan {
                 sink,
@@ -2552,6 +2782,8 @@ impl From<EnvelopePersistDesc> for SerializedEnvelopePersistDetails {
 struct SerializedPlanContext {
     pub logical_time: Option<u64>,
     pub wall_time: Option<DateTime<Utc>>,
+    /// Absent for items serialized before the flag was recorded.
+    pub outer_join_anti_joins: Option<bool>,
 }
 
 impl From<SerializedPlanContext> for PlanContext {
@@ -2559,6 +2791,7 @@ impl From<SerializedPlanContext> for PlanContext {
         PlanContext {
             wall_time: cx.wall_time.unwrap_or_else(|| Utc.timestamp(0, 0)),
             qgm_optimizations: false,
+            outer_join_anti_joins: cx.outer_join_anti_joins.unwrap_or(false),
         }
     }
 }
@@ -2568,6 +2801,7 @@ impl From<PlanContext> for SerializedPlanContext {
         SerializedPlanContext {
             logical_time: None,
             wall_time: Some(cx.wall_time),
+            outer_join_anti_joins: Some(cx.outer_join_anti_joins),
         }
     }
 }
@@ -2919,10 +3153,14 @@ impl mz_sql::catalog::CatalogItem for CatalogEntry {
 mod tests {
     use tempfile::NamedTempFile;
 
+    use mz_expr::{GlobalId, PartitionId};
     use mz_ore::now::NOW_ZERO;
     use mz_sql::names::{DatabaseSpecifier, FullName, PartialName};
+    use mz_sql::plan::PlanContext;
 
-    use crate::catalog::{Catalog, Op, MZ_CATALOG_SCHEMA, PG_CATALOG_SCHEMA};
+    use crate::catalog::{
+        Catalog, CatalogItem, Op, SerializedCatalogItem, MZ_CATALOG_SCHEMA, PG_CATALOG_SCHEMA,
+    };
     use crate::session::Session;
 
     /// System sessions have an empty `search_path` so it's necessary to
@@ -3017,4 +3255,76 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_timestamp_bindings_since() -> Result<(), anyhow::Error> {
+        let catalog_file = NamedTempFile::new()?;
+        let mut catalog = Catalog::open_debug(catalog_file.path(), NOW_ZERO.clone()).await?;
+        let id = GlobalId::User(1);
+        let early = PartitionId::Kafka(0).to_string();
+        let late = PartitionId::Kafka(1).to_string();
+
+        assert_eq!(catalog.timestamp_bindings_since(id)?, 0);
+
+        catalog.insert_timestamp_bindings(vec![
+            (id, early.clone(), 1, 10),
+            (id, early.clone(), 5, 20),
+            (id, early.clone(), 10, 30),
+        ])?;
+        assert_eq!(catalog.timestamp_bindings_since(id)?, 1);
+
+        // The binding at 5 outlives the compaction, but the since is the
+        // compaction frontier.
+        catalog.compact_timestamp_bindings(id, 8)?;
+        assert_eq!(catalog.load_timestamp_bindings(id)?.len(), 2);
+        assert_eq!(catalog.timestamp_bindings_since(id)?, 8);
+
+        // A partition that is added after the compaction only has bindings
+        // beyond the compaction frontier.
+        catalog.insert_timestamp_bindings(vec![(id, late, 12, 5)])?;
+        assert_eq!(catalog.timestamp_bindings_since(id)?, 8);
+
+        // The frontier survives a restart.
+        drop(catalog);
+        let mut catalog = Catalog::open_debug(catalog_file.path(), NOW_ZERO.clone()).await?;
+        assert_eq!(catalog.timestamp_bindings_since(id)?, 8);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_view_outer_join_anti_joins_persisted() -> Result<(), anyhow::Error> {
+        let catalog_file = NamedTempFile::new()?;
+        let catalog = Catalog::open_debug(catalog_file.path(), NOW_ZERO.clone()).await?;
+        let id = GlobalId::User(1);
+        let create_sql = "CREATE VIEW materialize.public.v AS SELECT 1".to_string();
+        let outer_join_anti_joins = |item: &CatalogItem| match item {
+            CatalogItem::View(view) => view.outer_join_anti_joins,
+            _ => panic!("expected a view"),
+        };
+
+        for flag in [false, true] {
+            let pcx = PlanContext {
+                outer_join_anti_joins: flag,
+                ..PlanContext::zero()
+            };
+            let item = catalog.parse_item(id, create_sql.clone(), Some(&pcx), None, None)?;
+            assert_eq!(outer_join_anti_joins(&item), flag);
+            let item = catalog.deserialize_item(id, catalog.serialize_item(&item))?;
+            assert_eq!(outer_join_anti_joins(&item), flag);
+        }
+
+        // Views serialized before the flag was recorded are replanned without
+        // anti-joins.
+        let bytes = serde_json::to_vec(&SerializedCatalogItem::V1 {
+            create_sql,
+            eval_env: None,
+            table_persist_name: None,
+            source_persist_details: None,
+        })?;
+        let item = catalog.deserialize_item(id, bytes)?;
+        assert!(!outer_join_anti_joins(&item));
+
+        Ok(())
+    }
 }
@@ -643,7 +643,23 @@ pub const MZ_KAFKA_SOURCE_STATISTICS: BuiltinLog = BuiltinLog {
     index_id: GlobalId::System(3041),
 };
 
-// Next id BuiltinLog: 3042
+pub const MZ_KAFKA_SINK_BACKLOG: BuiltinLog = BuiltinLog {
+    name: "mz_kafka_sink_backlog",
+    schema: MZ_CATALOG_SCHEMA,
+    variant: LogVariant::Materialized(MaterializedLog::KafkaSinkBacklog),
+    id: GlobalId::System(3042),
+    index_id: GlobalId::System(3043),
+};
+
+pub const MZ_DATAFLOW_OPERATOR_PLAN_NODES: BuiltinLog = BuiltinLog {
+    name: "mz_dataflow_operator_plan_nodes",
+    schema: MZ_CATALOG_SCHEMA,
+    variant: LogVariant::Materialized(MaterializedLog::PlanNodeOperators),
+    id: GlobalId::System(3044),
+    index_id: GlobalId::System(3045),
+};
+
+// Next id BuiltinLog: 3046
 
 lazy_static! {
     pub static ref MZ_VIEW_KEYS: BuiltinTable = BuiltinTable {
@@ -951,6 +967,19 @@ lazy_static! {
         // for this to be persisted.
         persistent: true,
     };
+    pub static ref MZ_INDEX_RECOMMENDATIONS: BuiltinTable = BuiltinTable {
+        name: "mz_index_recommendations",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+                .with_column("on_id", ScalarType::String.nullable(false))
+                .with_column("key", ScalarType::String.nullable(false))
+                .with_column("fast_path", ScalarType::Bool.nullable(false))
+                .with_column("peeks", ScalarType::Int64.nullable(false))
+                .with_key(vec![0, 1, 2]),
+        id: GlobalId::System(4049),
+        index_id: GlobalId::System(4050),
+        persistent: false,
+    };
 }
 
 pub const MZ_RELATIONS: BuiltinView = BuiltinView {
@@ -1701,7 +1730,63 @@ JOIN mz_catalog.mz_databases d on s.database_id = d.id",
     needs_logs: false,
 };
 
-// Next id BuiltinView: 5041
+pub const MZ_DATAFLOW_PLAN_NODE_STATISTICS: BuiltinView = BuiltinView {
+    name: "mz_dataflow_plan_node_statistics",
+    schema: MZ_CATALOG_SCHEMA,
+    sql: "CREATE VIEW mz_dataflow_plan_node_statistics AS
+WITH records_cte AS (
+    SELECT
+        operator,
+        worker,
+        pg_catalog.count(*) AS records
+    FROM
+        mz_catalog.mz_arrangement_records_internal
+    GROUP BY
+        operator, worker
+),
+batches_cte AS (
+    SELECT
+        operator,
+        worker,
+        pg_catalog.count(*) AS batches
+    FROM
+        mz_catalog.mz_arrangement_batches_internal
+    GROUP BY
+        operator, worker
+),
+elapsed_cte AS (
+    SELECT
+        id,
+        worker,
+        pg_catalog.count(*) AS elapsed_ns
+    FROM
+        mz_catalog.mz_scheduling_elapsed_internal
+    GROUP BY
+        id, worker
+)
+SELECT
+    nodes.export_id,
+    nodes.node,
+    pg_catalog.count(DISTINCT nodes.id) AS operators,
+    pg_catalog.count(DISTINCT batches_cte.operator) AS arrangements,
+    coalesce(pg_catalog.sum(records_cte.records), 0)::pg_catalog.int8 AS records,
+    coalesce(pg_catalog.sum(batches_cte.batches), 0)::pg_catalog.int8 AS batches,
+    coalesce(pg_catalog.sum(elapsed_cte.elapsed_ns), 0)::pg_catalog.int8 AS elapsed_ns
+FROM
+    mz_catalog.mz_dataflow_operator_plan_nodes AS nodes
+    LEFT JOIN records_cte
+        ON records_cte.operator = nodes.id AND records_cte.worker = nodes.worker
+    LEFT JOIN batches_cte
+        ON batches_cte.operator = nodes.id AND batches_cte.worker = nodes.worker
+    LEFT JOIN elapsed_cte
+        ON elapsed_cte.id = nodes.id AND elapsed_cte.worker = nodes.worker
+GROUP BY
+    nodes.export_id, nodes.node",
+    id: GlobalId::System(5041),
+    needs_logs: true,
+};
+
+// Next id BuiltinView: 5042
 
 pub const MZ_SYSTEM: BuiltinRole = BuiltinRole {
     name: "mz_system",
@@ -1771,7 +1856,9 @@ lazy_static! {
             Builtin::Log(&MZ_DATAFLOW_CHANNELS),
             Builtin::Log(&MZ_DATAFLOW_OPERATORS),
             Builtin::Log(&MZ_DATAFLOW_OPERATORS_ADDRESSES),
+            Builtin::Log(&MZ_DATAFLOW_OPERATOR_PLAN_NODES),
             Builtin::Log(&MZ_DATAFLOW_OPERATOR_REACHABILITY_INTERNAL),
+            Builtin::Log(&MZ_KAFKA_SINK_BACKLOG),
             Builtin::Log(&MZ_KAFKA_SOURCE_STATISTICS),
             Builtin::Log(&MZ_MATERIALIZATIONS),
             Builtin::Log(&MZ_MATERIALIZATION_DEPENDENCIES),
@@ -1808,12 +1895,14 @@ lazy_static! {
             Builtin::Table(&MZ_PROMETHEUS_READINGS),
             Builtin::Table(&MZ_PROMETHEUS_HISTOGRAMS),
             Builtin::Table(&MZ_PROMETHEUS_METRICS),
+            Builtin::Table(&MZ_INDEX_RECOMMENDATIONS),
             Builtin::View(&MZ_CATALOG_NAMES),
             Builtin::View(&MZ_ARRANGEMENT_SHARING),
             Builtin::View(&MZ_ARRANGEMENT_SIZES),
             Builtin::View(&MZ_DATAFLOW_NAMES),
             Builtin::View(&MZ_DATAFLOW_OPERATOR_DATAFLOWS),
             Builtin::View(&MZ_DATAFLOW_OPERATOR_REACHABILITY),
+            Builtin::View(&MZ_DATAFLOW_PLAN_NODE_STATISTICS),
             Builtin::View(&MZ_MATERIALIZATION_FRONTIERS),
             Builtin::View(&MZ_MESSAGE_COUNTS),
             Builtin::View(&MZ_OBJECTS),
@@ -156,6 +156,7 @@ fn ast_rewrite_ccsr_with_options_to_compiled_0_20_0(
                 }
                 CreateSourceFormat::Bare(Format::Protobuf(ProtobufSchema::Csr {
                     csr_connector,
+                    ..
                 })) => Some(&mut csr_connector.with_options),
                 _ => None,
             };
@@ -244,6 +245,7 @@ fn ast_rewrite_kafka_protobuf_source_text_to_compiled_0_9_13(
             CreateSourceFormat::Bare(value) => {
                 if let Format::Protobuf(ProtobufSchema::Csr {
                     csr_connector: CsrConnectorProto { seed: Some(s), .. },
+                    ..
                 }) = value
                 {
                     do_upgrade(s)?;
@@ -252,12 +254,14 @@ fn ast_rewrite_kafka_protobuf_source_text_to_compiled_0_9_13(
             CreateSourceFormat::KeyValue { key, value } => {
                 if let Format::Protobuf(ProtobufSchema::Csr {
                     csr_connector: CsrConnectorProto { seed: Some(s), .. },
+                    ..
                 }) = key
                 {
                     do_upgrade(s)?;
                 }
                 if let Format::Protobuf(ProtobufSchema::Csr {
                     csr_connector: CsrConnectorProto { seed: Some(s), .. },
+                    ..
                 }) = value
                 {
                     do_upgrade(s)?;
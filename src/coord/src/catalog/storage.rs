@@ -132,6 +132,31 @@ const MIGRATIONS: &[&str] = &[
     //
     // Introduced in v0.12.0.
     "CREATE INDEX timestamps_sid_timestamp ON timestamps (sid, timestamp)",
+    // Adds a table to track the position of named TAIL subscriptions.
+    //
+    // Introduced in v0.21.0.
+    "CREATE TABLE subscriptions (
+        name             text PRIMARY KEY,
+        gid              blob NOT NULL,
+        as_of            integer NOT NULL,
+        strict           integer NOT NULL,
+        max_retention_ms integer NOT NULL
+    );",
+    // Adds a table to track the latest timestamp delivered by HTTP sinks.
+    //
+    // Introduced in v0.21.0.
+    "CREATE TABLE sink_progress (
+        gid blob PRIMARY KEY,
+        ts  integer NOT NULL
+    );",
+    // Adds a table to track the frontier to which each source's timestamp
+    // bindings have been compacted.
+    //
+    // Introduced in v0.21.0.
+    "CREATE TABLE timestamp_compaction (
+        sid      blob PRIMARY KEY,
+        frontier integer NOT NULL
+    );",
     // Add new migrations here.
     //
     // Migrations should be preceded with a comment of the following form:
@@ -359,6 +384,30 @@ impl Connection {
             .collect()
     }
 
+    pub fn load_subscriptions(&self) -> Result<Vec<Subscription>, Error> {
+        self.inner
+            .prepare("SELECT name, gid, as_of, strict, max_retention_ms FROM subscriptions")?
+            .query_and_then(params![], |row| -> Result<_, Error> {
+                let object_id: SqlVal<GlobalId> = row.get(1)?;
+                Ok(Subscription {
+                    name: row.get(0)?,
+                    object_id: object_id.0,
+                    as_of: row.get(2)?,
+                    strict: row.get(3)?,
+                    max_retention_ms: row.get(4)?,
+                })
+            })?
+            .collect()
+    }
+
+    pub fn load_sink_progress(&self, id: GlobalId) -> Result<Option<Timestamp>, Error> {
+        Ok(self
+            .inner
+            .prepare("SELECT ts FROM sink_progress WHERE gid = ?")?
+            .query_row(params![SqlVal(&id)], |row| row.get(0))
+            .optional()?)
+    }
+
     pub fn allocate_id(&mut self) -> Result<GlobalId, Error> {
         let tx = self.inner.transaction()?;
         // SQLite doesn't support u64s, so we constrain ourselves to the more
@@ -389,6 +438,21 @@ impl Connection {
     }
 }
 
+/// The durable position of a named `TAIL` subscription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subscription {
+    /// The name of the subscription.
+    pub name: String,
+    /// The ID of the tailed object.
+    pub object_id: GlobalId,
+    /// The timestamp from which a resumed `TAIL` produces updates.
+    pub as_of: Timestamp,
+    /// Whether updates at `as_of` itself have already been acknowledged.
+    pub strict: bool,
+    /// How far behind the present the subscription may hold back compaction.
+    pub max_retention_ms: Timestamp,
+}
+
 pub struct Transaction<'a> {
     inner: rusqlite::Transaction<'a>,
 }
@@ -588,6 +652,10 @@ impl Transaction<'_> {
     }
 
     pub fn delete_timestamp_bindings(&self, source_id: GlobalId) -> Result<(), Error> {
+        self.inner
+            .prepare_cached("DELETE FROM timestamp_compaction WHERE sid = ?")?
+            .execute(params![SqlVal(&source_id)])?;
+
         let result = self
             .inner
             .prepare_cached("DELETE FROM timestamps WHERE sid = ?")?
@@ -599,38 +667,100 @@ impl Transaction<'_> {
         }
     }
 
+    pub fn load_timestamp_compaction(
+        &self,
+        source_id: GlobalId,
+    ) -> Result<Option<Timestamp>, Error> {
+        Ok(self
+            .inner
+            .prepare_cached("SELECT frontier FROM timestamp_compaction WHERE sid = ?")?
+            .query_row(params![SqlVal(&source_id)], |row| row.get(0))
+            .optional()?)
+    }
+
     pub fn compact_timestamp_bindings(
         &self,
         source_id: GlobalId,
         frontier: Timestamp,
     ) -> Result<(), Error> {
-        // we need to keep one binding that is not beyond the frontier, so that
-        // on restart we don't emit timestamps that are beyond the previously
-        // written consistency frontier. Otherwise, data with those timestamps
-        // would get written again.
-        let latest_not_beyond_compaction: Option<Timestamp> = self
+        // we need to keep one binding per partition that is not beyond the
+        // frontier, so that on restart we don't emit timestamps that are beyond
+        // the previously written consistency frontier. Otherwise, data with
+        // those timestamps would get written again. This has to be determined
+        // per partition: a partition that has not received data in a while
+        // would otherwise lose all of its bindings, and its offsets would be
+        // assigned new timestamps on restart.
+        //
+        // The remaining bindings can be older than the frontier, so the
+        // frontier itself is recorded to restore the source's since.
+        self.inner
+            .prepare_cached(
+                "INSERT OR REPLACE INTO timestamp_compaction (sid, frontier) VALUES (?, ?)",
+            )?
+            .execute(params![SqlVal(&source_id), frontier])?;
+
+        let result = self
             .inner
             .prepare_cached(
-                "SELECT max(timestamp) FROM timestamps WHERE sid = ? AND timestamp <= ?",
+                "DELETE FROM timestamps WHERE sid = ? AND timestamp < (
+                    SELECT max(latest.timestamp) FROM timestamps AS latest
+                    WHERE latest.sid = timestamps.sid
+                    AND latest.pid = timestamps.pid
+                    AND latest.timestamp <= ?
+                )",
             )?
-            .query_row(params![SqlVal(&source_id), frontier], |row| row.get(0))?;
-
-        if let Some(latest_not_beyond_compaction) = latest_not_beyond_compaction {
-            let result = match self
-                .inner
-                .prepare_cached("DELETE FROM timestamps WHERE sid = ? AND timestamp < ?")?
-                .execute(params![SqlVal(&source_id), latest_not_beyond_compaction])
-            {
-                Ok(_) => Ok(()),
-                Err(err) => Err(err.into()),
-            };
-
-            result
-        } else {
-            Ok(())
+            .execute(params![SqlVal(&source_id), frontier]);
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err.into()),
         }
     }
 
+    pub fn upsert_subscription(&self, subscription: &Subscription) -> Result<(), Error> {
+        self.inner
+            .prepare_cached(
+                "INSERT OR REPLACE INTO subscriptions (name, gid, as_of, strict, max_retention_ms)
+                VALUES (?, ?, ?, ?, ?)",
+            )?
+            .execute(params![
+                subscription.name,
+                SqlVal(&subscription.object_id),
+                subscription.as_of,
+                subscription.strict,
+                subscription.max_retention_ms,
+            ])?;
+        Ok(())
+    }
+
+    pub fn remove_subscription(&self, name: &str) -> Result<(), Error> {
+        self.inner
+            .prepare_cached("DELETE FROM subscriptions WHERE name = ?")?
+            .execute(params![name])?;
+        Ok(())
+    }
+
+    pub fn remove_subscriptions_on(&self, object_id: GlobalId) -> Result<(), Error> {
+        self.inner
+            .prepare_cached("DELETE FROM subscriptions WHERE gid = ?")?
+            .execute(params![SqlVal(&object_id)])?;
+        Ok(())
+    }
+
+    pub fn upsert_sink_progress(&self, id: GlobalId, ts: Timestamp) -> Result<(), Error> {
+        self.inner
+            .prepare_cached("INSERT OR REPLACE INTO sink_progress (gid, ts) VALUES (?, ?)")?
+            .execute(params![SqlVal(&id), ts])?;
+        Ok(())
+    }
+
+    pub fn remove_sink_progress(&self, id: GlobalId) -> Result<(), Error> {
+        self.inner
+            .prepare_cached("DELETE FROM sink_progress WHERE gid = ?")?
+            .execute(params![SqlVal(&id)])?;
+        Ok(())
+    }
+
     pub fn remove_database(&self, name: &str) -> Result<(), Error> {
         let n = self
             .inner
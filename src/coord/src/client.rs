@@ -7,6 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashMap;
 use std::future::Future;
 use std::sync::Arc;
 use std::time::Instant;
@@ -20,6 +21,7 @@ use mz_ore::collections::CollectionExt;
 use mz_ore::thread::JoinOnDropHandle;
 use mz_repr::{Datum, Row};
 use mz_sql::ast::{Raw, Statement};
+use mz_sql::names::PartialName;
 
 use crate::command::{
     Canceled, Command, ExecuteResponse, Response, SimpleExecuteResponse, SimpleResult,
@@ -378,6 +380,27 @@ impl SessionClient {
         .await
     }
 
+    /// Appends a request received by the HTTP server to the named webhook
+    /// source.
+    ///
+    /// `headers` must be keyed by lowercase header name. The write is only
+    /// durable once the enclosing transaction commits.
+    pub async fn append_webhook(
+        &mut self,
+        name: PartialName,
+        headers: HashMap<String, String>,
+        body: Vec<u8>,
+    ) -> Result<ExecuteResponse, CoordError> {
+        self.send(|tx, session| Command::AppendWebhook {
+            name,
+            headers,
+            body,
+            session,
+            tx,
+        })
+        .await
+    }
+
     /// Executes SQL statements using a simple protocol that does not involve
     /// portals.
     ///
@@ -7,6 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashMap;
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
@@ -21,6 +22,7 @@ use mz_expr::GlobalId;
 use mz_ore::str::StrExt;
 use mz_repr::Row;
 use mz_sql::ast::{FetchDirection, ObjectType, Raw, Statement};
+use mz_sql::names::PartialName;
 use mz_sql::plan::ExecuteTimeout;
 use tokio::sync::watch;
 
@@ -93,6 +95,14 @@ pub enum Command {
         tx: oneshot::Sender<Response<ExecuteResponse>>,
     },
 
+    AppendWebhook {
+        name: PartialName,
+        headers: HashMap<String, String>,
+        body: Vec<u8>,
+        session: Session,
+        tx: oneshot::Sender<Response<ExecuteResponse>>,
+    },
+
     Terminate {
         session: Session,
     },
@@ -231,6 +241,8 @@ pub enum ExecuteResponse {
     DroppedSchema,
     /// The requested source was dropped.
     DroppedSource,
+    /// The requested subscription was dropped.
+    DroppedSubscription,
     /// The requested table was dropped.
     DroppedTable,
     /// The requested view was dropped.
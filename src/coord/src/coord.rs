@@ -84,8 +84,10 @@
 //!
 
 use std::cell::RefCell;
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::future::Future;
+use std::iter;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::thread;
@@ -99,6 +101,7 @@ use futures::future::{self, FutureExt, TryFutureExt};
 use futures::stream::StreamExt;
 use itertools::Itertools;
 use mz_repr::adt::interval::Interval;
+use mz_repr::adt::jsonb::Jsonb;
 use rand::Rng;
 use timely::order::PartialOrder;
 use timely::progress::frontier::MutableAntichain;
@@ -112,45 +115,53 @@ use mz_dataflow_types::client::{ComputeClient, StorageClient, DEFAULT_COMPUTE_IN
 use mz_dataflow_types::client::{ComputeResponse, TimestampBindingFeedback};
 use mz_dataflow_types::client::{Response as DataflowResponse, StorageResponse};
 use mz_dataflow_types::logging::LoggingConfig as DataflowLoggingConfig;
-use mz_dataflow_types::sinks::{SinkAsOf, SinkConnector, SinkDesc, TailSinkConnector};
+use mz_dataflow_types::plan::analyze::{PlanAnalysis, PlanNodeStatistics};
+use mz_dataflow_types::sinks::{
+    SinkAsOf, SinkConnector, SinkConnectorBuilder, SinkDesc, TailEnvelope, TailSinkConnector,
+};
+use mz_dataflow_types::sources::encoding::{DataEncoding, SourceDataEncoding};
 use mz_dataflow_types::sources::{
-    ExternalSourceConnector, PostgresSourceConnector, SourceConnector, Timeline,
+    ExternalSourceConnector, PostgresSourceConnector, SourceConnector, Timeline, WebhookValidation,
 };
 use mz_dataflow_types::{DataflowDesc, DataflowDescription, IndexDesc, PeekResponse, Update};
 use mz_expr::{
-    permutation_for_arrangement, GlobalId, MirRelationExpr, MirScalarExpr, NullaryFunc,
+    permutation_for_arrangement, BinaryFunc, GlobalId, MirRelationExpr, MirScalarExpr, NullaryFunc,
     OptimizedMirRelationExpr, RowSetFinishing,
 };
 use mz_ore::metrics::MetricsRegistry;
 use mz_ore::now::{to_datetime, NowFn};
 use mz_ore::retry::Retry;
 use mz_ore::soft_assert_eq;
+use mz_ore::str::StrExt;
 use mz_ore::task;
 use mz_ore::thread::JoinHandleExt;
 use mz_repr::adt::numeric;
-use mz_repr::{Datum, Diff, RelationDesc, Row, RowArena, Timestamp};
+use mz_repr::{Datum, Diff, RelationDesc, Row, RowArena, ScalarType, Timestamp};
 use mz_sql::ast::display::AstDisplay;
 use mz_sql::ast::{
     ConnectorType, CreateIndexStatement, CreateSinkStatement, CreateSourceStatement, ExplainStage,
     FetchStatement, Ident, InsertSource, ObjectType, Query, Raw, SetExpr, Statement,
 };
 use mz_sql::catalog::{CatalogError, SessionCatalog as _};
-use mz_sql::names::{DatabaseSpecifier, FullName};
+use mz_sql::names::{DatabaseSpecifier, FullName, PartialName};
 use mz_sql::plan::{
     AlterIndexEnablePlan, AlterIndexResetOptionsPlan, AlterIndexSetOptionsPlan,
-    AlterItemRenamePlan, CreateDatabasePlan, CreateIndexPlan, CreateRolePlan, CreateSchemaPlan,
-    CreateSinkPlan, CreateSourcePlan, CreateTablePlan, CreateTypePlan, CreateViewPlan,
-    CreateViewsPlan, DropDatabasePlan, DropItemsPlan, DropRolesPlan, DropSchemaPlan, ExecutePlan,
+    AlterItemRenamePlan, AlterSourceRefreshSchemaPlan, CreateDatabasePlan, CreateIndexPlan,
+    CreateRolePlan, CreateSchemaPlan, CreateSinkPlan, CreateSourcePlan, CreateTablePlan,
+    CreateTypePlan, CreateViewPlan, CreateViewsPlan, DropDatabasePlan, DropItemsPlan,
+    DropRolesPlan, DropSchemaPlan, DropSubscriptionPlan, ExecutePlan, ExplainAnalyzePlan,
     ExplainPlan, FetchPlan, HirRelationExpr, IndexOption, IndexOptionName, InsertPlan,
     MutationKind, Params, PeekPlan, PeekWhen, Plan, ReadThenWritePlan, SendDiffsPlan,
-    SetVariablePlan, ShowVariablePlan, TailFrom, TailPlan,
+    SetVariablePlan, ShowVariablePlan, TailFrom, TailPlan, TailSubscription,
 };
 use mz_sql::plan::{OptimizerConfig, StatementDesc, View};
 use mz_transform::Optimizer;
 
 use self::arrangement_state::{ArrangementFrontiers, Frontiers, SinkWrites};
 use self::prometheus::Scraper;
-use crate::catalog::builtin::{BUILTINS, MZ_VIEW_FOREIGN_KEYS, MZ_VIEW_KEYS};
+use crate::catalog::builtin::{
+    BUILTINS, MZ_DATAFLOW_PLAN_NODE_STATISTICS, MZ_VIEW_FOREIGN_KEYS, MZ_VIEW_KEYS,
+};
 use crate::catalog::{
     self, storage, BuiltinTableUpdate, Catalog, CatalogItem, CatalogState, SinkConnectorState,
 };
@@ -159,7 +170,9 @@ use crate::command::{
     Canceled, Command, ExecuteResponse, Response, StartupMessage, StartupResponse,
 };
 use crate::coord::antichain::AntichainToken;
-use crate::coord::dataflow_builder::DataflowBuilder;
+use crate::coord::dataflow_builder::{DataflowBuilder, SharedSubplans};
+use crate::coord::index_advisor::IndexAdvisorState;
+use crate::coord::statistics::CollectionStatisticsState;
 use crate::error::CoordError;
 use crate::persistcfg::PersisterWithConfig;
 use crate::session::{
@@ -173,7 +186,9 @@ use crate::util::ClientTransmitter;
 mod antichain;
 mod arrangement_state;
 mod dataflow_builder;
+mod index_advisor;
 mod prometheus;
+mod statistics;
 
 #[derive(Debug)]
 pub enum Message {
@@ -260,6 +275,7 @@ pub struct Config {
     pub metrics_registry: MetricsRegistry,
     pub persister: PersisterWithConfig,
     pub now: NowFn,
+    pub cost_based_join_ordering: bool,
 }
 
 /// Glues the external world to the Timely workers.
@@ -280,6 +296,15 @@ pub struct Coordinator {
     indexes: ArrangementFrontiers<Timestamp>,
     /// Map of frontier information for sources
     sources: ArrangementFrontiers<Timestamp>,
+    /// Size estimates for collections, used to order joins.
+    statistics: CollectionStatisticsState,
+    /// The indexes that peeks would have used, had they existed.
+    index_advisor: IndexAdvisorState,
+    /// Joins maintained by internal indexes on behalf of index dataflows.
+    shared_subplans: SharedSubplans,
+    /// The plans of running dataflows, by the ids of the indexes and sinks
+    /// they export.
+    dataflow_plans: HashMap<GlobalId, DataflowDescription<mz_dataflow_types::Plan>>,
     /// Delta from leading edge of an arrangement from which we allow compaction.
     logical_compaction_window_ms: Option<Timestamp>,
     /// Whether base sources are enabled.
@@ -324,6 +349,11 @@ pub struct Coordinator {
     txn_reads: HashMap<u32, TxnReads>,
     /// Tracks write frontiers for active exactly-once sinks.
     sink_writes: HashMap<GlobalId, SinkWrites<Timestamp>>,
+    /// The latest timestamp recorded in the catalog as delivered, for sinks
+    /// that cannot record their progress in the systems they write to.
+    sink_progress: HashMap<GlobalId, Timestamp>,
+    /// Named `TAIL` subscriptions, by name.
+    subscriptions: HashMap<String, Subscription>,
 
     /// A map from pending peeks to the queue into which responses are sent, and
     /// the IDs of workers who have responded.
@@ -363,6 +393,19 @@ struct TxnReads {
     _handles: Vec<AntichainToken<Timestamp>>,
 }
 
+/// A named `TAIL` subscription.
+struct Subscription {
+    /// The durable position of the subscription.
+    state: storage::Subscription,
+    /// Holds back compaction of the arrangements the subscription reads from,
+    /// by the ID of the arrangement.
+    handles: HashMap<GlobalId, AntichainToken<Timestamp>>,
+    /// Whether the history the subscription would resume from was lost in a
+    /// restart, because the tailed relation does not only read from persisted
+    /// tables.
+    lost: bool,
+}
+
 /// Enforces critical section invariants for functions that perform writes to
 /// tables, e.g. `INSERT`, `UPDATE`.
 ///
@@ -528,12 +571,27 @@ impl Coordinator {
                 CatalogItem::Source(source) => {
                     // Inform the timestamper about this source.
                     self.update_timestamper(entry.id(), true).await;
-                    let since_ts = self
+                    if let Some(webhook) = source.webhook() {
+                        self.persister
+                            .add_webhook_source(entry.id(), webhook)
+                            .map_err(CoordError::Persistence)?;
+                    }
+                    let persist_since_ts = self
                         .persister
                         .load_source_persist_desc(&source)
                         .map_err(CoordError::Persistence)?
                         .map(|p| p.since_ts)
+                        .or_else(|| {
+                            self.persister
+                                .table_details
+                                .get(&entry.id())
+                                .map(|td| td.since_ts)
+                        })
                         .unwrap_or(0);
+                    // Restored timestamp bindings have been compacted, so the
+                    // source cannot be replayed faithfully from earlier times.
+                    let bindings_since_ts = self.catalog.timestamp_bindings_since(entry.id())?;
+                    let since_ts = std::cmp::max(persist_since_ts, bindings_since_ts);
 
                     let frontiers = self.new_source_frontiers(
                         entry.id(),
@@ -703,6 +761,24 @@ impl Coordinator {
             }
         }
 
+        // Named subscriptions hold back compaction of the arrangements they
+        // read from. Only persisted tables retain their history across
+        // restarts, so subscriptions on anything else cannot resume.
+        for state in self.catalog.load_subscriptions()? {
+            let name = state.name.clone();
+            let as_of = state.as_of;
+            let lost = self.persisted_inputs(state.object_id).is_none();
+            self.subscriptions.insert(
+                name.clone(),
+                Subscription {
+                    state,
+                    handles: HashMap::new(),
+                    lost,
+                },
+            );
+            self.update_subscription_handles(&name, as_of);
+        }
+
         Ok(())
     }
 
@@ -800,18 +876,21 @@ impl Coordinator {
         // Close the stream up to the newly opened timestamp.
         let advance_to = self.last_open_local_ts;
 
-        // Ensure that the persister is aware of exactly the set of tables for
-        // which persistence is enabled.
+        // Ensure that the persister is aware of exactly the set of tables (and
+        // webhook sources) for which persistence is enabled.
         soft_assert_eq!(
             self.catalog
                 .entries()
-                .filter(|entry| matches!(
-                    entry.item(),
+                .filter(|entry| match entry.item() {
                     CatalogItem::Table(catalog::Table {
                         persist_name: Some(_),
                         ..
-                    })
-                ))
+                    }) => true,
+                    CatalogItem::Source(source) => source
+                        .webhook()
+                        .map_or(false, |webhook| webhook.persisted_name.is_some()),
+                    _ => false,
+                })
                 .map(|entry| entry.id())
                 .collect::<Vec<_>>(),
             self.persister
@@ -881,9 +960,15 @@ impl Coordinator {
                 }
                 self.maintenance().await;
             }
+            DataflowResponse::Compute(ComputeResponse::ArrangementStatistics(updates)) => {
+                self.statistics
+                    .update_arrangements(updates, self.catalog.enabled_indexes());
+            }
             DataflowResponse::Storage(StorageResponse::TimestampBindings(
                 TimestampBindingFeedback { bindings, changes },
             )) => {
+                self.statistics
+                    .update_source_offsets(&bindings, self.catalog.enabled_indexes());
                 self.catalog
                     .insert_timestamp_bindings(
                         bindings
@@ -1210,6 +1295,7 @@ impl Coordinator {
                                 // Statements below must by run singly (in Started).
                                 Statement::AlterIndex(_)
                                 | Statement::AlterObjectRename(_)
+                                | Statement::AlterSource(_)
                                 | Statement::CreateDatabase(_)
                                 | Statement::CreateIndex(_)
                                 | Statement::CreateRole(_)
@@ -1223,6 +1309,7 @@ impl Coordinator {
                                 | Statement::Delete(_)
                                 | Statement::DropDatabase(_)
                                 | Statement::DropObjects(_)
+                                | Statement::DropSubscription(_)
                                 | Statement::Insert(_)
                                 | Statement::Update(_) => {
                                     let _ = tx.send(Response {
@@ -1321,6 +1408,17 @@ impl Coordinator {
                 let _ = tx.send(Response { result, session });
             }
 
+            Command::AppendWebhook {
+                name,
+                headers,
+                body,
+                mut session,
+                tx,
+            } => {
+                let result = self.sequence_append_webhook(&mut session, name, headers, body);
+                let _ = tx.send(Response { result, session });
+            }
+
             Command::Terminate { mut session } => {
                 self.handle_terminate(&mut session).await;
             }
@@ -1452,6 +1550,19 @@ impl Coordinator {
 
             if !changes.is_empty() {
                 sink_state.advance_source_handles();
+
+                // The write frontier of a sink is the latest timestamp that
+                // it has written out.
+                if let Some(recorded) = self.sink_progress.get_mut(name) {
+                    if let Some(ts) = sink_state.frontier.frontier().first() {
+                        if ts > recorded {
+                            self.catalog
+                                .upsert_sink_progress(*name, *ts)
+                                .expect("recording sink progress cannot fail");
+                            *recorded = *ts;
+                        }
+                    }
+                }
             }
         }
     }
@@ -1539,6 +1650,8 @@ impl Coordinator {
     /// Primarily, this involves sequencing compaction commands, which should be
     /// issued whenever available.
     async fn maintenance(&mut self) {
+        self.advance_subscriptions();
+
         // Take this opportunity to drain `since_update` commands.
         // Don't try to compact to an empty frontier. There may be a good reason to do this
         // in principle, but not in any current Mz use case.
@@ -1743,8 +1856,16 @@ impl Coordinator {
         &mut self,
         id: GlobalId,
         oid: u32,
-        connector: SinkConnector,
+        mut connector: SinkConnector,
     ) -> Result<(), CoordError> {
+        // HTTP endpoints cannot tell a sink what it has already delivered, so
+        // the progress of HTTP sinks is recorded in the catalog instead.
+        if let SinkConnector::Http(http) = &mut connector {
+            http.gate_ts = self.catalog.load_sink_progress(id)?;
+            self.sink_progress
+                .insert(id, http.gate_ts.unwrap_or_else(Timestamp::minimum));
+        }
+
         // Update catalog entry with sink connector.
         let entry = self.catalog.get_by_id(&id);
         let name = entry.name().clone();
@@ -1777,7 +1898,7 @@ impl Coordinator {
                         .unwrap()
                         .clone(),
                     connector: connector.clone(),
-                    envelope: Some(sink.envelope),
+                    envelope: sink.envelope,
                     as_of,
                 };
                 Ok(builder.build_sink_dataflow(name.to_string(), id, sink_description)?)
@@ -1797,6 +1918,14 @@ impl Coordinator {
                 if let Some(token) = self.since_handles.get(&id) {
                     tokens.push(token.clone());
                 }
+                // Persisted tables are compacted along with their primary
+                // index, so that is what needs to be held back.
+                if self.persister.table_details.contains_key(id) {
+                    let index_id = self.catalog.default_index_for(*id);
+                    if let Some(token) = index_id.and_then(|i| self.since_handles.get(&i)) {
+                        tokens.push(token.clone());
+                    }
+                }
             }
 
             let sink_writes = SinkWrites::new(tokens);
@@ -1805,6 +1934,38 @@ impl Coordinator {
         Ok(self.ship_dataflow(df).await)
     }
 
+    /// Validates that the given sources and tables replay the same updates at
+    /// the same timestamps after a restart, which a sink needs in order to
+    /// resume from the last timestamp it has written out.
+    fn validate_durable_sink_inputs(
+        &self,
+        requirer: &str,
+        ids: &[GlobalId],
+    ) -> Result<(), CoordError> {
+        for id in ids {
+            let entry = self.catalog.get_by_id(id);
+            let durable = match entry.item() {
+                CatalogItem::Source(source) => {
+                    source.connector.yields_stable_input()
+                        || source.persist_details.is_some()
+                        || source
+                            .webhook()
+                            .map_or(false, |webhook| webhook.persisted_name.is_some())
+                }
+                CatalogItem::Table(_) => self.persister.table_details.contains_key(id),
+                _ => false,
+            };
+            if !durable {
+                coord_bail!(
+                    "{} that sink input dependencies are replayable or persisted, {} is not",
+                    requirer,
+                    entry.name()
+                );
+            }
+        }
+        Ok(())
+    }
+
     async fn sequence_plan(
         &mut self,
         tx: ClientTransmitter<ExecuteResponse>,
@@ -1860,6 +2021,9 @@ impl Coordinator {
             Plan::DropItems(plan) => {
                 tx.send(self.sequence_drop_items(plan).await, session);
             }
+            Plan::DropSubscription(plan) => {
+                tx.send(self.sequence_drop_subscription(plan), session);
+            }
             Plan::EmptyQuery => {
                 tx.send(Ok(ExecuteResponse::EmptyQuery), session);
             }
@@ -1913,6 +2077,12 @@ impl Coordinator {
             Plan::Explain(plan) => {
                 tx.send(self.sequence_explain(&session, plan), session);
             }
+            Plan::ExplainAnalyze(plan) => {
+                tx.send(
+                    self.sequence_explain_analyze(&mut session, plan).await,
+                    session,
+                );
+            }
             Plan::SendDiffs(plan) => {
                 tx.send(self.sequence_send_diffs(&mut session, plan), session);
             }
@@ -1940,6 +2110,12 @@ impl Coordinator {
             Plan::AlterIndexEnable(plan) => {
                 tx.send(self.sequence_alter_index_enable(plan).await, session);
             }
+            Plan::AlterSourceRefreshSchema(plan) => {
+                tx.send(
+                    self.sequence_alter_source_refresh_schema(plan).await,
+                    session,
+                );
+            }
             Plan::DiscardTemp => {
                 self.drop_temp_items(session.conn_id()).await;
                 tx.send(Ok(ExecuteResponse::DiscardedTemp), session);
@@ -2271,6 +2447,14 @@ impl Coordinator {
                 // Do everything to instantiate the source at the coordinator and
                 // inform the timestamper and dataflow workers of its existence before
                 // shipping any dataflows that depend on its existence.
+                for id in &source_ids {
+                    let entry = self.catalog.get_by_id(id);
+                    if let Some(webhook) = entry.source().and_then(|source| source.webhook()) {
+                        self.persister
+                            .add_webhook_source(*id, webhook)
+                            .map_err(CoordError::Persistence)?;
+                    }
+                }
                 let catalog_state = self.catalog.state();
 
                 // Ask persistence if it has a since timestamps for any
@@ -2286,6 +2470,7 @@ impl Coordinator {
                             .load_source_persist_desc(&source)
                             .map_err(CoordError::Persistence)?
                             .map(|p| p.since_ts)
+                            .or_else(|| self.persister.table_details.get(id).map(|td| td.since_ts))
                             .unwrap_or(0);
                         Ok::<_, CoordError>(since_ts)
                     })
@@ -2339,13 +2524,28 @@ impl Coordinator {
         for plan in plans {
             let CreateSourcePlan {
                 name,
-                source,
+                mut source,
                 materialized,
                 ..
             } = plan;
             let source_id = self.catalog.allocate_id()?;
             let source_oid = self.catalog.allocate_oid()?;
 
+            if let SourceConnector::External {
+                connector: ExternalSourceConnector::Webhook(webhook),
+                ..
+            } = &mut source.connector
+            {
+                webhook.persisted_name = self
+                    .persister
+                    .new_webhook_persist_name(source_id, &name.to_string());
+                // Requests are acknowledged once they are committed, which
+                // would be a lie if they were lost on restart.
+                if webhook.persisted_name.is_none() {
+                    coord_bail!("webhook sources require --persistent-user-tables");
+                }
+            }
+
             let persist_details = self.persister.new_serialized_source_persist_details(
                 source_id,
                 &source.connector,
@@ -2410,6 +2610,32 @@ impl Coordinator {
             if_not_exists,
         } = plan;
 
+        let durable_inputs = match &sink.connector_builder {
+            SinkConnectorBuilder::Kafka(builder) if builder.reuse_topic => Some((
+                "reuse_topic requires",
+                &builder.transitive_source_dependencies,
+            )),
+            SinkConnectorBuilder::Postgres(builder) => Some((
+                "postgres sinks require",
+                &builder.transitive_source_dependencies,
+            )),
+            SinkConnectorBuilder::Http(builder) => Some((
+                "http sinks require",
+                &builder.transitive_source_dependencies,
+            )),
+            SinkConnectorBuilder::Parquet(builder) if builder.reuse_directory => Some((
+                "reuse_directory requires",
+                &builder.transitive_source_dependencies,
+            )),
+            _ => None,
+        };
+        if let Some((requirer, ids)) = durable_inputs {
+            if let Err(e) = self.validate_durable_sink_inputs(requirer, ids) {
+                tx.send(Err(e), session);
+                return;
+            }
+        }
+
         // First try to allocate an ID and an OID. If either fails, we're done.
         let id = match self.catalog.allocate_id() {
             Ok(id) => id,
@@ -2462,8 +2688,8 @@ impl Coordinator {
                                 .desc()
                                 .unwrap()
                                 .clone(),
-                            connector: SinkConnector::Tail(TailSinkConnector {}),
-                            envelope: Some(sink.envelope),
+                            connector: SinkConnector::Tail(TailSinkConnector::default()),
+                            envelope: sink.envelope,
                             as_of: SinkAsOf {
                                 frontier: Antichain::new(),
                                 strict: false,
@@ -2538,6 +2764,7 @@ impl Coordinator {
                 None
             },
             depends_on: view.depends_on,
+            outer_join_anti_joins: view.outer_join_anti_joins,
         };
         ops.push(catalog::Op::CreateItem {
             id: view_id,
@@ -2682,6 +2909,8 @@ impl Coordinator {
             conn_id: None,
             depends_on: index.depends_on,
             enabled: self.catalog.index_enabled_by_default(&id),
+            spill: index.spill,
+            spill_threshold: index.spill_threshold,
         };
         let oid = self.catalog.allocate_oid()?;
         let op = catalog::Op::CreateItem {
@@ -2770,6 +2999,20 @@ impl Coordinator {
         Ok(ExecuteResponse::DroppedRole)
     }
 
+    fn sequence_drop_subscription(
+        &mut self,
+        plan: DropSubscriptionPlan,
+    ) -> Result<ExecuteResponse, CoordError> {
+        if self.subscriptions.contains_key(&plan.name) {
+            self.catalog.remove_subscription(&plan.name)?;
+            // Dropping the subscription releases its hold on its inputs.
+            self.subscriptions.remove(&plan.name);
+        } else if !plan.if_exists {
+            coord_bail!("unknown subscription {}", plan.name.quoted());
+        }
+        Ok(ExecuteResponse::DroppedSubscription)
+    }
+
     async fn sequence_drop_items(
         &mut self,
         plan: DropItemsPlan,
@@ -3195,7 +3438,14 @@ impl Coordinator {
         );
 
         // Optimize the dataflow across views, and any other ways that appeal.
-        mz_transform::optimize_dataflow(&mut dataflow, self.catalog.enabled_indexes())?;
+        mz_transform::optimize_dataflow(
+            &mut dataflow,
+            self.catalog.enabled_indexes(),
+            self.statistics.collections(),
+        )?;
+
+        // The indexes the dataflow would have used, had they existed.
+        let index_requests = index_advisor::index_requests(&dataflow, view_id);
 
         // Finalization optimizes the dataflow as much as possible.
         let dataflow_plan = self.finalize_dataflow(dataflow);
@@ -3211,6 +3461,14 @@ impl Coordinator {
             thinning.len(),
         )?;
 
+        // Peeks that must build a dataflow inform the index recommendations.
+        if let fast_path_peek::Plan::PeekDataflow(_) = &fast_path {
+            if !index_requests.is_empty() {
+                let updates = self.index_advisor.record(index_requests, &self.catalog);
+                self.send_builtin_table_updates(updates).await;
+            }
+        }
+
         // Implement the peek, and capture the response.
         let resp = self
             .implement_fast_path_peek(fast_path, timestamp, finishing, conn_id, source.arity())
@@ -3236,6 +3494,8 @@ impl Coordinator {
             ts,
             copy_to,
             emit_progress,
+            subscription,
+            envelope,
         } = plan;
         // TAIL AS OF, similar to peeks, doesn't need to worry about transaction
         // timestamp semantics.
@@ -3245,7 +3505,28 @@ impl Coordinator {
             session.add_transaction_ops(TransactionOps::Tail)?;
         }
 
+        // A named subscription that already exists resumes from its durable
+        // position rather than from the usual TAIL frontier.
+        let resume_as_of = match (&subscription, &from) {
+            (Some(subscription), TailFrom::Id(from_id)) => {
+                self.resume_subscription(subscription, *from_id, ts)?
+            }
+            _ => None,
+        };
+
         let make_sink_desc = |coord: &mut Coordinator, from, from_desc, uses| {
+            if let Some(as_of) = &resume_as_of {
+                return Ok::<_, CoordError>(SinkDesc {
+                    from,
+                    from_desc,
+                    connector: SinkConnector::Tail(TailSinkConnector {
+                        envelope: envelope.clone(),
+                    }),
+                    envelope: None,
+                    as_of: as_of.clone(),
+                });
+            }
+
             // Determine the frontier of updates to tail *from*.
             // Updates greater or equal to this frontier will be produced.
             let frontier = if let Some(ts) = ts {
@@ -3259,7 +3540,9 @@ impl Coordinator {
             Ok::<_, CoordError>(SinkDesc {
                 from,
                 from_desc,
-                connector: SinkConnector::Tail(TailSinkConnector::default()),
+                connector: SinkConnector::Tail(TailSinkConnector {
+                    envelope: envelope.clone(),
+                }),
                 envelope: None,
                 as_of: SinkAsOf {
                     frontier,
@@ -3296,11 +3579,22 @@ impl Coordinator {
         };
 
         let (sink_id, sink_desc) = &dataflow.sink_exports[0];
+        if let (Some(subscription), None) = (subscription, &resume_as_of) {
+            self.create_subscription(subscription, sink_desc.from, &sink_desc.as_of)?;
+        }
         session.add_drop_sink(*sink_id);
-        let arity = sink_desc.from_desc.arity();
+        // Tails with an envelope present each update as a single row without
+        // a diff.
+        let arity = match &envelope {
+            None => sink_desc.from_desc.arity(),
+            Some(TailEnvelope::Upsert { .. }) => 1 + sink_desc.from_desc.arity(),
+            Some(TailEnvelope::Debezium { .. }) => 2,
+        };
         let (tx, rx) = mpsc::unbounded_channel();
-        self.pending_tails
-            .insert(*sink_id, PendingTail::new(tx, emit_progress, arity));
+        self.pending_tails.insert(
+            *sink_id,
+            PendingTail::new(tx, emit_progress, envelope.is_none(), arity),
+        );
         self.ship_dataflow(dataflow).await;
 
         let resp = ExecuteResponse::Tailing { rx };
@@ -3313,6 +3607,185 @@ impl Coordinator {
         }
     }
 
+    /// Determines where a TAIL of the named subscription `subscription` on
+    /// `from_id` resumes, or `None` if no such subscription exists yet.
+    ///
+    /// An explicit `AS OF` timestamp acknowledges all updates at or before that
+    /// timestamp, which durably advances the subscription and allows its inputs
+    /// to compact up to it.
+    fn resume_subscription(
+        &mut self,
+        subscription: &TailSubscription,
+        from_id: GlobalId,
+        ts: Option<Timestamp>,
+    ) -> Result<Option<SinkAsOf>, CoordError> {
+        let existing = match self.subscriptions.get(&subscription.name) {
+            Some(existing) => existing,
+            None => return Ok(None),
+        };
+        if existing.lost {
+            coord_bail!(
+                "subscription {} cannot resume after a restart, because {} does not only read \
+                 from persisted tables",
+                subscription.name.quoted(),
+                self.catalog.get_by_id(&existing.state.object_id).name(),
+            );
+        }
+        if existing.state.object_id != from_id {
+            coord_bail!(
+                "subscription {} tails {}, not {}",
+                subscription.name.quoted(),
+                self.catalog.get_by_id(&existing.state.object_id).name(),
+                self.catalog.get_by_id(&from_id).name(),
+            );
+        }
+
+        let mut state = existing.state.clone();
+        if let Some(ts) = ts {
+            if ts < state.as_of {
+                coord_bail!(
+                    "subscription {} has already acknowledged timestamp {}, which is later than {}",
+                    subscription.name.quoted(),
+                    state.as_of,
+                    ts
+                );
+            }
+            state.as_of = ts;
+            state.strict = true;
+        }
+        state.max_retention_ms = duration_to_timestamp_millis(subscription.max_retention);
+
+        // Validate that the subscription's history has not been compacted away,
+        // e.g. because the client stayed away for longer than the retention.
+        self.determine_timestamp(&[from_id], PeekWhen::AtTimestamp(state.as_of))
+            .map_err(|_| {
+                CoordError::Unstructured(anyhow!(
+                    "subscription {} can no longer resume from timestamp {}, \
+                     because its history has been compacted",
+                    subscription.name.quoted(),
+                    state.as_of
+                ))
+            })?;
+
+        let as_of = SinkAsOf {
+            frontier: Antichain::from_elem(state.as_of),
+            strict: state.strict,
+        };
+        self.catalog.upsert_subscription(&state)?;
+        let since = state.as_of;
+        self.subscriptions
+            .get_mut(&subscription.name)
+            .unwrap()
+            .state = state;
+        self.update_subscription_handles(&subscription.name, since);
+        Ok(Some(as_of))
+    }
+
+    /// Durably records a new named subscription that starts at `as_of`, and
+    /// holds back compaction of its inputs from there on.
+    fn create_subscription(
+        &mut self,
+        subscription: TailSubscription,
+        object_id: GlobalId,
+        as_of: &SinkAsOf,
+    ) -> Result<(), CoordError> {
+        let state = storage::Subscription {
+            max_retention_ms: duration_to_timestamp_millis(subscription.max_retention),
+            name: subscription.name,
+            object_id,
+            as_of: as_of.frontier.elements().first().copied().unwrap_or(0),
+            strict: as_of.strict,
+        };
+        self.catalog.upsert_subscription(&state)?;
+        let name = state.name.clone();
+        let as_of = state.as_of;
+        self.subscriptions.insert(
+            name.clone(),
+            Subscription {
+                state,
+                handles: HashMap::new(),
+                lost: false,
+            },
+        );
+        self.update_subscription_handles(&name, as_of);
+        Ok(())
+    }
+
+    /// Returns the persisted tables that `object_id` reads from, or `None` if
+    /// it also reads from anything whose history does not survive restarts.
+    fn persisted_inputs(&self, object_id: GlobalId) -> Option<Vec<GlobalId>> {
+        let mut tables = vec![];
+        let mut stack = vec![object_id];
+        while let Some(id) = stack.pop() {
+            match self.catalog.get_by_id(&id).item() {
+                CatalogItem::Table(_) if self.persister.table_details.contains_key(&id) => {
+                    tables.push(id)
+                }
+                CatalogItem::Table(_) | CatalogItem::Source(_) => return None,
+                item => stack.extend(item.uses()),
+            }
+        }
+        Some(tables)
+    }
+
+    /// Holds back compaction of the arrangements that subscription `name`
+    /// currently reads from at `since`, or at their current `since` if that is
+    /// already beyond it.
+    ///
+    /// Indexes on the tailed relation may have been created or dropped since
+    /// the subscription last took its handles, so handles on arrangements it
+    /// no longer reads from are released.
+    fn update_subscription_handles(&mut self, name: &str, since: Timestamp) {
+        let subscription = &self.subscriptions[name];
+        if subscription.lost {
+            return;
+        }
+        let object_id = subscription.state.object_id;
+        let (mut input_ids, unmaterialized_source_ids) = self.catalog.nearest_indexes(&[object_id]);
+        input_ids.extend(unmaterialized_source_ids);
+        // Persisted tables only compact as far as their default indexes, so
+        // holding those back retains the tables' history across restarts.
+        for table_id in self.persisted_inputs(object_id).unwrap_or_default() {
+            input_ids.extend(self.catalog.default_index_for(table_id));
+        }
+
+        let new_handles: Vec<_> = input_ids
+            .iter()
+            .filter(|id| !subscription.handles.contains_key(id))
+            .filter_map(|id| {
+                let frontiers = self.indexes.get(id).or_else(|| self.sources.get(id))?;
+                let current = frontiers.since.borrow().frontier().to_owned();
+                let current = current.elements().first()?;
+                Some((*id, frontiers.since_handle(vec![cmp::max(since, *current)])))
+            })
+            .collect();
+
+        let subscription = self.subscriptions.get_mut(name).unwrap();
+        subscription.handles.retain(|id, _| input_ids.contains(id));
+        subscription.handles.extend(new_handles);
+        for handle in subscription.handles.values_mut() {
+            handle.maybe_advance(iter::once(since));
+        }
+    }
+
+    /// Allows the inputs of named subscriptions to compact up to the maximum
+    /// retention of each subscription, even if the subscription's position
+    /// trails further behind.
+    fn advance_subscriptions(&mut self) {
+        let now = (self.catalog.config().now)();
+        let sinces: Vec<_> = self
+            .subscriptions
+            .iter()
+            .map(|(name, subscription)| {
+                let retained = now.saturating_sub(subscription.state.max_retention_ms);
+                (name.clone(), cmp::max(subscription.state.as_of, retained))
+            })
+            .collect();
+        for (name, since) in sinces {
+            self.update_subscription_handles(&name, since);
+        }
+    }
+
     /// A policy for determining the timestamp for a peek.
     ///
     /// The Timestamp result may be `None` in the case that the `when` policy
@@ -3550,6 +4023,7 @@ impl Coordinator {
             let start = Instant::now();
             let decorrelated_plan = raw_plan.optimize_and_lower(&OptimizerConfig {
                 qgm_optimizations: session.vars().qgm_optimizations(),
+                outer_join_anti_joins: session.vars().outer_join_anti_joins(),
             });
             timings.decorrelation = Some(start.elapsed());
             decorrelated_plan
@@ -3570,11 +4044,18 @@ impl Coordinator {
                     &optimized_plan,
                     &mut dataflow,
                 )?;
-                mz_transform::optimize_dataflow(&mut dataflow, coord.catalog.enabled_indexes())?;
+                mz_transform::optimize_dataflow(
+                    &mut dataflow,
+                    coord.catalog.enabled_indexes(),
+                    coord.statistics.collections(),
+                )?;
                 timings.optimization = Some(start.elapsed());
                 Ok(dataflow)
             };
 
+        // The collections read by the optimized dataflow, if one was built.
+        let mut imported_ids = BTreeSet::new();
+
         let mut explanation_string = match stage {
             ExplainStage::RawPlan => {
                 let catalog = self.catalog.for_session(session);
@@ -3617,6 +4098,7 @@ impl Coordinator {
                 let decorrelated_plan = decorrelate(&mut timings, raw_plan);
                 self.validate_timeline(decorrelated_plan.global_uses())?;
                 let dataflow = optimize(&mut timings, self, decorrelated_plan)?;
+                imported_ids = imported_collections(&dataflow);
                 let catalog = self.catalog.for_session(session);
                 let formatter =
                     mz_dataflow_types::DataflowGraphFormatter::new(&catalog, options.typed);
@@ -3632,6 +4114,7 @@ impl Coordinator {
                 let decorrelated_plan = decorrelate(&mut timings, raw_plan);
                 self.validate_timeline(decorrelated_plan.global_uses())?;
                 let dataflow = optimize(&mut timings, self, decorrelated_plan)?;
+                imported_ids = imported_collections(&dataflow);
                 let dataflow_plan = mz_dataflow_types::Plan::finalize_dataflow(dataflow)
                     .expect("Dataflow planning failed; unrecoverable error");
                 let catalog = self.catalog.for_session(session);
@@ -3645,6 +4128,7 @@ impl Coordinator {
                 }
                 explanation.to_string()
             }
+            ExplainStage::Analyze => unreachable!("EXPLAIN ANALYZE is planned separately"),
         };
         if options.timing {
             if let Some(decorrelation) = &timings.decorrelation {
@@ -3669,10 +4153,121 @@ impl Coordinator {
                 explanation_string.push_str("\n");
             }
         }
+        if options.statistics && !imported_ids.is_empty() {
+            explanation_string.push_str("\nStatistics:");
+            for id in imported_ids {
+                let name = self.catalog.get_by_id(&id).name().to_string();
+                match self.statistics.collections().get(&id) {
+                    Some(statistics) => {
+                        explanation_string
+                            .push_str(&format!("\n  {}: {} records", name, statistics.records));
+                        for (key, distinct) in &statistics.distinct_keys {
+                            explanation_string.push_str(&format!(
+                                "\n    ({}): {} distinct keys",
+                                key.iter().join(", "),
+                                distinct
+                            ));
+                        }
+                    }
+                    None => explanation_string.push_str(&format!("\n  {}: unknown", name)),
+                }
+            }
+            explanation_string.push_str("\n");
+        }
         let rows = vec![Row::pack_slice(&[Datum::from(&*explanation_string)])];
         Ok(send_immediate_rows(rows))
     }
 
+    async fn sequence_explain_analyze(
+        &mut self,
+        session: &mut Session,
+        plan: ExplainAnalyzePlan,
+    ) -> Result<ExecuteResponse, CoordError> {
+        if !self.logging_enabled {
+            coord_bail!("EXPLAIN ANALYZE requires introspection logging to be enabled");
+        }
+        let entry = self.catalog.get_by_id(&plan.id);
+        let name = entry.name().to_string();
+        // A view is explained through the dataflow that maintains its index.
+        let export_id = match entry.item() {
+            CatalogItem::View(_) => match self
+                .catalog
+                .enabled_indexes()
+                .get(&plan.id)
+                .and_then(|indexes| indexes.first())
+            {
+                Some((index_id, _keys)) => *index_id,
+                None => coord_bail!("EXPLAIN ANALYZE requires {} to be materialized", name),
+            },
+            _ => plan.id,
+        };
+        let dataflow = match self.dataflow_plans.get(&export_id) {
+            Some(dataflow) => dataflow,
+            None => coord_bail!("{} is not maintained by a running dataflow", name),
+        };
+        let analysis = {
+            let catalog = self.catalog.for_session(session);
+            PlanAnalysis::new(dataflow, &catalog)
+        };
+
+        let view = self.catalog.get_by_id(&MZ_DATAFLOW_PLAN_NODE_STATISTICS.id);
+        let typ = view.desc()?.typ().clone();
+        let arity = typ.arity();
+        let export_id = export_id.to_string();
+        let source =
+            MirRelationExpr::global_get(MZ_DATAFLOW_PLAN_NODE_STATISTICS.id, typ).filter(vec![
+                MirScalarExpr::column(0).call_binary(
+                    MirScalarExpr::literal_ok(Datum::String(&export_id), ScalarType::String),
+                    BinaryFunc::Eq,
+                ),
+            ]);
+        let response = self
+            .sequence_peek(
+                session,
+                PeekPlan {
+                    source,
+                    when: PeekWhen::Immediately,
+                    finishing: RowSetFinishing {
+                        order_by: vec![],
+                        limit: None,
+                        offset: 0,
+                        project: (0..arity).collect(),
+                    },
+                    copy_to: None,
+                },
+            )
+            .await?;
+        let rows = match response {
+            ExecuteResponse::SendingRows(rows) => rows,
+            _ => unreachable!("peeks without COPY TO send rows"),
+        };
+        Ok(ExecuteResponse::SendingRows(Box::pin(rows.map(
+            move |response| match response {
+                PeekResponse::Rows(rows) => {
+                    let mut statistics = HashMap::new();
+                    for row in rows {
+                        let datums = row.unpack();
+                        let node = datums[1].unwrap_int64() as usize;
+                        let stat = |i: usize| datums[i].unwrap_int64() as u64;
+                        statistics.insert(
+                            node,
+                            PlanNodeStatistics {
+                                operators: stat(2),
+                                arrangements: stat(3),
+                                records: stat(4),
+                                batches: stat(5),
+                                elapsed: Duration::from_nanos(stat(6)),
+                            },
+                        );
+                    }
+                    let explanation = analysis.render(&statistics);
+                    PeekResponse::Rows(vec![Row::pack_slice(&[Datum::from(&*explanation)])])
+                }
+                response => response,
+            },
+        ))))
+    }
+
     fn sequence_send_diffs(
         &mut self,
         session: &mut Session,
@@ -3829,14 +4424,95 @@ impl Coordinator {
         let catalog = self.catalog.for_session(session);
         let values = mz_sql::plan::plan_copy_from(&session.pcx(), &catalog, id, columns, rows)?;
 
+        let values = values.lower(&OptimizerConfig {
+            qgm_optimizations: session.vars().qgm_optimizations(),
+            outer_join_anti_joins: session.vars().outer_join_anti_joins(),
+        });
+
         let constants = self
-            .prep_relation_expr(values.lower(), ExprPrepStyle::Write)?
+            .prep_relation_expr(values, ExprPrepStyle::Write)?
             .into_inner();
 
         // Copied rows must always be constants.
         self.sequence_insert_constant(session, id, constants)
     }
 
+    fn sequence_append_webhook(
+        &mut self,
+        session: &mut Session,
+        name: PartialName,
+        headers: HashMap<String, String>,
+        body: Vec<u8>,
+    ) -> Result<ExecuteResponse, CoordError> {
+        let id = self.catalog.for_session(session).resolve_item(&name)?.id();
+        let entry = self.catalog.get_by_id(&id);
+        let (webhook, encoding) = match entry.item() {
+            CatalogItem::Source(catalog::Source {
+                connector:
+                    SourceConnector::External {
+                        connector: ExternalSourceConnector::Webhook(webhook),
+                        encoding: SourceDataEncoding::Single(encoding),
+                        ..
+                    },
+                ..
+            }) => (webhook, encoding),
+            _ => coord_bail!("{} is not a webhook source", entry.name()),
+        };
+
+        // Requests must not be acknowledged unless they survive restarts.
+        if webhook.persisted_name.is_none() {
+            coord_bail!("webhook source {} is not persisted", entry.name());
+        }
+
+        if let Some(validation) = &webhook.validation {
+            let key = match std::env::var(&validation.key_env) {
+                Ok(key) => key,
+                Err(_) => coord_bail!(
+                    "environment variable {} of webhook source {} is not set",
+                    validation.key_env,
+                    entry.name()
+                ),
+            };
+            if !webhook_signature_is_valid(validation, key.as_bytes(), &headers, &body)? {
+                return Err(CoordError::InvalidWebhookSignature(
+                    entry.name().to_string(),
+                ));
+            }
+        }
+
+        let mut row = Row::default();
+        match encoding {
+            DataEncoding::Bytes => row.push(Datum::Bytes(&body)),
+            DataEncoding::Text => match std::str::from_utf8(&body) {
+                Ok(text) => row.push(Datum::String(text)),
+                Err(_) => coord_bail!("webhook request body is not valid UTF-8"),
+            },
+            DataEncoding::Json => {
+                let jsonb = Jsonb::from_slice(&body)
+                    .map_err(|e| anyhow!("webhook request body is not valid JSON: {}", e))?;
+                row.extend_by_row(&jsonb.into_row());
+            }
+            encoding => {
+                return Err(CoordError::Internal(format!(
+                    "unexpected webhook source encoding {}",
+                    encoding.op_name()
+                )))
+            }
+        }
+        for header in &webhook.headers {
+            match headers.get(header) {
+                Some(value) => row.push(Datum::String(value)),
+                None => row.push(Datum::Null),
+            }
+        }
+
+        session.add_transaction_ops(TransactionOps::Writes(vec![WriteOp {
+            id,
+            rows: vec![(row, 1)],
+        }]))?;
+        Ok(ExecuteResponse::Inserted(1))
+    }
+
     // ReadThenWrite is a plan whose writes depend on the results of a
     // read. This works by doing a Peek then queuing a SendDiffs. No writes
     // or read-then-writes can occur between the Peek and SendDiff otherwise a
@@ -4032,6 +4708,92 @@ impl Coordinator {
         Ok(ExecuteResponse::AlteredObject(ObjectType::Index))
     }
 
+    async fn sequence_alter_source_refresh_schema(
+        &mut self,
+        plan: AlterSourceRefreshSchemaPlan,
+    ) -> Result<ExecuteResponse, CoordError> {
+        let AlterSourceRefreshSchemaPlan { id, source } = plan;
+        let entry = self.catalog.get_by_id(&id);
+        let old_source = entry.source().expect("planned as source");
+        if old_source.create_sql == source.create_sql {
+            // The source already uses the latest schema.
+            return Ok(ExecuteResponse::AlteredObject(ObjectType::Source));
+        }
+        if old_source.persist_details.is_some() {
+            return Err(CoordError::Unsupported(
+                "schema refreshes of persisted sources",
+            ));
+        }
+
+        // Sinks have already published a schema for the source's columns, and
+        // rebuilding them would write to new topics, so refuse to disturb them.
+        let mut dependent_sinks = vec![];
+        let mut stack = entry.used_by().to_vec();
+        while let Some(dependent_id) = stack.pop() {
+            let dependent = self.catalog.get_by_id(&dependent_id);
+            if let CatalogItem::Sink(_) = dependent.item() {
+                dependent_sinks.push(dependent.name().to_string());
+            }
+            stack.extend(dependent.used_by());
+        }
+        if !dependent_sinks.is_empty() {
+            dependent_sinks.sort();
+            dependent_sinks.dedup();
+            return Err(CoordError::InvalidSchemaRefresh {
+                source_name: entry.name().to_string(),
+                dependent_sinks,
+            });
+        }
+
+        let source = catalog::Source {
+            create_sql: source.create_sql,
+            connector: source.connector,
+            persist_details: None,
+            desc: source.desc,
+        };
+        // Every running index that (transitively) reads from the source was
+        // built with the old schema. They are rebuilt in the order they were
+        // created, so that indexes are rebuilt before the indexes that use
+        // them.
+        //
+        // The source keeps its read capability, and the rebuilt indexes start
+        // at the source's since rather than at the current time, so the
+        // compaction history that the source retains is preserved. Reads at
+        // earlier times remain valid, and observe the source's historical
+        // data decoded with the new schema.
+        let mut index_ids = self.catalog.state().dependent_indexes(id);
+        index_ids.sort();
+        index_ids.dedup();
+        let index_ids = self.indexes.intersection(index_ids);
+
+        // The dataflows of the rebuilt indexes are built against the refreshed
+        // catalog before it is committed, so that a failure leaves the source
+        // and its running indexes untouched. The running indexes are not
+        // imported until they are rebuilt. The rebuilt indexes keep their IDs,
+        // so later dataflows import the rebuilt versions of the indexes that
+        // they use, which are shipped before them.
+        let ops = self.catalog.refresh_source_schema_ops(id, source)?;
+        let dfs = self
+            .catalog_transact(ops, |mut builder| {
+                builder.replaced_indexes = index_ids.iter().copied().collect();
+                let mut dfs = vec![];
+                for index_id in &index_ids {
+                    if let Some((name, description)) =
+                        Self::prepare_index_build(builder.catalog, index_id)
+                    {
+                        dfs.push(builder.build_index_dataflow(name, *index_id, description)?);
+                    }
+                    builder.replaced_indexes.remove(index_id);
+                }
+                Ok(dfs)
+            })
+            .await?;
+        self.drop_indexes(index_ids).await;
+        self.ship_dataflows(dfs).await;
+
+        Ok(ExecuteResponse::AlteredObject(ObjectType::Source))
+    }
+
     /// Perform a catalog transaction. The closure is passed a [`DataflowBuilder`]
     /// made from the prospective [`CatalogState`] (i.e., the `Catalog` with `ops`
     /// applied but before the transaction is committed). The closure can return
@@ -4044,19 +4806,25 @@ impl Coordinator {
         F: FnOnce(DataflowBuilder) -> Result<T, CoordError>,
     {
         let mut sources_to_drop = vec![];
+        let mut webhook_sources_to_drop = vec![];
         let mut tables_to_drop = vec![];
         let mut sinks_to_drop = vec![];
         let mut indexes_to_drop = vec![];
         let mut replication_slots_to_drop: HashMap<String, Vec<String>> = HashMap::new();
+        let mut dropped_ids = HashSet::new();
 
         for op in &ops {
             if let catalog::Op::DropItem(id) = op {
+                dropped_ids.insert(*id);
                 match self.catalog.get_by_id(id).item() {
                     CatalogItem::Table(_) => {
                         tables_to_drop.push(*id);
                     }
                     CatalogItem::Source(source) => {
                         sources_to_drop.push(*id);
+                        if source.webhook().is_some() {
+                            webhook_sources_to_drop.push(*id);
+                        }
                         if let SourceConnector::External {
                             connector:
                                 ExternalSourceConnector::Postgres(PostgresSourceConnector {
@@ -4090,13 +4858,16 @@ impl Coordinator {
         let indexes = &self.indexes;
         let persister = &self.persister;
         let storage = &self.dataflow_client;
+        let statistics = self.statistics.collections();
 
-        let (builtin_table_updates, result) = self.catalog.transact(ops, |catalog| {
+        let (mut builtin_table_updates, result) = self.catalog.transact(ops, |catalog| {
             let builder = DataflowBuilder {
                 catalog,
                 indexes,
                 persister,
                 storage,
+                statistics,
+                replaced_indexes: HashSet::new(),
             };
             f(builder)
         })?;
@@ -4104,6 +4875,8 @@ impl Coordinator {
         // No error returns are allowed after this point. Enforce this at compile time
         // by using this odd structure so we don't accidentally add a stray `?`.
         let _: () = async {
+            // Dropped collections and new indexes retire index recommendations.
+            builtin_table_updates.extend(self.index_advisor.refresh(&self.catalog));
             self.send_builtin_table_updates(builtin_table_updates).await;
 
             if !sources_to_drop.is_empty() {
@@ -4111,6 +4884,10 @@ impl Coordinator {
                     self.update_timestamper(id, false).await;
                     self.sources.remove(&id);
                 }
+                // Webhook sources are persisted like tables.
+                for &id in &webhook_sources_to_drop {
+                    self.persister.remove_table(id);
+                }
                 self.dataflow_client.drop_sources(sources_to_drop).await;
             }
             if !tables_to_drop.is_empty() {
@@ -4123,9 +4900,16 @@ impl Coordinator {
                 }
                 self.dataflow_client.drop_sources(tables_to_drop).await;
             }
+            // The catalog has removed the subscriptions on dropped objects.
+            self.subscriptions
+                .retain(|_, subscription| !dropped_ids.contains(&subscription.state.object_id));
+            self.statistics
+                .remove(&dropped_ids, self.catalog.enabled_indexes());
             if !sinks_to_drop.is_empty() {
                 for id in sinks_to_drop.iter() {
                     self.sink_writes.remove(id);
+                    self.sink_progress.remove(id);
+                    self.dataflow_plans.remove(id);
                 }
                 self.dataflow_client
                     .drop_sinks(DEFAULT_COMPUTE_INSTANCE_ID, sinks_to_drop)
@@ -4213,6 +4997,9 @@ impl Coordinator {
     }
 
     async fn drop_sinks(&mut self, dataflow_names: Vec<GlobalId>) {
+        for id in dataflow_names.iter() {
+            self.dataflow_plans.remove(id);
+        }
         if !dataflow_names.is_empty() {
             self.dataflow_client
                 .drop_sinks(DEFAULT_COMPUTE_INSTANCE_ID, dataflow_names)
@@ -4220,9 +5007,16 @@ impl Coordinator {
         }
     }
 
-    async fn drop_indexes(&mut self, indexes: Vec<GlobalId>) {
+    async fn drop_indexes(&mut self, mut indexes: Vec<GlobalId>) {
         let mut trace_keys = Vec::new();
+        // Internal indexes are dropped along with the last index reading them.
+        let released = indexes
+            .iter()
+            .flat_map(|id| self.shared_subplans.release(*id))
+            .collect::<Vec<_>>();
+        indexes.extend(released);
         for id in indexes {
+            self.dataflow_plans.remove(&id);
             if self.indexes.remove(&id).is_some() {
                 trace_keys.push(id);
             }
@@ -4342,8 +5136,28 @@ impl Coordinator {
     /// Finalizes a list of dataflows and then broadcasts it to all workers.
     async fn ship_dataflows(&mut self, dataflows: Vec<DataflowDesc>) {
         let mut dataflow_plans = Vec::with_capacity(dataflows.len());
-        for dataflow in dataflows.into_iter() {
-            dataflow_plans.push(self.finalize_dataflow(dataflow));
+        for mut dataflow in dataflows.into_iter() {
+            let mut shared_subplans = std::mem::take(&mut self.shared_subplans);
+            let mut shared = shared_subplans.share(&mut dataflow, || {
+                self.allocate_transient_id()
+                    .expect("transient id counter exhausted")
+            });
+            self.shared_subplans = shared_subplans;
+            // Dataflows maintaining shared subplans must be built before the
+            // dataflows that import them.
+            shared.push(dataflow);
+            for dataflow in shared {
+                let dataflow_plan = self.finalize_dataflow(dataflow);
+                let export_ids = dataflow_plan
+                    .index_exports
+                    .iter()
+                    .map(|(id, _, _)| *id)
+                    .chain(dataflow_plan.sink_exports.iter().map(|(id, _)| *id));
+                for id in export_ids {
+                    self.dataflow_plans.insert(id, dataflow_plan.clone());
+                }
+                dataflow_plans.push(dataflow_plan);
+            }
         }
         self.dataflow_client
             .create_dataflows(DEFAULT_COMPUTE_INSTANCE_ID, dataflow_plans)
@@ -4584,6 +5398,7 @@ pub async fn serve(
         metrics_registry,
         persister,
         now,
+        cost_based_join_ordering,
     }: Config,
 ) -> Result<(Handle, Client), CoordError> {
     let (cmd_tx, cmd_rx) = mpsc::unbounded_channel();
@@ -4625,6 +5440,10 @@ pub async fn serve(
                 persister,
                 indexes: ArrangementFrontiers::default(),
                 sources: ArrangementFrontiers::default(),
+                statistics: CollectionStatisticsState::new(cost_based_join_ordering),
+                index_advisor: IndexAdvisorState::default(),
+                shared_subplans: SharedSubplans::default(),
+                dataflow_plans: HashMap::new(),
                 logical_compaction_window_ms: logical_compaction_window
                     .map(duration_to_timestamp_millis),
                 logging_enabled: logging.is_some(),
@@ -4640,6 +5459,8 @@ pub async fn serve(
                 index_since_updates: Rc::new(RefCell::new(HashMap::new())),
                 source_since_updates: Rc::new(RefCell::new(HashMap::new())),
                 sink_writes: HashMap::new(),
+                sink_progress: HashMap::new(),
+                subscriptions: HashMap::new(),
                 pending_peeks: HashMap::new(),
                 pending_tails: HashMap::new(),
                 write_lock: Arc::new(tokio::sync::Mutex::new(())),
@@ -4706,6 +5527,22 @@ fn send_immediate_rows(rows: Vec<Row>) -> ExecuteResponse {
     ExecuteResponse::SendingRows(Box::pin(async { PeekResponse::Rows(rows) }))
 }
 
+/// Returns the identifiers of the collections that `dataflow` reads, either
+/// directly or through one of their indexes.
+fn imported_collections<P>(dataflow: &DataflowDescription<P>) -> BTreeSet<GlobalId> {
+    dataflow
+        .source_imports
+        .keys()
+        .copied()
+        .chain(
+            dataflow
+                .index_imports
+                .values()
+                .map(|(desc, _typ)| desc.on_id),
+        )
+        .collect()
+}
+
 fn auto_generate_primary_idx(
     index_name: String,
     on_name: FullName,
@@ -4726,6 +5563,8 @@ fn auto_generate_primary_idx(
         conn_id,
         depends_on,
         enabled,
+        spill: false,
+        spill_threshold: None,
     }
 }
 
@@ -4769,6 +5608,37 @@ fn duration_to_timestamp_millis(d: Duration) -> Timestamp {
     }
 }
 
+/// Compares two byte strings in time independent of their contents, so that
+/// comparing signatures does not leak how much of a forged signature matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Reports whether `headers` carry the signature of `body` that `validation`
+/// prescribes, computed with `key`.
+fn webhook_signature_is_valid(
+    validation: &WebhookValidation,
+    key: &[u8],
+    headers: &HashMap<String, String>,
+    body: &[u8],
+) -> Result<bool, CoordError> {
+    let signature = match headers.get(&validation.header) {
+        Some(signature) => signature,
+        None => return Ok(false),
+    };
+    // Accept both `<signature>` and `<algorithm>=<signature>`, which is the
+    // form many webhook providers use.
+    let signature = signature
+        .strip_prefix(&format!("{}=", validation.algorithm))
+        .unwrap_or(signature);
+    let temp_storage = RowArena::new();
+    let expected = mz_expr::func::hmac_inner(body, key, &validation.algorithm, &temp_storage)?;
+    Ok(match hex::decode(signature.trim()) {
+        Ok(signature) => constant_time_eq(&signature, expected.unwrap_bytes()),
+        Err(_) => false,
+    })
+}
+
 /// Creates a description of the statement `stmt`.
 ///
 /// This function is identical to sql::plan::describe except this is also
@@ -5151,3 +6021,49 @@ impl Coordinator {
         let _: DataflowDescription<mz_dataflow_types::plan::Plan> = self.finalize_dataflow(df);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"signature", b"signature"));
+        assert!(!constant_time_eq(b"signature", b"signaturf"));
+        assert!(!constant_time_eq(b"signature", b"signatur"));
+        assert!(!constant_time_eq(b"", b"s"));
+    }
+
+    #[test]
+    fn test_webhook_signature_is_valid() {
+        let validation = WebhookValidation {
+            algorithm: "sha256".into(),
+            key_env: "UNUSED".into(),
+            header: "x-signature".into(),
+        };
+        let body = b"The quick brown fox jumps over the lazy dog";
+        let signature = "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8";
+        let is_valid = |key: &[u8], signature: Option<&str>| {
+            let headers = signature
+                .into_iter()
+                .map(|signature| ("x-signature".to_string(), signature.to_string()))
+                .collect();
+            webhook_signature_is_valid(&validation, key, &headers, body).unwrap()
+        };
+
+        assert!(is_valid(b"key", Some(signature)));
+        assert!(is_valid(b"key", Some(&format!("sha256={}", signature))));
+        assert!(is_valid(b"key", Some(&signature.to_uppercase())));
+        // A signature computed with another key.
+        assert!(!is_valid(b"other key", Some(signature)));
+        // A signature that differs in its last byte.
+        assert!(!is_valid(b"key", Some(&format!("{}d9", &signature[..62]))));
+        // A signature computed with another algorithm.
+        assert!(!is_valid(b"key", Some(&format!("sha1={}", signature))));
+        assert!(!is_valid(b"key", Some("not hex")));
+        assert!(!is_valid(b"key", Some("")));
+        // A request without the signature header.
+        assert!(!is_valid(b"key", None));
+    }
+}
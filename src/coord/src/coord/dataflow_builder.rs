@@ -18,6 +18,8 @@ use mz_ore::stack::maybe_grow;
 
 use mz_dataflow_types::sinks::SinkDesc;
 use mz_dataflow_types::IndexDesc;
+use mz_expr::{Id, JoinImplementation};
+use mz_repr::RelationType;
 
 use super::*;
 use crate::error::RematerializedSourceType;
@@ -30,6 +32,11 @@ pub struct DataflowBuilder<'a> {
     /// A handle to the storage abstraction, which describe sources from their identifier.
     pub storage:
         &'a mz_dataflow_types::client::Controller<Box<dyn mz_dataflow_types::client::Client>>,
+    /// Size estimates for collections, used to order joins.
+    pub statistics: &'a HashMap<GlobalId, mz_dataflow_types::CollectionStatistics>,
+    /// Indexes in `indexes` whose dataflows are about to be replaced, and
+    /// which therefore must not be imported.
+    pub replaced_indexes: HashSet<GlobalId>,
 }
 
 impl Coordinator {
@@ -40,6 +47,8 @@ impl Coordinator {
             indexes: &self.indexes,
             persister: &self.persister,
             storage: &self.dataflow_client,
+            statistics: self.statistics.collections(),
+            replaced_indexes: HashSet::new(),
         }
     }
 
@@ -70,6 +79,13 @@ impl Coordinator {
 }
 
 impl<'a> DataflowBuilder<'a> {
+    /// Reports whether the index with `id` is known to the dataflow layer, as
+    /// indicated by its presence in `self.indexes`, and is not about to be
+    /// replaced.
+    fn is_valid_index(&self, id: GlobalId) -> bool {
+        self.indexes.contains_key(id) && !self.replaced_indexes.contains(&id)
+    }
+
     /// Imports the view, source, or table with `id` into the provided
     /// dataflow description.
     fn import_into_dataflow(
@@ -83,11 +99,9 @@ impl<'a> DataflowBuilder<'a> {
                 return Ok(());
             }
 
-            // A valid index is any index on `id` that is known to the dataflow
-            // layer, as indicated by its presence in `self.indexes`.
             let valid_index = self.catalog.enabled_indexes()[id]
                 .iter()
-                .find(|(id, _keys)| self.indexes.contains_key(*id));
+                .find(|(id, _keys)| self.is_valid_index(*id));
             if let Some((index_id, keys)) = valid_index {
                 let index_desc = IndexDesc {
                     on_id: *id,
@@ -114,7 +128,10 @@ impl<'a> DataflowBuilder<'a> {
                             // If this source relies on any pre-existing indexes (i.e., indexes
                             // that we're not building as part of this `DataflowBuilder`), we're
                             // attempting to reinstantiate a single-use source.
-                            let intersection = self.indexes.intersection(dependent_indexes);
+                            let intersection = dependent_indexes
+                                .into_iter()
+                                .filter(|id| self.is_valid_index(*id))
+                                .collect::<Vec<_>>();
                             if !intersection.is_empty() {
                                 let existing_indexes = intersection
                                     .iter()
@@ -166,7 +183,7 @@ impl<'a> DataflowBuilder<'a> {
                 for (id, keys) in indexes.iter() {
                     // Ensure only valid indexes (i.e. those in self.indexes) are imported.
                     // TODO(#8318): Ensure this logic is accounted for.
-                    if !self.indexes.contains_key(*id) {
+                    if !self.is_valid_index(*id) {
                         continue;
                     }
                     let on_entry = self.catalog.get_by_id(&get_id);
@@ -194,11 +211,19 @@ impl<'a> DataflowBuilder<'a> {
         let on_entry = self.catalog.get_by_id(&index_description.on_id);
         let on_type = on_entry.desc().unwrap().typ().clone();
         let mut dataflow = DataflowDesc::new(name);
+        if let CatalogItem::Index(index) = self.catalog.get_by_id(&id).item() {
+            dataflow.spill = index.spill.then(|| id);
+            dataflow.spill_threshold = index.spill_threshold;
+        }
         self.import_into_dataflow(&index_description.on_id, &mut dataflow)?;
         dataflow.export_index(id, index_description, on_type);
 
         // Optimize the dataflow across views, and any other ways that appeal.
-        mz_transform::optimize_dataflow(&mut dataflow, self.catalog.enabled_indexes())?;
+        mz_transform::optimize_dataflow(
+            &mut dataflow,
+            self.catalog.enabled_indexes(),
+            self.statistics,
+        )?;
 
         Ok(dataflow)
     }
@@ -232,8 +257,202 @@ impl<'a> DataflowBuilder<'a> {
         dataflow.export_sink(id, sink_description);
 
         // Optimize the dataflow across views, and any other ways that appeal.
-        mz_transform::optimize_dataflow(dataflow, self.catalog.enabled_indexes())?;
+        mz_transform::optimize_dataflow(dataflow, self.catalog.enabled_indexes(), self.statistics)?;
 
         Ok(())
     }
 }
+
+/// Joins shared across index dataflows.
+///
+/// `relation_cse` only deduplicates within a single expression, and each
+/// dataflow is otherwise built in isolation. To avoid maintaining the same join
+/// of indexed collections once per index, a join is maintained by a dataflow
+/// of its own that exports it as an internal index, which index dataflows
+/// import in place of the join. Internal indexes are reference counted by the
+/// exported indexes that read them, and are dropped, along with the dataflows
+/// that maintain them, with the last of their readers.
+#[derive(Debug, Default)]
+pub struct SharedSubplans {
+    /// Shared subplans, by their optimized expression.
+    subplans: HashMap<MirRelationExpr, SharedSubplan>,
+    /// The internal indexes read by each exported index.
+    readers: HashMap<GlobalId, Vec<GlobalId>>,
+}
+
+/// A subplan maintained by an internal index.
+#[derive(Debug)]
+struct SharedSubplan {
+    /// The identifier of the collection the subplan computes.
+    view_id: GlobalId,
+    /// The identifier of the internal index.
+    index_id: GlobalId,
+    /// The description of the internal index.
+    desc: IndexDesc,
+    /// The type of the collection the subplan computes.
+    typ: RelationType,
+    /// The number of exported indexes that read the internal index.
+    readers: usize,
+}
+
+impl SharedSubplans {
+    /// Rewrites `dataflow` to share its joins with other index dataflows.
+    ///
+    /// Joins are replaced by imports of the internal indexes that maintain
+    /// them. Joins seen for the first time are exported as new internal
+    /// indexes, whose identifiers are drawn from `allocate_id`, by the returned
+    /// dataflows, which must be shipped before `dataflow`. Only dataflows that
+    /// export a single index and no sinks take part, as other dataflows are too
+    /// short lived to be worth sharing with.
+    pub fn share<F>(&mut self, dataflow: &mut DataflowDesc, mut allocate_id: F) -> Vec<DataflowDesc>
+    where
+        F: FnMut() -> GlobalId,
+    {
+        if dataflow.as_of.is_some()
+            || dataflow.index_exports.len() != 1
+            || !dataflow.sink_exports.is_empty()
+        {
+            return Vec::new();
+        }
+        let reader = dataflow.index_exports[0].0;
+        let indexed = dataflow
+            .index_imports
+            .values()
+            .map(|(desc, _typ)| desc.on_id)
+            .collect::<HashSet<_>>();
+
+        let index_imports = &dataflow.index_imports;
+        let mut shared = Vec::new();
+        let mut imports = Vec::new();
+        let mut read = Vec::new();
+        let mut replaced = HashSet::new();
+        for object in dataflow.objects_to_build.iter_mut() {
+            let object_id = object.id;
+            object.view.as_inner_mut().visit_mut_pre(&mut |expr| {
+                if !Self::shareable(expr, &indexed) {
+                    return;
+                }
+                replaced.extend(expr.global_uses());
+                if !self.subplans.contains_key(&*expr) {
+                    let view_id = allocate_id();
+                    let index_id = allocate_id();
+                    let typ = expr.typ();
+                    let desc = IndexDesc {
+                        on_id: view_id,
+                        key: typ
+                            .default_key()
+                            .into_iter()
+                            .map(MirScalarExpr::Column)
+                            .collect(),
+                    };
+                    // The subplan is maintained by a dataflow of its own, so
+                    // that it outlives the dataflow that first reads it.
+                    let mut subplan_dataflow =
+                        DataflowDesc::new(format!("shared-subplan-{}", index_id));
+                    let uses = expr.global_uses();
+                    for (id, (desc, typ)) in index_imports {
+                        if uses.contains(&desc.on_id) {
+                            subplan_dataflow.import_index(*id, desc.clone(), typ.clone(), view_id);
+                        }
+                    }
+                    subplan_dataflow.insert_view(
+                        view_id,
+                        OptimizedMirRelationExpr::declare_optimized(expr.clone()),
+                    );
+                    subplan_dataflow.export_index(index_id, desc.clone(), typ.clone());
+                    shared.push(subplan_dataflow);
+                    let subplan = SharedSubplan {
+                        view_id,
+                        index_id,
+                        desc,
+                        typ,
+                        readers: 0,
+                    };
+                    self.subplans.insert(expr.clone(), subplan);
+                }
+                let subplan = &self.subplans[&*expr];
+                if !read.contains(&subplan.index_id) {
+                    imports.push((
+                        subplan.index_id,
+                        subplan.desc.clone(),
+                        subplan.typ.clone(),
+                        object_id,
+                    ));
+                    read.push(subplan.index_id);
+                }
+                *expr = MirRelationExpr::global_get(subplan.view_id, subplan.typ.clone());
+            });
+        }
+
+        // Stop importing the indexes that only the replaced joins used.
+        let used = dataflow
+            .objects_to_build
+            .iter()
+            .flat_map(|object| object.view.global_uses())
+            .collect::<HashSet<_>>();
+        let unused = |id: &GlobalId| replaced.contains(id) && !used.contains(id);
+        dataflow
+            .index_imports
+            .retain(|_id, (desc, _typ)| !unused(&desc.on_id));
+        for dependencies in dataflow.dependent_objects.values_mut() {
+            dependencies.retain(|id| !unused(id));
+        }
+
+        for (index_id, desc, typ, requesting_view) in imports {
+            dataflow.import_index(index_id, desc, typ, requesting_view);
+        }
+        for index_id in &read {
+            for subplan in self.subplans.values_mut() {
+                if subplan.index_id == *index_id {
+                    subplan.readers += 1;
+                }
+            }
+        }
+        if !read.is_empty() {
+            self.readers.insert(reader, read);
+        }
+        shared
+    }
+
+    /// Records that the exported index `id` has been dropped.
+    ///
+    /// Returns the internal indexes that are no longer read by any exported
+    /// index, and which should be dropped in turn.
+    pub fn release(&mut self, id: GlobalId) -> Vec<GlobalId> {
+        let read = self.readers.remove(&id).unwrap_or_default();
+        let mut released = Vec::new();
+        self.subplans.retain(|_expr, subplan| {
+            if read.contains(&subplan.index_id) {
+                subplan.readers -= 1;
+                if subplan.readers == 0 {
+                    released.push(subplan.index_id);
+                    return false;
+                }
+            }
+            true
+        });
+        released
+    }
+
+    /// Returns true if `expr` is a planned join whose inputs are all indexed
+    /// collections, and which can therefore be maintained by another dataflow.
+    fn shareable(expr: &MirRelationExpr, indexed: &HashSet<GlobalId>) -> bool {
+        if !matches!(
+            expr,
+            MirRelationExpr::Join { implementation, .. }
+                if !matches!(implementation, JoinImplementation::Unimplemented)
+        ) {
+            return false;
+        }
+        let mut shareable = true;
+        expr.visit_pre(&mut |expr| {
+            if let MirRelationExpr::Get { id, .. } = expr {
+                shareable &= match id {
+                    Id::Global(id) => indexed.contains(id),
+                    Id::Local(_) => false,
+                };
+            }
+        });
+        shareable
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Recommendations of indexes, derived from the arrangements that peeks build.
+//!
+//! A peek that cannot be served from an existing index builds a dataflow. The
+//! arrangements of existing collections that the dataflow asks for, which
+//! `JoinImplementation` expresses as `ArrangeBy` operators around `Get`s, are
+//! indexes that would have spared the dataflow from building them. A peek that
+//! only reads one collection would have been served directly from any of its
+//! indexes, without a dataflow at all.
+
+use std::collections::HashMap;
+
+use mz_dataflow_types::DataflowDesc;
+use mz_expr::{GlobalId, Id, MapFilterProject, MirRelationExpr, MirScalarExpr};
+use mz_ore::str::separated;
+use mz_repr::{Datum, Row};
+
+use crate::catalog::builtin::MZ_INDEX_RECOMMENDATIONS;
+use crate::catalog::{BuiltinTableUpdate, Catalog};
+
+/// An index that a peek would have used: the collection it is on, its key,
+/// and whether the peek would have been served directly from it.
+pub type IndexRequest = (GlobalId, Vec<MirScalarExpr>, bool);
+
+/// The indexes that peeks would have used, and how many peeks would have.
+#[derive(Default)]
+pub struct IndexAdvisorState {
+    /// The number of peeks that requested each index, and the row that
+    /// currently describes it in `mz_index_recommendations`.
+    recommendations: HashMap<IndexRequest, (i64, Row)>,
+}
+
+impl IndexAdvisorState {
+    /// Records that a peek built a dataflow that requested `requests`,
+    /// returning the resulting updates to `mz_index_recommendations`.
+    pub fn record(
+        &mut self,
+        requests: Vec<IndexRequest>,
+        catalog: &Catalog,
+    ) -> Vec<BuiltinTableUpdate> {
+        let mut updates = Vec::new();
+        for request in requests {
+            let key = match catalog.try_get_by_id(request.0) {
+                Some(entry) => match entry.desc() {
+                    Ok(desc) => separated(
+                        ", ",
+                        request.1.iter().map(|expr| match expr {
+                            MirScalarExpr::Column(c) => desc.get_name(*c).as_str().to_string(),
+                            _ => expr.to_string(),
+                        }),
+                    )
+                    .to_string(),
+                    Err(_) => continue,
+                },
+                None => continue,
+            };
+            let (peeks, row) = self
+                .recommendations
+                .entry(request.clone())
+                .or_insert_with(|| (0, Row::default()));
+            if *peeks > 0 {
+                updates.push(BuiltinTableUpdate {
+                    id: MZ_INDEX_RECOMMENDATIONS.id,
+                    row: row.clone(),
+                    diff: -1,
+                });
+            }
+            *peeks += 1;
+            *row = Row::pack_slice(&[
+                Datum::String(&request.0.to_string()),
+                Datum::String(&key),
+                Datum::from(request.2),
+                Datum::Int64(*peeks),
+            ]);
+            updates.push(BuiltinTableUpdate {
+                id: MZ_INDEX_RECOMMENDATIONS.id,
+                row: row.clone(),
+                diff: 1,
+            });
+        }
+        updates
+    }
+
+    /// Forgets the recommendations that no longer apply, because the indexed
+    /// collection was dropped or because the index now exists, returning the
+    /// resulting updates to `mz_index_recommendations`.
+    pub fn refresh(&mut self, catalog: &Catalog) -> Vec<BuiltinTableUpdate> {
+        let mut updates = Vec::new();
+        self.recommendations.retain(|(on_id, key, _), (_, row)| {
+            let exists = catalog
+                .enabled_indexes()
+                .get(on_id)
+                .map(|indexes| indexes.iter().any(|(_, k)| k == key))
+                .unwrap_or(false);
+            if exists || catalog.try_get_by_id(*on_id).is_none() {
+                updates.push(BuiltinTableUpdate {
+                    id: MZ_INDEX_RECOMMENDATIONS.id,
+                    row: row.clone(),
+                    diff: -1,
+                });
+                false
+            } else {
+                true
+            }
+        });
+        updates
+    }
+}
+
+/// Determines the indexes that `dataflow`, which computes the peeked view
+/// `view_id`, would have used had they existed.
+pub fn index_requests(dataflow: &DataflowDesc, view_id: GlobalId) -> Vec<IndexRequest> {
+    // Whether an index of `id` is imported, with `key` if one is named.
+    let indexed = |id: GlobalId, key: Option<&[MirScalarExpr]>| {
+        dataflow
+            .index_imports
+            .values()
+            .any(|(desc, _typ)| desc.on_id == id && key.map_or(true, |key| desc.key == key))
+    };
+
+    let mut requests = Vec::new();
+    for object in dataflow.objects_to_build.iter() {
+        object.view.visit_pre(&mut |expr| {
+            if let MirRelationExpr::ArrangeBy { input, keys } = expr {
+                if let MirRelationExpr::Get {
+                    id: Id::Global(id), ..
+                } = &**input
+                {
+                    for key in keys {
+                        let request = (*id, key.clone(), false);
+                        if id.is_user()
+                            && !key.is_empty()
+                            && !indexed(*id, Some(key))
+                            && !requests.contains(&request)
+                        {
+                            requests.push(request);
+                        }
+                    }
+                }
+            }
+        });
+
+        // A peek that reads a single collection would have been served from
+        // any index of it, and preferably from one whose key is constrained
+        // to literals.
+        if object.id == view_id {
+            let (mfp, input) = MapFilterProject::extract_from_expression(&object.view);
+            if let MirRelationExpr::Get {
+                id: Id::Global(id),
+                typ,
+            } = input
+            {
+                if id.is_user() && !indexed(*id, None) {
+                    let mut key = (0..mfp.input_arity)
+                        .map(MirScalarExpr::Column)
+                        .filter(|expr| mfp.literal_constraints(&[expr.clone()]).is_some())
+                        .collect::<Vec<_>>();
+                    if key.is_empty() {
+                        key = typ
+                            .default_key()
+                            .into_iter()
+                            .map(MirScalarExpr::Column)
+                            .collect();
+                    }
+                    if !key.is_empty() {
+                        requests.push((*id, key, true));
+                    }
+                }
+            }
+        }
+    }
+    requests
+}
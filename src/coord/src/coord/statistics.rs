@@ -0,0 +1,139 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Size estimates for collections, used to inform cost-based planning.
+//!
+//! Estimates are assembled from the arrangement sizes reported by the dataflow
+//! workers and from the offsets that sources have ingested, as reported in
+//! their timestamp bindings.
+
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+
+use mz_dataflow_types::client::ArrangementStatistics;
+use mz_dataflow_types::sources::MzOffset;
+use mz_dataflow_types::CollectionStatistics;
+use mz_expr::{GlobalId, MirScalarExpr, PartitionId};
+use mz_repr::Timestamp;
+
+/// The most recent size information about each collection.
+pub struct CollectionStatisticsState {
+    /// Whether size information is collected at all.
+    enabled: bool,
+    /// The most recently reported sizes of each index.
+    arrangements: HashMap<GlobalId, ArrangementStatistics>,
+    /// The highest offset ingested by each partition of each source.
+    source_offsets: HashMap<GlobalId, HashMap<PartitionId, i64>>,
+    /// The estimates derived from `arrangements` and `source_offsets`.
+    collections: HashMap<GlobalId, CollectionStatistics>,
+}
+
+impl CollectionStatisticsState {
+    /// Creates an empty state, which ignores all updates unless `enabled`.
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            arrangements: HashMap::new(),
+            source_offsets: HashMap::new(),
+            collections: HashMap::new(),
+        }
+    }
+
+    /// The size estimates of all collections about which anything is known.
+    pub fn collections(&self) -> &HashMap<GlobalId, CollectionStatistics> {
+        &self.collections
+    }
+
+    /// Records new arrangement sizes, as reported by the dataflow workers.
+    pub fn update_arrangements(
+        &mut self,
+        updates: Vec<(GlobalId, ArrangementStatistics)>,
+        indexes: &HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        self.arrangements.extend(updates);
+        self.refresh(indexes);
+    }
+
+    /// Records the offsets named by new timestamp bindings.
+    pub fn update_source_offsets<'a>(
+        &mut self,
+        bindings: impl IntoIterator<Item = &'a (GlobalId, PartitionId, Timestamp, MzOffset)>,
+        indexes: &HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        for (id, pid, _ts, offset) in bindings {
+            let highest = self
+                .source_offsets
+                .entry(*id)
+                .or_default()
+                .entry(pid.clone())
+                .or_insert(0);
+            *highest = cmp::max(*highest, offset.offset);
+        }
+        self.refresh(indexes);
+    }
+
+    /// Forgets everything known about the dropped objects `ids`.
+    pub fn remove(
+        &mut self,
+        ids: &HashSet<GlobalId>,
+        indexes: &HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        self.arrangements.retain(|id, _| !ids.contains(id));
+        self.source_offsets.retain(|id, _| !ids.contains(id));
+        self.refresh(indexes);
+    }
+
+    /// Recomputes the estimates for each collection.
+    ///
+    /// Every index of a collection holds all of its records, as does the
+    /// sequence of messages ingested by a source, and each of these counts is
+    /// an upper bound of the number of records. We use the smallest of them.
+    fn refresh(&mut self, indexes: &HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>) {
+        self.collections.clear();
+        for (id, offsets) in self.source_offsets.iter() {
+            let records = offsets
+                .values()
+                .map(|offset| u64::try_from(*offset).unwrap_or(0))
+                .sum();
+            self.collections.insert(
+                *id,
+                CollectionStatistics {
+                    records,
+                    distinct_keys: Vec::new(),
+                },
+            );
+        }
+        for (on_id, on_indexes) in indexes {
+            for (index_id, keys) in on_indexes {
+                if let Some(arrangement) = self.arrangements.get(index_id) {
+                    let statistics =
+                        self.collections
+                            .entry(*on_id)
+                            .or_insert_with(|| CollectionStatistics {
+                                records: u64::MAX,
+                                distinct_keys: Vec::new(),
+                            });
+                    statistics.records = cmp::min(statistics.records, arrangement.records);
+                    statistics
+                        .distinct_keys
+                        .push((keys.clone(), arrangement.keys));
+                }
+            }
+        }
+    }
+}
@@ -53,6 +53,17 @@ pub enum CoordError {
         existing_indexes: Vec<String>,
         source_type: RematerializedSourceType,
     },
+    /// Attempted to refresh the schema of a source that sinks depend on.
+    InvalidSchemaRefresh {
+        source_name: String,
+        dependent_sinks: Vec<String>,
+    },
+    /// Attempted to refresh the schema of a source in a way that would change
+    /// the columns of views that depend on it.
+    SchemaRefreshChangesViews {
+        source_name: String,
+        dependent_views: Vec<String>,
+    },
     /// The value for the specified parameter does not have the right type.
     InvalidParameterType(&'static (dyn Var + Send + Sync)),
     /// The value of the specified parameter is incorrect
@@ -63,6 +74,8 @@ pub enum CoordError {
     },
     /// The selection value for a table mutation operation refers to an invalid object.
     InvalidTableMutationSelection,
+    /// A request to the named webhook source did not carry a valid signature.
+    InvalidWebhookSignature(String),
     /// Expression violated a column's constraint
     ConstraintViolation(NotNullViolation),
     /// The named operation cannot be run in a transaction.
@@ -181,6 +194,7 @@ impl CoordError {
                 let source_name = match source_type {
                     RematerializedSourceType::Postgres => "Postgres",
                     RematerializedSourceType::S3 => "S3 with SQS notification ",
+                    RematerializedSourceType::Webhook => "Webhook",
                     RematerializedSourceType::PersistedSource => "Persisted",
                 };
                 Some(format!(
@@ -230,6 +244,27 @@ impl CoordError {
                 INDEX {} SET ENABLED",
                 idx.quoted()
             )),
+            CoordError::InvalidSchemaRefresh {
+                dependent_sinks, ..
+            } => Some(format!(
+                "Drop the dependent sinks, refresh the schema, and then recreate the sinks: {}",
+                dependent_sinks
+                    .iter()
+                    .map(|name| name.quoted().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            CoordError::SchemaRefreshChangesViews {
+                dependent_views, ..
+            } => Some(format!(
+                "Views that select * from the source, or otherwise depend on all of its columns, \
+                must be dropped before the refresh and recreated afterwards: {}",
+                dependent_views
+                    .iter()
+                    .map(|name| name.quoted().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
             CoordError::UnknownLoginRole(_) => {
                 // TODO(benesch): this will be a bad hint when people are used
                 // to creating roles in Materialize, since they might drop the
@@ -244,6 +279,7 @@ impl CoordError {
                 let doc_page = match source_type {
                     RematerializedSourceType::Postgres => "postgres",
                     RematerializedSourceType::S3 => "text-s3",
+                    RematerializedSourceType::Webhook => "webhook",
                     RematerializedSourceType::PersistedSource => {
                         // TODO: Make this more helpful once we have documentation for persisted
                         // sources.
@@ -297,6 +333,17 @@ impl fmt::Display for CoordError {
             } => {
                 write!(f, "Cannot re-materialize source {}", base_source)
             }
+            CoordError::InvalidSchemaRefresh { source_name, .. } => write!(
+                f,
+                "cannot refresh the schema of source {} while sinks depend on it",
+                source_name.quoted()
+            ),
+            CoordError::SchemaRefreshChangesViews { source_name, .. } => write!(
+                f,
+                "cannot refresh the schema of source {} because it would change the columns \
+                of dependent views",
+                source_name.quoted()
+            ),
             CoordError::InvalidParameterType(p) => write!(
                 f,
                 "parameter {} requires a {} value",
@@ -317,6 +364,9 @@ impl fmt::Display for CoordError {
             CoordError::InvalidTableMutationSelection => {
                 f.write_str("invalid selection: operation may only refer to user-defined tables")
             }
+            CoordError::InvalidWebhookSignature(name) => {
+                write!(f, "invalid signature for webhook source {}", name.quoted())
+            }
             CoordError::ConstraintViolation(not_null_violation) => {
                 write!(f, "{}", not_null_violation)
             }
@@ -418,6 +468,7 @@ impl Error for CoordError {}
 pub enum RematerializedSourceType {
     Postgres,
     S3,
+    Webhook,
     PersistedSource,
 }
 
@@ -436,6 +487,7 @@ impl RematerializedSourceType {
             SourceConnector::External { connector, .. } => match connector {
                 ExternalSourceConnector::S3(_) => RematerializedSourceType::S3,
                 ExternalSourceConnector::Postgres(_) => RematerializedSourceType::Postgres,
+                ExternalSourceConnector::Webhook(_) => RematerializedSourceType::Webhook,
                 _ => unreachable!(),
             },
             _ => unreachable!(),
@@ -20,7 +20,7 @@ use itertools::Itertools;
 use mz_build_info::BuildInfo;
 use mz_dataflow_types::sources::{
     persistence::{EnvelopePersistDesc, SourcePersistDesc},
-    ExternalSourceConnector, SourceConnector, SourceEnvelope,
+    ExternalSourceConnector, SourceConnector, SourceEnvelope, WebhookSourceConnector,
 };
 use mz_ore::metrics::MetricsRegistry;
 use mz_persist::error::{Error, ErrorLog};
@@ -258,9 +258,38 @@ impl PersisterWithConfig {
         self.stream_name(id, pretty)
     }
 
+    /// Creates a persist stream name for a new webhook source.
+    ///
+    /// Webhook sources are written by the coordinator just like tables, so
+    /// they are persisted whenever user tables are.
+    pub fn new_webhook_persist_name(&self, id: GlobalId, pretty: &str) -> Option<String> {
+        match id {
+            // NB: This gets written down in the catalog, so it should be
+            // safe to change the naming, if necessary.
+            GlobalId::User(id) if self.config.user_table_enabled => {
+                Some(format!("user-webhook-{:?}-{}", id, pretty))
+            }
+            _ => None,
+        }
+    }
+
     /// Adds the given table to the set of tables managed by the persister.
     pub fn add_table(&mut self, id: GlobalId, table: &catalog::Table) -> Result<(), Error> {
-        let stream_name = match &table.persist_name {
+        self.add_local_input(id, table.persist_name.as_ref())
+    }
+
+    /// Adds the given webhook source to the set of tables managed by the
+    /// persister, so that it is written and sealed along with them.
+    pub fn add_webhook_source(
+        &mut self,
+        id: GlobalId,
+        connector: &WebhookSourceConnector,
+    ) -> Result<(), Error> {
+        self.add_local_input(id, connector.persisted_name.as_ref())
+    }
+
+    fn add_local_input(&mut self, id: GlobalId, stream_name: Option<&String>) -> Result<(), Error> {
+        let stream_name = match stream_name {
             Some(x) => x.clone(),
             None => return Ok(()),
         };
@@ -300,7 +329,8 @@ impl PersisterWithConfig {
         Ok(())
     }
 
-    /// Removes the given table from the set of tables managed by the persister.
+    /// Removes the given table (or webhook source) from the set of tables
+    /// managed by the persister.
     pub fn remove_table(&mut self, id: GlobalId) {
         self.table_details.remove(&id);
         self.regenerate_table_metadata();
@@ -96,7 +96,11 @@ impl Session {
         match self.transaction {
             TransactionStatus::Default | TransactionStatus::Started(_) => {
                 self.transaction = TransactionStatus::InTransaction(Transaction {
-                    pcx: PlanContext::new(wall_time, self.vars.qgm_optimizations()),
+                    pcx: PlanContext::new(
+                        wall_time,
+                        self.vars.qgm_optimizations(),
+                        self.vars.outer_join_anti_joins(),
+                    ),
                     ops: TransactionOps::None,
                     write_lock_guard: None,
                     access,
@@ -116,7 +120,11 @@ impl Session {
     pub fn start_transaction_implicit(mut self, wall_time: DateTime<Utc>, stmts: usize) -> Self {
         if let TransactionStatus::Default = self.transaction {
             let txn = Transaction {
-                pcx: PlanContext::new(wall_time, self.vars.qgm_optimizations()),
+                pcx: PlanContext::new(
+                    wall_time,
+                    self.vars.qgm_optimizations(),
+                    self.vars.outer_join_anti_joins(),
+                ),
                 ops: TransactionOps::None,
                 write_lock_guard: None,
                 access: None,
@@ -72,6 +72,13 @@ const QGM_OPTIMIZATIONS: ServerVar<bool> = ServerVar {
     description: "Enables optimizations based on a Query Graph Model (QGM) query representation.",
 };
 
+const OUTER_JOIN_ANTI_JOINS: ServerVar<bool> = ServerVar {
+    name: static_uncased_str!("outer_join_anti_joins_experimental"),
+    value: &false,
+    description:
+        "Finds the unmatched rows of outer joins with anti-joins, an experimental rewrite of their plans.",
+};
+
 const SEARCH_PATH: ServerVar<[&str]> = ServerVar {
     name: static_uncased_str!("search_path"),
     value: &["mz_catalog", "pg_catalog", "public", "mz_temp"],
@@ -154,6 +161,7 @@ pub struct Vars {
     extra_float_digits: SessionVar<i32>,
     failpoints: ServerVar<str>,
     integer_datetimes: ServerVar<bool>,
+    outer_join_anti_joins: SessionVar<bool>,
     qgm_optimizations: SessionVar<bool>,
     search_path: ServerVar<[&'static str]>,
     server_version: ServerVar<str>,
@@ -174,6 +182,7 @@ impl Default for Vars {
             extra_float_digits: SessionVar::new(&EXTRA_FLOAT_DIGITS),
             failpoints: FAILPOINTS,
             integer_datetimes: INTEGER_DATETIMES,
+            outer_join_anti_joins: SessionVar::new(&OUTER_JOIN_ANTI_JOINS),
             qgm_optimizations: SessionVar::new(&QGM_OPTIMIZATIONS),
             search_path: SEARCH_PATH,
             server_version: SERVER_VERSION,
@@ -198,6 +207,7 @@ impl Vars {
             &self.extra_float_digits,
             &self.failpoints,
             &self.integer_datetimes,
+            &self.outer_join_anti_joins,
             &self.qgm_optimizations,
             &self.search_path,
             &self.server_version,
@@ -250,6 +260,8 @@ impl Vars {
             Ok(&self.failpoints)
         } else if name == INTEGER_DATETIMES.name {
             Ok(&self.integer_datetimes)
+        } else if name == OUTER_JOIN_ANTI_JOINS.name {
+            Ok(&self.outer_join_anti_joins)
         } else if name == QGM_OPTIMIZATIONS.name {
             Ok(&self.qgm_optimizations)
         } else if name == SEARCH_PATH.name {
@@ -336,6 +348,8 @@ impl Vars {
             Ok(())
         } else if name == INTEGER_DATETIMES.name {
             Err(CoordError::ReadOnlyParameter(&INTEGER_DATETIMES))
+        } else if name == OUTER_JOIN_ANTI_JOINS.name {
+            self.outer_join_anti_joins.set(value, local)
         } else if name == QGM_OPTIMIZATIONS.name {
             self.qgm_optimizations.set(value, local)
         } else if name == SEARCH_PATH.name {
@@ -374,6 +388,7 @@ impl Vars {
             extra_float_digits,
             failpoints: _,
             integer_datetimes: _,
+            outer_join_anti_joins,
             qgm_optimizations,
             search_path: _,
             server_version: _,
@@ -385,6 +400,7 @@ impl Vars {
         } = self;
         application_name.end_transaction(action);
         database.end_transaction(action);
+        outer_join_anti_joins.end_transaction(action);
         qgm_optimizations.end_transaction(action);
         extra_float_digits.end_transaction(action);
         sql_safe_updates.end_transaction(action);
@@ -420,6 +436,12 @@ impl Vars {
         *self.integer_datetimes.value
     }
 
+    /// Returns the value of the `outer_join_anti_joins_experimental`
+    /// configuration parameter.
+    pub fn outer_join_anti_joins(&self) -> bool {
+        *self.outer_join_anti_joins.value()
+    }
+
     /// Returns the value of the `qgm_optimizations` configuration parameter.
     pub fn qgm_optimizations(&self) -> bool {
         *self.qgm_optimizations.value()
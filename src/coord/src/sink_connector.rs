@@ -7,17 +7,20 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::time::Duration;
 
 use anyhow::{anyhow, Context};
+use itertools::Itertools;
 use rdkafka::admin::{AdminClient, AdminOptions, NewTopic, ResourceSpecifier, TopicReplication};
 use rdkafka::config::ClientConfig;
 
 use mz_dataflow_types::sinks::{
-    AvroOcfSinkConnector, AvroOcfSinkConnectorBuilder, KafkaSinkConnector,
-    KafkaSinkConnectorBuilder, KafkaSinkConnectorRetention, KafkaSinkConsistencyConnector,
-    PublishedSchemaInfo, SinkConnector, SinkConnectorBuilder,
+    AvroOcfSinkConnector, AvroOcfSinkConnectorBuilder, HttpSinkConnector, HttpSinkConnectorBuilder,
+    KafkaSinkConnector, KafkaSinkConnectorBuilder, KafkaSinkConnectorRetention,
+    KafkaSinkConsistencyConnector, ParquetSinkConnector, ParquetSinkConnectorBuilder,
+    ParquetSinkLocation, PostgresSinkConnector, PostgresSinkConnectorBuilder, PublishedSchemaInfo,
+    PublishedSchemaType, SinkConnector, SinkConnectorBuilder,
 };
 use mz_expr::GlobalId;
 use mz_kafka_util::client::MzClientContext;
@@ -32,6 +35,9 @@ pub async fn build(
     match builder {
         SinkConnectorBuilder::Kafka(k) => build_kafka(k, id).await,
         SinkConnectorBuilder::AvroOcf(a) => build_avro_ocf(a, id),
+        SinkConnectorBuilder::Postgres(p) => build_postgres(p, id).await,
+        SinkConnectorBuilder::Parquet(p) => build_parquet(p, id),
+        SinkConnectorBuilder::Http(h) => build_http(h, id),
     }
 }
 
@@ -267,6 +273,30 @@ async fn build_kafka(
             Some(PublishedSchemaInfo {
                 key_schema_id,
                 value_schema_id,
+                schema_type: PublishedSchemaType::Avro,
+            })
+        }
+        mz_dataflow_types::sinks::KafkaSinkFormat::Protobuf {
+            key_schema,
+            value_schema,
+            ccsr_config,
+            ..
+        } => {
+            let ccsr = ccsr_config.build()?;
+            let (key_schema_id, value_schema_id) = publish_kafka_schemas(
+                &ccsr,
+                &topic,
+                key_schema.as_deref(),
+                Some(mz_ccsr::SchemaType::Protobuf),
+                &value_schema,
+                mz_ccsr::SchemaType::Protobuf,
+            )
+            .await
+            .context("error publishing kafka schemas for sink")?;
+            Some(PublishedSchemaInfo {
+                key_schema_id,
+                value_schema_id,
+                schema_type: PublishedSchemaType::Protobuf,
             })
         }
         mz_dataflow_types::sinks::KafkaSinkFormat::Json => None,
@@ -324,6 +354,12 @@ async fn build_kafka(
         relation_key_indices: builder.relation_key_indices,
         key_desc_and_indices: builder.key_desc_and_indices,
         value_desc: builder.value_desc,
+        partition_by: builder.partition_by,
+        headers: builder.headers,
+        coalesce_interval: builder.coalesce_interval,
+        max_messages_per_second: builder.max_messages_per_second,
+        max_bytes_per_second: builder.max_bytes_per_second,
+        max_read_ahead: builder.max_read_ahead,
         published_schema_info,
         consistency,
         exactly_once: builder.reuse_topic,
@@ -369,6 +405,110 @@ fn build_avro_ocf(
         })?;
     Ok(SinkConnector::AvroOcf(AvroOcfSinkConnector {
         path,
+        codec: builder.codec,
+        max_file_size: builder.max_file_size,
+        rotate_interval: builder.rotate_interval,
+        value_desc: builder.value_desc,
+    }))
+}
+
+async fn build_postgres(
+    builder: PostgresSinkConnectorBuilder,
+    id: GlobalId,
+) -> Result<SinkConnector, CoordError> {
+    let client = mz_postgres_util::connect(&builder.conn)
+        .await
+        .context("error connecting to postgres for sink")?;
+
+    // Check that the upstream table has all of the sinked columns, so that
+    // mistakes are reported here rather than when the first update is written.
+    let columns = builder
+        .value_desc
+        .iter_names()
+        .map(|name| mz_postgres_util::quote_identifier(name.as_str()))
+        .join(", ");
+    client
+        .simple_query(&format!(
+            "SELECT {} FROM {} LIMIT 0",
+            columns, builder.table
+        ))
+        .await
+        .with_context(|| format!("error checking upstream table {} for sink", builder.table))?;
+
+    client
+        .batch_execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (sink_id text PRIMARY KEY, ts bigint NOT NULL)",
+            builder.progress_table
+        ))
+        .await
+        .with_context(|| {
+            format!(
+                "error creating progress table {} for sink",
+                builder.progress_table
+            )
+        })?;
+
+    Ok(SinkConnector::Postgres(PostgresSinkConnector {
+        conn: builder.conn,
+        table: builder.table,
+        key_indices: builder.key_indices,
+        value_desc: builder.value_desc,
+        progress_table: builder.progress_table,
+        progress_key: format!("{}-{}", id, builder.cluster_id),
+        transitive_source_dependencies: builder.transitive_source_dependencies,
+    }))
+}
+
+fn build_parquet(
+    builder: ParquetSinkConnectorBuilder,
+    id: GlobalId,
+) -> Result<SinkConnector, CoordError> {
+    // Unless the sink reuses its directory, every incarnation of the sink
+    // writes to a fresh directory, so that the snapshot it starts with is not
+    // mixed up with earlier files.
+    let location = if builder.reuse_directory {
+        builder.location
+    } else {
+        builder
+            .location
+            .join(&format!("{}-{}", id, builder.directory_suffix))
+    };
+
+    // S3 has no directories to create, and the sink reports errors writing
+    // objects as it goes.
+    if let ParquetSinkLocation::Local(path) = &location {
+        fs::create_dir_all(path).map_err(|e| {
+            anyhow!(
+                "unable to create parquet sink directory {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+    }
+
+    Ok(SinkConnector::Parquet(ParquetSinkConnector {
         value_desc: builder.value_desc,
+        location,
+        max_file_size: builder.max_file_size,
+        exactly_once: builder.reuse_directory,
+        transitive_source_dependencies: builder.transitive_source_dependencies,
+    }))
+}
+
+fn build_http(
+    builder: HttpSinkConnectorBuilder,
+    id: GlobalId,
+) -> Result<SinkConnector, CoordError> {
+    // The endpoint is not contacted here, because a sink that is recreated
+    // while the endpoint is unavailable must still come up and retry.
+    Ok(SinkConnector::Http(HttpSinkConnector {
+        url: builder.url,
+        key_desc_and_indices: builder.key_desc_and_indices,
+        relation_key_indices: builder.relation_key_indices,
+        value_desc: builder.value_desc,
+        // The coordinator fills in the progress recorded in the catalog.
+        gate_ts: None,
+        idempotency_key_prefix: format!("{}-{}", id, builder.cluster_id),
+        transitive_source_dependencies: builder.transitive_source_dependencies,
     }))
 }
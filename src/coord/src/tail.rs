@@ -23,7 +23,9 @@ pub(crate) struct PendingTail {
     channel: mpsc::UnboundedSender<Vec<Row>>,
     /// Whether progress information should be emitted
     emit_progress: bool,
-    /// Number of columns in the output
+    /// Whether the diff of each update should be emitted
+    emit_diff: bool,
+    /// Number of columns in the rows produced by the sink
     arity: usize,
 }
 
@@ -31,15 +33,18 @@ impl PendingTail {
     /// Create a new [PendingTail].
     /// * The `channel` receives batches of finalized rows.
     /// * If `emit_progress` is true, the finalized rows are either data or progress updates
-    /// * `arity` is the arity of the sink relation.
+    /// * If `emit_diff` is true, the finalized rows include the diff of each update
+    /// * `arity` is the arity of the rows produced by the sink.
     pub(crate) fn new(
         channel: mpsc::UnboundedSender<Vec<Row>>,
         emit_progress: bool,
+        emit_diff: bool,
         arity: usize,
     ) -> Self {
         Self {
             channel,
             emit_progress,
+            emit_diff,
             arity,
         }
     }
@@ -60,7 +65,8 @@ impl PendingTail {
                     packer.push(Datum::from(numeric::Numeric::from(*&upper[0])));
                     packer.push(Datum::True);
                     // Fill in the diff column and all table columns with NULL.
-                    for _ in 0..(self.arity + 1) {
+                    let nulls = self.arity + usize::from(self.emit_diff);
+                    for _ in 0..nulls {
                         packer.push(Datum::Null);
                     }
                     let row = packer.finish_and_reuse();
@@ -92,7 +98,9 @@ impl PendingTail {
                             packer.push(Datum::False);
                         }
 
-                        packer.push(Datum::Int64(i64::cast_from(diff)));
+                        if self.emit_diff {
+                            packer.push(Datum::Int64(i64::cast_from(diff)));
+                        }
 
                         packer.extend_by_row(&row);
 
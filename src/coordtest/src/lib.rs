@@ -125,6 +125,7 @@ impl CoordTest {
             now: now.clone(),
             metrics_registry: metrics_registry.clone(),
             persister: None,
+            spill_directory: None,
         })?;
         let dataflow_client = InterceptingDataflowClient::new(dataflow_client);
 
@@ -150,6 +151,7 @@ impl CoordTest {
             metrics_registry,
             persister,
             now,
+            cost_based_join_ordering: false,
         })
         .await?;
         let coordtest = CoordTest {
@@ -256,6 +256,8 @@ impl Command {
                                 sink_exports: dataflow.sink_exports.clone(),
                                 dependent_objects: dataflow.dependent_objects.clone(),
                                 as_of: dataflow.as_of.clone(),
+                                spill: dataflow.spill,
+                                spill_threshold: dataflow.spill_threshold,
                                 debug_name: dataflow.debug_name.clone(),
                             });
                         }
@@ -497,6 +499,18 @@ pub struct TimestampBindingFeedback {
     pub bindings: Vec<(GlobalId, PartitionId, Timestamp, MzOffset)>,
 }
 
+/// Size information about a maintained arrangement, as held by one worker or,
+/// once summed, by all workers.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ArrangementStatistics {
+    /// The number of updates held in the arrangement's batches.
+    pub records: u64,
+    /// The number of keys held in the arrangement's batches.
+    ///
+    /// Keys present in more than one batch are counted once per batch.
+    pub keys: u64,
+}
+
 /// Responses that the worker/dataflow can provide back to the coordinator.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Response {
@@ -515,6 +529,9 @@ pub enum ComputeResponse {
     PeekResponse(u32, PeekResponse),
     /// The worker's next response to a specified tail.
     TailResponse(GlobalId, TailResponse),
+    /// The current sizes of the maintained arrangements that have changed
+    /// since they were last reported.
+    ArrangementStatistics(Vec<(GlobalId, ArrangementStatistics)>),
 }
 
 /// Responses that the storage nature of a worker/dataflow can provide back to the coordinator.
@@ -652,7 +669,7 @@ pub mod partitioned {
     use mz_expr::GlobalId;
     use mz_repr::Timestamp;
 
-    use super::{Client, ComputeResponse, StorageResponse};
+    use super::{ArrangementStatistics, Client, ComputeResponse, StorageResponse};
     use super::{Command, PeekResponse, Response};
 
     /// A client whose implementation is sharded across a number of other clients.
@@ -712,6 +729,8 @@ pub mod partitioned {
         uppers: HashMap<GlobalId, MutableAntichain<Timestamp>>,
         /// Pending responses for a peek; returnable once all are available.
         peek_responses: HashMap<u32, HashMap<usize, PeekResponse>>,
+        /// The most recently reported arrangement sizes, by part.
+        statistics: HashMap<GlobalId, Vec<ArrangementStatistics>>,
         /// Number of parts the state machine represents.
         parts: usize,
     }
@@ -722,6 +741,7 @@ pub mod partitioned {
             Self {
                 uppers: Default::default(),
                 peek_responses: Default::default(),
+                statistics: Default::default(),
                 parts,
             }
         }
@@ -745,6 +765,7 @@ pub mod partitioned {
                 assert!(previous.is_none(), "Protocol error: starting frontier tracking for already present identifier {:?} due to command {:?}", id, command);
             }
             for id in cease.into_iter() {
+                self.statistics.remove(&id);
                 let previous = self.uppers.remove(&id);
                 if previous.is_none() {
                     tracing::debug!("Protocol error: ceasing frontier tracking for absent identifier {:?} due to command {:?}", id, command);
@@ -824,6 +845,31 @@ pub mod partitioned {
                         None
                     }
                 }
+                Response::Compute(ComputeResponse::ArrangementStatistics(mut list)) => {
+                    // Each part reports the sizes of its own shard of the arrangement,
+                    // so we present the sum over the most recent report of every part.
+                    for (id, statistics) in list.iter_mut() {
+                        if self.uppers.contains_key(id) {
+                            let parts = self
+                                .statistics
+                                .entry(*id)
+                                .or_insert_with(|| vec![Default::default(); self.parts]);
+                            parts[shard_id] = *statistics;
+                            *statistics = ArrangementStatistics {
+                                records: parts.iter().map(|s| s.records).sum(),
+                                keys: parts.iter().map(|s| s.keys).sum(),
+                            };
+                        }
+                    }
+                    list.retain(|(id, _)| self.uppers.contains_key(id));
+                    if list.is_empty() {
+                        None
+                    } else {
+                        Some(Response::Compute(ComputeResponse::ArrangementStatistics(
+                            list,
+                        )))
+                    }
+                }
                 message => {
                     // TimestampBindings and TailResponses are mirrored out,
                     // as they do not seem to contain worker-specific information.
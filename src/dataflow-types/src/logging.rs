@@ -62,9 +62,11 @@ pub enum MaterializedLog {
     DataflowCurrent,
     DataflowDependency,
     FrontierCurrent,
+    KafkaSinkBacklog,
     KafkaSourceStatistics,
     PeekCurrent,
     PeekDuration,
+    PlanNodeOperators,
     SourceInfo,
 }
 
@@ -182,6 +184,13 @@ impl LogVariant {
                 .with_column("worker", ScalarType::Int64.nullable(false))
                 .with_column("time", ScalarType::Int64.nullable(false)),
 
+            LogVariant::Materialized(MaterializedLog::KafkaSinkBacklog) => RelationDesc::empty()
+                .with_column("sink_id", ScalarType::String.nullable(false))
+                .with_column("worker", ScalarType::Int64.nullable(false))
+                .with_column("messages", ScalarType::Int64.nullable(false))
+                .with_column("bytes", ScalarType::Int64.nullable(false))
+                .with_key(vec![0, 1]),
+
             LogVariant::Materialized(MaterializedLog::KafkaSourceStatistics) => {
                 RelationDesc::empty()
                     .with_column("source_id", ScalarType::String.nullable(false))
@@ -190,6 +199,13 @@ impl LogVariant {
                     .with_key(vec![0, 1])
             }
 
+            LogVariant::Materialized(MaterializedLog::PlanNodeOperators) => RelationDesc::empty()
+                .with_column("export_id", ScalarType::String.nullable(false))
+                .with_column("node", ScalarType::Int64.nullable(false))
+                .with_column("id", ScalarType::Int64.nullable(false))
+                .with_column("worker", ScalarType::Int64.nullable(false))
+                .with_key(vec![0, 2, 3]),
+
             LogVariant::Materialized(MaterializedLog::PeekCurrent) => RelationDesc::empty()
                 .with_column("uuid", ScalarType::String.nullable(false))
                 .with_column("worker", ScalarType::Int64.nullable(false))
@@ -247,6 +263,7 @@ impl LogVariant {
             LogVariant::Materialized(MaterializedLog::DataflowCurrent) => vec![],
             LogVariant::Materialized(MaterializedLog::DataflowDependency) => vec![],
             LogVariant::Materialized(MaterializedLog::FrontierCurrent) => vec![],
+            LogVariant::Materialized(MaterializedLog::KafkaSinkBacklog) => vec![],
             LogVariant::Materialized(MaterializedLog::KafkaSourceStatistics) => vec![(
                 LogVariant::Materialized(MaterializedLog::SourceInfo),
                 vec![(0, 1)],
@@ -254,6 +271,10 @@ impl LogVariant {
             LogVariant::Materialized(MaterializedLog::PeekCurrent) => vec![],
             LogVariant::Materialized(MaterializedLog::SourceInfo) => vec![],
             LogVariant::Materialized(MaterializedLog::PeekDuration) => vec![],
+            LogVariant::Materialized(MaterializedLog::PlanNodeOperators) => vec![(
+                LogVariant::Timely(TimelyLog::Operates),
+                vec![(2, 0), (3, 1)],
+            )],
         }
     }
 }
@@ -0,0 +1,183 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Descriptions of the plans of running dataflows, annotated with measurements
+//! of the operators that implement each plan node.
+//!
+//! Plan nodes are identified by their position in a pre-order traversal of the
+//! plans of a dataflow's objects, taken in the order in which the objects are
+//! built. This is the order in which the rendering layer visits them, and the
+//! numbering it uses when it logs the operators that implement each node.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use mz_expr::{ExprHumanizer, Id};
+use mz_ore::str::{bracketed, separated};
+
+use super::join::JoinPlan;
+use super::reduce::ReducePlan;
+use super::threshold::ThresholdPlan;
+use super::top_k::TopKPlan;
+use super::Plan;
+use crate::DataflowDescription;
+
+/// Measurements of the operators that implement one plan node, summed across
+/// workers.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PlanNodeStatistics {
+    /// The number of operators.
+    pub operators: u64,
+    /// The number of arrangements the operators maintain.
+    pub arrangements: u64,
+    /// The number of records in those arrangements.
+    pub records: u64,
+    /// The number of batches in those arrangements.
+    pub batches: u64,
+    /// The time the operators have spent scheduled.
+    pub elapsed: Duration,
+}
+
+/// The plan nodes of a dataflow, one line per node.
+#[derive(Clone, Debug)]
+pub struct PlanAnalysis {
+    /// For each object the dataflow builds, its name and, for each of its plan
+    /// nodes, the node's depth in the plan and a description of the node.
+    objects: Vec<(String, Vec<(usize, String)>)>,
+}
+
+impl PlanAnalysis {
+    /// Describes the plan nodes of `dataflow`.
+    pub fn new(dataflow: &DataflowDescription<Plan>, humanizer: &dyn ExprHumanizer) -> Self {
+        let objects = dataflow
+            .objects_to_build
+            .iter()
+            .map(|object| {
+                let name = humanizer
+                    .humanize_id(object.id)
+                    .unwrap_or_else(|| object.id.to_string());
+                let mut nodes = Vec::new();
+                describe_nodes(&object.view, 0, humanizer, &mut nodes);
+                (name, nodes)
+            })
+            .collect();
+        PlanAnalysis { objects }
+    }
+
+    /// Renders the plan nodes, annotating each with its entry in `statistics`,
+    /// if any.
+    pub fn render(&self, statistics: &HashMap<usize, PlanNodeStatistics>) -> String {
+        let mut out = String::new();
+        let mut node = 0;
+        for (name, nodes) in &self.objects {
+            writeln!(out, "{}:", name).unwrap();
+            for (depth, description) in nodes {
+                write!(
+                    out,
+                    "{:indent$}{}",
+                    "",
+                    description,
+                    indent = 2 * (depth + 1)
+                )
+                .unwrap();
+                if let Some(s) = statistics.get(&node) {
+                    write!(
+                        out,
+                        " [operators={} arrangements={} records={} batches={} elapsed={:?}]",
+                        s.operators, s.arrangements, s.records, s.batches, s.elapsed
+                    )
+                    .unwrap();
+                }
+                writeln!(out).unwrap();
+                node += 1;
+            }
+        }
+        out
+    }
+}
+
+/// Appends the descriptions of `plan` and its inputs to `nodes`, in pre-order.
+fn describe_nodes(
+    plan: &Plan,
+    depth: usize,
+    humanizer: &dyn ExprHumanizer,
+    nodes: &mut Vec<(usize, String)>,
+) {
+    let (description, inputs): (String, Vec<&Plan>) = match plan {
+        Plan::Constant { rows } => match rows {
+            Ok(rows) => (format!("Constant ({} rows)", rows.len()), vec![]),
+            Err(e) => (format!("Constant (error: {})", e), vec![]),
+        },
+        Plan::Get { id, .. } => {
+            let name = match id {
+                Id::Global(id) => humanizer.humanize_id(*id).unwrap_or_else(|| id.to_string()),
+                _ => id.to_string(),
+            };
+            (format!("Get {}", name), vec![])
+        }
+        Plan::Let { id, value, body } => (format!("Let {}", id), vec![&**value, &**body]),
+        Plan::Mfp { input, .. } => ("Map/Filter/Project".into(), vec![&**input]),
+        Plan::FlatMap { input, func, .. } => (format!("FlatMap {}", func), vec![&**input]),
+        Plan::Join { inputs, plan } => {
+            let kind = match plan {
+                JoinPlan::Linear(_) => "linear",
+                JoinPlan::Delta(_) => "delta",
+                JoinPlan::Anti(_) => "anti",
+            };
+            (format!("Join ({})", kind), inputs.iter().collect())
+        }
+        Plan::Reduce { input, plan, .. } => {
+            let kind = match plan {
+                ReducePlan::Distinct => "distinct",
+                ReducePlan::DistinctNegated => "distinct negated",
+                ReducePlan::Accumulable(_) => "accumulable",
+                ReducePlan::Hierarchical(_) => "hierarchical",
+                ReducePlan::Basic(_) => "basic",
+                ReducePlan::Collation(_) => "collation",
+            };
+            (format!("Reduce ({})", kind), vec![&**input])
+        }
+        Plan::TopK { input, top_k_plan } => {
+            let kind = match top_k_plan {
+                TopKPlan::MonotonicTop1(_) => "monotonic top 1",
+                TopKPlan::MonotonicTopK(_) => "monotonic",
+                TopKPlan::Basic(_) => "basic",
+            };
+            (format!("TopK ({})", kind), vec![&**input])
+        }
+        Plan::Negate { input } => ("Negate".into(), vec![&**input]),
+        Plan::Threshold {
+            input,
+            threshold_plan,
+        } => {
+            let kind = match threshold_plan {
+                ThresholdPlan::Basic(_) => "basic",
+                ThresholdPlan::Retractions(_) => "retractions",
+            };
+            (format!("Threshold ({})", kind), vec![&**input])
+        }
+        Plan::Union { inputs } => ("Union".into(), inputs.iter().collect()),
+        Plan::ArrangeBy { input, forms, .. } => {
+            let keys = forms
+                .arranged
+                .iter()
+                .map(|(key, _, _)| bracketed("(", ")", separated(", ", key)).to_string())
+                .collect::<Vec<_>>();
+            (
+                format!("ArrangeBy {}", separated(" ", keys)),
+                vec![&**input],
+            )
+        }
+    };
+    nodes.push((depth, description));
+    for input in inputs {
+        describe_nodes(input, depth + 1, humanizer, nodes);
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Planning of anti-joins.
+
+use serde::{Deserialize, Serialize};
+
+use mz_expr::{JoinInputMapper, MapFilterProject, MirScalarExpr};
+
+use crate::plan::join::LinearJoinPlan;
+use crate::plan::AvailableCollections;
+
+/// A plan for the execution of an anti-join.
+///
+/// An anti-join has two inputs, a collection and a collection of keys to
+/// exclude from it, with at most one record per key. Its output is the
+/// records of the former collection, each extended with the values of its key
+/// columns, less those whose keys appear in the latter collection.
+///
+/// The excluded records are found by a linear join that streams the excluded
+/// keys and looks them up in an arrangement of the collection, and are then
+/// retracted from the collection.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AntiJoinPlan {
+    /// The arrangement of the collection to read its records from.
+    pub input_key: Vec<MirScalarExpr>,
+    /// Extends the records of the collection with the values of its key
+    /// columns.
+    pub input_mfp: MapFilterProject,
+    /// The join of the collection with the excluded keys.
+    pub matches: LinearJoinPlan,
+}
+
+impl AntiJoinPlan {
+    /// Create a new anti-join plan from the required arguments.
+    ///
+    /// The collection of arity `input_arity` is looked up in its arrangement
+    /// by `key`, whose expressions must reference exactly the `columns` that
+    /// correspond, in order, to the columns of the excluded keys.
+    pub fn create_from(
+        input_arity: usize,
+        key: &[MirScalarExpr],
+        columns: &[usize],
+        available: &[AvailableCollections],
+    ) -> (Self, Vec<AvailableCollections>) {
+        let excluded_arity = columns.len();
+        let equivalences = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                vec![
+                    MirScalarExpr::Column(*column),
+                    MirScalarExpr::Column(input_arity + index),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let mut mfp_above = MapFilterProject::new(input_arity + excluded_arity);
+        let (matches, missing) = LinearJoinPlan::create_from(
+            1,
+            available[1].arbitrary_arrangement(),
+            &equivalences,
+            &[(0, key.to_vec())],
+            JoinInputMapper::new_from_input_arities(vec![input_arity, excluded_arity]),
+            &mut mfp_above,
+            available,
+        );
+
+        // Read the records of the collection from the arrangement the matches
+        // are looked up in, which either exists or is among those `missing`.
+        let (permutation, thinning) = available[0]
+            .arranged
+            .iter()
+            .chain(missing[0].arranged.iter())
+            .find(|(k, _, _)| k == key)
+            .map(|(_, permutation, thinning)| (permutation.clone(), thinning.clone()))
+            .expect("Anti-join arrangement absent");
+        let mut input_mfp = MapFilterProject::new(input_arity)
+            .project((0..input_arity).chain(columns.iter().cloned()));
+        input_mfp.permute(permutation, key.len() + thinning.len());
+
+        (
+            AntiJoinPlan {
+                input_key: key.to_vec(),
+                input_mfp,
+                matches,
+            },
+            missing,
+        )
+    }
+}
@@ -27,6 +27,7 @@
 //! output column reckoning", as is what we use when reasoning about
 //! work still available to be done on the partial join results.
 
+pub mod anti_join;
 pub mod delta_join;
 pub mod linear_join;
 
@@ -37,6 +38,7 @@ use serde::{Deserialize, Serialize};
 use mz_expr::{MapFilterProject, MirScalarExpr};
 use mz_repr::{Datum, Row, RowArena};
 
+pub use anti_join::AntiJoinPlan;
 pub use delta_join::DeltaJoinPlan;
 pub use linear_join::LinearJoinPlan;
 
@@ -47,6 +49,8 @@ pub enum JoinPlan {
     Linear(LinearJoinPlan),
     /// A join implemented by a delta join.
     Delta(DeltaJoinPlan),
+    /// A join implemented by an anti-join.
+    Anti(AntiJoinPlan),
 }
 
 /// A manual closure implementation of filtering and logic application.
@@ -11,12 +11,13 @@
 
 #![warn(missing_debug_implementations, missing_docs)]
 
+pub mod analyze;
 pub mod join;
 pub mod reduce;
 pub mod threshold;
 pub mod top_k;
 
-use join::{DeltaJoinPlan, JoinPlan, LinearJoinPlan};
+use join::{AntiJoinPlan, DeltaJoinPlan, JoinPlan, LinearJoinPlan};
 use mz_expr::permutation_for_arrangement;
 use reduce::{KeyValPlan, ReducePlan};
 use threshold::ThresholdPlan;
@@ -534,6 +535,92 @@ impl Plan {
                     AvailableCollections::new_raw(),
                 )
             }
+            MirRelationExpr::Join {
+                inputs,
+                equivalences,
+                implementation: mz_expr::JoinImplementation::AntiJoin(input, key),
+            } => {
+                let input_mapper = JoinInputMapper::new(inputs);
+                let input_arity = input_mapper.input_arity(*input);
+
+                // The other input is the union of the distinct keys of `input` and
+                // the negation of the keys to exclude, of which only the latter are
+                // planned. Their columns each correspond to a column of `input`.
+                let excluded = 1 - *input;
+                let excluded_input = match &inputs[excluded] {
+                    MirRelationExpr::Union { base, inputs } => match (&**base, &inputs[..]) {
+                        (MirRelationExpr::Negate { input }, _)
+                        | (_, [MirRelationExpr::Negate { input }]) => &**input,
+                        _ => return Err(()),
+                    },
+                    _ => return Err(()),
+                };
+                let columns = input_mapper
+                    .global_columns(excluded)
+                    .map(|column| {
+                        let column = MirScalarExpr::Column(column);
+                        let class = equivalences.iter().find(|class| class.contains(&column))?;
+                        class.iter().find_map(|expr| match expr {
+                            MirScalarExpr::Column(c) => {
+                                let (c, index) = input_mapper.map_column_to_local(*c);
+                                if index == *input {
+                                    Some(c)
+                                } else {
+                                    None
+                                }
+                            }
+                            _ => None,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(())?;
+
+                let mut plans = Vec::new();
+                let mut input_keys = Vec::new();
+                for input in [&inputs[*input], excluded_input] {
+                    let (plan, keys) = Plan::from_mir(input, arrangements)?;
+                    plans.push(plan);
+                    input_keys.push(keys);
+                }
+                let (ajp, missing) =
+                    AntiJoinPlan::create_from(input_arity, key, &columns, &input_keys);
+                for (((input_plan, input_keys), missing), arity) in plans
+                    .iter_mut()
+                    .zip(input_keys.iter())
+                    .zip(missing.into_iter())
+                    .zip([input_arity, columns.len()])
+                {
+                    if missing != Default::default() {
+                        let raw_plan = std::mem::replace(
+                            input_plan,
+                            Plan::Constant {
+                                rows: Ok(Vec::new()),
+                            },
+                        );
+                        *input_plan = raw_plan.arrange_by(missing, input_keys, arity);
+                    }
+                }
+
+                // The anti-join produces the columns of `input` first, which the
+                // join only does if `input` is its first input.
+                if *input == 1 {
+                    let excluded_arity = columns.len();
+                    let shuffle = (0..excluded_arity)
+                        .map(|c| (c, input_arity + c))
+                        .chain((0..input_arity).map(|c| (excluded_arity + c, c)))
+                        .collect();
+                    mfp.permute(shuffle, input_arity + excluded_arity);
+                }
+
+                // Return the plan, and no arrangements.
+                (
+                    Plan::Join {
+                        inputs: plans,
+                        plan: JoinPlan::Anti(ajp),
+                    },
+                    AvailableCollections::new_raw(),
+                )
+            }
             MirRelationExpr::Join {
                 inputs,
                 equivalences,
@@ -949,6 +1036,8 @@ This is not expected to cause incorrect results, but could indicate a performanc
             sink_exports: desc.sink_exports,
             dependent_objects: desc.dependent_objects,
             as_of: desc.as_of,
+            spill: desc.spill,
+            spill_threshold: desc.spill_threshold,
             debug_name: desc.debug_name,
         })
     }
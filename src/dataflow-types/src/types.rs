@@ -78,6 +78,36 @@ pub struct BuildDesc<View> {
     pub view: View,
 }
 
+/// Size estimates for a collection, used to inform cost-based planning.
+///
+/// The estimates are derived from the sizes of the arrangements and the
+/// ingested offsets of the sources that back the collection. They are upper
+/// bounds, as neither reflects records that have since been retracted.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CollectionStatistics {
+    /// The estimated number of records in the collection.
+    pub records: u64,
+    /// The estimated number of distinct values of each arrangement key of the
+    /// collection.
+    pub distinct_keys: Vec<(Vec<MirScalarExpr>, u64)>,
+}
+
+impl CollectionStatistics {
+    /// Estimates the number of records that share each value of `key`.
+    ///
+    /// When no distinct count is known for `key`, every record is assumed to
+    /// share the same value.
+    pub fn records_per_key(&self, key: &[MirScalarExpr]) -> u64 {
+        match self.distinct_keys.iter().find(|(k, _)| k == key) {
+            Some((_, distinct)) => {
+                let distinct = std::cmp::max(*distinct, 1);
+                self.records.saturating_add(distinct - 1) / distinct
+            }
+            None => self.records,
+        }
+    }
+}
+
 /// A description of an instantation of a source.
 ///
 /// This includes a description of the source, but additionally any
@@ -118,6 +148,12 @@ pub struct DataflowDescription<View> {
     /// the upper bound of `since` frontiers contributing to the dataflow.
     /// It is an error for this to be set to a frontier not beyond that default.
     pub as_of: Option<Antichain<Timestamp>>,
+    /// The index exported by the dataflow whose arrangement may spill its
+    /// state to disk, if any.
+    pub spill: Option<GlobalId>,
+    /// The number of updates at which the batches of that index spill, if
+    /// not the default.
+    pub spill_threshold: Option<usize>,
     /// Human readable name
     pub debug_name: String,
 }
@@ -133,6 +169,8 @@ impl DataflowDescription<OptimizedMirRelationExpr> {
             sink_exports: Default::default(),
             dependent_objects: Default::default(),
             as_of: Default::default(),
+            spill: None,
+            spill_threshold: None,
             debug_name: name,
         }
     }
@@ -353,9 +391,11 @@ pub mod sources {
             Protobuf(ProtobufEncoding),
             Csv(CsvEncoding),
             Regex(RegexEncoding),
+            Parquet(ParquetEncoding),
             Postgres,
             Bytes,
             Text,
+            Json,
         }
 
         impl SourceDataEncoding {
@@ -467,6 +507,14 @@ pub mod sources {
                     },
                     DataEncoding::Text => RelationDesc::empty()
                         .with_column("text", ScalarType::String.nullable(false)),
+                    DataEncoding::Json => {
+                        RelationDesc::empty().with_column("data", ScalarType::Jsonb.nullable(false))
+                    }
+                    DataEncoding::Parquet(ParquetEncoding { columns }) => columns
+                        .iter()
+                        .fold(RelationDesc::empty(), |desc, (name, ty)| {
+                            desc.with_column(name, ty.clone())
+                        }),
                     DataEncoding::Postgres => RelationDesc::empty()
                         .with_column("oid", ScalarType::Int32.nullable(false))
                         .with_column(
@@ -489,6 +537,8 @@ pub mod sources {
                     DataEncoding::Regex { .. } => "Regex",
                     DataEncoding::Csv(_) => "Csv",
                     DataEncoding::Text => "Text",
+                    DataEncoding::Json => "Json",
+                    DataEncoding::Parquet(_) => "Parquet",
                     DataEncoding::Postgres => "Postgres",
                 }
             }
@@ -554,6 +604,16 @@ pub mod sources {
         pub struct RegexEncoding {
             pub regex: mz_repr::adt::regex::Regex,
         }
+
+        /// Encoding in Apache Parquet format.
+        ///
+        /// Parquet objects are self-describing, but the columns of the source
+        /// must be fixed at creation time, so they are inferred from a sample
+        /// object during purification and recorded here.
+        #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+        pub struct ParquetEncoding {
+            pub columns: Vec<(String, ColumnType)>,
+        }
     }
 
     pub mod persistence {
@@ -1019,6 +1079,9 @@ pub mod sources {
     #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
     pub enum Compression {
         Gzip,
+        Zstd,
+        Bzip2,
+        Xz,
         None,
     }
 
@@ -1121,6 +1184,7 @@ pub mod sources {
         S3(S3SourceConnector),
         Postgres(PostgresSourceConnector),
         PubNub(PubNubSourceConnector),
+        Webhook(WebhookSourceConnector),
     }
 
     impl ExternalSourceConnector {
@@ -1190,6 +1254,10 @@ pub mod sources {
                 }
                 Self::Postgres(_) => vec![],
                 Self::PubNub(_) => vec![],
+                Self::Webhook(WebhookSourceConnector { headers, .. }) => headers
+                    .iter()
+                    .map(|header| (header.as_str(), ScalarType::String.nullable(true)))
+                    .collect(),
             }
         }
 
@@ -1203,6 +1271,7 @@ pub mod sources {
                 ExternalSourceConnector::S3(_) => Some("mz_record"),
                 ExternalSourceConnector::Postgres(_) => None,
                 ExternalSourceConnector::PubNub(_) => None,
+                ExternalSourceConnector::Webhook(_) => None,
             }
         }
 
@@ -1246,9 +1315,9 @@ pub mod sources {
                         Vec::new()
                     }
                 }
-                ExternalSourceConnector::Postgres(_) | ExternalSourceConnector::PubNub(_) => {
-                    Vec::new()
-                }
+                ExternalSourceConnector::Postgres(_)
+                | ExternalSourceConnector::PubNub(_)
+                | ExternalSourceConnector::Webhook(_) => Vec::new(),
             }
         }
 
@@ -1262,6 +1331,7 @@ pub mod sources {
                 ExternalSourceConnector::S3(_) => "s3",
                 ExternalSourceConnector::Postgres(_) => "postgres",
                 ExternalSourceConnector::PubNub(_) => "pubnub",
+                ExternalSourceConnector::Webhook(_) => "webhook",
             }
         }
 
@@ -1281,6 +1351,7 @@ pub mod sources {
                 ExternalSourceConnector::S3(_) => None,
                 ExternalSourceConnector::Postgres(_) => None,
                 ExternalSourceConnector::PubNub(_) => None,
+                ExternalSourceConnector::Webhook(_) => None,
             }
         }
 
@@ -1288,6 +1359,9 @@ pub mod sources {
             match self {
                 ExternalSourceConnector::S3(c) => c.requires_single_materialization(),
                 ExternalSourceConnector::Postgres(_) => true,
+                // Webhook requests are written by the coordinator into a
+                // single local input.
+                ExternalSourceConnector::Webhook(_) => true,
 
                 ExternalSourceConnector::Kafka(_)
                 | ExternalSourceConnector::Kinesis(_)
@@ -1324,6 +1398,45 @@ pub mod sources {
         pub channel: String,
     }
 
+    /// A source whose data is pushed to Materialize's HTTP server.
+    ///
+    /// Unlike other external sources, webhook sources are not read by the
+    /// dataflow workers. The coordinator appends each request to the source
+    /// just like it would an insert into a table.
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub struct WebhookSourceConnector {
+        /// The request headers whose values are appended to each row, in order.
+        pub headers: Vec<String>,
+        /// How to verify the signature of each request, if at all.
+        pub validation: Option<WebhookValidation>,
+        /// The name of the persistent stream that backs the source, if the
+        /// source is persisted.
+        pub persisted_name: Option<String>,
+    }
+
+    /// The prefix of the environment variables that may hold the keys of
+    /// webhook sources.
+    ///
+    /// Without it, any user could create a source that reads, or probes for,
+    /// arbitrary variables in the environment of `materialized`.
+    pub const WEBHOOK_KEY_ENV_PREFIX: &str = "MZ_WEBHOOK_";
+
+    /// Verifies that a webhook request was signed with a key that is shared
+    /// with its sender.
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub struct WebhookValidation {
+        /// The hash algorithm, as accepted by the `hmac` function.
+        pub algorithm: String,
+        /// The environment variable that holds the shared key, which starts
+        /// with [`WEBHOOK_KEY_ENV_PREFIX`].
+        ///
+        /// The key is read whenever a request arrives rather than stored, so
+        /// that it does not leak into the catalog.
+        pub key_env: String,
+        /// The request header that carries the hex-encoded signature.
+        pub header: String,
+    }
+
     #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
     pub struct S3SourceConnector {
         pub key_sources: Vec<S3KeySource>,
@@ -1484,17 +1597,21 @@ pub mod sources {
 pub mod sinks {
 
     use std::collections::BTreeMap;
+    use std::fmt;
     use std::path::PathBuf;
     use std::time::Duration;
 
     use serde::{Deserialize, Serialize};
     use timely::progress::frontier::Antichain;
     use url::Url;
+    use uuid::Uuid;
 
-    use mz_expr::GlobalId;
+    use mz_expr::{GlobalId, MirScalarExpr};
     use mz_kafka_util::KafkaAddrs;
     use mz_repr::{RelationDesc, Timestamp};
 
+    use crate::sources::AwsConfig;
+
     /// A sink for updates to a relational collection.
     #[derive(Clone, Debug, Serialize, Deserialize)]
     pub struct SinkDesc {
@@ -1522,6 +1639,9 @@ pub mod sinks {
         Kafka(KafkaSinkConnector),
         Tail(TailSinkConnector),
         AvroOcf(AvroOcfSinkConnector),
+        Postgres(PostgresSinkConnector),
+        Parquet(ParquetSinkConnector),
+        Http(HttpSinkConnector),
     }
 
     #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -1538,6 +1658,23 @@ pub mod sinks {
         pub key_desc_and_indices: Option<(RelationDesc, Vec<usize>)>,
         pub relation_key_indices: Option<Vec<usize>>,
         pub value_desc: RelationDesc,
+        /// An expression over the key that picks the partition of each message.
+        pub partition_by: Option<MirScalarExpr>,
+        /// Expressions over the key that become the headers of each message.
+        pub headers: Vec<(String, MirScalarExpr)>,
+        /// The interval over which the updates of each key are coalesced
+        /// into a single message, for upsert sinks.
+        pub coalesce_interval: Option<Duration>,
+        /// The maximum number of messages per second that the sink hands to
+        /// the producer, if any.
+        pub max_messages_per_second: Option<u64>,
+        /// The maximum number of key, value, and header bytes per second that
+        /// the sink hands to the producer, if any.
+        pub max_bytes_per_second: Option<u64>,
+        /// The maximum number of updates that the sink reads from its input
+        /// ahead of the updates that it has handed to the producer, if the
+        /// sink paces its input.
+        pub max_read_ahead: Option<u64>,
         pub published_schema_info: Option<PublishedSchemaInfo>,
         pub consistency: Option<KafkaSinkConsistencyConnector>,
         pub exactly_once: bool,
@@ -1554,12 +1691,154 @@ pub mod sinks {
     pub struct PublishedSchemaInfo {
         pub key_schema_id: Option<i32>,
         pub value_schema_id: i32,
+        pub schema_type: PublishedSchemaType,
+    }
+
+    /// The kind of schema published to the schema registry for a sink.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub enum PublishedSchemaType {
+        Avro,
+        Protobuf,
     }
 
     #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
     pub struct AvroOcfSinkConnector {
         pub value_desc: RelationDesc,
+        /// The path of the first file of the sink.
         pub path: PathBuf,
+        /// The codec that compresses the blocks of the files.
+        pub codec: AvroOcfCodec,
+        /// The number of bytes after which the sink starts a new file.
+        pub max_file_size: Option<usize>,
+        /// The duration after which the sink starts a new file.
+        pub rotate_interval: Option<Duration>,
+    }
+
+    impl AvroOcfSinkConnector {
+        /// Returns the path of the `n`-th file of the sink.
+        ///
+        /// The first file is at `path`. The files that the sink starts when it
+        /// rotates have `-<n>` appended to the file stem of `path`.
+        pub fn file_path(&self, n: usize) -> PathBuf {
+            if n == 0 {
+                return self.path.clone();
+            }
+            let mut name = self.path.file_stem().unwrap_or_default().to_owned();
+            name.push(format!("-{}", n));
+            if let Some(extension) = self.path.extension() {
+                name.push(".");
+                name.push(extension);
+            }
+            self.path.with_file_name(name)
+        }
+    }
+
+    /// The compression codec of the blocks of an Avro OCF sink.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub enum AvroOcfCodec {
+        Null,
+        Deflate,
+        Snappy,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub struct PostgresSinkConnector {
+        /// The connection string of the upstream database.
+        pub conn: String,
+        /// The quoted, possibly qualified name of the upstream table.
+        pub table: String,
+        /// The columns of `value_desc` that identify rows in the upstream table.
+        pub key_indices: Vec<usize>,
+        pub value_desc: RelationDesc,
+        /// The name of the upstream table that records the progress of the sink.
+        pub progress_table: String,
+        /// The key of the sink's row in the progress table, which combines
+        /// the sink's ID with the cluster ID, so that sinks of different
+        /// Materialize deployments do not share progress.
+        pub progress_key: String,
+        // Source dependencies for exactly-once sinks.
+        pub transitive_source_dependencies: Vec<GlobalId>,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub struct HttpSinkConnector {
+        /// The URL that the sink POSTs the updates of each timestamp to.
+        pub url: Url,
+        pub key_desc_and_indices: Option<(RelationDesc, Vec<usize>)>,
+        pub relation_key_indices: Option<Vec<usize>>,
+        pub value_desc: RelationDesc,
+        /// The latest timestamp that a previous incarnation of the sink had
+        /// delivered, if any. Updates at or before it are not sent again.
+        pub gate_ts: Option<Timestamp>,
+        /// The prefix of the idempotency key of each request, which combines
+        /// the sink's ID with the cluster ID, so that sinks of different
+        /// Materialize deployments do not send the same keys.
+        pub idempotency_key_prefix: String,
+        // Source dependencies for exactly-once sinks.
+        pub transitive_source_dependencies: Vec<GlobalId>,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub struct ParquetSinkConnector {
+        pub value_desc: RelationDesc,
+        /// The directory that holds the files and the manifest of the sink.
+        pub location: ParquetSinkLocation,
+        /// The number of bytes of row data after which the sink starts a new file.
+        pub max_file_size: usize,
+        /// Whether the sink resumes after the files listed in the manifest of
+        /// a previous incarnation.
+        pub exactly_once: bool,
+        // Source dependencies for exactly-once sinks.
+        pub transitive_source_dependencies: Vec<GlobalId>,
+    }
+
+    /// A directory that a Parquet sink writes to.
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub enum ParquetSinkLocation {
+        /// A directory on the local filesystem.
+        Local(PathBuf),
+        /// A key prefix in an S3 bucket.
+        S3 {
+            bucket: String,
+            prefix: String,
+            aws: AwsConfig,
+        },
+    }
+
+    impl ParquetSinkLocation {
+        /// Returns the location of the entry `name` in this directory.
+        pub fn join(&self, name: &str) -> ParquetSinkLocation {
+            match self {
+                ParquetSinkLocation::Local(path) => ParquetSinkLocation::Local(path.join(name)),
+                ParquetSinkLocation::S3 {
+                    bucket,
+                    prefix,
+                    aws,
+                } => {
+                    let prefix = if prefix.is_empty() || prefix.ends_with('/') {
+                        format!("{}{}", prefix, name)
+                    } else {
+                        format!("{}/{}", prefix, name)
+                    };
+                    ParquetSinkLocation::S3 {
+                        bucket: bucket.clone(),
+                        prefix,
+                        aws: aws.clone(),
+                    }
+                }
+            }
+        }
+    }
+
+    impl fmt::Display for ParquetSinkLocation {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ParquetSinkLocation::Local(path) => write!(f, "{}", path.display()),
+                ParquetSinkLocation::S3 { bucket, prefix, .. } => {
+                    write!(f, "s3://{}/{}", bucket, prefix)
+                }
+            }
+        }
     }
 
     impl SinkConnector {
@@ -1569,6 +1848,9 @@ pub mod sinks {
                 SinkConnector::AvroOcf(_) => "avro-ocf",
                 SinkConnector::Kafka(_) => "kafka",
                 SinkConnector::Tail(_) => "tail",
+                SinkConnector::Postgres(_) => "postgres",
+                SinkConnector::Parquet(_) => "parquet",
+                SinkConnector::Http(_) => "http",
             }
         }
 
@@ -1591,6 +1873,9 @@ pub mod sinks {
                 SinkConnector::Kafka(k) => k.exactly_once,
                 SinkConnector::AvroOcf(_) => false,
                 SinkConnector::Tail(_) => false,
+                SinkConnector::Postgres(_) => true,
+                SinkConnector::Parquet(p) => p.exactly_once,
+                SinkConnector::Http(_) => true,
             }
         }
 
@@ -1601,24 +1886,89 @@ pub mod sinks {
                 SinkConnector::Kafka(k) => &k.transitive_source_dependencies,
                 SinkConnector::AvroOcf(_) => &[],
                 SinkConnector::Tail(_) => &[],
+                SinkConnector::Postgres(p) => &p.transitive_source_dependencies,
+                SinkConnector::Parquet(p) => &p.transitive_source_dependencies,
+                SinkConnector::Http(h) => &h.transitive_source_dependencies,
             }
         }
     }
 
     #[derive(Default, Clone, Debug, Serialize, Deserialize)]
-    pub struct TailSinkConnector {}
+    pub struct TailSinkConnector {
+        /// The shape in which updates are presented, if not as rows with diffs.
+        pub envelope: Option<TailEnvelope>,
+    }
+
+    /// The shape in which a `TAIL` presents updates.
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub enum TailEnvelope {
+        /// One row per key and timestamp, with either the new value of the key
+        /// or a marker that the key was deleted.
+        Upsert { key_indices: Vec<usize> },
+        /// One row per change, with the value of the row before and after the
+        /// change. Changes are paired up by key, if a key is given.
+        Debezium { key_indices: Vec<usize> },
+    }
+
+    impl TailEnvelope {
+        /// Returns the indices of the columns that make up the key.
+        pub fn key_indices(&self) -> &[usize] {
+            match self {
+                TailEnvelope::Upsert { key_indices } | TailEnvelope::Debezium { key_indices } => {
+                    key_indices
+                }
+            }
+        }
+    }
 
     #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
     pub enum SinkConnectorBuilder {
         Kafka(KafkaSinkConnectorBuilder),
         AvroOcf(AvroOcfSinkConnectorBuilder),
+        Postgres(PostgresSinkConnectorBuilder),
+        Parquet(ParquetSinkConnectorBuilder),
+        Http(HttpSinkConnectorBuilder),
     }
 
     #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
     pub struct AvroOcfSinkConnectorBuilder {
         pub path: PathBuf,
         pub file_name_suffix: String,
+        pub codec: AvroOcfCodec,
+        pub max_file_size: Option<usize>,
+        pub rotate_interval: Option<Duration>,
+        pub value_desc: RelationDesc,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub struct PostgresSinkConnectorBuilder {
+        pub conn: String,
+        pub table: String,
+        pub key_indices: Vec<usize>,
+        pub value_desc: RelationDesc,
+        pub progress_table: String,
+        pub cluster_id: Uuid,
+        pub transitive_source_dependencies: Vec<GlobalId>,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub struct ParquetSinkConnectorBuilder {
+        pub location: ParquetSinkLocation,
+        pub directory_suffix: String,
+        pub reuse_directory: bool,
+        pub max_file_size: usize,
         pub value_desc: RelationDesc,
+        pub transitive_source_dependencies: Vec<GlobalId>,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub struct HttpSinkConnectorBuilder {
+        pub url: Url,
+        pub key_desc_and_indices: Option<(RelationDesc, Vec<usize>)>,
+        pub relation_key_indices: Option<Vec<usize>>,
+        pub value_desc: RelationDesc,
+        pub cluster_id: Uuid,
+        pub transitive_source_dependencies: Vec<GlobalId>,
     }
 
     #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -1630,6 +1980,23 @@ pub mod sinks {
         /// The user-specified key for the sink.
         pub key_desc_and_indices: Option<(RelationDesc, Vec<usize>)>,
         pub value_desc: RelationDesc,
+        /// An expression over the key that picks the partition of each message.
+        pub partition_by: Option<MirScalarExpr>,
+        /// Expressions over the key that become the headers of each message.
+        pub headers: Vec<(String, MirScalarExpr)>,
+        /// The interval over which the updates of each key are coalesced
+        /// into a single message, for upsert sinks.
+        pub coalesce_interval: Option<Duration>,
+        /// The maximum number of messages per second that the sink hands to
+        /// the producer, if any.
+        pub max_messages_per_second: Option<u64>,
+        /// The maximum number of key, value, and header bytes per second that
+        /// the sink hands to the producer, if any.
+        pub max_bytes_per_second: Option<u64>,
+        /// The maximum number of updates that the sink reads from its input
+        /// ahead of the updates that it has handed to the producer, if the
+        /// sink paces its input.
+        pub max_read_ahead: Option<u64>,
         pub topic_prefix: String,
         pub consistency_topic_prefix: Option<String>,
         pub consistency_format: Option<KafkaSinkFormat>,
@@ -1660,6 +2027,12 @@ pub mod sinks {
             value_schema: String,
             ccsr_config: mz_ccsr::ClientConfig,
         },
+        Protobuf {
+            schema_registry_url: Url,
+            key_schema: Option<String>,
+            value_schema: String,
+            ccsr_config: mz_ccsr::ClientConfig,
+        },
         Json,
     }
 }
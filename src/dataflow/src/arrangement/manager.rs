@@ -15,7 +15,9 @@ use std::rc::Rc;
 
 use differential_dataflow::operators::arrange::TraceAgent;
 use differential_dataflow::trace::implementations::ord::{OrdKeySpine, OrdValSpine};
-use differential_dataflow::trace::TraceReader;
+use differential_dataflow::trace::implementations::spine_fueled::Spine;
+use differential_dataflow::trace::layers::Trie;
+use differential_dataflow::trace::{BatchReader, TraceReader};
 use mz_ore::metric;
 use mz_ore::metrics::{
     CounterVec, CounterVecExt, DeleteOnDropCounter, DeleteOnDropGauge, GaugeVecExt,
@@ -27,13 +29,20 @@ use mz_dataflow_types::DataflowError;
 use mz_expr::GlobalId;
 use mz_repr::{Diff, Row, Timestamp};
 
+use crate::arrangement::spill::SpillBatch;
+
 pub type RowSpine<K, V, T, R, O = usize> = OrdValSpine<K, V, T, R, O>;
 pub type ErrSpine<K, T, R, O = usize> = OrdKeySpine<K, T, R, O>;
+/// A trace of rows whose batches can spill to disk, which only the indexes
+/// created `WITH (spill = true)` use. See [`super::spill`].
+pub type SpillRowSpine<K, V, T, R> = Spine<K, V, T, R, Rc<SpillBatch<K, V, T, R>>>;
 
 pub type TraceRowHandle<K, V, T, R> = TraceAgent<RowSpine<K, V, T, R>>;
 pub type TraceErrHandle<K, T, R> = TraceAgent<ErrSpine<K, T, R>>;
+pub type TraceSpillHandle<K, V, T, R> = TraceAgent<SpillRowSpine<K, V, T, R>>;
 pub type KeysValsHandle = TraceRowHandle<Row, Row, Timestamp, Diff>;
 pub type ErrsHandle = TraceErrHandle<DataflowError, Timestamp, Diff>;
+pub type SpillKeysValsHandle = TraceSpillHandle<Row, Row, Timestamp, Diff>;
 
 use prometheus::core::{AtomicF64, AtomicU64};
 use std::time::Instant;
@@ -196,13 +205,62 @@ impl TraceManager {
     }
 }
 
+/// The trace of the successful computations of an index, which holds batches
+/// that can spill to disk only if the index was created `WITH (spill = true)`.
+#[derive(Clone)]
+pub enum KeysValsTrace {
+    /// A trace held in memory.
+    Memory(KeysValsHandle),
+    /// A trace whose batches can spill to disk.
+    Spill(SpillKeysValsHandle),
+}
+
+impl KeysValsTrace {
+    /// Reads the upper frontier of the trace into `target`.
+    pub fn read_upper(&mut self, target: &mut Antichain<Timestamp>) {
+        match self {
+            KeysValsTrace::Memory(trace) => trace.read_upper(target),
+            KeysValsTrace::Spill(trace) => trace.read_upper(target),
+        }
+    }
+
+    /// Allows the trace to compact its logical representation to `frontier`.
+    pub fn set_logical_compaction(&mut self, frontier: AntichainRef<Timestamp>) {
+        match self {
+            KeysValsTrace::Memory(trace) => trace.set_logical_compaction(frontier),
+            KeysValsTrace::Spill(trace) => trace.set_logical_compaction(frontier),
+        }
+    }
+
+    /// Allows the trace to merge batches whose upper is not beyond `frontier`.
+    pub fn set_physical_compaction(&mut self, frontier: AntichainRef<Timestamp>) {
+        match self {
+            KeysValsTrace::Memory(trace) => trace.set_physical_compaction(frontier),
+            KeysValsTrace::Spill(trace) => trace.set_physical_compaction(frontier),
+        }
+    }
+
+    /// Calls `logic` with the number of updates and of distinct keys of each
+    /// batch of the trace.
+    pub fn map_batch_sizes<F: FnMut(usize, usize)>(&mut self, mut logic: F) {
+        match self {
+            KeysValsTrace::Memory(trace) => {
+                trace.map_batches(|batch| logic(batch.len(), batch.layer.keys()))
+            }
+            KeysValsTrace::Spill(trace) => {
+                trace.map_batches(|batch| logic(batch.len(), batch.keys()))
+            }
+        }
+    }
+}
+
 /// Bundles together traces for the successful computations (`oks`), the
 /// failed computations (`errs`), additional tokens that should share
 /// the lifetime of the bundled traces (`to_drop`), and a permutation
 /// describing how to reconstruct the original row (`permutation`).
 #[derive(Clone)]
 pub struct TraceBundle {
-    oks: KeysValsHandle,
+    oks: KeysValsTrace,
     errs: ErrsHandle,
     to_drop: Option<Rc<dyn Any>>,
 }
@@ -211,7 +269,17 @@ impl TraceBundle {
     /// Constructs a new trace bundle out of an `oks` trace and `errs` trace.
     pub fn new(oks: KeysValsHandle, errs: ErrsHandle) -> TraceBundle {
         TraceBundle {
-            oks,
+            oks: KeysValsTrace::Memory(oks),
+            errs,
+            to_drop: None,
+        }
+    }
+
+    /// Constructs a new trace bundle out of an `oks` trace whose batches can
+    /// spill to disk and an `errs` trace.
+    pub fn spilling(oks: SpillKeysValsHandle, errs: ErrsHandle) -> TraceBundle {
+        TraceBundle {
+            oks: KeysValsTrace::Spill(oks),
             errs,
             to_drop: None,
         }
@@ -229,7 +297,7 @@ impl TraceBundle {
     }
 
     /// Returns a mutable reference to the `oks` trace.
-    pub fn oks_mut(&mut self) -> &mut KeysValsHandle {
+    pub fn oks_mut(&mut self) -> &mut KeysValsTrace {
         &mut self.oks
     }
 
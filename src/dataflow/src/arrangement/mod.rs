@@ -10,4 +10,5 @@
 //! Types related to the arrangement and management of collections.
 
 pub mod manager;
-pub use manager::{KeysValsHandle, TraceManager};
+pub mod spill;
+pub use manager::{KeysValsHandle, KeysValsTrace, TraceManager};
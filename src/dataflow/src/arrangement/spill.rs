@@ -0,0 +1,1541 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Arrangement batches that can spill their updates to disk.
+//!
+//! The arrangements of indexes created `WITH (spill = true)` are made of
+//! [`SpillBatch`]es, which hold their updates either in memory, exactly as
+//! differential's `OrdValBatch` does, or in files on disk. All other
+//! arrangements are made of `OrdValBatch`es, and never spill. Such an
+//! arrangement is formed by [`arrange`], which is given the [`SpillConfig`] of
+//! the index. Its batches, and the batches that merging them produces, are
+//! written to disk once they hold at least as many updates as the threshold of
+//! that configuration, which is that of the `spill_threshold` option of the
+//! index if it is set. As merges produce the largest batches of an
+//! arrangement, they stream through their inputs and write their output to
+//! disk as they go, rather than building it in memory.
+//!
+//! A spilled batch is split into chunks at key boundaries, each of which is a
+//! file of columnar records in the Arrow format used by persist. The first key
+//! of each chunk stays in memory, so that cursors can seek to the chunk that
+//! holds a key. A chunk is read back into memory when a cursor first reaches
+//! it, and stays there until its batch is dropped, as the keys and values that
+//! cursors return borrow from the batch. Merges read their sources a chunk at
+//! a time without keeping them, so a batch that is merged away before it is
+//! read never returns to memory, and the chunks of a batch that was read are
+//! released once the batch is merged away.
+//!
+//! Failing to write a chunk to disk, or to read it back, is fatal. The updates
+//! of the chunk would otherwise be lost, and every reader of the arrangement,
+//! be it a peek, a join, a sink or another dataflow, would silently compute
+//! with wrong data from then on.
+
+use std::cell::Cell;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::difference::Semigroup;
+use differential_dataflow::hashable::Hashable;
+use differential_dataflow::lattice::Lattice;
+use differential_dataflow::logging::DifferentialEvent;
+use differential_dataflow::operators::arrange::{Arranged, TraceAgent};
+use differential_dataflow::trace::implementations::ord::OrdValBatch;
+use differential_dataflow::trace::layers::Trie;
+use differential_dataflow::trace::{
+    Batch, BatchReader, Batcher, Builder, Cursor, Description, Merger, Trace,
+};
+use differential_dataflow::Collection;
+use once_cell::unsync::OnceCell;
+use timely::communication::message::RefOrMut;
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::generic::Operator;
+use timely::dataflow::operators::Capability;
+use timely::dataflow::Scope;
+use timely::progress::frontier::AntichainRef;
+use timely::progress::Antichain;
+use timely::scheduling::Scheduler;
+use timely::worker::AsWorker;
+use tracing::warn;
+
+use mz_persist::indexed::columnar::arrow::{decode_arrow_records, encode_arrow_records};
+use mz_persist::indexed::columnar::{ColumnarRecords, ColumnarRecordsVecBuilder};
+use mz_persist_types::Codec;
+use mz_repr::{Diff, Row, Timestamp};
+
+use crate::arrangement::manager::{SpillRowSpine, TraceSpillHandle};
+
+type OrdBatcher<K, V, T, R> = <OrdValBatch<K, V, T, R> as Batch<K, V, T, R>>::Batcher;
+type OrdBuilder<K, V, T, R> = <OrdValBatch<K, V, T, R> as Batch<K, V, T, R>>::Builder;
+type OrdMerger<K, V, T, R> = <OrdValBatch<K, V, T, R> as Batch<K, V, T, R>>::Merger;
+type OrdCursor<K, V, T, R> = <OrdValBatch<K, V, T, R> as BatchReader<K, V, T, R>>::Cursor;
+
+/// The number of updates at which batches spill by default.
+pub const DEFAULT_SPILL_THRESHOLD: usize = 1 << 20;
+
+/// Prepares `directory` to hold spilled batches, removing any batches that
+/// a previous process left behind.
+pub fn prepare_directory(directory: &Path) -> Result<(), io::Error> {
+    match fs::remove_dir_all(directory) {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+        Err(e) => return Err(e),
+    }
+    fs::create_dir_all(directory)
+}
+
+/// Arranges `collection` by key into batches that spill according to
+/// `config`.
+///
+/// This is differential's `arrange_named`, except that it constructs its
+/// batcher with `config`. The batchers of differential's arrangements are
+/// constructed without arguments, and could only learn of the configuration
+/// through some ambient state.
+pub fn arrange<G>(
+    collection: &Collection<G, (Row, Row), Diff>,
+    config: Rc<SpillConfig<Row, Row, Timestamp, Diff>>,
+    name: &str,
+) -> Arranged<G, TraceSpillHandle<Row, Row, Timestamp, Diff>>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let mut reader = None;
+    let stream = {
+        let reader = &mut reader;
+        let scope = collection.scope();
+        let exchange = Exchange::new(
+            |((key, _val), _time, _diff): &((Row, Row), Timestamp, Diff)| key.hashed(),
+        );
+        collection
+            .inner
+            .unary_frontier(exchange, name, move |_capability, info| {
+                let logger = scope
+                    .log_register()
+                    .get::<DifferentialEvent>("differential/arrange");
+                let activator = Some(scope.activator_for(&info.address[..]));
+                let trace = SpillRowSpine::new(info.clone(), logger.clone(), activator);
+                let (reader_local, mut writer) = TraceAgent::new(trace, info, logger);
+                *reader = Some(reader_local);
+
+                let mut batcher = SpillBatcher::with_config(config);
+                // Capabilities for the lower envelope of the updates in `batcher`.
+                let mut capabilities = Antichain::<Capability<Timestamp>>::new();
+                let mut prev_frontier =
+                    Antichain::from_elem(<Timestamp as timely::progress::Timestamp>::minimum());
+
+                move |input, output| {
+                    input.for_each(|capability, data| {
+                        capabilities.insert(capability.retain());
+                        batcher.push_batch(data);
+                    });
+
+                    if prev_frontier.borrow() == input.frontier().frontier() {
+                        return;
+                    }
+                    if capabilities
+                        .elements()
+                        .iter()
+                        .any(|c| !input.frontier().less_equal(c.time()))
+                    {
+                        // Seal a batch for each capability that the input frontier
+                        // has passed, up to the next capability, and send it with
+                        // that capability.
+                        let mut upper = Antichain::new();
+                        for (index, capability) in capabilities.elements().iter().enumerate() {
+                            if !input.frontier().less_equal(capability.time()) {
+                                upper.clear();
+                                upper.extend(input.frontier().frontier().iter().cloned());
+                                upper.extend(
+                                    capabilities.elements()[index + 1..]
+                                        .iter()
+                                        .map(|c| c.time().clone()),
+                                );
+                                let batch = Rc::new(batcher.seal(upper.clone()));
+                                writer.insert(Rc::clone(&batch), Some(capability.time().clone()));
+                                output.session(capability).give(batch);
+                            }
+                        }
+                        // Hold capabilities only for the updates that remain.
+                        let mut new_capabilities = Antichain::new();
+                        for time in batcher.frontier().iter() {
+                            let capability = capabilities
+                                .elements()
+                                .iter()
+                                .find(|c| c.time().less_equal(time))
+                                .expect("failed to find capability");
+                            new_capabilities.insert(capability.delayed(time));
+                        }
+                        capabilities = new_capabilities;
+                    } else {
+                        // Announce progress, even without updates.
+                        batcher.seal(input.frontier().frontier().to_owned());
+                        writer.seal(input.frontier().frontier().to_owned());
+                    }
+                    prev_frontier = input.frontier().frontier().to_owned();
+                }
+            })
+    };
+    Arranged {
+        stream,
+        trace: reader.expect("constructed with the operator"),
+    }
+}
+
+/// How to spill batches of updates of type `((K, V), T, R)` to disk.
+pub struct SpillConfig<K, V, T, R> {
+    /// The directory in which to write spilled batches.
+    directory: PathBuf,
+    /// The number of updates at which a batch spills, which is also the
+    /// number of updates in each chunk of a spilled batch.
+    threshold: usize,
+    /// The number of the next file to write, shared with the configurations
+    /// derived from this one.
+    next_file: Rc<Cell<u64>>,
+    /// Encodes updates as columnar records.
+    encode: fn(&[((K, V), T, R)]) -> Vec<ColumnarRecords>,
+    /// Decodes updates from columnar records.
+    decode: fn(&[ColumnarRecords]) -> Result<Vec<((K, V), T, R)>, String>,
+}
+
+impl<K: Codec, V: Codec> SpillConfig<K, V, Timestamp, Diff> {
+    /// Returns a configuration that spills batches of at least `threshold`
+    /// updates to files in `directory`, which must exist and be reserved
+    /// for this configuration.
+    pub fn new(directory: PathBuf, threshold: usize) -> Self {
+        SpillConfig {
+            directory,
+            threshold: threshold.max(1),
+            next_file: Rc::new(Cell::new(0)),
+            encode: encode_updates::<K, V>,
+            decode: decode_updates::<K, V>,
+        }
+    }
+}
+
+impl<K, V, T, R> SpillConfig<K, V, T, R> {
+    /// Returns a configuration that spills to the same directory as this one,
+    /// at `threshold` updates if it is set.
+    pub fn derive(&self, threshold: Option<usize>) -> Self {
+        SpillConfig {
+            directory: self.directory.clone(),
+            threshold: threshold.map_or(self.threshold, |threshold| threshold.max(1)),
+            next_file: Rc::clone(&self.next_file),
+            encode: self.encode,
+            decode: self.decode,
+        }
+    }
+
+    /// Writes `updates` to a new file, returning its path.
+    fn write(&self, updates: &[((K, V), T, R)]) -> Result<PathBuf, String> {
+        let path = self
+            .directory
+            .join(format!("{}.arrow", self.next_file.get()));
+        self.next_file.set(self.next_file.get() + 1);
+        let result = File::create(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| {
+                let mut file = BufWriter::new(file);
+                encode_arrow_records(&mut file, &(self.encode)(updates))
+                    .map_err(|e| e.to_string())?;
+                file.flush().map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(()) => Ok(path),
+            Err(e) => {
+                // Remove whatever part of the file was written, if any.
+                let _ = fs::remove_file(&path);
+                Err(format!("writing spill file {}: {}", path.display(), e))
+            }
+        }
+    }
+
+    /// Reads the updates written to `path` by [`SpillConfig::write`].
+    fn read(&self, path: &Path) -> Result<Vec<((K, V), T, R)>, String> {
+        let file = File::open(path)
+            .map_err(|e| format!("opening spill file {}: {}", path.display(), e))?;
+        let records = decode_arrow_records(&mut BufReader::new(file))
+            .map_err(|e| format!("reading spill file {}: {}", path.display(), e))?;
+        (self.decode)(&records)
+            .map_err(|e| format!("decoding spill file {}: {}", path.display(), e))
+    }
+}
+
+fn encode_updates<K: Codec, V: Codec>(
+    updates: &[((K, V), Timestamp, Diff)],
+) -> Vec<ColumnarRecords> {
+    let mut builder = ColumnarRecordsVecBuilder::default();
+    let mut key_buf = Vec::new();
+    let mut val_buf = Vec::new();
+    for ((key, val), time, diff) in updates {
+        key_buf.clear();
+        val_buf.clear();
+        key.encode(&mut key_buf);
+        val.encode(&mut val_buf);
+        builder.push(((&key_buf[..], &val_buf[..]), *time, *diff));
+    }
+    builder.finish()
+}
+
+fn decode_updates<K: Codec, V: Codec>(
+    records: &[ColumnarRecords],
+) -> Result<Vec<((K, V), Timestamp, Diff)>, String> {
+    let mut updates = Vec::new();
+    for records in records {
+        for ((key, val), time, diff) in records.iter() {
+            updates.push(((K::decode(key)?, V::decode(val)?), time, diff));
+        }
+    }
+    Ok(updates)
+}
+
+/// Some of the updates of a spilled batch, sorted by key, value, and time,
+/// written to a file.
+struct Chunk<K, V, T, R> {
+    /// The first key of the chunk. No other chunk holds updates for it.
+    first_key: K,
+    /// The file that holds the updates of the chunk.
+    path: PathBuf,
+    /// The updates of the chunk, once a cursor has read them. Cursors return
+    /// references into them, so they stay in memory as long as the chunk.
+    loaded: OnceCell<Vec<((K, V), T, R)>>,
+}
+
+impl<K: Clone, V, T, R> Chunk<K, V, T, R> {
+    /// Writes `updates`, which must not be empty, to a new file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the updates cannot be written. See the module documentation.
+    fn write(config: &SpillConfig<K, V, T, R>, updates: Vec<((K, V), T, R)>) -> Self {
+        let first_key = (updates[0].0).0.clone();
+        let path = config.write(&updates).unwrap_or_else(|e| panic!("{}", e));
+        Chunk {
+            first_key,
+            path,
+            loaded: OnceCell::new(),
+        }
+    }
+
+    /// Reads the updates of the chunk from disk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the updates cannot be read back. See the module
+    /// documentation.
+    fn read(&self, config: &SpillConfig<K, V, T, R>) -> Vec<((K, V), T, R)> {
+        config.read(&self.path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Returns the updates of the chunk, reading them from disk the first
+    /// time they are asked for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the updates cannot be read back. See the module
+    /// documentation.
+    fn load(&self, config: &SpillConfig<K, V, T, R>) -> &[((K, V), T, R)] {
+        self.loaded.get_or_init(|| self.read(config))
+    }
+}
+
+impl<K, V, T, R> Drop for Chunk<K, V, T, R> {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!("removing spill file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// A batch of updates, held either in memory or on disk.
+pub enum SpillBatch<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    /// A batch held in memory, which its merges can spill if `config` is set.
+    Memory {
+        batch: OrdValBatch<K, V, T, R>,
+        config: Option<Rc<SpillConfig<K, V, T, R>>>,
+    },
+    /// A batch held on disk, in chunks sorted by their first key.
+    Disk {
+        chunks: Vec<Chunk<K, V, T, R>>,
+        len: usize,
+        keys: usize,
+        desc: Description<T>,
+        config: Rc<SpillConfig<K, V, T, R>>,
+    },
+}
+
+impl<K, V, T, R> SpillBatch<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    /// Wraps `batch`, spilling it to disk if `config` is set and it is large
+    /// enough.
+    fn from_memory(
+        batch: OrdValBatch<K, V, T, R>,
+        config: Option<Rc<SpillConfig<K, V, T, R>>>,
+    ) -> Self {
+        match config {
+            Some(config) if batch.len() >= config.threshold => {
+                let mut builder = SpillBuilder::spilling(config);
+                let mut cursor = batch.cursor();
+                while cursor.key_valid(&batch) {
+                    while cursor.val_valid(&batch) {
+                        let key = cursor.key(&batch);
+                        let val = cursor.val(&batch);
+                        cursor.map_times(&batch, |time, diff| {
+                            builder.push((key.clone(), val.clone(), time.clone(), diff.clone()));
+                        });
+                        cursor.step_val(&batch);
+                    }
+                    cursor.step_key(&batch);
+                }
+                let desc = batch.description();
+                builder.done(
+                    desc.lower().clone(),
+                    desc.upper().clone(),
+                    desc.since().clone(),
+                )
+            }
+            config => SpillBatch::Memory { batch, config },
+        }
+    }
+
+    /// The number of distinct keys in the batch.
+    pub fn keys(&self) -> usize {
+        match self {
+            SpillBatch::Memory { batch, .. } => batch.layer.keys(),
+            SpillBatch::Disk { keys, .. } => *keys,
+        }
+    }
+
+    /// The configuration with which the batch and its merges spill, if any.
+    fn config(&self) -> Option<&Rc<SpillConfig<K, V, T, R>>> {
+        match self {
+            SpillBatch::Memory { config, .. } => config.as_ref(),
+            SpillBatch::Disk { config, .. } => Some(config),
+        }
+    }
+}
+
+impl<K, V, T, R> BatchReader<K, V, T, R> for SpillBatch<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    type Cursor = SpillCursor<K, V, T, R>;
+
+    fn cursor(&self) -> Self::Cursor {
+        match self {
+            SpillBatch::Memory { batch, .. } => SpillCursor::Memory(batch.cursor()),
+            SpillBatch::Disk { .. } => SpillCursor::Disk {
+                chunk: 0,
+                key_pos: 0,
+                val_pos: 0,
+            },
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            SpillBatch::Memory { batch, .. } => batch.len(),
+            SpillBatch::Disk { len, .. } => *len,
+        }
+    }
+
+    fn description(&self) -> &Description<T> {
+        match self {
+            SpillBatch::Memory { batch, .. } => batch.description(),
+            SpillBatch::Disk { desc, .. } => desc,
+        }
+    }
+}
+
+impl<K, V, T, R> Batch<K, V, T, R> for SpillBatch<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    type Batcher = SpillBatcher<K, V, T, R>;
+    type Builder = SpillBuilder<K, V, T, R>;
+    type Merger = SpillMerger<K, V, T, R>;
+}
+
+/// A cursor over a [`SpillBatch`].
+///
+/// A cursor over a spilled batch is positioned within one of its chunks, and
+/// moves to the next chunk once it steps past the last key of the current
+/// one. All chunks are non-empty.
+pub enum SpillCursor<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    /// A cursor over an in-memory batch.
+    Memory(OrdCursor<K, V, T, R>),
+    /// A cursor over a spilled batch.
+    Disk {
+        /// The chunk that holds the current key.
+        chunk: usize,
+        /// The position in the chunk of the first update of the current key.
+        key_pos: usize,
+        /// The position in the chunk of the first update of the current value.
+        val_pos: usize,
+    },
+}
+
+/// Returns the updates of chunk `index` of `batch`, reading them from disk
+/// if no cursor has yet. Returns no updates if `index` is past the last chunk.
+fn chunk_updates<K, V, T, R>(batch: &SpillBatch<K, V, T, R>, index: usize) -> &[((K, V), T, R)]
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    match batch {
+        SpillBatch::Memory { .. } => unreachable!("chunk of an in-memory batch"),
+        SpillBatch::Disk { chunks, config, .. } => match chunks.get(index) {
+            Some(chunk) => chunk.load(config),
+            None => &[],
+        },
+    }
+}
+
+/// Returns the position of the first update after `pos` in `updates` whose
+/// key differs from the key of the update at `pos`.
+fn key_end<K: Eq, V, T, R>(updates: &[((K, V), T, R)], pos: usize) -> usize {
+    let key = &(updates[pos].0).0;
+    pos + updates[pos..].partition_point(|((k, _), _, _)| k == key)
+}
+
+/// Returns the position of the first update after `pos` in `updates` whose
+/// key or value differs from those of the update at `pos`.
+fn val_end<K: Eq, V: Eq, T, R>(updates: &[((K, V), T, R)], pos: usize) -> usize {
+    let key_val = &updates[pos].0;
+    pos + updates[pos..].partition_point(|(kv, _, _)| kv == key_val)
+}
+
+impl<K, V, T, R> Cursor<K, V, T, R> for SpillCursor<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    type Storage = SpillBatch<K, V, T, R>;
+
+    fn key_valid(&self, storage: &Self::Storage) -> bool {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                cursor.key_valid(batch)
+            }
+            (SpillCursor::Disk { chunk, .. }, SpillBatch::Disk { .. }) => {
+                !chunk_updates(storage, *chunk).is_empty()
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+
+    fn val_valid(&self, storage: &Self::Storage) -> bool {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                cursor.val_valid(batch)
+            }
+            (
+                SpillCursor::Disk {
+                    chunk,
+                    key_pos,
+                    val_pos,
+                },
+                SpillBatch::Disk { .. },
+            ) => {
+                let updates = chunk_updates(storage, *chunk);
+                !updates.is_empty() && *val_pos < key_end(updates, *key_pos)
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+
+    fn key<'a>(&self, storage: &'a Self::Storage) -> &'a K {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => cursor.key(batch),
+            (SpillCursor::Disk { chunk, key_pos, .. }, SpillBatch::Disk { .. }) => {
+                &(chunk_updates(storage, *chunk)[*key_pos].0).0
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+
+    fn val<'a>(&self, storage: &'a Self::Storage) -> &'a V {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => cursor.val(batch),
+            (SpillCursor::Disk { chunk, val_pos, .. }, SpillBatch::Disk { .. }) => {
+                &(chunk_updates(storage, *chunk)[*val_pos].0).1
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+
+    fn map_times<L: FnMut(&T, &R)>(&mut self, storage: &Self::Storage, mut logic: L) {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                cursor.map_times(batch, logic)
+            }
+            (
+                SpillCursor::Disk {
+                    chunk,
+                    key_pos,
+                    val_pos,
+                },
+                SpillBatch::Disk { .. },
+            ) => {
+                let updates = chunk_updates(storage, *chunk);
+                if !updates.is_empty() && *val_pos < key_end(updates, *key_pos) {
+                    for (_, time, diff) in &updates[*val_pos..val_end(updates, *val_pos)] {
+                        logic(time, diff);
+                    }
+                }
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+
+    fn step_key(&mut self, storage: &Self::Storage) {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                cursor.step_key(batch)
+            }
+            (
+                SpillCursor::Disk {
+                    chunk,
+                    key_pos,
+                    val_pos,
+                },
+                SpillBatch::Disk { .. },
+            ) => {
+                let updates = chunk_updates(storage, *chunk);
+                if !updates.is_empty() {
+                    *key_pos = key_end(updates, *key_pos);
+                    if *key_pos == updates.len() {
+                        *chunk += 1;
+                        *key_pos = 0;
+                    }
+                    *val_pos = *key_pos;
+                }
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+
+    fn seek_key(&mut self, storage: &Self::Storage, key: &K) {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                cursor.seek_key(batch, key)
+            }
+            (
+                SpillCursor::Disk {
+                    chunk,
+                    key_pos,
+                    val_pos,
+                },
+                SpillBatch::Disk { chunks, .. },
+            ) => {
+                // The key is in the last chunk whose first key is not greater
+                // than it, unless the cursor is already past that chunk.
+                let target = chunks
+                    .partition_point(|c| &c.first_key <= key)
+                    .saturating_sub(1);
+                if target > *chunk {
+                    *chunk = target;
+                    *key_pos = 0;
+                }
+                let updates = chunk_updates(storage, *chunk);
+                if !updates.is_empty() {
+                    *key_pos += updates[*key_pos..].partition_point(|((k, _), _, _)| k < key);
+                    if *key_pos == updates.len() {
+                        *chunk += 1;
+                        *key_pos = 0;
+                    }
+                    *val_pos = *key_pos;
+                }
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+
+    fn step_val(&mut self, storage: &Self::Storage) {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                cursor.step_val(batch)
+            }
+            (SpillCursor::Disk { chunk, val_pos, .. }, SpillBatch::Disk { .. }) => {
+                let updates = chunk_updates(storage, *chunk);
+                if *val_pos < updates.len() {
+                    *val_pos = val_end(updates, *val_pos);
+                }
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+
+    fn seek_val(&mut self, storage: &Self::Storage, val: &V) {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                cursor.seek_val(batch, val)
+            }
+            (
+                SpillCursor::Disk {
+                    chunk,
+                    key_pos,
+                    val_pos,
+                },
+                SpillBatch::Disk { .. },
+            ) => {
+                let updates = chunk_updates(storage, *chunk);
+                if !updates.is_empty() {
+                    let end = key_end(updates, *key_pos);
+                    if *val_pos < end {
+                        *val_pos +=
+                            updates[*val_pos..end].partition_point(|((_, v), _, _)| v < val);
+                    }
+                }
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+
+    fn rewind_keys(&mut self, storage: &Self::Storage) {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                cursor.rewind_keys(batch)
+            }
+            (
+                SpillCursor::Disk {
+                    chunk,
+                    key_pos,
+                    val_pos,
+                },
+                SpillBatch::Disk { .. },
+            ) => {
+                *chunk = 0;
+                *key_pos = 0;
+                *val_pos = 0;
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+
+    fn rewind_vals(&mut self, storage: &Self::Storage) {
+        match (self, storage) {
+            (SpillCursor::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                cursor.rewind_vals(batch)
+            }
+            (
+                SpillCursor::Disk {
+                    key_pos, val_pos, ..
+                },
+                SpillBatch::Disk { .. },
+            ) => {
+                *val_pos = *key_pos;
+            }
+            _ => unreachable!("cursor does not match its batch"),
+        }
+    }
+}
+
+/// Forms [`SpillBatch`]es from unsorted updates, spilling them if it was
+/// constructed with a [`SpillConfig`].
+pub struct SpillBatcher<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    batcher: OrdBatcher<K, V, T, R>,
+    config: Option<Rc<SpillConfig<K, V, T, R>>>,
+}
+
+impl<K, V, T, R> SpillBatcher<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    /// Returns a batcher whose batches spill according to `config`.
+    fn with_config(config: Rc<SpillConfig<K, V, T, R>>) -> Self {
+        SpillBatcher {
+            batcher: <OrdBatcher<K, V, T, R> as Batcher<_, _, _, _, OrdValBatch<_, _, _, _>>>::new(
+            ),
+            config: Some(config),
+        }
+    }
+}
+
+impl<K, V, T, R> Batcher<K, V, T, R, SpillBatch<K, V, T, R>> for SpillBatcher<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    fn new() -> Self {
+        SpillBatcher {
+            batcher: <OrdBatcher<K, V, T, R> as Batcher<_, _, _, _, OrdValBatch<_, _, _, _>>>::new(
+            ),
+            config: None,
+        }
+    }
+
+    fn push_batch(&mut self, batch: RefOrMut<Vec<((K, V), T, R)>>) {
+        <OrdBatcher<K, V, T, R> as Batcher<_, _, _, _, OrdValBatch<_, _, _, _>>>::push_batch(
+            &mut self.batcher,
+            batch,
+        )
+    }
+
+    fn seal(&mut self, upper: Antichain<T>) -> SpillBatch<K, V, T, R> {
+        let batch = <OrdBatcher<K, V, T, R> as Batcher<_, _, _, _, OrdValBatch<_, _, _, _>>>::seal(
+            &mut self.batcher,
+            upper,
+        );
+        SpillBatch::from_memory(batch, self.config.clone())
+    }
+
+    fn frontier(&mut self) -> AntichainRef<T> {
+        <OrdBatcher<K, V, T, R> as Batcher<_, _, _, _, OrdValBatch<_, _, _, _>>>::frontier(
+            &mut self.batcher,
+        )
+    }
+}
+
+/// Builds [`SpillBatch`]es from sorted updates.
+///
+/// Builders created through the [`Builder`] trait build in-memory batches.
+/// Those of merges that can spill write chunks to disk as they go.
+pub enum SpillBuilder<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    /// Builds an in-memory batch.
+    Memory(OrdBuilder<K, V, T, R>),
+    /// Builds a batch that spills once it reaches the threshold of `config`.
+    Spilling {
+        config: Rc<SpillConfig<K, V, T, R>>,
+        /// The updates not yet written to a chunk.
+        pending: Vec<((K, V), T, R)>,
+        /// The chunks written so far.
+        chunks: Vec<Chunk<K, V, T, R>>,
+        /// The number of updates written to chunks so far.
+        len: usize,
+        /// The number of distinct keys pushed so far.
+        keys: usize,
+    },
+}
+
+impl<K, V, T, R> SpillBuilder<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    /// Returns a builder that spills according to `config`.
+    fn spilling(config: Rc<SpillConfig<K, V, T, R>>) -> Self {
+        SpillBuilder::Spilling {
+            config,
+            pending: Vec::new(),
+            chunks: Vec::new(),
+            len: 0,
+            keys: 0,
+        }
+    }
+}
+
+impl<K, V, T, R> Builder<K, V, T, R, SpillBatch<K, V, T, R>> for SpillBuilder<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    fn new() -> Self {
+        SpillBuilder::Memory(<OrdBuilder<K, V, T, R> as Builder<
+            _,
+            _,
+            _,
+            _,
+            OrdValBatch<_, _, _, _>,
+        >>::new())
+    }
+
+    fn with_capacity(cap: usize) -> Self {
+        SpillBuilder::Memory(<OrdBuilder<K, V, T, R> as Builder<
+            _,
+            _,
+            _,
+            _,
+            OrdValBatch<_, _, _, _>,
+        >>::with_capacity(cap))
+    }
+
+    fn push(&mut self, (key, val, time, diff): (K, V, T, R)) {
+        match self {
+            SpillBuilder::Memory(builder) => builder.push((key, val, time, diff)),
+            SpillBuilder::Spilling {
+                config,
+                pending,
+                chunks,
+                len,
+                keys,
+            } => {
+                // Chunks end at key boundaries, so that each key is in one.
+                // Chunks are only written at key boundaries, so a key that
+                // starts a new chunk is always new.
+                let at_key_boundary = pending.last().map_or(true, |((k, _), _, _)| *k != key);
+                if at_key_boundary {
+                    *keys += 1;
+                }
+                if pending.len() >= config.threshold && at_key_boundary {
+                    *len += pending.len();
+                    chunks.push(Chunk::write(config, std::mem::take(pending)));
+                }
+                pending.push(((key, val), time, diff));
+            }
+        }
+    }
+
+    fn done(
+        self,
+        lower: Antichain<T>,
+        upper: Antichain<T>,
+        since: Antichain<T>,
+    ) -> SpillBatch<K, V, T, R> {
+        match self {
+            SpillBuilder::Memory(builder) => SpillBatch::Memory {
+                batch: builder.done(lower, upper, since),
+                config: None,
+            },
+            SpillBuilder::Spilling {
+                config,
+                pending,
+                mut chunks,
+                mut len,
+                keys,
+            } => {
+                if chunks.is_empty() && pending.len() < config.threshold {
+                    let mut builder = <OrdBuilder<K, V, T, R> as Builder<
+                        _,
+                        _,
+                        _,
+                        _,
+                        OrdValBatch<_, _, _, _>,
+                    >>::with_capacity(pending.len());
+                    for ((key, val), time, diff) in pending {
+                        builder.push((key, val, time, diff));
+                    }
+                    return SpillBatch::Memory {
+                        batch: builder.done(lower, upper, since),
+                        config: Some(config),
+                    };
+                }
+                if !pending.is_empty() {
+                    len += pending.len();
+                    chunks.push(Chunk::write(&config, pending));
+                }
+                SpillBatch::Disk {
+                    chunks,
+                    len,
+                    keys,
+                    desc: Description::new(lower, upper, since),
+                    config,
+                }
+            }
+        }
+    }
+}
+
+/// Merges [`SpillBatch`]es.
+///
+/// Merges of batches that cannot spill are those of `OrdValBatch`. Merges of
+/// batches that can spill stream through their sources, reading one chunk of
+/// each spilled source at a time, and spill their output as it grows.
+pub enum SpillMerger<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    /// The merge of two in-memory batches that cannot spill.
+    Memory(OrdMerger<K, V, T, R>),
+    /// The merge of two batches that can spill.
+    Spilling {
+        sources: [MergeSource<K, V, T, R>; 2],
+        frontier: Option<Antichain<T>>,
+        builder: SpillBuilder<K, V, T, R>,
+        desc: Description<T>,
+    },
+}
+
+/// The position of a [`SpillMerger`] in one of its sources.
+pub enum MergeSource<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    /// The position in an in-memory source.
+    Memory(OrdCursor<K, V, T, R>),
+    /// The position in a spilled source: the updates of the current chunk,
+    /// the position of the first of them that remains to be merged, and the
+    /// next chunk to read.
+    Disk {
+        updates: Vec<((K, V), T, R)>,
+        pos: usize,
+        next_chunk: usize,
+    },
+}
+
+impl<K, V, T, R> MergeSource<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    fn new(source: &SpillBatch<K, V, T, R>) -> Self {
+        match source {
+            SpillBatch::Memory { batch, .. } => MergeSource::Memory(batch.cursor()),
+            SpillBatch::Disk { .. } => MergeSource::Disk {
+                updates: Vec::new(),
+                pos: 0,
+                next_chunk: 0,
+            },
+        }
+    }
+
+    /// Returns the key and value that the source is positioned at, if any.
+    fn peek(&mut self, source: &SpillBatch<K, V, T, R>) -> Option<(K, V)> {
+        match (self, source) {
+            (MergeSource::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                if cursor.key_valid(batch) && cursor.val_valid(batch) {
+                    Some((cursor.key(batch).clone(), cursor.val(batch).clone()))
+                } else {
+                    None
+                }
+            }
+            (
+                MergeSource::Disk {
+                    updates,
+                    pos,
+                    next_chunk,
+                },
+                SpillBatch::Disk { chunks, config, .. },
+            ) => {
+                // Read each chunk without keeping it in the batch, and let go of
+                // it before reading the next.
+                while *pos == updates.len() && *next_chunk < chunks.len() {
+                    *updates = Vec::new();
+                    *updates = chunks[*next_chunk].read(config);
+                    *pos = 0;
+                    *next_chunk += 1;
+                }
+                updates.get(*pos).map(|(key_val, _, _)| key_val.clone())
+            }
+            _ => unreachable!("merge source does not match its batch"),
+        }
+    }
+
+    /// Moves the times and differences of the key and value that the source
+    /// is positioned at into `times`, and advances the source past them.
+    fn drain(&mut self, source: &SpillBatch<K, V, T, R>, times: &mut Vec<(T, R)>) {
+        match (self, source) {
+            (MergeSource::Memory(cursor), SpillBatch::Memory { batch, .. }) => {
+                cursor.map_times(batch, |time, diff| times.push((time.clone(), diff.clone())));
+                cursor.step_val(batch);
+                if !cursor.val_valid(batch) {
+                    cursor.step_key(batch);
+                }
+            }
+            (MergeSource::Disk { updates, pos, .. }, SpillBatch::Disk { .. }) => {
+                if *pos < updates.len() {
+                    let end = val_end(updates, *pos);
+                    for (_, time, diff) in &updates[*pos..end] {
+                        times.push((time.clone(), diff.clone()));
+                    }
+                    *pos = end;
+                }
+            }
+            _ => unreachable!("merge source does not match its batch"),
+        }
+    }
+}
+
+impl<K, V, T, R> Merger<K, V, T, R, SpillBatch<K, V, T, R>> for SpillMerger<K, V, T, R>
+where
+    K: Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Lattice + timely::progress::Timestamp,
+    R: Semigroup,
+    OrdValBatch<K, V, T, R>: Batch<K, V, T, R>,
+{
+    fn new(
+        source1: &SpillBatch<K, V, T, R>,
+        source2: &SpillBatch<K, V, T, R>,
+        compaction_frontier: Option<AntichainRef<T>>,
+    ) -> Self {
+        match (source1, source2) {
+            (
+                SpillBatch::Memory {
+                    batch: batch1,
+                    config: None,
+                },
+                SpillBatch::Memory {
+                    batch: batch2,
+                    config: None,
+                },
+            ) => SpillMerger::Memory(<OrdMerger<K, V, T, R> as Merger<
+                _,
+                _,
+                _,
+                _,
+                OrdValBatch<_, _, _, _>,
+            >>::new(batch1, batch2, compaction_frontier)),
+            _ => {
+                assert!(source1.upper() == source2.lower());
+                let config = source1
+                    .config()
+                    .or_else(|| source2.config())
+                    .expect("one of the sources can spill")
+                    .clone();
+                let mut since = source1
+                    .description()
+                    .since()
+                    .join(source2.description().since());
+                if let Some(frontier) = compaction_frontier {
+                    since = since.join(&frontier.to_owned());
+                }
+                SpillMerger::Spilling {
+                    sources: [MergeSource::new(source1), MergeSource::new(source2)],
+                    frontier: compaction_frontier.map(|frontier| frontier.to_owned()),
+                    builder: SpillBuilder::spilling(config),
+                    desc: Description::new(source1.lower().clone(), source2.upper().clone(), since),
+                }
+            }
+        }
+    }
+
+    fn work(
+        &mut self,
+        source1: &SpillBatch<K, V, T, R>,
+        source2: &SpillBatch<K, V, T, R>,
+        fuel: &mut isize,
+    ) {
+        match self {
+            SpillMerger::Memory(merger) => match (source1, source2) {
+                (
+                    SpillBatch::Memory { batch: batch1, .. },
+                    SpillBatch::Memory { batch: batch2, .. },
+                ) => merger.work(batch1, batch2, fuel),
+                _ => unreachable!("merger does not match its sources"),
+            },
+            SpillMerger::Spilling {
+                sources,
+                frontier,
+                builder,
+                ..
+            } => {
+                let mut times = Vec::new();
+                while *fuel > 0 {
+                    let next1 = sources[0].peek(source1);
+                    let next2 = sources[1].peek(source2);
+                    let (key, val) = match (&next1, &next2) {
+                        (None, None) => break,
+                        (Some(next), None) | (None, Some(next)) => next.clone(),
+                        (Some(next1), Some(next2)) => std::cmp::min(next1, next2).clone(),
+                    };
+                    if next1
+                        .as_ref()
+                        .map_or(false, |(k, v)| *k == key && *v == val)
+                    {
+                        sources[0].drain(source1, &mut times);
+                    }
+                    if next2
+                        .as_ref()
+                        .map_or(false, |(k, v)| *k == key && *v == val)
+                    {
+                        sources[1].drain(source2, &mut times);
+                    }
+                    *fuel -= isize::try_from(times.len()).unwrap_or(isize::MAX);
+                    if let Some(frontier) = frontier {
+                        for (time, _) in times.iter_mut() {
+                            time.advance_by(frontier.borrow());
+                        }
+                    }
+                    consolidate(&mut times);
+                    for (time, diff) in times.drain(..) {
+                        builder.push((key.clone(), val.clone(), time, diff));
+                    }
+                }
+            }
+        }
+    }
+
+    fn done(self) -> SpillBatch<K, V, T, R> {
+        match self {
+            SpillMerger::Memory(merger) => SpillBatch::Memory {
+                batch: merger.done(),
+                config: None,
+            },
+            SpillMerger::Spilling { builder, desc, .. } => builder.done(
+                desc.lower().clone(),
+                desc.upper().clone(),
+                desc.since().clone(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    type Updates = Vec<((String, String), Timestamp, Diff)>;
+
+    fn updates(
+        keys: std::ops::Range<usize>,
+        vals: &[&str],
+        times: &[Timestamp],
+        diff: Diff,
+    ) -> Updates {
+        let mut updates = Vec::new();
+        for key in keys {
+            for val in vals {
+                for time in times {
+                    updates.push(((format!("k{:02}", key), val.to_string()), *time, diff));
+                }
+            }
+        }
+        updates
+    }
+
+    fn build(
+        updates: &Updates,
+        config: Rc<SpillConfig<String, String, Timestamp, Diff>>,
+        lower: Timestamp,
+        upper: Timestamp,
+    ) -> SpillBatch<String, String, Timestamp, Diff> {
+        let mut builder = SpillBuilder::spilling(config);
+        for ((key, val), time, diff) in updates.iter().cloned() {
+            builder.push((key, val, time, diff));
+        }
+        builder.done(
+            Antichain::from_elem(lower),
+            Antichain::from_elem(upper),
+            Antichain::from_elem(0),
+        )
+    }
+
+    fn contents(batch: &SpillBatch<String, String, Timestamp, Diff>) -> Updates {
+        let mut contents = Vec::new();
+        let mut cursor = batch.cursor();
+        while cursor.key_valid(batch) {
+            while cursor.val_valid(batch) {
+                let key_val = (cursor.key(batch).clone(), cursor.val(batch).clone());
+                cursor.map_times(batch, |time, diff| {
+                    contents.push((key_val.clone(), *time, *diff));
+                });
+                cursor.step_val(batch);
+            }
+            cursor.step_key(batch);
+        }
+        contents
+    }
+
+    /// Returns the contents of the merge of `updates1` and `updates2` with
+    /// all times advanced to `time`.
+    fn merged(updates1: Updates, updates2: Updates, time: Timestamp) -> Updates {
+        let mut merged = BTreeMap::new();
+        for (key_val, _time, diff) in updates1.into_iter().chain(updates2) {
+            *merged.entry(key_val).or_insert(0) += diff;
+        }
+        merged
+            .into_iter()
+            .filter(|(_, diff)| *diff != 0)
+            .map(|(key_val, diff)| (key_val, time, diff))
+            .collect()
+    }
+
+    /// Returns which chunks of a spilled batch are in memory.
+    fn loaded(batch: &SpillBatch<String, String, Timestamp, Diff>) -> Vec<bool> {
+        match batch {
+            SpillBatch::Disk { chunks, .. } => chunks
+                .iter()
+                .map(|chunk| chunk.loaded.get().is_some())
+                .collect(),
+            SpillBatch::Memory { .. } => panic!("batch did not spill"),
+        }
+    }
+
+    #[test]
+    fn spilled_batch_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Rc::new(SpillConfig::new(dir.path().to_path_buf(), 3));
+        let updates = updates(0..10, &["v0", "v1"], &[1, 2], 1);
+        let batch = build(&updates, config, 0, 10);
+        match &batch {
+            SpillBatch::Disk { chunks, .. } => assert_eq!(chunks.len(), 10),
+            SpillBatch::Memory { .. } => panic!("batch did not spill"),
+        }
+        assert_eq!(batch.len(), 40);
+        assert_eq!(batch.keys(), 10);
+        assert_eq!(contents(&batch), updates);
+
+        drop(batch);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn spilled_batch_seeks() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Rc::new(SpillConfig::new(dir.path().to_path_buf(), 3));
+        let batch = build(&updates(0..10, &["v0", "v1"], &[1], 1), config, 0, 10);
+        let mut cursor = batch.cursor();
+
+        cursor.seek_key(&batch, &"k05".to_string());
+        assert_eq!(cursor.key(&batch), "k05");
+        cursor.seek_val(&batch, &"v1".to_string());
+        assert_eq!(cursor.val(&batch), "v1");
+        cursor.step_val(&batch);
+        assert!(!cursor.val_valid(&batch));
+        cursor.rewind_vals(&batch);
+        assert_eq!(cursor.val(&batch), "v0");
+
+        // Seeking a missing key moves to the next one, and seeking never
+        // moves backwards.
+        cursor.seek_key(&batch, &"k055".to_string());
+        assert_eq!(cursor.key(&batch), "k06");
+        cursor.seek_key(&batch, &"k03".to_string());
+        assert_eq!(cursor.key(&batch), "k06");
+        cursor.seek_key(&batch, &"k99".to_string());
+        assert!(!cursor.key_valid(&batch));
+
+        cursor.rewind_keys(&batch);
+        assert_eq!(cursor.key(&batch), "k00");
+    }
+
+    #[test]
+    fn spilled_batches_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Rc::new(SpillConfig::new(dir.path().to_path_buf(), 3));
+        let updates1 = updates(0..10, &["v0", "v1"], &[1, 2], 1);
+        let updates2 = updates(5..15, &["v0"], &[6], -2);
+        let batch1 = build(&updates1, Rc::clone(&config), 0, 5);
+        let batch2 = build(&updates2, config, 5, 10);
+
+        let frontier = Antichain::from_elem(10);
+        let mut merger = SpillMerger::new(&batch1, &batch2, Some(frontier.borrow()));
+        let mut fuel = isize::MAX;
+        merger.work(&batch1, &batch2, &mut fuel);
+        let merged_batch = merger.done();
+
+        assert_eq!(contents(&merged_batch), merged(updates1, updates2, 10));
+        assert_eq!(merged_batch.description().lower(), &Antichain::from_elem(0));
+        assert_eq!(
+            merged_batch.description().upper(),
+            &Antichain::from_elem(10)
+        );
+        assert_eq!(
+            merged_batch.description().since(),
+            &Antichain::from_elem(10)
+        );
+    }
+
+    #[test]
+    fn spilled_chunks_are_read_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Rc::new(SpillConfig::new(dir.path().to_path_buf(), 3));
+        let batch = build(&updates(0..10, &["v0", "v1"], &[1, 2], 1), config, 0, 10);
+
+        // Cursors read chunks only once they reach them.
+        let mut cursor = batch.cursor();
+        assert_eq!(loaded(&batch), vec![false; 10]);
+        cursor.seek_key(&batch, &"k05".to_string());
+        assert_eq!(cursor.key(&batch), "k05");
+        assert_eq!(loaded(&batch), (0..10).map(|i| i == 5).collect::<Vec<_>>());
+
+        // Keys and values remain valid after the cursor leaves their chunk.
+        let key = cursor.key(&batch);
+        let val = cursor.val(&batch);
+        cursor.step_key(&batch);
+        cursor.rewind_keys(&batch);
+        assert_eq!(cursor.key(&batch), "k00");
+        assert_eq!((key.as_str(), val.as_str()), ("k05", "v0"));
+        assert_eq!(
+            loaded(&batch),
+            (0..10).map(|i| i == 0 || i == 5).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn merges_leave_chunks_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Rc::new(SpillConfig::new(dir.path().to_path_buf(), 3));
+        let updates1 = updates(0..10, &["v0", "v1"], &[1, 2], 1);
+        let updates2 = updates(5..15, &["v0"], &[6], -2);
+        let batch1 = build(&updates1, Rc::clone(&config), 0, 5);
+        let batch2 = build(&updates2, config, 5, 10);
+
+        let mut merger = SpillMerger::new(&batch1, &batch2, None);
+        let mut fuel = isize::MAX;
+        merger.work(&batch1, &batch2, &mut fuel);
+        let merged_batch = merger.done();
+
+        assert_eq!(loaded(&batch1), vec![false; 10]);
+        assert_eq!(loaded(&batch2), vec![false; 4]);
+        assert!(loaded(&merged_batch).iter().all(|loaded| !loaded));
+    }
+
+    #[test]
+    #[should_panic(expected = "opening spill file")]
+    fn unreadable_chunks_are_fatal() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Rc::new(SpillConfig::new(dir.path().to_path_buf(), 3));
+        let batch = build(&updates(0..10, &["v0", "v1"], &[1, 2], 1), config, 0, 10);
+        let mut cursor = batch.cursor();
+        assert_eq!(cursor.key(&batch), "k00");
+
+        for entry in fs::read_dir(dir.path()).unwrap() {
+            fs::remove_file(entry.unwrap().path()).unwrap();
+        }
+
+        // The chunk that the cursor has read remains readable, but the next
+        // one cannot be read back.
+        cursor.step_val(&batch);
+        assert_eq!(cursor.val(&batch), "v1");
+        cursor.step_key(&batch);
+        cursor.key_valid(&batch);
+    }
+
+    #[test]
+    #[should_panic(expected = "writing spill file")]
+    fn unwritable_chunks_are_fatal() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Rc::new(SpillConfig::new(dir.path().join("missing"), 3));
+        build(&updates(0..10, &["v0", "v1"], &[1, 2], 1), config, 0, 10);
+    }
+
+    #[test]
+    fn derived_configs_spill_at_their_threshold() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SpillConfig::new(dir.path().to_path_buf(), DEFAULT_SPILL_THRESHOLD);
+        let updates = updates(0..10, &["v0", "v1"], &[1, 2], 1);
+
+        let batch = build(&updates, Rc::new(config.derive(None)), 0, 10);
+        assert!(matches!(batch, SpillBatch::Memory { .. }));
+
+        // Derived configurations share the directory without overwriting
+        // each other's files.
+        let batch1 = build(&updates, Rc::new(config.derive(Some(3))), 0, 10);
+        let batch2 = build(&updates, Rc::new(config.derive(Some(7))), 0, 10);
+        assert_eq!(loaded(&batch1).len(), 10);
+        assert_eq!(loaded(&batch2).len(), 5);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 15);
+        assert_eq!(contents(&batch1), updates);
+        assert_eq!(contents(&batch2), updates);
+    }
+
+    #[test]
+    fn arrangements_without_spilling_stay_in_memory() {
+        fn seal(
+            batcher: &mut SpillBatcher<String, String, Timestamp, Diff>,
+            mut updates: Updates,
+            upper: Timestamp,
+        ) -> SpillBatch<String, String, Timestamp, Diff> {
+            batcher.push_batch(RefOrMut::Mut(&mut updates));
+            batcher.seal(Antichain::from_elem(upper))
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = Rc::new(SpillConfig::new(dir.path().to_path_buf(), 3));
+        let updates1 = updates(0..10, &["v0", "v1"], &[1, 2], 1);
+        let updates2 = updates(5..15, &["v0"], &[6], -2);
+
+        // Only the batchers constructed with a configuration spill.
+        let mut spilling = SpillBatcher::with_config(Rc::clone(&config));
+        let mut batcher = SpillBatcher::new();
+        assert!(spilling.config.is_some());
+        assert!(batcher.config.is_none());
+        assert!(matches!(
+            seal(&mut spilling, updates1.clone(), 5),
+            SpillBatch::Disk { .. }
+        ));
+
+        let batch1 = seal(&mut batcher, updates1.clone(), 5);
+        let batch2 = seal(&mut batcher, updates2.clone(), 10);
+        for batch in [&batch1, &batch2] {
+            assert!(matches!(batch, SpillBatch::Memory { config: None, .. }));
+        }
+        assert_eq!(contents(&batch1), updates1);
+
+        // Their merges are those of `OrdValBatch`, and stay in memory.
+        let frontier = Antichain::from_elem(10);
+        let mut merger = SpillMerger::new(&batch1, &batch2, Some(frontier.borrow()));
+        assert!(matches!(merger, SpillMerger::Memory(_)));
+        let mut fuel = isize::MAX;
+        merger.work(&batch1, &batch2, &mut fuel);
+        let merged_batch = merger.done();
+        assert!(matches!(
+            merged_batch,
+            SpillBatch::Memory { config: None, .. }
+        ));
+        assert_eq!(contents(&merged_batch), merged(updates1, updates2, 10));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -38,6 +38,11 @@ impl AvroDecoderState {
         })
     }
 
+    /// Restricts decoding to the columns for which `demanded` is true.
+    pub fn demand_columns(&mut self, demanded: &[bool]) {
+        self.decoder.demand_columns(demanded);
+    }
+
     pub fn decode(&mut self, bytes: &mut &[u8]) -> Result<Option<Row>, DecodeError> {
         match block_on(self.decoder.decode(bytes)) {
             Ok(row) => {
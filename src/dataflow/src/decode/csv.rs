@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use mz_dataflow_types::{sources::encoding::CsvEncoding, DecodeError, LinearOperator};
+use mz_dataflow_types::{sources::encoding::CsvEncoding, DecodeError};
 use mz_repr::{Datum, Row};
 
 #[derive(Debug)]
@@ -31,18 +31,12 @@ impl CsvDecoderState {
         self.events_error + self.events_success
     }
 
-    pub fn new(format: CsvEncoding, operators: &mut Option<LinearOperator>) -> Self {
+    pub fn new(format: CsvEncoding, demanded: Option<&[bool]>) -> Self {
         let CsvEncoding { columns, delimiter } = format;
         let n_cols = columns.arity();
 
-        let operators = operators.take();
         let demanded = (0..n_cols)
-            .map(move |c| {
-                operators
-                    .as_ref()
-                    .map(|o| o.projection.contains(&c))
-                    .unwrap_or(true)
-            })
+            .map(|c| demanded.map_or(true, |demanded| demanded.get(c).copied().unwrap_or(true)))
             .collect::<Vec<_>>();
 
         let header_names = columns.into_header_names();
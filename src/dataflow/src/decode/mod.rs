@@ -16,8 +16,8 @@ use differential_dataflow::Hashable;
 use differential_dataflow::{AsCollection, Collection};
 use futures::executor::block_on;
 use mz_avro::{AvroDeserializer, GeneralDeserializer};
-use mz_expr::PartitionId;
-use mz_repr::MessagePayload;
+use mz_expr::{MirScalarExpr, PartitionId};
+use mz_repr::{DatumVec, MessagePayload, RowArena};
 use timely::dataflow::channels::pact::{Exchange, Pipeline};
 use timely::dataflow::operators::Operator;
 use timely::dataflow::{Scope, Stream};
@@ -31,20 +31,107 @@ use mz_dataflow_types::{
     DecodeError, LinearOperator,
 };
 use mz_interchange::avro::ConfluentAvroResolver;
+use mz_repr::adt::jsonb::Jsonb;
 use mz_repr::Datum;
 use mz_repr::{Diff, Row, Timestamp};
 use tracing::error;
 
 use self::avro::AvroDecoderState;
 use self::csv::CsvDecoderState;
+use self::parquet::ParquetDecoderState;
 use self::protobuf::ProtobufDecoderState;
 use crate::metrics::Metrics;
 use crate::source::{DecodeResult, SourceOutput};
 
 mod avro;
 mod csv;
+mod parquet;
 mod protobuf;
 
+/// Projection and filtering that decoders apply to the values they decode.
+///
+/// Whole-dataflow optimization pushes the columns that a dataflow demands of a
+/// source, and the predicates it applies to the source, into the source's
+/// [`LinearOperator`]. When each decoded value is a row of the source on its
+/// own, decoders apply the part of the operator that concerns the value's
+/// columns: they skip the fields of undemanded columns, and drop the values
+/// that fail the predicates before they enter the dataflow.
+#[derive(Debug, Clone)]
+pub struct DecodeOperators {
+    /// Whether each column of the decoded value is demanded.
+    ///
+    /// Undemanded columns may be replaced with nulls.
+    demanded: Vec<bool>,
+    /// Predicates over the columns of the decoded value.
+    predicates: Vec<MirScalarExpr>,
+}
+
+impl DecodeOperators {
+    /// Splits `operator`, which applies to rows of `arity` columns whose first
+    /// `value_arity` columns are decoded values, into the operators decoders
+    /// apply and the remainder, if any, that must be applied to the rows.
+    pub fn split(
+        operator: LinearOperator,
+        value_arity: usize,
+        arity: usize,
+    ) -> (Self, Option<LinearOperator>) {
+        let demanded = (0..value_arity)
+            .map(|c| operator.projection.contains(&c))
+            .collect();
+        let (predicates, remaining) = operator
+            .predicates
+            .into_iter()
+            .partition(|p| p.support().iter().all(|c| *c < value_arity));
+        let remainder = LinearOperator {
+            predicates: remaining,
+            projection: (0..value_arity)
+                .chain(
+                    operator
+                        .projection
+                        .into_iter()
+                        .filter(|c| *c >= value_arity),
+                )
+                .collect(),
+        };
+        let remainder = if remainder.is_trivial(arity) {
+            None
+        } else {
+            Some(remainder)
+        };
+        (
+            DecodeOperators {
+                demanded,
+                predicates,
+            },
+            remainder,
+        )
+    }
+
+    /// Whether each column of the decoded value is demanded.
+    pub fn demanded(&self) -> &[bool] {
+        &self.demanded
+    }
+
+    /// Reports whether a decoded value may satisfy the predicates.
+    ///
+    /// Errors, and values for which a predicate errors, are admitted, so that
+    /// they are reported by the operators that evaluate them in full.
+    fn admits(&self, value: &Result<Row, DecodeError>, datum_vec: &mut DatumVec) -> bool {
+        let row = match value {
+            Ok(row) if !self.predicates.is_empty() => row,
+            _ => return true,
+        };
+        let datums = datum_vec.borrow_with(row);
+        let arena = RowArena::new();
+        self.predicates.iter().all(|predicate| {
+            !matches!(
+                predicate.eval(&datums, &arena),
+                Ok(Datum::False) | Ok(Datum::Null)
+            )
+        })
+    }
+}
+
 pub fn decode_cdcv2<G: Scope<Timestamp = Timestamp>>(
     stream: &Stream<G, SourceOutput<Option<Vec<u8>>, Option<Vec<u8>>>>,
     schema: &str,
@@ -119,6 +206,7 @@ pub fn decode_cdcv2<G: Scope<Timestamp = Timestamp>>(
 pub(crate) enum PreDelimitedFormat {
     Bytes,
     Text,
+    Json,
     Regex(Regex, Row),
     Protobuf(ProtobufDecoderState),
 }
@@ -132,6 +220,11 @@ impl PreDelimitedFormat {
                     .map_err(|_| DecodeError::Text("Failed to decode UTF-8".to_string()))?;
                 Ok(Some(Row::pack(Some(Datum::String(s)))))
             }
+            PreDelimitedFormat::Json => {
+                let jsonb = Jsonb::from_slice(bytes)
+                    .map_err(|e| DecodeError::Text(format!("Failed to decode JSON: {}", e)))?;
+                Ok(Some(jsonb.into_row()))
+            }
             PreDelimitedFormat::Regex(regex, row_packer) => {
                 let s = std::str::from_utf8(bytes)
                     .map_err(|_| DecodeError::Text("Failed to decode UTF-8".to_string()))?;
@@ -160,6 +253,7 @@ pub(crate) enum DataDecoderInner {
         format: PreDelimitedFormat,
     },
     Csv(CsvDecoderState),
+    Parquet(ParquetDecoderState),
 
     PreDelimited(PreDelimitedFormat),
 }
@@ -185,6 +279,7 @@ impl DataDecoder {
             }
             DataDecoderInner::Avro(avro) => avro.decode(bytes),
             DataDecoderInner::Csv(csv) => csv.decode(bytes),
+            DataDecoderInner::Parquet(parquet) => parquet.decode(bytes),
             DataDecoderInner::PreDelimited(format) => {
                 let result = format.decode(*bytes);
                 *bytes = &[];
@@ -214,10 +309,23 @@ impl DataDecoder {
                     format.decode(data)
                 }
             }
+            DataDecoderInner::Parquet(parquet) => parquet.eof(),
             _ => Ok(None),
         }
     }
 
+    /// Get the next record produced by the most recent `eof`, if any.
+    ///
+    /// Most formats produce at most one record at EOF, but formats that can
+    /// only decode whole objects (like Parquet) produce all of their records
+    /// there.
+    pub fn next_after_eof(&mut self) -> Option<Result<Row, DecodeError>> {
+        match &mut self.inner {
+            DataDecoderInner::Parquet(parquet) => parquet.next_pending(),
+            _ => None,
+        }
+    }
+
     pub fn log_errors(&self, n: usize) {
         self.metrics.count_errors(&self.inner, n);
     }
@@ -230,10 +338,8 @@ impl DataDecoder {
 fn get_decoder(
     encoding: DataEncoding,
     debug_name: &str,
-    // Information about optional transformations that can be eagerly done.
-    // If the decoding elects to perform them, it should replace this with
-    // `None`.
-    operators: &mut Option<LinearOperator>,
+    // Projection that the decoder may apply eagerly.
+    operators: Option<&DecodeOperators>,
     is_connector_delimited: bool,
     metrics: Metrics,
 ) -> DataDecoder {
@@ -243,13 +349,16 @@ fn get_decoder(
             schema_registry_config,
             confluent_wire_format,
         }) => {
-            let state = avro::AvroDecoderState::new(
+            let mut state = avro::AvroDecoderState::new(
                 &schema,
                 schema_registry_config,
                 debug_name.to_string(),
                 confluent_wire_format,
             )
             .expect("Failed to create avro decoder, even though we validated ccsr client creation in purification.");
+            if let Some(operators) = operators {
+                state.demand_columns(operators.demanded());
+            }
             DataDecoder {
                 inner: DataDecoderInner::Avro(state),
                 metrics,
@@ -257,6 +366,7 @@ fn get_decoder(
         }
         DataEncoding::Text
         | DataEncoding::Bytes
+        | DataEncoding::Json
         | DataEncoding::Protobuf(_)
         | DataEncoding::Regex(_) => {
             let after_delimiting = match encoding {
@@ -264,13 +374,18 @@ fn get_decoder(
                     PreDelimitedFormat::Regex(regex.0, Default::default())
                 }
                 DataEncoding::Protobuf(encoding) => {
-                    PreDelimitedFormat::Protobuf(ProtobufDecoderState::new(encoding).expect(
+                    let mut state = ProtobufDecoderState::new(encoding).expect(
                         "Failed to create protobuf decoder, even though we validated ccsr \
                                     client creation in purification.",
-                    ))
+                    );
+                    if let Some(operators) = operators {
+                        state.demand_columns(operators.demanded());
+                    }
+                    PreDelimitedFormat::Protobuf(state)
                 }
                 DataEncoding::Bytes => PreDelimitedFormat::Bytes,
                 DataEncoding::Text => PreDelimitedFormat::Text,
+                DataEncoding::Json => PreDelimitedFormat::Json,
                 _ => unreachable!(),
             };
             let inner = if is_connector_delimited {
@@ -284,21 +399,28 @@ fn get_decoder(
             DataDecoder { inner, metrics }
         }
         DataEncoding::AvroOcf(AvroOcfEncoding { reader_schema }) => {
-            let state =
+            let mut state =
                 avro::AvroDecoderState::new(&reader_schema, None, debug_name.to_string(), false)
                     .expect("Schema was verified to be correct during purification");
+            if let Some(operators) = operators {
+                state.demand_columns(operators.demanded());
+            }
             DataDecoder {
                 inner: DataDecoderInner::Avro(state),
                 metrics,
             }
         }
         DataEncoding::Csv(enc) => {
-            let state = CsvDecoderState::new(enc, operators);
+            let state = CsvDecoderState::new(enc, operators.map(DecodeOperators::demanded));
             DataDecoder {
                 inner: DataDecoderInner::Csv(state),
                 metrics,
             }
         }
+        DataEncoding::Parquet(enc) => DataDecoder {
+            inner: DataDecoderInner::Parquet(ParquetDecoderState::new(enc)),
+            metrics,
+        },
         DataEncoding::Postgres => {
             unreachable!("Postgres sources should not go through the general decoding path.")
         }
@@ -342,10 +464,8 @@ pub fn render_decode_delimited<G>(
     debug_name: &str,
     envelope: &SourceEnvelope,
     metadata_items: Vec<IncludedColumnSource>,
-    // Information about optional transformations that can be eagerly done.
-    // If the decoding elects to perform them, it should replace this with
-    // `None`.
-    operators: &mut Option<LinearOperator>,
+    // Projection and filtering to apply to the decoded values, if any.
+    operators: Option<DecodeOperators>,
     metrics: Metrics,
 ) -> (Stream<G, DecodeResult>, Option<Box<dyn Any>>)
 where
@@ -359,11 +479,23 @@ where
             .unwrap_or(""),
         value_encoding.op_name()
     );
-    let mut key_decoder = key_encoding.map(|key_encoding| {
-        get_decoder(key_encoding, debug_name, operators, true, metrics.clone())
-    });
-
-    let mut value_decoder = get_decoder(value_encoding, debug_name, operators, true, metrics);
+    let mut key_decoder = key_encoding
+        .map(|key_encoding| get_decoder(key_encoding, debug_name, None, true, metrics.clone()));
+
+    let mut value_decoder = get_decoder(
+        value_encoding,
+        debug_name,
+        operators.as_ref(),
+        true,
+        metrics,
+    );
+    let mut datum_vec = DatumVec::new();
+    // Whether to emit a decoded value, rather than drop it as failing the predicates.
+    let mut admits = move |value: &Result<Row, DecodeError>| {
+        operators
+            .as_ref()
+            .map_or(true, |operators| operators.admits(value, &mut datum_vec))
+    };
 
     let dist: fn(&SourceOutput<Option<Vec<u8>>, Option<Vec<u8>>>) -> _ = match envelope {
         SourceEnvelope::Debezium(dbz) => match dbz.mode {
@@ -404,6 +536,10 @@ where
                         n_successes += 1;
                     }
 
+                    if !value.as_ref().map_or(true, &mut admits) {
+                        continue;
+                    }
+
                     session.give(DecodeResult {
                         key,
                         value,
@@ -451,10 +587,8 @@ pub fn render_decode<G>(
     value_encoding: DataEncoding,
     debug_name: &str,
     metadata_items: Vec<IncludedColumnSource>,
-    // Information about optional transformations that can be eagerly done.
-    // If the decoding elects to perform them, it should replace this with
-    // `None`.
-    operators: &mut Option<LinearOperator>,
+    // Projection and filtering to apply to the decoded values, if any.
+    operators: Option<DecodeOperators>,
     metrics: Metrics,
 ) -> (Stream<G, DecodeResult>, Option<Box<dyn Any>>)
 where
@@ -462,7 +596,20 @@ where
 {
     let op_name = format!("{}Decode", value_encoding.op_name());
 
-    let mut value_decoder = get_decoder(value_encoding, debug_name, operators, false, metrics);
+    let mut value_decoder = get_decoder(
+        value_encoding,
+        debug_name,
+        operators.as_ref(),
+        false,
+        metrics,
+    );
+    let mut datum_vec = DatumVec::new();
+    // Whether to emit a decoded value, rather than drop it as failing the predicates.
+    let mut admits = move |value: &Result<Row, DecodeError>| {
+        operators
+            .as_ref()
+            .map_or(true, |operators| operators.admits(value, &mut datum_vec))
+    };
 
     let mut value_buf = vec![];
 
@@ -500,15 +647,15 @@ where
                             }
                             value_buf.clear();
 
-                            match result.transpose() {
-                                None => continue,
-                                Some(value) => {
-                                    if value.is_err() {
-                                        n_errors += 1;
-                                    } else if matches!(&value, Ok(_)) {
-                                        n_successes += 1;
-                                    }
-                                    let position = n_seen.next();
+                            let mut next = result.transpose();
+                            while let Some(value) = next {
+                                if value.is_err() {
+                                    n_errors += 1;
+                                } else if matches!(&value, Ok(_)) {
+                                    n_successes += 1;
+                                }
+                                let position = n_seen.next();
+                                if admits(&value) {
                                     let metadata = to_metadata_row(
                                         &metadata_items,
                                         partition.clone(),
@@ -525,6 +672,7 @@ where
                                         metadata,
                                     });
                                 }
+                                next = value_decoder.next_after_eof();
                             }
                             continue;
                         }
@@ -584,14 +732,14 @@ where
                                 n_successes += 1;
                             }
                             let position = n_seen.next();
-                            let metadata = to_metadata_row(
-                                &metadata_items,
-                                partition.clone(),
-                                position,
-                                *upstream_time_millis,
-                            );
+                            if admits(&value) {
+                                let metadata = to_metadata_row(
+                                    &metadata_items,
+                                    partition.clone(),
+                                    position,
+                                    *upstream_time_millis,
+                                );
 
-                            if value_bytes_remaining.is_empty() {
                                 session.give(DecodeResult {
                                     key: None,
                                     value: Some(value),
@@ -600,17 +748,10 @@ where
                                     partition: partition.clone(),
                                     metadata,
                                 });
+                            }
+                            if value_bytes_remaining.is_empty() {
                                 value_buf = vec![];
                                 break;
-                            } else {
-                                session.give(DecodeResult {
-                                    key: None,
-                                    value: Some(value),
-                                    position,
-                                    upstream_time_millis: *upstream_time_millis,
-                                    partition: partition.clone(),
-                                    metadata,
-                                });
                             }
                             if is_err {
                                 // If decoding has gone off the rails, we can no longer be sure that the delimiters are correct, so it
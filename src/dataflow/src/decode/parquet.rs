@@ -0,0 +1,78 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use mz_dataflow_types::{sources::encoding::ParquetEncoding, DecodeError};
+use mz_interchange::parquet::{DecodedRows, Decoder};
+use mz_repr::Row;
+
+/// Decodes Parquet objects.
+///
+/// The metadata that describes a Parquet object lives in its footer, so no
+/// rows can be decoded until the entire object has arrived. Bytes are
+/// buffered until EOF, at which point the rows of the object are decoded one
+/// row group at a time as they are handed out.
+#[derive(Debug)]
+pub struct ParquetDecoderState {
+    decoder: Decoder,
+    buffer: Vec<u8>,
+    pending: Option<DecodedRows>,
+}
+
+impl ParquetDecoderState {
+    pub fn new(encoding: ParquetEncoding) -> Self {
+        ParquetDecoderState {
+            decoder: Decoder::new(encoding.columns),
+            buffer: vec![],
+            pending: None,
+        }
+    }
+
+    /// Buffers `bytes` for decoding at EOF. Never produces a row.
+    pub fn decode(&mut self, bytes: &mut &[u8]) -> Result<Option<Row>, DecodeError> {
+        self.buffer.extend_from_slice(bytes);
+        *bytes = &[];
+        Ok(None)
+    }
+
+    /// Starts decoding the buffered object, returning its first row.
+    ///
+    /// The remaining rows are available via [`ParquetDecoderState::next_pending`].
+    pub fn eof(&mut self) -> Result<Option<Row>, DecodeError> {
+        let bytes = std::mem::take(&mut self.buffer);
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        let rows = self.decoder.decode(bytes).map_err(decode_error)?;
+        self.pending = Some(rows);
+        self.next_pending().transpose()
+    }
+
+    /// Returns the next row of the most recently decoded object, if any.
+    ///
+    /// Decoding stops at the first error, as the rest of the object cannot be
+    /// trusted to be well-formed either.
+    pub fn next_pending(&mut self) -> Option<Result<Row, DecodeError>> {
+        let next = self.pending.as_mut()?.next();
+        match next {
+            Some(Ok(row)) => Some(Ok(row)),
+            Some(Err(e)) => {
+                self.pending = None;
+                Some(Err(decode_error(e)))
+            }
+            None => {
+                self.pending = None;
+                None
+            }
+        }
+    }
+}
+
+fn decode_error(e: anyhow::Error) -> DecodeError {
+    DecodeError::Text(format!("Failed to decode Parquet object: {:#}", e))
+}
@@ -34,6 +34,12 @@ impl ProtobufDecoderState {
             events_error: 0,
         })
     }
+
+    /// Restricts decoding to the columns for which `demanded` is true.
+    pub fn demand_columns(&mut self, demanded: &[bool]) {
+        self.decoder.demand_columns(demanded);
+    }
+
     pub fn get_value(&mut self, bytes: &[u8]) -> Option<Result<Row, DecodeError>> {
         // TODO(guswynn): make this async-sync-async sandwich open-faced.
         //   Figuring out how to do async-to-sync work in timely land needs a general solution.
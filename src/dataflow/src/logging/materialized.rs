@@ -46,6 +46,15 @@ pub enum MaterializedEvent {
         /// Globally unique identifier for the source on which the dataflow depends.
         source: GlobalId,
     },
+    /// The backlog of a Kafka sink.
+    KafkaSinkBacklog {
+        /// Materialize sink identifier.
+        sink_id: GlobalId,
+        /// The old number of messages and bytes to retract, if any.
+        old: Option<(i64, i64)>,
+        /// The new number of messages and bytes to produce, if any.
+        new: Option<(i64, i64)>,
+    },
     /// Underling librdkafka statistics for a Kafka source.
     KafkaSourceStatistics {
         /// Materialize source identifier.
@@ -57,6 +66,19 @@ pub enum MaterializedEvent {
     },
     /// Peek command, true for install and false for retire.
     Peek(Peek, bool),
+    /// The operators that render one node of the plan of a dataflow.
+    PlanNodeOperators {
+        /// Identifier of an index or sink exported by the dataflow.
+        export_id: GlobalId,
+        /// The position of the node in a pre-order traversal of the plans of
+        /// the dataflow's objects.
+        node: usize,
+        /// The identifiers of the operators.
+        operators: Vec<usize>,
+    },
+    /// The dataflow exporting the index or sink was dropped, retracting its
+    /// `PlanNodeOperators` events.
+    PlanNodesDropped(GlobalId),
     /// Tracks the source name, id, partition id, and received/ingested offsets
     SourceInfo {
         /// Name of the source
@@ -127,22 +149,27 @@ pub fn construct<A: Allocate>(
         let (mut dataflow_out, dataflow) = demux.new_output();
         let (mut dependency_out, dependency) = demux.new_output();
         let (mut frontier_out, frontier) = demux.new_output();
+        let (mut kafka_sink_backlog_out, kafka_sink_backlog) = demux.new_output();
         let (mut kafka_source_statistics_out, kafka_source_statistics) = demux.new_output();
         let (mut peek_out, peek) = demux.new_output();
         let (mut peek_duration_out, peek_duration) = demux.new_output();
+        let (mut plan_node_out, plan_node) = demux.new_output();
         let (mut source_info_out, source_info) = demux.new_output();
 
         let mut demux_buffer = Vec::new();
         demux.build(move |_capability| {
             let mut active_dataflows = std::collections::HashMap::new();
             let mut peek_stash = std::collections::HashMap::new();
+            let mut plan_node_stash = std::collections::HashMap::new();
             move |_frontiers| {
                 let mut dataflow = dataflow_out.activate();
                 let mut dependency = dependency_out.activate();
                 let mut frontier = frontier_out.activate();
+                let mut kafka_sink_backlog = kafka_sink_backlog_out.activate();
                 let mut kafka_source_statistics = kafka_source_statistics_out.activate();
                 let mut peek = peek_out.activate();
                 let mut peek_duration = peek_duration_out.activate();
+                let mut plan_node = plan_node_out.activate();
                 let mut source_info = source_info_out.activate();
 
                 input.for_each(|time, data| {
@@ -151,10 +178,12 @@ pub fn construct<A: Allocate>(
                     let mut dataflow_session = dataflow.session(&time);
                     let mut dependency_session = dependency.session(&time);
                     let mut frontier_session = frontier.session(&time);
+                    let mut kafka_sink_backlog_session = kafka_sink_backlog.session(&time);
                     let mut kafka_source_statistics_session =
                         kafka_source_statistics.session(&time);
                     let mut peek_session = peek.session(&time);
                     let mut peek_duration_session = peek_duration.session(&time);
+                    let mut plan_node_session = plan_node.session(&time);
                     let mut source_info_session = source_info.session(&time);
 
                     for (time, worker, datum) in demux_buffer.drain(..) {
@@ -219,6 +248,22 @@ pub fn construct<A: Allocate>(
                                     delta as isize,
                                 ));
                             }
+                            MaterializedEvent::KafkaSinkBacklog { sink_id, old, new } => {
+                                if let Some(old) = old {
+                                    kafka_sink_backlog_session.give((
+                                        (sink_id, worker, old),
+                                        time_ms,
+                                        -1,
+                                    ));
+                                }
+                                if let Some(new) = new {
+                                    kafka_sink_backlog_session.give((
+                                        (sink_id, worker, new),
+                                        time_ms,
+                                        1,
+                                    ));
+                                }
+                            }
                             MaterializedEvent::KafkaSourceStatistics {
                                 source_id,
                                 old,
@@ -269,6 +314,33 @@ pub fn construct<A: Allocate>(
                                     }
                                 }
                             }
+                            MaterializedEvent::PlanNodeOperators {
+                                export_id,
+                                node,
+                                operators,
+                            } => {
+                                let stash: &mut Vec<_> =
+                                    plan_node_stash.entry((export_id, worker)).or_default();
+                                for operator in operators {
+                                    plan_node_session.give((
+                                        (export_id, node, operator, worker),
+                                        time_ms,
+                                        1,
+                                    ));
+                                    stash.push((node, operator));
+                                }
+                            }
+                            MaterializedEvent::PlanNodesDropped(export_id) => {
+                                if let Some(stash) = plan_node_stash.remove(&(export_id, worker)) {
+                                    for (node, operator) in stash {
+                                        plan_node_session.give((
+                                            (export_id, node, operator, worker),
+                                            time_ms,
+                                            -1,
+                                        ));
+                                    }
+                                }
+                            }
                             MaterializedEvent::SourceInfo {
                                 source_name,
                                 source_id,
@@ -309,6 +381,17 @@ pub fn construct<A: Allocate>(
 
         let frontier_current = frontier.as_collection();
 
+        let kafka_sink_backlog_current = kafka_sink_backlog.as_collection().map({
+            move |(sink_id, worker, (messages, bytes))| {
+                Row::pack_slice(&[
+                    Datum::String(&sink_id.to_string()),
+                    Datum::Int64(worker as i64),
+                    Datum::Int64(messages),
+                    Datum::Int64(bytes),
+                ])
+            }
+        });
+
         let kafka_source_statistics_current = kafka_source_statistics.as_collection().map({
             move |(source_id, worker, stats)| {
                 let mut row = Row::pack_slice(&[
@@ -331,6 +414,17 @@ pub fn construct<A: Allocate>(
             }
         });
 
+        let plan_node_current = plan_node.as_collection().map({
+            move |(export_id, node, operator, worker)| {
+                Row::pack_slice(&[
+                    Datum::String(&export_id.to_string()),
+                    Datum::Int64(node as i64),
+                    Datum::Int64(operator as i64),
+                    Datum::Int64(worker as i64),
+                ])
+            }
+        });
+
         let source_info_current = source_info.as_collection().count().map({
             move |((name, id, pid), (offset, timestamp))| {
                 Row::pack_slice(&[
@@ -368,6 +462,10 @@ pub fn construct<A: Allocate>(
                 LogVariant::Materialized(MaterializedLog::FrontierCurrent),
                 frontier_current,
             ),
+            (
+                LogVariant::Materialized(MaterializedLog::KafkaSinkBacklog),
+                kafka_sink_backlog_current,
+            ),
             (
                 LogVariant::Materialized(MaterializedLog::KafkaSourceStatistics),
                 kafka_source_statistics_current,
@@ -380,6 +478,10 @@ pub fn construct<A: Allocate>(
                 LogVariant::Materialized(MaterializedLog::PeekDuration),
                 peek_duration,
             ),
+            (
+                LogVariant::Materialized(MaterializedLog::PlanNodeOperators),
+                plan_node_current,
+            ),
             (
                 LogVariant::Materialized(MaterializedLog::SourceInfo),
                 source_info_current,
@@ -10,7 +10,9 @@
 //! Management of dataflow-local state, like arrangements, while building a
 //! dataflow.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+use std::rc::Rc;
 
 use differential_dataflow::lattice::Lattice;
 use differential_dataflow::operators::arrange::Arrange;
@@ -28,7 +30,10 @@ use timely::dataflow::{Scope, ScopeParent};
 use timely::progress::timestamp::Refines;
 use timely::progress::{Antichain, Timestamp};
 
-use crate::arrangement::manager::{ErrSpine, RowSpine, TraceErrHandle, TraceRowHandle};
+use crate::arrangement::manager::{
+    ErrSpine, RowSpine, TraceErrHandle, TraceRowHandle, TraceSpillHandle,
+};
+use crate::arrangement::spill::{self, SpillConfig};
 use crate::operator::CollectionExt;
 use mz_dataflow_types::{DataflowDescription, DataflowError};
 use mz_expr::{GlobalId, Id, MapFilterProject, MirScalarExpr};
@@ -38,10 +43,16 @@ use mz_repr::{DatumVec, Diff, Row, RowArena};
 pub type Arrangement<S, V> = Arranged<S, TraceRowHandle<V, V, <S as ScopeParent>::Timestamp, Diff>>;
 pub type ErrArrangement<S> =
     Arranged<S, TraceErrHandle<DataflowError, <S as ScopeParent>::Timestamp, Diff>>;
+pub type SpillArrangement<S, V> =
+    Arranged<S, TraceSpillHandle<V, V, <S as ScopeParent>::Timestamp, Diff>>;
 pub type ArrangementImport<S, V, T> = Arranged<
     S,
     TraceEnter<TraceFrontier<TraceRowHandle<V, V, T, Diff>>, <S as ScopeParent>::Timestamp>,
 >;
+pub type SpillArrangementImport<S, V, T> = Arranged<
+    S,
+    TraceEnter<TraceFrontier<TraceSpillHandle<V, V, T, Diff>>, <S as ScopeParent>::Timestamp>,
+>;
 pub type ErrArrangementImport<S, T> = Arranged<
     S,
     TraceEnter<
@@ -76,6 +87,19 @@ where
     pub as_of_frontier: Antichain<mz_repr::Timestamp>,
     /// Bindings of identifiers to collections.
     pub bindings: BTreeMap<Id, CollectionBundle<S, V, T>>,
+    /// The position of the next plan node to render, in a pre-order traversal
+    /// of the plans of the dataflow's objects.
+    pub next_plan_node: usize,
+    /// For each rendered plan node, the identifiers of the operators that
+    /// were created while rendering it and its inputs.
+    pub plan_node_operators: Vec<(usize, Range<usize>)>,
+    /// The configuration with which the object being built spills the
+    /// arrangements that the root of its plan forms, if it is an index
+    /// created `WITH (spill = true)`.
+    pub spill_config: Option<Rc<SpillConfig<V, V, mz_repr::Timestamp, Diff>>>,
+    /// The arrangements formed with `spill_config`, by their keys. They are
+    /// only read by the export of the index.
+    pub spilled: BTreeMap<Vec<MirScalarExpr>, (SpillArrangement<S, V>, ErrArrangement<S>)>,
 }
 
 impl<S: Scope, V: Data, T> Context<S, V, T>
@@ -95,9 +119,29 @@ where
             dataflow_id,
             as_of_frontier,
             bindings: BTreeMap::new(),
+            next_plan_node: 0,
+            plan_node_operators: Vec::new(),
+            spill_config: None,
+            spilled: BTreeMap::new(),
         }
     }
 
+    /// Returns the operators that each rendered plan node created itself,
+    /// excluding those created by its inputs.
+    pub fn take_plan_node_operators(&mut self) -> Vec<(usize, Vec<usize>)> {
+        // Nodes are recorded after their inputs, whose operators lie within
+        // the range of the node. Assigning each operator to the first range
+        // that contains it thus assigns it to the innermost node.
+        let mut assigned = HashSet::new();
+        std::mem::take(&mut self.plan_node_operators)
+            .into_iter()
+            .map(|(node, operators)| {
+                let operators = operators.filter(|id| assigned.insert(*id)).collect();
+                (node, operators)
+            })
+            .collect()
+    }
+
     /// Insert a collection bundle by an identifier.
     ///
     /// This is expected to be used to install external collections (sources, indexes, other views),
@@ -156,6 +200,15 @@ where
         ArrangementImport<S, V, T>,
         ErrArrangementImport<S, T>,
     ),
+    /// An imported trace from outside the dataflow, whose batches can spill
+    /// to disk, of an index created `WITH (spill = true)`.
+    ///
+    /// The `GlobalId` identifier serves the same purpose as for `Trace`.
+    SpillTrace(
+        GlobalId,
+        SpillArrangementImport<S, V, T>,
+        ErrArrangementImport<S, T>,
+    ),
 }
 
 impl<S: Scope, T> ArrangementFlavor<S, Row, T>
@@ -188,6 +241,14 @@ where
                 }),
                 errs.as_collection(|k, &()| k.clone()),
             ),
+            ArrangementFlavor::SpillTrace(_, oks, errs) => (
+                oks.as_collection(move |k, v| {
+                    let borrow = datum_vec.borrow_with_many(&[k, v]);
+                    row_builder.extend(&*borrow);
+                    row_builder.finish_and_reuse()
+                }),
+                errs.as_collection(|k, &()| k.clone()),
+            ),
         }
     }
 
@@ -243,6 +304,17 @@ where
                 let errs = errs.as_collection(|k, &()| k.clone());
                 return (oks, errs);
             }
+            ArrangementFlavor::SpillTrace(_, oks, errs) => {
+                let mut logic = constructor();
+                let oks = CollectionBundle::<S, Row, T>::flat_map_core(
+                    &oks,
+                    key,
+                    move |k, v, t, d| logic(&[&k, &v], t, d),
+                    refuel,
+                );
+                let errs = errs.as_collection(|k, &()| k.clone());
+                return (oks, errs);
+            }
         }
     }
 }
@@ -562,26 +634,7 @@ where
             if !self.arranged.contains_key(&key) {
                 // TODO: Consider allowing more expressive names.
                 let name = format!("ArrangeBy[{:?}]", key);
-                let key2 = key.clone();
-                let (oks, errs) = self
-                    .collection
-                    .clone()
-                    .expect("Collection constructed above");
-
-                let mut row_packer = Row::default();
-
-                let mut datums = DatumVec::new();
-                let (oks_keyed, errs_keyed) = oks.map_fallible("FormArrangementKey", move |row| {
-                    // TODO: Consider reusing the `row` allocation; probably in *next* invocation.
-                    let datums = datums.borrow_with(&row);
-                    let temp_storage = RowArena::new();
-                    row_packer.try_extend(key2.iter().map(|k| k.eval(&datums, &temp_storage)))?;
-                    let key_row = row_packer.finish_and_reuse();
-                    row_packer.extend(thinning.iter().map(|c| datums[*c]));
-                    let val_row = row_packer.finish_and_reuse();
-                    Ok::<(Row, Row), DataflowError>((key_row, val_row))
-                });
-
+                let (oks_keyed, errs, errs_keyed) = self.key_vals(key.clone(), thinning);
                 let oks = oks_keyed.arrange_named::<RowSpine<Row, Row, _, _>>(&name);
                 let errs = errs
                     .concat(&errs_keyed)
@@ -592,6 +645,81 @@ where
         }
         self
     }
+
+    /// Like [`CollectionBundle::ensure_collections`], but forms the
+    /// arrangements that `self` lacks from batches that spill according to
+    /// `config`, and returns them by their keys rather than adding them to the
+    /// bundle. The arrangements that `self` has already are shared as they
+    /// are, in memory.
+    pub fn ensure_spilling_collections(
+        self,
+        mut collections: AvailableCollections,
+        input_key: Option<Vec<MirScalarExpr>>,
+        input_mfp: MapFilterProject,
+        config: Rc<SpillConfig<Row, Row, mz_repr::Timestamp, Diff>>,
+    ) -> (
+        Self,
+        Vec<(
+            Vec<MirScalarExpr>,
+            (SpillArrangement<S, Row>, ErrArrangement<S>),
+        )>,
+    ) {
+        let (existing, spilling): (Vec<_>, Vec<_>) = collections
+            .arranged
+            .drain(..)
+            .partition(|(key, _, _)| self.arranged.contains_key(key));
+        collections.arranged = existing;
+        collections.raw |= !spilling.is_empty();
+        let bundle = self.ensure_collections(collections, input_key, input_mfp);
+
+        let spilled = spilling
+            .into_iter()
+            .map(|(key, _, thinning)| {
+                let name = format!("SpillArrangeBy[{:?}]", key);
+                let (oks_keyed, errs, errs_keyed) = bundle.key_vals(key.clone(), thinning);
+                let oks = spill::arrange(&oks_keyed, Rc::clone(&config), &name);
+                let errs = errs
+                    .concat(&errs_keyed)
+                    .arrange_named::<ErrSpine<_, _, _>>(&format!("{}-errors", name));
+                (key, (oks, errs))
+            })
+            .collect();
+        (bundle, spilled)
+    }
+
+    /// Forms the keys and values by which to arrange the collection of `self`,
+    /// which must exist, by `key`, keeping the columns in `thinning` as values.
+    /// Returns them with the errors of the collection and those of evaluating
+    /// the key.
+    fn key_vals(
+        &self,
+        key: Vec<MirScalarExpr>,
+        thinning: Vec<usize>,
+    ) -> (
+        Collection<S, (Row, Row), Diff>,
+        Collection<S, DataflowError, Diff>,
+        Collection<S, DataflowError, Diff>,
+    ) {
+        let (oks, errs) = self
+            .collection
+            .clone()
+            .expect("Collection constructed above");
+
+        let mut row_packer = Row::default();
+
+        let mut datums = DatumVec::new();
+        let (oks_keyed, errs_keyed) = oks.map_fallible("FormArrangementKey", move |row| {
+            // TODO: Consider reusing the `row` allocation; probably in *next* invocation.
+            let datums = datums.borrow_with(&row);
+            let temp_storage = RowArena::new();
+            row_packer.try_extend(key.iter().map(|k| k.eval(&datums, &temp_storage)))?;
+            let key_row = row_packer.finish_and_reuse();
+            row_packer.extend(thinning.iter().map(|c| datums[*c]));
+            let val_row = row_packer.finish_and_reuse();
+            Ok::<(Row, Row), DataflowError>((key_row, val_row))
+        });
+        (oks_keyed, errs, errs_keyed)
+    }
 }
 
 use timely::dataflow::operators::generic::OutputHandle;
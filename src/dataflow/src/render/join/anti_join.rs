@@ -0,0 +1,44 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Anti-join execution dataflow construction.
+//!
+//! Consult [AntiJoinPlan] documentation for details.
+
+use timely::dataflow::Scope;
+
+use mz_dataflow_types::plan::join::anti_join::AntiJoinPlan;
+use mz_repr::Row;
+
+use crate::render::context::CollectionBundle;
+use crate::render::context::Context;
+
+impl<G> Context<G, Row, mz_repr::Timestamp>
+where
+    G: Scope<Timestamp = mz_repr::Timestamp>,
+{
+    /// Renders `MirRelationExpr::Join` as an anti-join.
+    ///
+    /// The first input is the collection, and the second the keys to exclude.
+    pub fn render_anti_join(
+        &mut self,
+        inputs: Vec<CollectionBundle<G, Row, G::Timestamp>>,
+        join_plan: AntiJoinPlan,
+        scope: &mut G,
+    ) -> CollectionBundle<G, Row, G::Timestamp> {
+        // The errors of the collection are reported by the join below, which
+        // looks the excluded keys up in the same arrangement.
+        let (candidates, _errs) =
+            inputs[0].as_collection_core(join_plan.input_mfp, Some((join_plan.input_key, None)));
+        let (matches, errs) = self
+            .render_join(inputs, join_plan.matches, scope)
+            .as_specific_collection(None);
+        CollectionBundle::from_collections(candidates.concat(&matches.negate()), errs)
+    }
+}
@@ -28,6 +28,17 @@ use crate::operator::CollectionExt;
 use crate::render::context::CollectionBundle;
 use mz_repr::DatumVec;
 
+/// An arrangement that a delta join looks up, by the flavor of arrangement
+/// it was entered from.
+enum DeltaArrangement<L, Tr, Sp> {
+    /// A dataflow-local arrangement.
+    Local(L),
+    /// An imported trace.
+    Trace(Tr),
+    /// An imported trace that can spill to disk.
+    SpillTrace(Sp),
+}
+
 impl<G> Context<G, Row, mz_repr::Timestamp>
 where
     G: Scope<Timestamp = mz_repr::Timestamp>,
@@ -79,13 +90,19 @@ where
                                     if err_dedup.insert((lookup_idx, lookup_key)) {
                                         scope_errs.push(errs.as_collection(|k, _v| k.clone()));
                                     }
-                                    Ok(oks.enter(inner))
+                                    DeltaArrangement::Local(oks.enter(inner))
                                 }
                                 ArrangementFlavor::Trace(_gid, oks, errs) => {
                                     if err_dedup.insert((lookup_idx, lookup_key)) {
                                         scope_errs.push(errs.as_collection(|k, _v| k.clone()));
                                     }
-                                    Err(oks.enter(inner))
+                                    DeltaArrangement::Trace(oks.enter(inner))
+                                }
+                                ArrangementFlavor::SpillTrace(_gid, oks, errs) => {
+                                    if err_dedup.insert((lookup_idx, lookup_key)) {
+                                        scope_errs.push(errs.as_collection(|k, _v| k.clone()));
+                                    }
+                                    DeltaArrangement::SpillTrace(oks.enter(inner))
                                 }
                             }
                         });
@@ -134,7 +151,7 @@ where
                         .expect("Arrangement promised by the planner is absent!");
                     let as_of = self.as_of_frontier.clone();
                     let update_stream = match val {
-                        Ok(local) => {
+                        DeltaArrangement::Local(local) => {
                             let arranged = local.enter(region);
                             let (update_stream, err_stream) = build_update_stream(
                                 arranged,
@@ -145,7 +162,18 @@ where
                             region_errs.push(err_stream);
                             update_stream
                         }
-                        Err(trace) => {
+                        DeltaArrangement::Trace(trace) => {
+                            let arranged = trace.enter(region);
+                            let (update_stream, err_stream) = build_update_stream(
+                                arranged,
+                                as_of,
+                                source_relation,
+                                initial_closure,
+                            );
+                            region_errs.push(err_stream);
+                            update_stream
+                        }
+                        DeltaArrangement::SpillTrace(trace) => {
                             let arranged = trace.enter(region);
                             let (update_stream, err_stream) = build_update_stream(
                                 arranged,
@@ -183,11 +211,12 @@ where
                         // updates with less or equal `time`, and otherwise we present only updates
                         // with strictly less `time`.
                         //
-                        // We need to write the logic twice, as there are two types of arrangement
-                        // we might have: either dataflow-local or an imported trace.
+                        // We need to write the logic three times, as there are three types of
+                        // arrangement we might have: dataflow-local, an imported trace, or an
+                        // imported trace that can spill to disk.
                         let (oks, errs) =
                             match arrangements.get(&(lookup_relation, lookup_key)).unwrap() {
-                                Ok(local) => {
+                                DeltaArrangement::Local(local) => {
                                     if source_relation < lookup_relation {
                                         build_halfjoin(
                                             update_stream,
@@ -208,7 +237,28 @@ where
                                         )
                                     }
                                 }
-                                Err(trace) => {
+                                DeltaArrangement::Trace(trace) => {
+                                    if source_relation < lookup_relation {
+                                        build_halfjoin(
+                                            update_stream,
+                                            trace.enter_region(region),
+                                            stream_key,
+                                            stream_thinning,
+                                            |t1, t2| t1.le(t2),
+                                            closure,
+                                        )
+                                    } else {
+                                        build_halfjoin(
+                                            update_stream,
+                                            trace.enter_region(region),
+                                            stream_key,
+                                            stream_thinning,
+                                            |t1, t2| t1.lt(t2),
+                                            closure,
+                                        )
+                                    }
+                                }
+                                DeltaArrangement::SpillTrace(trace) => {
                                     if source_relation < lookup_relation {
                                         build_halfjoin(
                                             update_stream,
@@ -29,7 +29,9 @@ use mz_repr::{Diff, Row, RowArena};
 
 use crate::operator::CollectionExt;
 use crate::render::context::CollectionBundle;
-use crate::render::context::{Arrangement, ArrangementFlavor, ArrangementImport, Context};
+use crate::render::context::{
+    Arrangement, ArrangementFlavor, ArrangementImport, Context, SpillArrangementImport,
+};
 use mz_repr::DatumVec;
 
 /// Different forms the streamed data might take.
@@ -45,6 +47,8 @@ where
     Local(Arrangement<G, Row>),
     /// An imported arrangement.
     Trace(ArrangementImport<G, Row, T>),
+    /// An imported arrangement that can spill to disk.
+    SpillTrace(SpillArrangementImport<G, Row, T>),
 }
 
 impl<G, T> Context<G, Row, T>
@@ -80,6 +84,10 @@ where
                 errors.push(errs.as_collection(|k, _v| k.clone()));
                 JoinedFlavor::Trace(oks)
             }
+            (Some(ArrangementFlavor::SpillTrace(_gid, oks, errs)), None) => {
+                errors.push(errs.as_collection(|k, _v| k.clone()));
+                JoinedFlavor::SpillTrace(oks)
+            }
             (_, initial_closure) => {
                 // TODO: extract closure from the first stage in the join plan, should it exist.
                 // TODO: apply that closure in `flat_map_ref` rather than calling `.collection`.
@@ -206,7 +214,7 @@ where
             joined = JoinedFlavor::Local(arranged);
         }
 
-        // Demultiplex the four different cross products of arrangement types we might have.
+        // Demultiplex the different cross products of arrangement types we might have.
         let arrangement = lookup_relation
             .arrangement(&lookup_key[..])
             .expect("Arrangement absent despite explicit construction");
@@ -214,34 +222,49 @@ where
             JoinedFlavor::Collection(_) => {
                 unreachable!("JoinedFlavor::Collection variant avoided at top of method");
             }
-            JoinedFlavor::Local(local) => match arrangement {
-                ArrangementFlavor::Local(oks, errs1) => {
-                    let (oks, errs2) = self.differential_join_inner(local, oks, closure);
-                    errors.push(errs1.as_collection(|k, _v| k.clone()));
-                    errors.push(errs2);
-                    oks
-                }
-                ArrangementFlavor::Trace(_gid, oks, errs1) => {
-                    let (oks, errs2) = self.differential_join_inner(local, oks, closure);
-                    errors.push(errs1.as_collection(|k, _v| k.clone()));
-                    errors.push(errs2);
-                    oks
-                }
-            },
-            JoinedFlavor::Trace(trace) => match arrangement {
-                ArrangementFlavor::Local(oks, errs1) => {
-                    let (oks, errs2) = self.differential_join_inner(trace, oks, closure);
-                    errors.push(errs1.as_collection(|k, _v| k.clone()));
-                    errors.push(errs2);
-                    oks
-                }
-                ArrangementFlavor::Trace(_gid, oks, errs1) => {
-                    let (oks, errs2) = self.differential_join_inner(trace, oks, closure);
-                    errors.push(errs1.as_collection(|k, _v| k.clone()));
-                    errors.push(errs2);
-                    oks
-                }
-            },
+            JoinedFlavor::Local(local) => {
+                self.differential_join_arrangement(local, arrangement, closure, errors)
+            }
+            JoinedFlavor::Trace(trace) => {
+                self.differential_join_arrangement(trace, arrangement, closure, errors)
+            }
+            JoinedFlavor::SpillTrace(trace) => {
+                self.differential_join_arrangement(trace, arrangement, closure, errors)
+            }
+        }
+    }
+
+    /// Joins `arrangement` to the arranged version of the join of previous
+    /// inputs, whatever the flavor of `arrangement`.
+    fn differential_join_arrangement<J>(
+        &mut self,
+        prev_keyed: J,
+        arrangement: ArrangementFlavor<G, Row, T>,
+        closure: JoinClosure,
+        errors: &mut Vec<Collection<G, DataflowError>>,
+    ) -> Collection<G, Row>
+    where
+        J: JoinCore<G, Row, Row, mz_repr::Diff>,
+    {
+        match arrangement {
+            ArrangementFlavor::Local(oks, errs1) => {
+                let (oks, errs2) = self.differential_join_inner(prev_keyed, oks, closure);
+                errors.push(errs1.as_collection(|k, _v| k.clone()));
+                errors.push(errs2);
+                oks
+            }
+            ArrangementFlavor::Trace(_gid, oks, errs1) => {
+                let (oks, errs2) = self.differential_join_inner(prev_keyed, oks, closure);
+                errors.push(errs1.as_collection(|k, _v| k.clone()));
+                errors.push(errs2);
+                oks
+            }
+            ArrangementFlavor::SpillTrace(_gid, oks, errs1) => {
+                let (oks, errs2) = self.differential_join_inner(prev_keyed, oks, closure);
+                errors.push(errs1.as_collection(|k, _v| k.clone()));
+                errors.push(errs2);
+                oks
+            }
         }
     }
 
@@ -11,5 +11,6 @@
 //!
 //! Consult [mz_dataflow_types::plan::join::JoinPlan] documentation for details.
 
+mod anti_join;
 mod delta_join;
 mod linear_join;
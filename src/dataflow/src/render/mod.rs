@@ -113,9 +113,11 @@ use timely::worker::Worker as TimelyWorker;
 use mz_dataflow_types::*;
 use mz_expr::{GlobalId, Id};
 use mz_ore::collections::CollectionExt as IteratorExt;
-use mz_repr::{Row, Timestamp};
+use mz_repr::{Diff, Row, Timestamp};
 
-use crate::arrangement::manager::TraceBundle;
+use crate::arrangement::manager::{KeysValsTrace, TraceBundle};
+use crate::arrangement::spill::SpillConfig;
+use crate::logging::materialized::MaterializedEvent;
 pub use crate::render::context::CollectionBundle;
 use crate::render::context::{ArrangementFlavor, Context};
 use crate::server::boundary::{ComputeReplay, StorageCapture};
@@ -190,6 +192,16 @@ pub fn build_compute_dataflow<A: Allocate, B: ComputeReplay>(
     let worker_logging = timely_worker.log_register().get("timely");
     let name = format!("Dataflow: {}", &dataflow.debug_name);
 
+    // The index that the dataflow exports spills if it was created with
+    // `WITH (spill = true)`, and if spilling is configured. No other
+    // arrangement of the dataflow spills.
+    let spill = match (&compute_state.spill_config, dataflow.spill) {
+        (Some(config), Some(spill_id)) => {
+            Some((spill_id, Rc::new(config.derive(dataflow.spill_threshold))))
+        }
+        _ => None,
+    };
+
     timely_worker.dataflow_core(&name, worker_logging, Box::new(()), |_, scope| {
         // The scope.clone() occurs to allow import in the region.
         // We build a region here to establish a pattern of a scope inside the dataflow,
@@ -246,7 +258,29 @@ pub fn build_compute_dataflow<A: Allocate, B: ComputeReplay>(
 
             // Build declared objects.
             for object in dataflow.objects_to_build {
-                context.build_object(region, object);
+                let spill_config = spill
+                    .as_ref()
+                    .filter(|(spill_id, _)| *spill_id == object.id)
+                    .map(|(_, config)| Rc::clone(config));
+                context.build_object(region, object, spill_config);
+            }
+
+            // Attribute the operators of each plan node to the exports.
+            let plan_node_operators = context.take_plan_node_operators();
+            if let Some(logger) = compute_state.materialized_logger.as_mut() {
+                let export_ids = indexes
+                    .iter()
+                    .map(|(idx_id, _, _)| *idx_id)
+                    .chain(sinks.iter().map(|(sink_id, _, _)| *sink_id));
+                for export_id in export_ids {
+                    for (node, operators) in &plan_node_operators {
+                        logger.log(MaterializedEvent::PlanNodeOperators {
+                            export_id,
+                            node: *node,
+                            operators: operators.clone(),
+                        });
+                    }
+                }
             }
 
             // Export declared indexes.
@@ -277,24 +311,36 @@ where
     ) {
         if let Some(traces) = compute_state.traces.get_mut(&idx_id) {
             let token = traces.to_drop().clone();
-            let (ok_arranged, ok_button) = traces.oks_mut().import_frontier_core(
-                scope,
-                &format!("Index({}, {:?})", idx.on_id, idx.key),
-                self.as_of_frontier.clone(),
-            );
             let (err_arranged, err_button) = traces.errs_mut().import_frontier_core(
                 scope,
                 &format!("ErrIndex({}, {:?})", idx.on_id, idx.key),
                 self.as_of_frontier.clone(),
             );
-            let ok_arranged = ok_arranged.enter(region);
             let err_arranged = err_arranged.enter(region);
+            let ok_name = format!("Index({}, {:?})", idx.on_id, idx.key);
+            let (flavor, ok_button) = match traces.oks_mut() {
+                KeysValsTrace::Memory(oks) => {
+                    let (ok_arranged, ok_button) =
+                        oks.import_frontier_core(scope, &ok_name, self.as_of_frontier.clone());
+                    let ok_arranged = ok_arranged.enter(region);
+                    (
+                        ArrangementFlavor::Trace(idx_id, ok_arranged, err_arranged),
+                        ok_button,
+                    )
+                }
+                KeysValsTrace::Spill(oks) => {
+                    let (ok_arranged, ok_button) =
+                        oks.import_frontier_core(scope, &ok_name, self.as_of_frontier.clone());
+                    let ok_arranged = ok_arranged.enter(region);
+                    (
+                        ArrangementFlavor::SpillTrace(idx_id, ok_arranged, err_arranged),
+                        ok_button,
+                    )
+                }
+            };
             self.update_id(
                 Id::Global(idx.on_id),
-                CollectionBundle::from_expressions(
-                    idx.key.clone(),
-                    ArrangementFlavor::Trace(idx_id, ok_arranged, err_arranged),
-                ),
+                CollectionBundle::from_expressions(idx.key.clone(), flavor),
             );
             tokens.insert(
                 idx_id,
@@ -308,13 +354,18 @@ where
         }
     }
 
+    /// Builds `object`, whose plan forms the arrangement of an index from
+    /// batches that spill according to `spill_config` if it is set.
     pub(crate) fn build_object(
         &mut self,
         scope: &mut Child<'g, G, G::Timestamp>,
         object: BuildDesc<plan::Plan>,
+        spill_config: Option<Rc<SpillConfig<Row, Row, Timestamp, Diff>>>,
     ) {
         // First, transform the relation expression into a render plan.
+        self.spill_config = spill_config.filter(|_| matches!(object.view, Plan::ArrangeBy { .. }));
         let bundle = self.render_plan(object.view, scope, scope.index());
+        self.spill_config = None;
         self.insert_id(Id::Global(object.id), bundle);
     }
 
@@ -333,6 +384,13 @@ where
                 needed_tokens.push(Rc::clone(&token));
             }
         }
+        if let Some((oks, errs)) = self.spilled.remove(&idx.key) {
+            compute_state.traces.set(
+                idx_id,
+                TraceBundle::spilling(oks.trace, errs.trace).with_drop(needed_tokens),
+            );
+            return;
+        }
         let bundle = self.lookup_id(Id::Global(idx_id)).unwrap_or_else(|| {
             panic!(
                 "Arrangement alarmingly absent! id: {:?}",
@@ -346,7 +404,8 @@ where
                     TraceBundle::new(oks.trace, errs.trace).with_drop(needed_tokens),
                 );
             }
-            Some(ArrangementFlavor::Trace(gid, _, _)) => {
+            Some(ArrangementFlavor::Trace(gid, _, _))
+            | Some(ArrangementFlavor::SpillTrace(gid, _, _)) => {
                 // Duplicate of existing arrangement with id `gid`, so
                 // just create another handle to that arrangement.
                 let trace = compute_state.traces.get(&gid).unwrap().clone();
@@ -376,11 +435,30 @@ where
     ///
     /// The return type reflects the uncertainty about the data representation, perhaps
     /// as a stream of data, perhaps as an arrangement, perhaps as a stream of batches.
+    ///
+    /// The operators created for each node of the plan are recorded, so that their
+    /// runtime behavior can be attributed back to the plan.
     pub fn render_plan(
         &mut self,
         plan: plan::Plan,
         scope: &mut G,
         worker_index: usize,
+    ) -> CollectionBundle<G, Row, G::Timestamp> {
+        let node = self.next_plan_node;
+        self.next_plan_node += 1;
+        let first_operator = scope.peek_identifier();
+        let bundle = self.render_plan_node(plan, scope, worker_index);
+        self.plan_node_operators
+            .push((node, first_operator..scope.peek_identifier()));
+        bundle
+    }
+
+    /// Renders the root node of a plan, rendering its inputs with `render_plan`.
+    fn render_plan_node(
+        &mut self,
+        plan: plan::Plan,
+        scope: &mut G,
+        worker_index: usize,
     ) -> CollectionBundle<G, Row, G::Timestamp> {
         match plan {
             Plan::Constant { rows } => {
@@ -482,6 +560,9 @@ where
                     mz_dataflow_types::plan::join::JoinPlan::Delta(delta_plan) => {
                         self.render_delta_join(inputs, delta_plan, scope)
                     }
+                    mz_dataflow_types::plan::join::JoinPlan::Anti(anti_plan) => {
+                        self.render_anti_join(inputs, anti_plan, scope)
+                    }
                 }
             }
             Plan::Reduce {
@@ -529,8 +610,19 @@ where
                 input_key,
                 input_mfp,
             } => {
+                // Only the arrangements that the plan of a spilling index forms
+                // at its root spill, rather than those of its inputs.
+                let spill_config = self.spill_config.take();
                 let input = self.render_plan(*input, scope, worker_index);
-                input.ensure_collections(keys, input_key, input_mfp)
+                match spill_config {
+                    Some(config) => {
+                        let (bundle, spilled) =
+                            input.ensure_spilling_collections(keys, input_key, input_mfp, config);
+                        self.spilled.extend(spilled);
+                        bundle
+                    }
+                    None => input.ensure_collections(keys, input_key, input_mfp),
+                }
             }
         }
     }
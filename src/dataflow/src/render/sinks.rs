@@ -10,13 +10,19 @@
 //! Logic related to the creation of dataflow sinks.
 
 use std::any::Any;
-use std::cell::RefCell;
-use std::collections::HashSet;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashSet};
 use std::rc::Rc;
+use std::time::Duration;
 
 use differential_dataflow::operators::arrange::arrangement::ArrangeByKey;
-use differential_dataflow::{Collection, Hashable};
+use differential_dataflow::{AsCollection, Collection, Hashable};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::{Capability, Operator};
 use timely::dataflow::Scope;
+use timely::progress::frontier::AntichainRef;
+use timely::progress::Antichain;
+use timely::scheduling::Activator;
 
 use mz_dataflow_types::sinks::*;
 use mz_expr::{permutation_for_arrangement, GlobalId, MapFilterProject};
@@ -72,13 +78,26 @@ where
             collection
         };
 
+        let (collection, write_progress) = match sink_render.get_max_read_ahead() {
+            Some(max_read_ahead) => {
+                let (collection, write_progress) = pace(&collection, sink_id, max_read_ahead);
+                (collection, Some(write_progress))
+            }
+            None => (collection, None),
+        };
+
         let collection = apply_sink_envelope(sink, &sink_render, collection);
 
         // TODO(benesch): errors should stream out through the sink,
         // if we figure out a protocol for that.
 
-        let sink_token =
-            sink_render.render_continuous_sink(compute_state, sink, sink_id, collection);
+        let sink_token = sink_render.render_continuous_sink(
+            compute_state,
+            sink,
+            sink_id,
+            collection,
+            write_progress,
+        );
 
         if let Some(sink_token) = sink_token {
             needed_tokens.push(sink_token);
@@ -185,6 +204,20 @@ where
             collection
         }
         Some(SinkEnvelope::Upsert) => {
+            // Advancing every update to the end of its coalescing interval
+            // consolidates the updates of each key within the interval, so
+            // that only the last state of the key is emitted.
+            let keyed = match sink_render.get_coalesce_interval() {
+                Some(interval) => {
+                    let interval = Timestamp::try_from(interval.as_millis())
+                        .expect("coalesce interval fits in a timestamp");
+                    keyed.delay(move |time| match time % interval {
+                        0 => *time,
+                        rem => time.checked_add(interval - rem).unwrap_or(Timestamp::MAX),
+                    })
+                }
+                None => keyed,
+            };
             let combined = combine_at_timestamp(keyed.arrange_by_key().stream);
 
             let collection = combined.map(|(k, v)| {
@@ -193,8 +226,8 @@ where
             });
             collection
         }
-        // No envelope, this can only happen for TAIL sinks, which work
-        // on vanilla rows.
+        // No envelope, this can only happen for TAIL and Parquet sinks,
+        // which work on vanilla rows.
         None => keyed.map(|(key, value)| (key, Some(value))),
     };
 
@@ -211,12 +244,30 @@ where
 
     fn get_relation_key_indices(&self) -> Option<&[usize]>;
 
+    /// The interval over which the updates of each key are coalesced, if the
+    /// sink uses the upsert envelope.
+    fn get_coalesce_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// The maximum number of updates that the sink reads from its input ahead
+    /// of the updates that it has written, if the sink paces its input.
+    ///
+    /// A sink that paces its input must be written by the worker that its
+    /// identifier hashes to, and must report the times up to which it has
+    /// written its updates through the `write_progress` that it is rendered
+    /// with.
+    fn get_max_read_ahead(&self) -> Option<usize> {
+        None
+    }
+
     fn render_continuous_sink(
         &self,
         compute_state: &mut crate::server::ComputeState,
         sink: &SinkDesc,
         sink_id: GlobalId,
         sinked_collection: Collection<G, (Option<Row>, Option<Row>), Diff>,
+        write_progress: Option<WriteProgress>,
     ) -> Option<Rc<dyn Any>>
     where
         G: Scope<Timestamp = Timestamp>;
@@ -230,5 +281,272 @@ where
         SinkConnector::Kafka(connector) => Box::new(connector.clone()),
         SinkConnector::AvroOcf(connector) => Box::new(connector.clone()),
         SinkConnector::Tail(connector) => Box::new(connector.clone()),
+        SinkConnector::Postgres(connector) => Box::new(connector.clone()),
+        SinkConnector::Parquet(connector) => Box::new(connector.clone()),
+        SinkConnector::Http(connector) => Box::new(connector.clone()),
+    }
+}
+
+/// How far a sink that paces its input got in writing the updates that its
+/// input released to it.
+///
+/// The input of the sink and the sink share this on the worker that writes
+/// the sink, so that the sink can wake its input as soon as it has written
+/// the updates released before.
+#[derive(Clone)]
+pub struct WriteProgress {
+    /// The times whose updates the sink has not written yet.
+    lower: Rc<RefCell<Antichain<Timestamp>>>,
+    /// The number of updates that the input of the sink has read but not
+    /// released yet.
+    buffered: Rc<Cell<usize>>,
+    /// Activates the input of the sink.
+    activator: Activator,
+}
+
+impl WriteProgress {
+    /// Reports that the sink has written all updates at times that are not
+    /// beyond `lower`, and wakes the input of the sink if that changed.
+    pub fn advance(&self, lower: AntichainRef<Timestamp>) {
+        let mut current = self.lower.borrow_mut();
+        if current.borrow() != lower {
+            *current = lower.to_owned();
+            self.activator.activate();
+        }
+    }
+
+    /// Returns true if the sink has not written all updates before `time`.
+    fn less_than(&self, time: &Timestamp) -> bool {
+        self.lower.borrow().less_than(time)
+    }
+
+    #[cfg(test)]
+    fn buffered(&self) -> usize {
+        self.buffered.get()
+    }
+}
+
+/// Presents `collection` to the sink `sink_id`, which paces its input,
+/// releasing its updates only once the sink has written the updates released
+/// before, as reported through the returned [`WriteProgress`].
+///
+/// The updates are moved to the worker that writes the sink, where they are
+/// buffered by time along with a capability for each time, which holds back
+/// the sink. The worker releases the updates of the earliest complete times
+/// until it has released at least `max_read_ahead` updates, but always
+/// releases all updates of a time at once. It stops reading its input while it
+/// buffers `max_read_ahead` updates or more, and leaves the remaining updates
+/// in its input, whose frontier cannot advance past them.
+fn pace<G>(
+    collection: &Collection<G, Row, Diff>,
+    sink_id: GlobalId,
+    max_read_ahead: usize,
+) -> (Collection<G, Row, Diff>, WriteProgress)
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let scope = collection.scope();
+    let max_read_ahead = std::cmp::max(1, max_read_ahead);
+    // The sink is written by the worker that its identifier hashes to.
+    let hashed_id = sink_id.hashed();
+    let mut write_progress = None;
+
+    let paced = collection
+        .inner
+        .unary_frontier(
+            Exchange::new(move |_| hashed_id),
+            "PaceSinkInput",
+            |_capability, info| {
+                let progress = WriteProgress {
+                    lower: Rc::new(RefCell::new(Antichain::from_elem(0))),
+                    buffered: Rc::new(Cell::new(0)),
+                    activator: scope.activator_for(&info.address[..]),
+                };
+                write_progress = Some(progress.clone());
+                let mut pending: BTreeMap<Timestamp, (Capability<Timestamp>, Vec<(Row, Diff)>)> =
+                    BTreeMap::new();
+                let mut buffer = Vec::new();
+                move |input, output| {
+                    let upper = input.frontier().frontier().iter().next().copied();
+                    let complete = |time: &Timestamp| upper.map_or(true, |upper| *time < upper);
+
+                    // Release the complete times once the sink has written the
+                    // updates released before. The sink activates us once it
+                    // has.
+                    if let Some(lower) = pending.keys().next().copied() {
+                        if complete(&lower) && !progress.less_than(&lower) {
+                            let counts = pending
+                                .iter()
+                                .map(|(time, (_capability, updates))| (*time, updates.len()));
+                            let read_upper = read_ahead_upper(counts, max_read_ahead, upper);
+                            let unread = match read_upper {
+                                Some(read_upper) => pending.split_off(&read_upper),
+                                None => BTreeMap::new(),
+                            };
+                            for (time, (capability, updates)) in
+                                std::mem::replace(&mut pending, unread)
+                            {
+                                progress
+                                    .buffered
+                                    .set(progress.buffered.get() - updates.len());
+                                let mut session = output.session(&capability);
+                                for (row, diff) in updates {
+                                    session.give((row, time, diff));
+                                }
+                            }
+                        }
+                    }
+
+                    // If we do not read anything below, the frontier accounts
+                    // for everything that we read before, and the updates at
+                    // an incomplete earliest time exceed the limit by
+                    // themselves.
+                    let stalled = !pending.keys().next().map_or(true, complete);
+                    let mut read = || match input.next() {
+                        Some((capability, data)) => {
+                            data.swap(&mut buffer);
+                            progress
+                                .buffered
+                                .set(progress.buffered.get() + buffer.len());
+                            for (row, time, diff) in buffer.drain(..) {
+                                pending
+                                    .entry(time)
+                                    .or_insert_with(|| (capability.delayed(&time), Vec::new()))
+                                    .1
+                                    .push((row, diff));
+                            }
+                            true
+                        }
+                        None => false,
+                    };
+                    let mut any_read = false;
+                    while progress.buffered.get() < max_read_ahead && read() {
+                        any_read = true;
+                    }
+                    if !any_read && stalled {
+                        // Read on until we have all updates of that time, as a
+                        // time is released at once.
+                        any_read = read();
+                    }
+                    if any_read {
+                        // Check again once the frontier accounts for what we
+                        // read, as there may be nothing else to wake us.
+                        progress.activator.activate();
+                    }
+                }
+            },
+        )
+        .as_collection();
+    (
+        paced,
+        write_progress.expect("constructed with the operator"),
+    )
+}
+
+/// Returns the time up to which a paced sink reads, given the number of
+/// updates at each of the times that it can read, in ascending order of time,
+/// and the `upper` of the complete times.
+///
+/// The sink reads the updates of the earliest times until it has read at least
+/// `max_read_ahead` updates, but always reads all updates of a time at once.
+fn read_ahead_upper<I>(
+    counts: I,
+    max_read_ahead: usize,
+    upper: Option<Timestamp>,
+) -> Option<Timestamp>
+where
+    I: IntoIterator<Item = (Timestamp, usize)>,
+{
+    let mut read = 0;
+    for (time, count) in counts {
+        read += count;
+        if read >= max_read_ahead {
+            return match time.checked_add(1) {
+                Some(next) if upper.map_or(true, |upper| next < upper) => Some(next),
+                _ => upper,
+            };
+        }
+    }
+    upper
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use differential_dataflow::input::Input;
+    use timely::progress::Antichain;
+
+    use mz_expr::GlobalId;
+    use mz_repr::{Datum, Diff, Row, Timestamp};
+
+    use super::{pace, read_ahead_upper};
+
+    #[test]
+    fn pace_bounds_buffered_updates() {
+        timely::execute_directly(|worker| {
+            let (mut input, write_progress, probe) = worker.dataflow::<Timestamp, _, _>(|scope| {
+                let (input, collection) = scope.new_collection::<Row, Diff>();
+                let (paced, write_progress) = pace(&collection, GlobalId::User(1), 10);
+                (input, write_progress, paced.probe())
+            });
+            // The sink never writes anything, as if its broker was down.
+            for time in 0..100 {
+                input.advance_to(time);
+                for i in 0..5 {
+                    input.insert(Row::pack_slice(&[Datum::Int64(i)]));
+                }
+                input.flush();
+            }
+            input.advance_to(100);
+            input.flush();
+            for _ in 0..100 {
+                worker.step();
+            }
+            // The updates at times 0 and 1 were released, and those at times
+            // 2 and 3 are buffered. The rest were left unread.
+            assert_eq!(write_progress.buffered(), 10);
+            assert_eq!(
+                probe.with_frontier(|f| f.to_owned()),
+                Antichain::from_elem(2)
+            );
+
+            // Once the sink has written the released updates, the next ones
+            // are released and read.
+            write_progress.advance(Antichain::from_elem(2).borrow());
+            for _ in 0..100 {
+                worker.step();
+            }
+            assert_eq!(write_progress.buffered(), 10);
+            assert_eq!(
+                probe.with_frontier(|f| f.to_owned()),
+                Antichain::from_elem(4)
+            );
+        });
+    }
+
+    #[test]
+    fn read_ahead_upper_bounds_updates() {
+        let counts = BTreeMap::from([(1, 2), (3, 2), (4, 1), (7, 5)]);
+        assert_eq!(read_ahead_upper(counts.clone(), 4, Some(10)), Some(4));
+        assert_eq!(read_ahead_upper(counts.clone(), 5, Some(10)), Some(5));
+        // A time is always read as a whole, even if it exceeds the limit.
+        assert_eq!(read_ahead_upper(counts.clone(), 1, Some(10)), Some(2));
+        assert_eq!(read_ahead_upper(counts.clone(), 6, Some(10)), Some(8));
+        // Everything up to the upper is read if it fits within the limit.
+        assert_eq!(read_ahead_upper(counts.clone(), 100, Some(10)), Some(10));
+        assert_eq!(read_ahead_upper(counts.clone(), 100, None), None);
+        assert_eq!(read_ahead_upper(BTreeMap::new(), 1, Some(10)), Some(10));
+    }
+
+    #[test]
+    fn read_ahead_upper_stops_at_upper() {
+        let counts = BTreeMap::from([(9, 3)]);
+        assert_eq!(read_ahead_upper(counts.clone(), 1, Some(10)), Some(10));
+        let counts = BTreeMap::from([(u64::MAX, 3)]);
+        assert_eq!(read_ahead_upper(counts.clone(), 1, None), None);
+        // Times that are not complete yet are never read.
+        let counts = BTreeMap::from([(1, 1), (12, 5)]);
+        assert_eq!(read_ahead_upper(counts.clone(), 3, Some(10)), Some(10));
     }
 }
@@ -35,6 +35,7 @@ use timely::progress::Antichain;
 use crate::decode::decode_cdcv2;
 use crate::decode::render_decode;
 use crate::decode::render_decode_delimited;
+use crate::decode::DecodeOperators;
 use crate::logging::materialized::Logger;
 use crate::operator::{CollectionExt, StreamExt};
 use crate::render::envelope_none;
@@ -178,6 +179,18 @@ where
             ((ok, err), Rc::new(()))
         }
 
+        // Webhook sources are fed by the coordinator in the same way as tables.
+        SourceConnector::External {
+            connector: ExternalSourceConnector::Webhook(webhook),
+            ..
+        } => {
+            let (local_input, (ok, err)) =
+                import_table(as_of_frontier, storage_state, scope, webhook.persisted_name);
+            storage_state.local_inputs.insert(src_id, local_input);
+
+            ((ok, err), Rc::new(()))
+        }
+
         SourceConnector::External {
             connector,
             encoding,
@@ -322,6 +335,7 @@ where
                     }
                     ExternalSourceConnector::Postgres(_) => unreachable!(),
                     ExternalSourceConnector::PubNub(_) => unreachable!(),
+                    ExternalSourceConnector::Webhook(_) => unreachable!(),
                 };
 
                 // Include any source errors.
@@ -333,7 +347,8 @@ where
                 );
 
                 let (stream, errors) = {
-                    let (key_desc, _) = encoding.desc().expect("planning has verified this");
+                    let (key_desc, value_desc) =
+                        encoding.desc().expect("planning has verified this");
                     let (key_encoding, value_encoding) = match encoding {
                         SourceDataEncoding::KeyValue { key, value } => (Some(key), value),
                         SourceDataEncoding::Single(value) => (None, value),
@@ -347,6 +362,26 @@ where
 
                     let metadata_columns = connector.metadata_column_types(default_metadata);
 
+                    // Decoders can project and filter the values they decode as long as each
+                    // value forms a row of the source on its own: the rows must not include
+                    // keys, be related by an envelope, or be persisted before the filtering.
+                    let decode_operators = match (&envelope, &source_persist_config) {
+                        (SourceEnvelope::None(KeyEnvelope::None), None)
+                            if value_desc.arity() <= src.desc.arity() =>
+                        {
+                            linear_operators.take().map(|operator| {
+                                let (decode_operators, remainder) = DecodeOperators::split(
+                                    operator,
+                                    value_desc.arity(),
+                                    src.desc.arity(),
+                                );
+                                linear_operators = remainder;
+                                decode_operators
+                            })
+                        }
+                        _ => None,
+                    };
+
                     // CDCv2 can't quite be slotted in to the below code, since it determines
                     // its own diffs/timestamps as part of decoding.
                     if let SourceEnvelope::CdcV2 = &envelope {
@@ -381,7 +416,7 @@ where
                                 dataflow_debug_name,
                                 &envelope,
                                 metadata_columns,
-                                &mut linear_operators,
+                                decode_operators,
                                 storage_state.metrics.clone(),
                             ),
                             SourceType::ByteStream(source) => render_decode(
@@ -389,7 +424,7 @@ where
                                 value_encoding,
                                 dataflow_debug_name,
                                 metadata_columns,
-                                &mut linear_operators,
+                                decode_operators,
                                 storage_state.metrics.clone(),
                             ),
                         };
@@ -77,6 +77,12 @@ where
             let errs = errs.as_collection(|k, _| k.clone()).arrange_by_self();
             CollectionBundle::from_expressions(key, ArrangementFlavor::Local(oks, errs))
         }
+        ArrangementFlavor::SpillTrace(_, oks, errs) => {
+            let oks = threshold_arrangement(&oks, "Threshold trace", |count| *count > 0);
+            use differential_dataflow::operators::arrange::ArrangeBySelf;
+            let errs = errs.as_collection(|k, _| k.clone()).arrange_by_self();
+            CollectionBundle::from_expressions(key, ArrangementFlavor::Local(oks, errs))
+        }
     }
 }
 
@@ -104,6 +110,9 @@ where
         ArrangementFlavor::Trace(_, oks, _) => {
             threshold_arrangement(oks, "Threshold retractions trace", |count| *count < 0)
         }
+        ArrangementFlavor::SpillTrace(_, oks, _) => {
+            threshold_arrangement(oks, "Threshold retractions trace", |count| *count < 0)
+        }
     };
     let (oks, errs) = arrangement.as_collection();
 
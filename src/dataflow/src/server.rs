@@ -10,6 +10,8 @@
 //! An interactive dataflow server.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::sync::Mutex;
 use std::time::Instant;
 
@@ -35,7 +37,8 @@ use mz_ore::result::ResultExt;
 use mz_repr::{DatumVec, Diff, Row, RowArena, Timestamp};
 
 use self::metrics::{ServerMetrics, WorkerMetrics};
-use crate::arrangement::manager::{TraceBundle, TraceManager, TraceMetrics};
+use crate::arrangement::manager::{KeysValsTrace, TraceBundle, TraceManager, TraceMetrics};
+use crate::arrangement::spill::{self, SpillConfig, DEFAULT_SPILL_THRESHOLD};
 use crate::event::ActivatedEventPusher;
 use crate::logging::materialized::MaterializedEvent;
 use crate::metrics::Metrics;
@@ -67,6 +70,9 @@ pub struct Config {
     pub metrics_registry: MetricsRegistry,
     /// A handle to a persistence runtime, if persistence is enabled.
     pub persister: Option<mz_persist::client::RuntimeClient>,
+    /// The directory in which to spill the arrangements of indexes that
+    /// are allowed to spill, if any. Its contents are removed on startup.
+    pub spill_directory: Option<PathBuf>,
 }
 
 /// A handle to a running dataflow server.
@@ -105,6 +111,10 @@ pub fn serve(config: Config) -> Result<(Server, LocalClient), anyhow::Error> {
             .collect(),
     );
 
+    if let Some(spill_directory) = &config.spill_directory {
+        spill::prepare_directory(spill_directory)?;
+    }
+
     let tokio_executor = tokio::runtime::Handle::current();
     let now = config.now;
     let metrics = Metrics::register_with(&config.metrics_registry);
@@ -122,6 +132,12 @@ pub fn serve(config: Config) -> Result<(Server, LocalClient), anyhow::Error> {
         let sink_metrics = sink_metrics.clone();
         let timely_worker_index = timely_worker.index();
         let timely_worker_peers = timely_worker.peers();
+        let spill_config = config.spill_directory.as_ref().map(|directory| {
+            let directory = directory.join(worker_idx.to_string());
+            std::fs::create_dir_all(&directory)
+                .unwrap_or_else(|e| panic!("creating {}: {}", directory.display(), e));
+            Rc::new(SpillConfig::new(directory, DEFAULT_SPILL_THRESHOLD))
+        });
         Worker {
             timely_worker,
             compute_state: ComputeState {
@@ -131,8 +147,11 @@ pub fn serve(config: Config) -> Result<(Server, LocalClient), anyhow::Error> {
                 sink_write_frontiers: HashMap::new(),
                 pending_peeks: Vec::new(),
                 reported_frontiers: HashMap::new(),
+                reported_statistics: HashMap::new(),
+                statistics_reported_at: Instant::now(),
                 sink_metrics,
                 materialized_logger: None,
+                spill_config,
             },
             storage_state: StorageState {
                 local_inputs: HashMap::new(),
@@ -212,6 +231,7 @@ where
 
             // Report frontier information back the coordinator.
             self.activate_compute().report_compute_frontiers();
+            self.activate_compute().report_arrangement_statistics();
             self.activate_storage().update_rt_timestamps();
             self.activate_storage().report_timestamp_bindings();
 
@@ -395,7 +415,27 @@ impl PendingPeek {
         }
 
         // Cursor and bound lifetime for `Row` data in the backing trace.
-        let (mut cursor, storage) = self.trace_bundle.oks_mut().cursor();
+        match self.trace_bundle.oks_mut() {
+            KeysValsTrace::Memory(oks) => {
+                let (cursor, storage) = oks.cursor();
+                self.collect_finished_rows(cursor, storage)
+            }
+            KeysValsTrace::Spill(oks) => {
+                let (cursor, storage) = oks.cursor();
+                self.collect_finished_rows(cursor, storage)
+            }
+        }
+    }
+
+    /// Collects the rows of a known-complete peek from `cursor`.
+    fn collect_finished_rows<C>(
+        &self,
+        mut cursor: C,
+        storage: C::Storage,
+    ) -> Result<Vec<Row>, String>
+    where
+        C: Cursor<Row, Row, Timestamp, Diff>,
+    {
         // Accumulated `Vec<Datum>` results that we are likely to return.
         let mut results = Vec::new();
 
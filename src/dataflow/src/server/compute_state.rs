@@ -21,20 +21,25 @@ use timely::progress::ChangeBatch;
 use timely::worker::Worker as TimelyWorker;
 use tokio::sync::mpsc;
 
-use mz_dataflow_types::client::{ComputeCommand, ComputeResponse, Response};
+use mz_dataflow_types::client::{ArrangementStatistics, ComputeCommand, ComputeResponse, Response};
 use mz_dataflow_types::logging::LoggingConfig;
 use mz_dataflow_types::{DataflowError, PeekResponse, TailResponse};
 use mz_expr::GlobalId;
-use mz_repr::Timestamp;
+use mz_ore::cast::CastFrom;
+use mz_repr::{Diff, Row, Timestamp};
 
 use crate::activator::RcActivator;
 use crate::arrangement::manager::{TraceBundle, TraceManager};
+use crate::arrangement::spill::SpillConfig;
 use crate::logging;
 use crate::logging::materialized::MaterializedEvent;
 use crate::operator::CollectionExt;
 use crate::server::PendingPeek;
 use crate::sink::SinkBaseMetrics;
 
+/// The minimum interval between two reports of arrangement sizes.
+const STATISTICS_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Worker-local state that is maintained across dataflows.
 ///
 /// This state is restricted to the COMPUTE state, the deterministic, idempotent work
@@ -57,10 +62,17 @@ pub struct ComputeState {
     pub(crate) pending_peeks: Vec<PendingPeek>,
     /// Tracks the frontier information that has been sent over `response_tx`.
     pub reported_frontiers: HashMap<GlobalId, Antichain<Timestamp>>,
+    /// Tracks the arrangement sizes that have been sent over `response_tx`.
+    pub reported_statistics: HashMap<GlobalId, ArrangementStatistics>,
+    /// When arrangement sizes were last reported.
+    pub statistics_reported_at: Instant,
     /// Undocumented
     pub sink_metrics: SinkBaseMetrics,
     /// The logger, from Timely's logging framework, if logs are enabled.
     pub materialized_logger: Option<logging::materialized::Logger>,
+    /// How the arrangements of indexes that are allowed to spill do so, if
+    /// spilling is configured.
+    pub spill_config: Option<Rc<SpillConfig<Row, Row, Timestamp, Diff>>>,
 }
 
 /// A wrapper around [ComputeState] with a live timely worker and response channel.
@@ -91,11 +103,15 @@ impl<'a, A: Allocate> ActiveComputeState<'a, A> {
                     self.compute_state.reported_frontiers.remove(&id);
                     self.compute_state.sink_write_frontiers.remove(&id);
                     self.compute_state.dataflow_tokens.remove(&id);
+                    if let Some(logger) = self.compute_state.materialized_logger.as_mut() {
+                        logger.log(MaterializedEvent::PlanNodesDropped(id));
+                    }
                 }
             }
             ComputeCommand::DropIndexes(ids) => {
                 for id in ids {
                     self.compute_state.traces.del_trace(&id);
+                    self.compute_state.reported_statistics.remove(&id);
                     let frontier = self
                         .compute_state
                         .reported_frontiers
@@ -103,6 +119,7 @@ impl<'a, A: Allocate> ActiveComputeState<'a, A> {
                         .expect("Dropped index with no frontier");
                     if let Some(logger) = self.compute_state.materialized_logger.as_mut() {
                         logger.log(MaterializedEvent::Dataflow(id, false));
+                        logger.log(MaterializedEvent::PlanNodesDropped(id));
                         for time in frontier.elements().iter() {
                             logger.log(MaterializedEvent::Frontier(id, *time, -1));
                         }
@@ -517,6 +534,39 @@ impl<'a, A: Allocate> ActiveComputeState<'a, A> {
         }
     }
 
+    /// Send the sizes of arrangements that have changed to the coordinator.
+    ///
+    /// Sizes are reported at most once every [`STATISTICS_REPORT_INTERVAL`], as they
+    /// inform planning decisions but are not needed to make progress.
+    pub fn report_arrangement_statistics(&mut self) {
+        if self.compute_state.statistics_reported_at.elapsed() < STATISTICS_REPORT_INTERVAL {
+            return;
+        }
+        self.compute_state.statistics_reported_at = Instant::now();
+
+        let mut changed = Vec::new();
+        for (id, traces) in self.compute_state.traces.traces.iter_mut() {
+            let mut statistics = ArrangementStatistics::default();
+            traces.oks_mut().map_batch_sizes(|records, keys| {
+                statistics.records += u64::cast_from(records);
+                statistics.keys += u64::cast_from(keys);
+            });
+            let reported = self
+                .compute_state
+                .reported_statistics
+                .entry(*id)
+                .or_default();
+            if *reported != statistics {
+                *reported = statistics;
+                changed.push((*id, statistics));
+            }
+        }
+
+        if !changed.is_empty() {
+            self.send_compute_response(ComputeResponse::ArrangementStatistics(changed));
+        }
+    }
+
     /// Scan pending peeks and attempt to retire each.
     pub fn process_peeks(&mut self) {
         let mut upper = Antichain::new();
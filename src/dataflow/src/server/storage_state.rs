@@ -153,7 +153,8 @@ impl<'a, A: Allocate> ActiveStorageState<'a, A> {
                         }
                         ExternalSourceConnector::Kafka(_) => Some(rt_default),
                         ExternalSourceConnector::Postgres(_)
-                        | ExternalSourceConnector::PubNub(_) => None,
+                        | ExternalSourceConnector::PubNub(_)
+                        | ExternalSourceConnector::Webhook(_) => None,
                     }
                 } else {
                     tracing::debug!(
@@ -8,23 +8,25 @@
 // by the Apache License, Version 2.0.
 
 use std::any::Any;
-use std::fs::OpenOptions;
+use std::fs::{File, OpenOptions};
 use std::rc::Rc;
+use std::time::Instant;
 
 use differential_dataflow::{Collection, Hashable};
 
-use itertools::repeat_n;
+use itertools::{repeat_n, Itertools};
 use timely::dataflow::channels::pact::Exchange;
 use timely::dataflow::operators::generic::Operator;
 use timely::dataflow::Scope;
 use tracing::error;
 
-use mz_dataflow_types::sinks::{AvroOcfSinkConnector, SinkDesc};
+use mz_avro::Codec;
+use mz_dataflow_types::sinks::{AvroOcfCodec, AvroOcfSinkConnector, SinkDesc};
 use mz_expr::GlobalId;
 use mz_interchange::avro::{encode_datums_as_avro, AvroSchemaGenerator};
 use mz_repr::{Diff, RelationDesc, Row, Timestamp};
 
-use crate::render::sinks::SinkRender;
+use crate::render::sinks::{SinkRender, WriteProgress};
 
 impl<G> SinkRender<G> for AvroOcfSinkConnector
 where
@@ -48,6 +50,7 @@ where
         _sink: &SinkDesc,
         sink_id: GlobalId,
         sinked_collection: Collection<G, (Option<Row>, Option<Row>), Diff>,
+        _write_progress: Option<WriteProgress>,
     ) -> Option<Rc<dyn Any>>
     where
         G: Scope<Timestamp = Timestamp>,
@@ -64,6 +67,23 @@ where
     }
 }
 
+/// The file that an Avro OCF sink currently writes to.
+struct OcfFile {
+    writer: mz_avro::Writer<File>,
+    /// The number of bytes written to the file so far.
+    size: usize,
+    /// When the sink started to write to the file.
+    opened_at: Instant,
+}
+
+/// Writes a stream of updates to Avro OCF files.
+///
+/// This operator exchanges all updates to a single worker by hashing on the
+/// given sink `id`. Updates are written once the input frontier has passed
+/// their time, in ascending timestamp order. Before writing the updates at a
+/// timestamp, the sink starts a new file if the current one has exceeded the
+/// maximum file size or the rotation interval of the connector, so that every
+/// file contains complete timestamps.
 fn avro_ocf<G>(
     collection: Collection<G, (Option<Row>, Option<Row>)>,
     id: GlobalId,
@@ -83,11 +103,20 @@ fn avro_ocf<G>(
         let columns = schema_generator.value_columns().to_vec();
         (schema, columns)
     };
+    let codec = match connector.codec {
+        AvroOcfCodec::Null => Codec::Null,
+        AvroOcfCodec::Deflate => Codec::Deflate,
+        AvroOcfCodec::Snappy => Codec::Snappy,
+    };
 
     let mut vector = vec![];
-    let mut avro_writer = None;
+    let mut pending: Vec<(Row, Timestamp, Diff)> = vec![];
+    let mut file: Option<OcfFile> = None;
+    // The index of the file that the sink writes to, see
+    // `AvroOcfSinkConnector::file_path`.
+    let mut file_index = 0;
 
-    // We want exactly one worker to write to the single output file
+    // We want exactly one worker to write to the output files
     let hashed_id = id.hashed();
 
     collection.inner.sink(
@@ -96,40 +125,78 @@ fn avro_ocf<G>(
         move |input| {
             input.for_each(|_, rows| {
                 rows.swap(&mut vector);
-
-                let mut fallible = || -> Result<(), String> {
-                    let avro_writer = match avro_writer.as_mut() {
-                        Some(v) => v,
+                pending.extend(vector.drain(..));
+            });
+
+            let frontier = input.frontier().frontier();
+            let (mut complete, rest): (Vec<_>, Vec<_>) = pending
+                .drain(..)
+                .partition(|(_, time, _)| !frontier.less_equal(time));
+            pending = rest;
+            if complete.is_empty() {
+                return;
+            }
+            complete.sort_by_key(|(_, time, _)| *time);
+
+            let mut fallible = || -> Result<(), String> {
+                for (_time, updates) in &complete.drain(..).group_by(|(_, time, _)| *time) {
+                    if let Some(f) = &file {
+                        let full = connector.max_file_size.map_or(false, |max| f.size >= max);
+                        let expired = connector
+                            .rotate_interval
+                            .map_or(false, |interval| f.opened_at.elapsed() >= interval);
+                        if full || expired {
+                            file = None;
+                            file_index += 1;
+                        }
+                    }
+                    let f = match file.as_mut() {
+                        Some(f) => f,
                         None => {
-                            let file = OpenOptions::new()
-                                .append(true)
-                                .open(&connector.path)
-                                .map_err(|e| {
-                                    format!("creating avro ocf file writer for sink failed: {}", e)
-                                })?;
-                            avro_writer.get_or_insert(mz_avro::Writer::new(schema.clone(), file))
+                            // The first file is created when the sink is
+                            // created, all others when the sink rotates.
+                            let path = connector.file_path(file_index);
+                            let handle = if file_index == 0 {
+                                OpenOptions::new().append(true).open(&path)
+                            } else {
+                                OpenOptions::new().append(true).create_new(true).open(&path)
+                            }
+                            .map_err(|e| {
+                                format!(
+                                    "creating avro ocf file writer for sink file {} failed: {}",
+                                    path.display(),
+                                    e
+                                )
+                            })?;
+                            file.get_or_insert(OcfFile {
+                                writer: mz_avro::Writer::with_codec(schema.clone(), handle, codec),
+                                size: 0,
+                                opened_at: Instant::now(),
+                            })
                         }
                     };
 
-                    for (v, _time, diff) in vector.drain(..) {
+                    for (v, _time, diff) in updates {
                         let value = encode_datums_as_avro(v.iter(), &columns);
                         assert!(diff > 0, "can't sink negative multiplicities");
                         for value in repeat_n(value, diff as usize) {
-                            avro_writer
+                            f.size += f
+                                .writer
                                 .append(value)
                                 .map_err(|e| format!("appending to avro ocf failed: {}", e))?;
                         }
                     }
-                    avro_writer
+                    f.size += f
+                        .writer
                         .flush()
                         .map_err(|e| format!("flushing bytes to avro ocf failed: {}", e))?;
-                    Ok(())
-                };
-
-                if let Err(e) = fallible() {
-                    error!("{}", e);
                 }
-            })
+                Ok(())
+            };
+
+            if let Err(e) = fallible() {
+                error!("{}", e);
+            }
         },
     )
 }
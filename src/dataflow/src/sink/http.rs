@@ -0,0 +1,313 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::any::Any;
+use std::cmp;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use differential_dataflow::{Collection, Hashable};
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use serde_json::json;
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use timely::dataflow::Scope;
+use timely::progress::frontier::AntichainRef;
+use timely::scheduling::Activator;
+use tracing::debug;
+use url::Url;
+
+use mz_dataflow_types::sinks::{HttpSinkConnector, SinkDesc};
+use mz_expr::GlobalId;
+use mz_interchange::encode::column_names_and_types;
+use mz_interchange::json::encode_datums_as_json;
+use mz_ore::collections::CollectionExt;
+use mz_repr::{ColumnName, ColumnType, Diff, Row, Timestamp};
+use mz_timely_util::async_op;
+use mz_timely_util::operators_async_ext::OperatorBuilderExt;
+
+use super::ResumableProgress;
+use crate::render::sinks::{SinkRender, WriteProgress};
+
+/// How long to wait before the first retry of a failed request.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// The longest that the sink waits between retries of a failed request.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How long to wait for the endpoint to respond to a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The header that carries the idempotency key of each request.
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+impl<G> SinkRender<G> for HttpSinkConnector
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    fn uses_keys(&self) -> bool {
+        true
+    }
+
+    fn get_key_indices(&self) -> Option<&[usize]> {
+        self.key_desc_and_indices
+            .as_ref()
+            .map(|(_desc, indices)| indices.as_slice())
+    }
+
+    fn get_relation_key_indices(&self) -> Option<&[usize]> {
+        self.relation_key_indices.as_deref()
+    }
+
+    fn render_continuous_sink(
+        &self,
+        compute_state: &mut crate::server::ComputeState,
+        sink: &SinkDesc,
+        sink_id: GlobalId,
+        sinked_collection: Collection<G, (Option<Row>, Option<Row>), Diff>,
+        _write_progress: Option<WriteProgress>,
+    ) -> Option<Rc<dyn Any>>
+    where
+        G: Scope<Timestamp = Timestamp>,
+    {
+        let progress = ResumableProgress::new(
+            compute_state,
+            sink_id,
+            &sinked_collection.inner.scope(),
+            sink.as_of.clone(),
+        );
+
+        let token = http(sinked_collection, sink_id, self.clone(), progress);
+
+        Some(token)
+    }
+}
+
+struct HttpSinkToken {
+    shutdown_flag: Arc<AtomicBool>,
+}
+
+impl Drop for HttpSinkToken {
+    fn drop(&mut self) {
+        debug!("dropping http sink");
+        self.shutdown_flag.store(true, Ordering::SeqCst);
+    }
+}
+
+struct HttpSinkState {
+    name: String,
+    idempotency_key_prefix: String,
+    url: Url,
+    client: reqwest::Client,
+    key_columns: Option<Vec<(ColumnName, ColumnType)>>,
+    value_columns: Vec<(ColumnName, ColumnType)>,
+    pending_rows: HashMap<Timestamp, Vec<(Option<Row>, Option<Row>)>>,
+    /// How long to wait before retrying the next failed request.
+    backoff: Duration,
+    shutdown_flag: Arc<AtomicBool>,
+    activator: Activator,
+    /// The timestamps that the endpoint has acknowledged, which the
+    /// coordinator records in the catalog.
+    progress: ResumableProgress,
+}
+
+impl HttpSinkState {
+    fn new(
+        connector: HttpSinkConnector,
+        name: String,
+        shutdown_flag: Arc<AtomicBool>,
+        activator: Activator,
+        mut progress: ResumableProgress,
+    ) -> Self {
+        progress.set_gate_ts(connector.gate_ts);
+        HttpSinkState {
+            name,
+            idempotency_key_prefix: connector.idempotency_key_prefix,
+            url: connector.url,
+            client: mz_http_proxy::reqwest::client(),
+            key_columns: connector
+                .key_desc_and_indices
+                .map(|(desc, _indices)| column_names_and_types(desc)),
+            value_columns: column_names_and_types(connector.value_desc),
+            pending_rows: HashMap::new(),
+            backoff: INITIAL_BACKOFF,
+            shutdown_flag,
+            activator,
+            progress,
+        }
+    }
+
+    /// Encodes the updates at `ts` as a single JSON document.
+    fn encode_timestamp(&self, ts: Timestamp, rows: &[(Option<Row>, Option<Row>)]) -> Vec<u8> {
+        let updates = rows
+            .iter()
+            .map(|(key, value)| {
+                let key = match (key, &self.key_columns) {
+                    (Some(key), Some(key_columns)) => {
+                        encode_datums_as_json(key.iter(), key_columns, false)
+                    }
+                    _ => serde_json::Value::Null,
+                };
+                let value = match value {
+                    Some(value) => encode_datums_as_json(value.iter(), &self.value_columns, false),
+                    None => serde_json::Value::Null,
+                };
+                json!({ "key": key, "value": value })
+            })
+            .collect::<Vec<_>>();
+        json!({ "timestamp": ts, "updates": updates })
+            .to_string()
+            .into_bytes()
+    }
+
+    /// POSTs the updates at `ts` to the endpoint.
+    ///
+    /// The request carries an idempotency key derived from the sink ID, the
+    /// cluster ID and `ts`, so that the endpoint can recognize requests that are sent again
+    /// after a failure or a restart.
+    async fn send_timestamp(&mut self, ts: Timestamp) -> Result<(), anyhow::Error> {
+        let rows = self.pending_rows.get(&ts).map(Vec::as_slice).unwrap_or(&[]);
+        let body = self.encode_timestamp(ts, rows);
+        let idempotency_key = format!("{}-{}", self.idempotency_key_prefix, ts);
+        self.client
+            .post(self.url.clone())
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .header(IDEMPOTENCY_KEY, idempotency_key)
+            .timeout(REQUEST_TIMEOUT)
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        self.pending_rows.remove(&ts);
+        Ok(())
+    }
+
+    /// Advances the write frontier to the input frontier and the pending
+    /// timestamps.
+    fn maybe_emit_progress<'a>(&mut self, input_frontier: AntichainRef<'a, Timestamp>) {
+        let min_pending_ts = self.pending_rows.keys().min().cloned();
+        self.progress.advance(ResumableProgress::written_upto(
+            input_frontier,
+            min_pending_ts,
+        ));
+    }
+}
+
+/// POSTs the updates of each timestamp of a stream to an HTTP endpoint.
+///
+/// This operator exchanges all updates to a single worker by hashing on the
+/// given sink `id`. Once the input frontier has passed a timestamp, the
+/// updates at that timestamp are sent in a single request, which is retried
+/// with exponential backoff until the endpoint acknowledges it with a success
+/// status. Timestamps are sent one at a time, in ascending order. Updates that
+/// the given [`ResumableProgress`] does not need written are discarded.
+fn http<G>(
+    collection: Collection<G, (Option<Row>, Option<Row>)>,
+    id: GlobalId,
+    connector: HttpSinkConnector,
+    progress: ResumableProgress,
+) -> Rc<dyn Any>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let name = format!("http-{}", id);
+
+    let stream = &collection.inner;
+    let scope = stream.scope();
+    let mut builder = OperatorBuilder::new(name.clone(), scope.clone());
+    let activator = scope.activator_for(&builder.operator_info().address[..]);
+
+    let shutdown_flag = Arc::new(AtomicBool::new(false));
+
+    let mut s = HttpSinkState::new(
+        connector,
+        name,
+        Arc::clone(&shutdown_flag),
+        activator,
+        progress,
+    );
+
+    let mut vector = Vec::new();
+
+    // We want exactly one worker to talk to the endpoint.
+    let hashed_id = id.hashed();
+    let is_active_worker = s.progress.is_active_worker();
+
+    let mut input = builder.new_input(stream, Exchange::new(move |_| hashed_id));
+
+    builder.build_async(
+        scope,
+        async_op!(|_initial_capabilities, frontiers| {
+            if s.shutdown_flag.load(Ordering::SeqCst) {
+                debug!("shutting down sink: {}", &s.name);
+                s.progress.close();
+                return false;
+            }
+            // Panic if there's not exactly once element in the frontier like we expect.
+            let frontier = frontiers.clone().into_element();
+
+            input.for_each(|_, rows| {
+                assert!(is_active_worker);
+                rows.swap(&mut vector);
+                for ((key, value), time, diff) in vector.drain(..) {
+                    if !s.progress.should_write(time) {
+                        // Skip stale data for already written timestamps
+                        continue;
+                    }
+
+                    assert!(diff >= 0, "can't sink negative multiplicities");
+                    if diff == 0 {
+                        continue;
+                    }
+                    let rows = s.pending_rows.entry(time).or_default();
+                    for _ in 0..diff {
+                        rows.push((key.clone(), value.clone()));
+                    }
+                }
+            });
+
+            // Send newly closed timestamps in ascending order, so that the
+            // write frontier never passes a timestamp that was not delivered.
+            let mut closed_ts: Vec<Timestamp> = s
+                .pending_rows
+                .keys()
+                .filter(|ts| !frontier.less_equal(*ts))
+                .copied()
+                .collect();
+            closed_ts.sort_unstable();
+            for ts in closed_ts {
+                retry_err!(s, s.send_timestamp(ts).await, s.backoff, {
+                    s.backoff = cmp::min(s.backoff * 2, MAX_BACKOFF);
+                    if is_active_worker {
+                        s.maybe_emit_progress(frontier.borrow());
+                    }
+                });
+                s.backoff = INITIAL_BACKOFF;
+            }
+
+            if is_active_worker {
+                s.maybe_emit_progress(frontier.borrow());
+            }
+
+            if !s.pending_rows.is_empty() {
+                // We have some more rows that we need to wait for frontiers to
+                // advance before we can write them out.
+                s.activator.activate_after(Duration::from_millis(100));
+                return true;
+            }
+
+            false
+        }),
+    );
+
+    Rc::new(HttpSinkToken { shutdown_flag })
+}
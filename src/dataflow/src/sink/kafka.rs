@@ -15,7 +15,7 @@ use std::future::Future;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, Context};
 use differential_dataflow::{AsCollection, Collection, Hashable};
@@ -26,10 +26,11 @@ use rdkafka::client::ClientContext;
 use rdkafka::config::ClientConfig;
 use rdkafka::consumer::{BaseConsumer, Consumer};
 use rdkafka::error::{KafkaError, KafkaResult, RDKafkaErrorCode};
-use rdkafka::message::{Message, ToBytes};
+use rdkafka::message::{Message, OwnedHeaders, ToBytes};
 use rdkafka::producer::Producer;
 use rdkafka::producer::{BaseRecord, DeliveryResult, ProducerContext, ThreadedProducer};
 use rdkafka::{Offset, TopicPartitionList};
+use serde::{Deserialize, Serialize};
 use timely::dataflow::channels::pact::Exchange;
 use timely::dataflow::channels::pact::Pipeline;
 use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
@@ -43,25 +44,29 @@ use tracing::{debug, error, info};
 
 use mz_avro::types::Value;
 use mz_dataflow_types::sinks::{
-    KafkaSinkConnector, KafkaSinkConsistencyConnector, PublishedSchemaInfo, SinkAsOf, SinkDesc,
+    KafkaSinkConnector, KafkaSinkConsistencyConnector, PublishedSchemaInfo, PublishedSchemaType,
+    SinkAsOf, SinkDesc,
 };
-use mz_expr::GlobalId;
+use mz_expr::{GlobalId, MirScalarExpr};
 use mz_interchange::avro::{
     self, get_debezium_transaction_schema, AvroEncoder, AvroSchemaGenerator,
 };
 use mz_interchange::encode::Encode;
+use mz_interchange::protobuf::{ProtobufEncoder, ProtobufSchemaGenerator};
 use mz_kafka_util::client::MzClientContext;
 use mz_ore::cast::CastFrom;
 use mz_ore::collections::CollectionExt;
 use mz_ore::metrics::{CounterVecExt, DeleteOnDropCounter, DeleteOnDropGauge, GaugeVecExt};
 use mz_ore::retry::Retry;
+use mz_ore::str::StrExt;
 use mz_ore::task;
-use mz_repr::{Datum, Diff, RelationDesc, Row, Timestamp};
+use mz_repr::{Datum, Diff, RelationDesc, Row, RowArena, Timestamp};
 use mz_timely_util::async_op;
 use mz_timely_util::operators_async_ext::OperatorBuilderExt;
 
 use super::KafkaBaseMetrics;
-use crate::render::sinks::SinkRender;
+use crate::logging::materialized::{Logger, MaterializedEvent};
+use crate::render::sinks::{SinkRender, WriteProgress};
 use prometheus::core::{AtomicI64, AtomicU64};
 
 impl<G> SinkRender<G> for KafkaSinkConnector
@@ -82,12 +87,21 @@ where
         self.relation_key_indices.as_deref()
     }
 
+    fn get_coalesce_interval(&self) -> Option<Duration> {
+        self.coalesce_interval
+    }
+
+    fn get_max_read_ahead(&self) -> Option<usize> {
+        self.max_read_ahead.map(usize::cast_from)
+    }
+
     fn render_continuous_sink(
         &self,
         compute_state: &mut crate::server::ComputeState,
         sink: &SinkDesc,
         sink_id: GlobalId,
         sinked_collection: Collection<G, (Option<Row>, Option<Row>), Diff>,
+        write_progress: Option<WriteProgress>,
     ) -> Option<Rc<dyn Any>>
     where
         G: Scope<Timestamp = Timestamp>,
@@ -139,7 +153,9 @@ where
             self.value_desc.clone(),
             sink.as_of.clone(),
             Rc::clone(&shared_frontier),
+            write_progress,
             &compute_state.sink_metrics.kafka,
+            compute_state.materialized_logger.clone(),
         );
 
         compute_state
@@ -155,7 +171,10 @@ pub struct SinkMetrics {
     messages_sent_counter: DeleteOnDropCounter<'static, AtomicI64, Vec<String>>,
     message_send_errors_counter: DeleteOnDropCounter<'static, AtomicI64, Vec<String>>,
     message_delivery_errors_counter: DeleteOnDropCounter<'static, AtomicI64, Vec<String>>,
+    message_routing_errors_counter: DeleteOnDropCounter<'static, AtomicI64, Vec<String>>,
     rows_queued: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
+    backlog_messages: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
+    backlog_bytes: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
 }
 
 impl SinkMetrics {
@@ -180,7 +199,14 @@ impl SinkMetrics {
             message_delivery_errors_counter: base
                 .message_delivery_errors_counter
                 .get_delete_on_drop_counter(labels.clone()),
-            rows_queued: base.rows_queued.get_delete_on_drop_gauge(labels),
+            message_routing_errors_counter: base
+                .message_routing_errors_counter
+                .get_delete_on_drop_counter(labels.clone()),
+            rows_queued: base.rows_queued.get_delete_on_drop_gauge(labels.clone()),
+            backlog_messages: base
+                .backlog_messages
+                .get_delete_on_drop_gauge(labels.clone()),
+            backlog_bytes: base.backlog_bytes.get_delete_on_drop_gauge(labels),
         }
     }
 }
@@ -354,6 +380,7 @@ impl KafkaSinkStateEnum {
 
 struct KafkaSinkState {
     name: String,
+    sink_id: GlobalId,
     topic: String,
     topic_prefix: String,
     shutdown_flag: Arc<AtomicBool>,
@@ -363,7 +390,26 @@ struct KafkaSinkState {
     transactional: bool,
     pending_rows: HashMap<Timestamp, Vec<EncodedRow>>,
     ready_rows: VecDeque<(Timestamp, Vec<EncodedRow>)>,
+    /// How far the sink got in sending the first batch of `ready_rows`, if it
+    /// had to stop midway because the producer or the rate limit did not let
+    /// it send more. The transaction of that batch has already begun.
+    batch_progress: Option<BatchProgress>,
+    /// The number of messages in `ready_rows` that have not been handed to the
+    /// producer yet.
+    backlog_messages: usize,
+    /// The total size of the messages counted by `backlog_messages`.
+    backlog_bytes: usize,
+    /// The backlog that was last logged, as a number of messages and bytes.
+    logged_backlog: Option<(i64, i64)>,
+    logger: Option<Logger>,
+    rate_limiter: RateLimiter,
     sink_state: KafkaSinkStateEnum,
+    /// Whether the sink picks the partition of each message with a
+    /// `PARTITION BY` expression.
+    partitioned: bool,
+    /// The number of partitions of the topic, which is fetched when the sink
+    /// starts if it is `partitioned`.
+    partition_count: Option<i32>,
 
     /// Timestamp of the latest `END` record that was written out to Kafka.
     latest_progress_ts: Timestamp,
@@ -377,6 +423,9 @@ struct KafkaSinkState {
     /// ensures that we don't write updates more than once, ensuring
     /// exactly-once guaruantees.
     write_frontier: Rc<RefCell<Antichain<Timestamp>>>,
+
+    /// Reports how far the sink got to its input, if the input paces itself.
+    write_progress: Option<WriteProgress>,
 }
 
 impl KafkaSinkState {
@@ -388,7 +437,9 @@ impl KafkaSinkState {
         shutdown_flag: Arc<AtomicBool>,
         activator: Activator,
         write_frontier: Rc<RefCell<Antichain<Timestamp>>>,
+        write_progress: Option<WriteProgress>,
         metrics: &KafkaBaseMetrics,
+        logger: Option<Logger>,
     ) -> Self {
         let config = Self::create_producer_config(&connector);
         let consistency_client_config = Self::create_consistency_client_config(&connector);
@@ -412,6 +463,12 @@ impl KafkaSinkState {
             timeout: Duration::from_secs(5),
         };
 
+        let partitioned = connector.partition_by.is_some();
+        let rate_limiter = RateLimiter::new(
+            connector.max_messages_per_second,
+            connector.max_bytes_per_second,
+        );
+
         let sink_state = KafkaSinkStateEnum::Init(connector.consistency.map(
             |KafkaSinkConsistencyConnector { topic, schema_id }| KafkaConsistencyInitState {
                 topic,
@@ -422,6 +479,7 @@ impl KafkaSinkState {
 
         KafkaSinkState {
             name: sink_name,
+            sink_id: *sink_id,
             topic: connector.topic,
             topic_prefix: connector.topic_prefix,
             shutdown_flag,
@@ -431,9 +489,18 @@ impl KafkaSinkState {
             transactional: connector.exactly_once,
             pending_rows: HashMap::new(),
             ready_rows: VecDeque::new(),
+            batch_progress: None,
+            backlog_messages: 0,
+            backlog_bytes: 0,
+            logged_backlog: None,
+            logger,
+            rate_limiter,
             sink_state,
+            partitioned,
+            partition_count: None,
             latest_progress_ts: Timestamp::minimum(),
             write_frontier,
+            write_progress,
         }
     }
 
@@ -446,19 +513,17 @@ impl KafkaSinkState {
         // all bets are off and full exactly once support is required.
         config.set("enable.idempotence", "true");
 
-        // Increase limits for the Kafka producer's internal buffering of messages
-        // Currently we don't have a great backpressure mechanism to tell indexes or
-        // views to slow down, so the only thing we can do with a message that we
-        // can't immediately send is to put it in a buffer and there's no point
-        // having buffers within the dataflow layer and Kafka
-        // If the sink starts falling behind and the buffers start consuming
-        // too much memory the best thing to do is to drop the sink
-        // Sets the buffer size to be 16 GB (note that this setting is in KB)
-        config.set("queue.buffering.max.kbytes", &format!("{}", 16 << 20));
+        // Bound the Kafka producer's internal buffering of messages. When the
+        // queue of the producer is full, the sink stops pulling input until the
+        // producer has caught up, which holds back the frontier of the sink.
+        // If the sink paces its input, it reads at most `max_read_ahead`
+        // updates ahead of the producer, and leaves the updates that follow
+        // unread in its input.
+        // Sets the buffer size to be 1 GB (note that this setting is in KB)
+        config.set("queue.buffering.max.kbytes", &format!("{}", 1 << 20));
 
-        // Set the max messages buffered by the producer at any time to 10MM which
-        // is the maximum allowed value
-        config.set("queue.buffering.max.messages", &format!("{}", 10_000_000));
+        // Set the max messages buffered by the producer at any time to 1MM
+        config.set("queue.buffering.max.messages", &format!("{}", 1_000_000));
 
         // Make the Kafka producer wait at least 10 ms before sending out MessageSets
         // TODO(rkhaitan): experiment with different settings for this value to see
@@ -625,6 +690,41 @@ impl KafkaSinkState {
         Err(last_error)
     }
 
+    /// Hands a record to the producer without waiting for room in its queue.
+    ///
+    /// Returns `Ok(false)` if the queue of the producer is full, in which case
+    /// the record was not sent and should be sent again later.
+    fn try_send<'a, K, P>(&self, record: BaseRecord<'a, K, P>) -> KafkaResult<bool>
+    where
+        K: ToBytes + ?Sized,
+        P: ToBytes + ?Sized,
+    {
+        match self.producer.send(record) {
+            Ok(_) => {
+                self.metrics.messages_sent_counter.inc();
+                Ok(true)
+            }
+            Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) => {
+                self.metrics.message_send_errors_counter.inc();
+                debug!(
+                    "unable to produce message in {}: rdkafka queue full. Backing off.",
+                    self.name
+                );
+                Ok(false)
+            }
+            Err((e, _)) => {
+                self.metrics.message_send_errors_counter.inc();
+                // We've received an error that is not transient
+                error!(
+                    "unable to produce message in {}: {}. Shutting down sink.",
+                    self.name, e
+                );
+                self.shutdown_flag.store(true, Ordering::SeqCst);
+                Err(e)
+            }
+        }
+    }
+
     async fn flush(&self) -> KafkaResult<()> {
         let self_producer = self.producer.clone();
         // Only actually used for retriable errors.
@@ -821,6 +921,36 @@ impl KafkaSinkState {
         return Ok(None);
     }
 
+    /// Fetches the number of partitions of the topic, which maps the results
+    /// of the `PARTITION BY` expression of the sink to partitions.
+    async fn fetch_partition_count(&self) -> Result<i32, anyhow::Error> {
+        // Only actually used for retriable errors.
+        Retry::default()
+            .max_tries(usize::MAX)
+            .clamp_backoff(Duration::from_secs(60 * 10))
+            .retry_async(|_| async {
+                let producer = Arc::clone(&self.producer.inner);
+                let topic = self.topic.clone();
+                task::spawn_blocking(
+                    || format!("get_partitions:{}", self.name),
+                    move || -> Result<i32, anyhow::Error> {
+                        let partitions = mz_kafka_util::client::get_partitions(
+                            producer.client(),
+                            &topic,
+                            Duration::from_secs(10),
+                        )
+                        .with_context(|| {
+                            format!("Unable to fetch metadata about topic {}", topic)
+                        })?;
+                        Ok(i32::try_from(partitions.len())?)
+                    },
+                )
+                .await
+                .unwrap_or_else(|e| bail!(e))
+            })
+            .await
+    }
+
     async fn send_consistency_record(
         &self,
         transaction_id: &str,
@@ -843,6 +973,33 @@ impl KafkaSinkState {
         self.send(record).await
     }
 
+    /// Reports the number and the size of the messages that are ready to be
+    /// sent but have not been handed to the producer yet.
+    ///
+    /// The backlog is only logged when it changed since the last report.
+    fn report_backlog(&mut self) {
+        self.metrics
+            .backlog_messages
+            .set(u64::cast_from(self.backlog_messages));
+        self.metrics
+            .backlog_bytes
+            .set(u64::cast_from(self.backlog_bytes));
+
+        let backlog = (
+            i64::try_from(self.backlog_messages).unwrap_or(i64::MAX),
+            i64::try_from(self.backlog_bytes).unwrap_or(i64::MAX),
+        );
+        if self.logged_backlog != Some(backlog) {
+            if let Some(logger) = self.logger.as_mut() {
+                logger.log(MaterializedEvent::KafkaSinkBacklog {
+                    sink_id: self.sink_id,
+                    old: self.logged_backlog.replace(backlog),
+                    new: Some(backlog),
+                });
+            }
+        }
+    }
+
     /// Asserts that the write frontier has not yet advanced beyond `t`.
     fn assert_progress(&self, ts: &Timestamp) {
         assert!(self.write_frontier.borrow().less_equal(ts));
@@ -935,15 +1092,240 @@ impl KafkaSinkState {
 
         Ok(progress_emitted)
     }
+
+    /// Reports the earliest time that the sink has not yet handed to the
+    /// producer to the input of the sink, if it paces itself against that.
+    fn report_write_progress(&self, input_frontier: AntichainRef<Timestamp>) {
+        if let Some(write_progress) = &self.write_progress {
+            let lower = input_frontier
+                .iter()
+                .chain(self.pending_rows.keys())
+                .chain(self.ready_rows.iter().map(|(ts, _)| ts))
+                .min()
+                .cloned();
+            write_progress.advance(lower.into_iter().collect::<Antichain<_>>().borrow());
+        }
+    }
+}
+
+impl Drop for KafkaSinkState {
+    fn drop(&mut self) {
+        // Retract the backlog logged for this sink.
+        if let Some(logger) = self.logger.as_mut() {
+            if let Some(backlog) = self.logged_backlog.take() {
+                logger.log(MaterializedEvent::KafkaSinkBacklog {
+                    sink_id: self.sink_id,
+                    old: Some(backlog),
+                    new: None,
+                });
+            }
+        }
+    }
+}
+
+/// How far a Kafka sink got in sending a batch of rows.
+#[derive(Debug, Default)]
+struct BatchProgress {
+    /// The index of the row whose messages are being sent.
+    row: usize,
+    /// The number of messages of that row that have been sent.
+    repetition: usize,
+    /// The number of messages of the batch that have been sent.
+    sent: i64,
+}
+
+/// Limits the rate at which a Kafka sink hands messages to the producer.
+///
+/// Each limit is enforced with a token bucket that holds up to one second
+/// worth of tokens, so the sink may send bursts of up to that size.
+struct RateLimiter {
+    messages: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+}
+
+impl RateLimiter {
+    fn new(max_messages_per_second: Option<u64>, max_bytes_per_second: Option<u64>) -> Self {
+        let now = Instant::now();
+        RateLimiter {
+            messages: max_messages_per_second.map(|rate| TokenBucket::new(rate, now)),
+            bytes: max_bytes_per_second.map(|rate| TokenBucket::new(rate, now)),
+        }
+    }
+
+    /// Returns how long the sink needs to wait before it may send a message
+    /// of the given size, if at all.
+    fn delay(&mut self, size: usize) -> Option<Duration> {
+        self.delay_at(size, Instant::now())
+    }
+
+    /// Like [`RateLimiter::delay`], but as of the given point in time.
+    fn delay_at(&mut self, size: usize, now: Instant) -> Option<Duration> {
+        let messages = self
+            .messages
+            .as_mut()
+            .and_then(|bucket| bucket.delay(1.0, now));
+        let bytes = self
+            .bytes
+            .as_mut()
+            .and_then(|bucket| bucket.delay(size as f64, now));
+        messages.into_iter().chain(bytes).max()
+    }
+
+    /// Takes the tokens for a sent message of the given size.
+    fn consume(&mut self, size: usize) {
+        if let Some(bucket) = self.messages.as_mut() {
+            bucket.tokens -= 1.0;
+        }
+        if let Some(bucket) = self.bytes.as_mut() {
+            bucket.tokens -= size as f64;
+        }
+    }
+}
+
+struct TokenBucket {
+    /// The number of tokens added per second, which is also the capacity of
+    /// the bucket.
+    rate: f64,
+    /// The number of tokens in the bucket, which is negative if the bucket
+    /// owes tokens for a message that was larger than its capacity.
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, now: Instant) -> Self {
+        TokenBucket {
+            rate: rate as f64,
+            tokens: rate as f64,
+            last_refill: now,
+        }
+    }
+
+    /// Returns how long it takes until the bucket holds `cost` tokens, if it
+    /// does not already.
+    ///
+    /// A cost that exceeds the capacity of the bucket only requires a full
+    /// bucket, so that every message can be sent eventually.
+    fn delay(&mut self, cost: f64, now: Instant) -> Option<Duration> {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+
+        let missing = cost.min(self.rate) - self.tokens;
+        if missing > 0.0 {
+            Some(Duration::from_secs_f64(missing / self.rate))
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Debug)]
 struct EncodedRow {
+    message: EncodedMessage,
+    count: usize,
+}
+
+/// A message of a Kafka sink, with its key and value already encoded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EncodedMessage {
     key: Option<Vec<u8>>,
     value: Option<Vec<u8>>,
-    count: usize,
+    /// The result of the `PARTITION BY` expression of the sink, if any.
+    partition: Option<i64>,
+    /// The results of the `HEADERS` expressions of the sink that are not NULL.
+    headers: Vec<(String, Vec<u8>)>,
+    /// The error that evaluating the `PARTITION BY` or `HEADERS` expressions
+    /// of the sink produced, if any. The producer shuts down the sink when it
+    /// reaches such a message, instead of sending it to the wrong partition or
+    /// without its headers.
+    routing_error: Option<String>,
 }
 
+impl EncodedMessage {
+    /// Returns the size of the key, the value and the headers of the message.
+    fn size(&self) -> usize {
+        let key = self.key.as_ref().map_or(0, |key| key.len());
+        let value = self.value.as_ref().map_or(0, |value| value.len());
+        let headers: usize = self
+            .headers
+            .iter()
+            .map(|(name, value)| name.len() + value.len())
+            .sum();
+        key + value + headers
+    }
+}
+
+/// Evaluates the `PARTITION BY` and `HEADERS` expressions of a sink over the
+/// key of each message.
+struct MessageRouter {
+    partition_by: Option<MirScalarExpr>,
+    headers: Vec<(String, MirScalarExpr)>,
+}
+
+impl MessageRouter {
+    fn new(connector: &KafkaSinkConnector) -> Self {
+        MessageRouter {
+            partition_by: connector.partition_by.clone(),
+            headers: connector.headers.clone(),
+        }
+    }
+
+    /// Returns the partition and the headers of the message with the given
+    /// key.
+    ///
+    /// Expressions that evaluate to NULL leave the choice of partition to the
+    /// producer or omit the header. Expressions that fail to evaluate are
+    /// returned as an error, which shuts down the sink.
+    fn route(&self, key: Option<&Row>) -> Result<(Option<i64>, Vec<(String, Vec<u8>)>), String> {
+        // The expressions are only planned for sinks with a key.
+        let key = match key {
+            Some(key) if self.partition_by.is_some() || !self.headers.is_empty() => key,
+            _ => return Ok((None, vec![])),
+        };
+        let datums = key.unpack();
+        let arena = RowArena::new();
+        let partition = match &self.partition_by {
+            None => None,
+            Some(expr) => match expr.eval(&datums, &arena) {
+                Ok(Datum::Int16(i)) => Some(i64::from(i)),
+                Ok(Datum::Int32(i)) => Some(i64::from(i)),
+                Ok(Datum::Int64(i)) => Some(i),
+                Ok(Datum::Null) => None,
+                Ok(datum) => {
+                    return Err(format!(
+                        "PARTITION BY expression returned unexpected value {:?}",
+                        datum
+                    ))
+                }
+                Err(e) => return Err(format!("evaluating PARTITION BY expression: {}", e)),
+            },
+        };
+        let mut headers = vec![];
+        for (name, expr) in &self.headers {
+            let value = match expr.eval(&datums, &arena) {
+                Ok(Datum::String(s)) => s.as_bytes().to_vec(),
+                Ok(Datum::Bytes(b)) => b.to_vec(),
+                Ok(Datum::Null) => continue,
+                Ok(datum) => {
+                    return Err(format!(
+                        "header {} expression returned unexpected value {:?}",
+                        name.quoted(),
+                        datum
+                    ))
+                }
+                Err(e) => return Err(format!("evaluating header {}: {}", name.quoted(), e)),
+            };
+            headers.push((name.clone(), value));
+        }
+        Ok((partition, headers))
+    }
+}
+
+/// How long a Kafka sink waits before it tries again to hand messages to a
+/// producer whose queue is full.
+const QUEUE_FULL_BACKOFF: Duration = Duration::from_millis(10);
+
 // TODO@jldlaughlin: What guarantees does this sink support? #1728
 fn kafka<G>(
     collection: Collection<G, (Option<Row>, Option<Row>)>,
@@ -953,7 +1335,9 @@ fn kafka<G>(
     value_desc: RelationDesc,
     as_of: SinkAsOf,
     write_frontier: Rc<RefCell<Antichain<Timestamp>>>,
+    write_progress: Option<WriteProgress>,
     metrics: &KafkaBaseMetrics,
+    logger: Option<Logger>,
 ) -> Rc<dyn Any>
 where
     G: Scope<Timestamp = Timestamp>,
@@ -968,6 +1352,7 @@ where
         Some(PublishedSchemaInfo {
             key_schema_id,
             value_schema_id,
+            schema_type: PublishedSchemaType::Avro,
         }) => {
             let schema_generator = AvroSchemaGenerator::new(
                 None,
@@ -982,6 +1367,26 @@ where
                 as_of.clone(),
                 Rc::clone(&shared_gate_ts),
                 encoder,
+                MessageRouter::new(&connector),
+                connector.fuel,
+                name.clone(),
+            )
+        }
+        Some(PublishedSchemaInfo {
+            key_schema_id,
+            value_schema_id,
+            schema_type: PublishedSchemaType::Protobuf,
+        }) => {
+            let schema_generator =
+                ProtobufSchemaGenerator::new(key_desc, value_desc, connector.consistency.is_some())
+                    .expect("protobuf schema validated during planning");
+            let encoder = ProtobufEncoder::new(schema_generator, key_schema_id, value_schema_id);
+            encode_stream(
+                stream,
+                as_of.clone(),
+                Rc::clone(&shared_gate_ts),
+                encoder,
+                MessageRouter::new(&connector),
                 connector.fuel,
                 name.clone(),
             )
@@ -993,6 +1398,7 @@ where
                 as_of.clone(),
                 Rc::clone(&shared_gate_ts),
                 encoder,
+                MessageRouter::new(&connector),
                 connector.fuel,
                 name.clone(),
             )
@@ -1007,7 +1413,9 @@ where
         as_of,
         shared_gate_ts,
         write_frontier,
+        write_progress,
         metrics,
+        logger,
     )
 }
 
@@ -1022,15 +1430,27 @@ where
 ///
 /// Updates that are not beyond the given [`SinkAsOf`] and/or the `gate_ts` in
 /// [`KafkaSinkConnector`] will be discarded without producing them.
-pub fn produce_to_kafka<G>(
-    stream: Stream<G, ((Option<Vec<u8>>, Option<Vec<u8>>), Timestamp, Diff)>,
+///
+/// While updates are ready to be sent, this operator stops pulling input, so that the updates that
+/// follow stay in the input channel of this operator along with their capabilities. If the queue of
+/// the producer is full or the sink exceeds its rate limit, it backs off while holding on to the
+/// rest of the ready updates. Either way the input frontier of this operator, and thus the write
+/// frontier of the sink, cannot advance until the sink has caught up.
+///
+/// If the sink paces its input, the earliest time that has not been handed to the producer yet is
+/// reported through `write_progress`, which wakes the input of the sink so that it reads at most
+/// `max_read_ahead` updates ahead of the producer.
+fn produce_to_kafka<G>(
+    stream: Stream<G, (EncodedMessage, Timestamp, Diff)>,
     id: GlobalId,
     name: String,
     connector: KafkaSinkConnector,
     as_of: SinkAsOf,
     shared_gate_ts: Rc<Cell<Option<Timestamp>>>,
     write_frontier: Rc<RefCell<Antichain<Timestamp>>>,
+    write_progress: Option<WriteProgress>,
     metrics: &KafkaBaseMetrics,
+    logger: Option<Logger>,
 ) -> Rc<dyn Any>
 where
     G: Scope<Timestamp = Timestamp>,
@@ -1049,7 +1469,9 @@ where
         Arc::clone(&shutdown_flag),
         activator,
         write_frontier,
+        write_progress,
         metrics,
+        logger,
     );
 
     let mut vector = Vec::new();
@@ -1086,6 +1508,7 @@ where
             }
             // Panic if there's not exactly once element in the frontier like we expect.
             let frontier = frontiers.clone().into_element();
+            s.report_write_progress(frontier.borrow());
 
             // Can't use `?` when the return type is a `bool` so use a custom try operator
             macro_rules! bail_err {
@@ -1116,6 +1539,17 @@ where
                     };
                     shared_gate_ts.set(latest_ts);
 
+                    if s.partitioned {
+                        match s.fetch_partition_count().await {
+                            Ok(partition_count) => s.partition_count = Some(partition_count),
+                            Err(e) => {
+                                s.shutdown_flag.store(true, Ordering::SeqCst);
+                                info!("shutting down kafka sink while initializing: {}", e);
+                                return true;
+                            }
+                        }
+                    }
+
                     let consistency_state = init
                         .clone()
                         .map(|init| init.to_running(Rc::clone(&shared_gate_ts)));
@@ -1128,40 +1562,44 @@ where
                 }
             }
 
-            // Queue all pending rows waiting to be sent to kafka
-            input.for_each(|_, rows| {
-                assert!(is_active_worker);
-                rows.swap(&mut vector);
-                for ((key, value), time, diff) in vector.drain(..) {
-                    let should_emit = if as_of.strict {
-                        as_of.frontier.less_than(&time)
-                    } else {
-                        as_of.frontier.less_equal(&time)
-                    };
-
-                    let previously_published = Some(time) <= s.sink_state.gate_ts();
+            // Queue all pending rows waiting to be sent to kafka, unless we are
+            // still sending the rows that are ready, in which case the input
+            // stays where it is until we caught up.
+            let caught_up = s.ready_rows.is_empty();
+            if caught_up {
+                input.for_each(|_, rows| {
+                    assert!(is_active_worker);
+                    rows.swap(&mut vector);
+                    for (message, time, diff) in vector.drain(..) {
+                        let should_emit = if as_of.strict {
+                            as_of.frontier.less_than(&time)
+                        } else {
+                            as_of.frontier.less_equal(&time)
+                        };
 
-                    if !should_emit || previously_published {
-                        // Skip stale data for already published timestamps
-                        continue;
-                    }
+                        let previously_published = Some(time) <= s.sink_state.gate_ts();
 
-                    assert!(diff >= 0, "can't sink negative multiplicities");
-                    if diff == 0 {
-                        // Explicitly refuse to send no-op records
-                        continue;
-                    };
-                    let diff = diff as usize;
+                        if !should_emit || previously_published {
+                            // Skip stale data for already published timestamps
+                            continue;
+                        }
 
-                    let rows = s.pending_rows.entry(time).or_default();
-                    rows.push(EncodedRow {
-                        key,
-                        value,
-                        count: diff,
-                    });
-                    s.metrics.rows_queued.inc();
-                }
-            });
+                        assert!(diff >= 0, "can't sink negative multiplicities");
+                        if diff == 0 {
+                            // Explicitly refuse to send no-op records
+                            continue;
+                        };
+                        let diff = diff as usize;
+
+                        let rows = s.pending_rows.entry(time).or_default();
+                        rows.push(EncodedRow {
+                            message,
+                            count: diff,
+                        });
+                        s.metrics.rows_queued.inc();
+                    }
+                });
+            }
 
             // Move any newly closed timestamps from pending to ready
             let mut closed_ts: Vec<u64> = s
@@ -1173,59 +1611,124 @@ where
             closed_ts.sort_unstable();
             closed_ts.into_iter().for_each(|ts| {
                 let rows = s.pending_rows.remove(&ts).unwrap();
+                for encoded_row in &rows {
+                    s.backlog_messages += encoded_row.count;
+                    s.backlog_bytes += encoded_row.count * encoded_row.message.size();
+                }
                 s.ready_rows.push_back((ts, rows));
             });
 
-            while let Some((ts, rows)) = s.ready_rows.front() {
+            while !s.ready_rows.is_empty() {
                 assert!(is_active_worker);
+                let ts = s.ready_rows[0].0;
+
+                // Continue where we left off if we had to stop sending this
+                // batch midway, in which case its transaction has already
+                // begun.
+                let mut progress = match s.batch_progress.take() {
+                    Some(progress) => progress,
+                    None => {
+                        if s.transactional {
+                            bail_err!(s.retry_on_txn_error(|p| p.begin_transaction()).await);
+                        }
+                        if let Some(ref consistency_state) = s.sink_state.unwrap_running() {
+                            bail_err!(
+                                s.send_consistency_record(
+                                    &ts.to_string(),
+                                    "BEGIN",
+                                    None,
+                                    consistency_state
+                                )
+                                .await
+                            );
+                        }
+                        BatchProgress::default()
+                    }
+                };
 
-                if s.transactional {
-                    bail_err!(s.retry_on_txn_error(|p| p.begin_transaction()).await);
-                }
-                if let Some(ref consistency_state) = s.sink_state.unwrap_running() {
-                    bail_err!(
-                        s.send_consistency_record(
-                            &ts.to_string(),
-                            "BEGIN",
-                            None,
-                            consistency_state
-                        )
-                        .await
-                    );
-                }
+                let mut back_off = None;
+                let rows = &s.ready_rows[0].1;
+                while let Some(encoded_row) = rows.get(progress.row) {
+                    let message = &encoded_row.message;
+                    if let Some(e) = &message.routing_error {
+                        s.metrics.message_routing_errors_counter.inc();
+                        error!(
+                            "unable to route message in {}: {}. Shutting down sink.",
+                            s.name, e
+                        );
+                        s.shutdown_flag.store(true, Ordering::SeqCst);
+                        s.activator.activate();
+                        return true;
+                    }
+                    let size = message.size();
+                    if let Some(delay) = s.rate_limiter.delay(size) {
+                        back_off = Some(delay);
+                        break;
+                    }
 
-                let mut repeat_counter = 0;
-                let mut total_sent = 0;
-                for encoded_row in rows {
                     let record = BaseRecord::to(&s.topic);
-                    let record = match encoded_row.value.as_ref() {
+                    let record = match message.value.as_ref() {
                         Some(r) => record.payload(r),
                         None => record,
                     };
-                    let record = match encoded_row.key.as_ref() {
+                    let record = match message.key.as_ref() {
                         Some(r) => record.key(r),
                         None => record,
                     };
+                    let record = match (message.partition, s.partition_count) {
+                        (Some(partition), Some(partition_count)) => {
+                            let partition = partition.rem_euclid(i64::from(partition_count));
+                            record.partition(i32::try_from(partition).expect("less than i32::MAX"))
+                        }
+                        _ => record,
+                    };
+                    let record = if message.headers.is_empty() {
+                        record
+                    } else {
+                        let headers = message
+                            .headers
+                            .iter()
+                            .fold(OwnedHeaders::new(), |headers, (name, value)| {
+                                headers.add(name, value)
+                            });
+                        record.headers(headers)
+                    };
 
-                    // Only fatal errors are returned from send
-                    bail_err!(s.send(record).await);
+                    // Only fatal errors are returned from try_send
+                    if !bail_err!(s.try_send(record)) {
+                        back_off = Some(QUEUE_FULL_BACKOFF);
+                        break;
+                    }
+                    s.rate_limiter.consume(size);
+                    s.backlog_messages -= 1;
+                    s.backlog_bytes -= size;
 
                     // advance to the next repetition of this row, or the next row if all
                     // reptitions are exhausted
-                    total_sent += 1;
-                    repeat_counter += 1;
-                    if repeat_counter == encoded_row.count {
-                        repeat_counter = 0;
+                    progress.sent += 1;
+                    progress.repetition += 1;
+                    if progress.repetition == encoded_row.count {
+                        progress.repetition = 0;
+                        progress.row += 1;
                         s.metrics.rows_queued.dec();
                     }
                 }
 
+                if let Some(delay) = back_off {
+                    // Hold on to the rest of the batch, and to the input, until
+                    // the producer can take more messages.
+                    s.batch_progress = Some(progress);
+                    s.report_backlog();
+                    s.activator.activate_after(delay);
+                    return true;
+                }
+
                 if let Some(ref consistency_state) = s.sink_state.unwrap_running() {
                     bail_err!(
                         s.send_consistency_record(
                             &ts.to_string(),
                             "END",
-                            Some(total_sent),
+                            Some(progress.sent),
                             consistency_state
                         )
                         .await
@@ -1239,12 +1742,21 @@ where
 
                 // sanity check for the continuous updating
                 // of the write frontier below
-                s.assert_progress(ts);
-                progress_update.replace(ts.clone());
+                s.assert_progress(&ts);
+                progress_update.replace(ts);
 
                 s.ready_rows.pop_front();
             }
 
+            if is_active_worker {
+                s.report_backlog();
+            }
+            if !caught_up {
+                // Pull the input that we left in place now that we are no
+                // longer behind.
+                s.activator.activate();
+            }
+
             // update our state based on any END records we might have sent
             if let Some(ts) = progress_update.take() {
                 s.maybe_update_progress(&ts);
@@ -1277,6 +1789,8 @@ where
                 }
             }
 
+            s.report_write_progress(frontier.borrow());
+
             debug_assert_eq!(s.producer.inner.in_flight_count(), 0);
 
             if !s.pending_rows.is_empty() {
@@ -1316,9 +1830,10 @@ fn encode_stream<G>(
     as_of: SinkAsOf,
     shared_gate_ts: Rc<Cell<Option<Timestamp>>>,
     encoder: impl Encode + 'static,
+    router: MessageRouter,
     fuel: usize,
     name_prefix: String,
-) -> Stream<G, ((Option<Vec<u8>>, Option<Vec<u8>>), Timestamp, Diff)>
+) -> Stream<G, (EncodedMessage, Timestamp, Diff)>
 where
     G: Scope<Timestamp = Timestamp>,
 {
@@ -1335,75 +1850,81 @@ where
 
     let mut stash: HashMap<Capability<Timestamp>, Vec<_>> = HashMap::new();
     let mut vector = Vec::new();
-    let mut encode_logic = move |input: &mut InputHandle<
-        Timestamp,
-        ((Option<Row>, Option<Row>), Timestamp, Diff),
-        _,
-    >,
-                                 output: &mut OutputHandle<
-        _,
-        ((Option<Vec<u8>>, Option<Vec<u8>>), Timestamp, Diff),
-        _,
-    >| {
-        let mut fuel_remaining = fuel;
-        // stash away all the input we get, we want to be a nice citizen
-        input.for_each(|cap, data| {
-            data.swap(&mut vector);
-            let stashed = stash.entry(cap.retain()).or_default();
-            for update in vector.drain(..) {
-                let time = update.1;
-
-                let should_emit = if as_of.strict {
-                    as_of.frontier.less_than(&time)
-                } else {
-                    as_of.frontier.less_equal(&time)
-                };
-                let ts_gated = Some(time) <= shared_gate_ts.get();
+    let mut encode_logic =
+        move |input: &mut InputHandle<
+            Timestamp,
+            ((Option<Row>, Option<Row>), Timestamp, Diff),
+            _,
+        >,
+              output: &mut OutputHandle<_, (EncodedMessage, Timestamp, Diff), _>| {
+            let mut fuel_remaining = fuel;
+            // stash away all the input we get, we want to be a nice citizen
+            input.for_each(|cap, data| {
+                data.swap(&mut vector);
+                let stashed = stash.entry(cap.retain()).or_default();
+                for update in vector.drain(..) {
+                    let time = update.1;
+
+                    let should_emit = if as_of.strict {
+                        as_of.frontier.less_than(&time)
+                    } else {
+                        as_of.frontier.less_equal(&time)
+                    };
+                    let ts_gated = Some(time) <= shared_gate_ts.get();
 
-                if !should_emit || ts_gated {
-                    // Skip stale data for already published timestamps
-                    continue;
+                    if !should_emit || ts_gated {
+                        // Skip stale data for already published timestamps
+                        continue;
+                    }
+                    stashed.push(update);
                 }
-                stashed.push(update);
-            }
-        });
-
-        // work off some of our data and then yield, can't be hogging
-        // the worker for minutes at a time
-
-        while fuel_remaining > 0 && !stash.is_empty() {
-            let lowest_ts = stash
-                .keys()
-                .min_by(|x, y| x.time().cmp(y.time()))
-                .expect("known to exist")
-                .clone();
-            let records = stash.get_mut(&lowest_ts).expect("known to exist");
-
-            let mut session = output.session(&lowest_ts);
-            let num_records_to_drain = cmp::min(records.len(), fuel_remaining);
-            records
-                .drain(..num_records_to_drain)
-                .for_each(|((key, value), time, diff)| {
-                    let key = key.map(|key| encoder.encode_key_unchecked(key));
-                    let value = value.map(|value| encoder.encode_value_unchecked(value));
-                    session.give(((key, value), time, diff));
-                });
+            });
 
-            fuel_remaining -= num_records_to_drain;
+            // work off some of our data and then yield, can't be hogging
+            // the worker for minutes at a time
+
+            while fuel_remaining > 0 && !stash.is_empty() {
+                let lowest_ts = stash
+                    .keys()
+                    .min_by(|x, y| x.time().cmp(y.time()))
+                    .expect("known to exist")
+                    .clone();
+                let records = stash.get_mut(&lowest_ts).expect("known to exist");
+
+                let mut session = output.session(&lowest_ts);
+                let num_records_to_drain = cmp::min(records.len(), fuel_remaining);
+                records
+                    .drain(..num_records_to_drain)
+                    .for_each(|((key, value), time, diff)| {
+                        let (partition, headers, routing_error) = match router.route(key.as_ref()) {
+                            Ok((partition, headers)) => (partition, headers, None),
+                            Err(e) => (None, vec![], Some(e)),
+                        };
+                        let message = EncodedMessage {
+                            key: key.map(|key| encoder.encode_key_unchecked(key)),
+                            value: value.map(|value| encoder.encode_value_unchecked(value)),
+                            partition,
+                            headers,
+                            routing_error,
+                        };
+                        session.give((message, time, diff));
+                    });
 
-            if records.is_empty() {
-                // drop our capability for this time
-                stash.remove(&lowest_ts);
+                fuel_remaining -= num_records_to_drain;
+
+                if records.is_empty() {
+                    // drop our capability for this time
+                    stash.remove(&lowest_ts);
+                }
             }
-        }
 
-        if !stash.is_empty() {
-            activator.activate();
-            return true;
-        }
-        // signal that we're complete now
-        false
-    };
+            if !stash.is_empty() {
+                activator.activate();
+                return true;
+            }
+            // signal that we're complete now
+            false
+        };
 
     builder.build_reschedule(|_capabilities| {
         move |_frontiers| {
@@ -1414,3 +1935,84 @@ where
 
     output_stream
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{RateLimiter, TokenBucket};
+
+    #[test]
+    fn token_bucket_refills_at_rate() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10, start);
+        assert_eq!(bucket.delay(10.0, start), None);
+        bucket.tokens -= 10.0;
+
+        // An empty bucket needs a tenth of a second per token.
+        assert_eq!(bucket.delay(1.0, start), Some(Duration::from_millis(100)));
+        assert_eq!(bucket.delay(5.0, start), Some(Duration::from_millis(500)));
+
+        let later = start + Duration::from_millis(500);
+        assert_eq!(bucket.delay(5.0, later), None);
+        assert_eq!(bucket.delay(6.0, later), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn token_bucket_caps_burst_at_capacity() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10, start);
+
+        // Waiting for longer than a second does not accumulate more than a
+        // second worth of tokens.
+        let later = start + Duration::from_secs(60);
+        assert_eq!(bucket.delay(10.0, later), None);
+        assert_eq!(bucket.tokens, 10.0);
+        bucket.tokens -= 10.0;
+        assert_eq!(bucket.delay(1.0, later), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn token_bucket_admits_oversize_cost() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10, start);
+
+        // A cost larger than the capacity only requires a full bucket...
+        assert_eq!(bucket.delay(25.0, start), None);
+        bucket.tokens -= 25.0;
+
+        // ...but the bucket owes the excess, so the next message has to wait
+        // until the debt is paid off as well.
+        assert_eq!(bucket.delay(1.0, start), Some(Duration::from_millis(1600)));
+        let later = start + Duration::from_secs(2);
+        assert_eq!(bucket.delay(1.0, later), None);
+    }
+
+    #[test]
+    fn rate_limiter_waits_for_slowest_limit() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter {
+            messages: Some(TokenBucket::new(2, start)),
+            bytes: Some(TokenBucket::new(100, start)),
+        };
+
+        assert_eq!(limiter.delay_at(50, start), None);
+        limiter.consume(50);
+        assert_eq!(limiter.delay_at(50, start), None);
+        limiter.consume(50);
+
+        // Both limits are exhausted, so the sink waits for whichever of them
+        // takes longer to admit a message of the given size.
+        assert_eq!(
+            limiter.delay_at(80, start),
+            Some(Duration::from_millis(800))
+        );
+        assert_eq!(
+            limiter.delay_at(10, start),
+            Some(Duration::from_millis(500))
+        );
+
+        let mut unlimited = RateLimiter::new(None, None);
+        assert_eq!(unlimited.delay_at(usize::MAX, start), None);
+    }
+}
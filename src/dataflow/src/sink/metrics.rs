@@ -20,7 +20,10 @@ pub struct KafkaBaseMetrics {
     pub(crate) messages_sent_counter: IntCounterVec,
     pub(crate) message_send_errors_counter: IntCounterVec,
     pub(crate) message_delivery_errors_counter: IntCounterVec,
+    pub(crate) message_routing_errors_counter: IntCounterVec,
     pub(crate) rows_queued: UIntGaugeVec,
+    pub(crate) backlog_messages: UIntGaugeVec,
+    pub(crate) backlog_bytes: UIntGaugeVec,
 }
 
 impl KafkaBaseMetrics {
@@ -41,11 +44,26 @@ impl KafkaBaseMetrics {
                 help: "The number of messages that the Kafka producer could not deliver to the topic",
                 var_labels: ["topic", "sink_id", "worker_id"],
             )),
+            message_routing_errors_counter: registry.register(metric!(
+                name: "mz_kafka_message_routing_errors_total",
+                help: "The number of messages whose PARTITION BY or HEADERS expressions failed to evaluate, which shuts down the sink",
+                var_labels: ["topic", "sink_id", "worker_id"],
+            )),
             rows_queued: registry.register(metric!(
                 name: "mz_kafka_sink_rows_queued",
                 help: "The current number of rows queued by the Kafka sink operator (note that one row can generate multiple Kafka messages)",
                 var_labels: ["topic", "sink_id", "worker_id"],
             )),
+            backlog_messages: registry.register(metric!(
+                name: "mz_kafka_sink_backlog_messages",
+                help: "The current number of messages that are ready to be sent by the Kafka sink but have not been handed to the Kafka producer",
+                var_labels: ["topic", "sink_id", "worker_id"],
+            )),
+            backlog_bytes: registry.register(metric!(
+                name: "mz_kafka_sink_backlog_bytes",
+                help: "The current size of the messages that are ready to be sent by the Kafka sink but have not been handed to the Kafka producer",
+                var_labels: ["topic", "sink_id", "worker_id"],
+            )),
         }
     }
 }
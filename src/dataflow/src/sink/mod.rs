@@ -7,10 +7,182 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use differential_dataflow::Hashable;
+use itertools::Itertools;
+use timely::dataflow::Scope;
+use timely::progress::frontier::AntichainRef;
+use timely::progress::{Antichain, Timestamp as _};
+
+use mz_dataflow_types::sinks::SinkAsOf;
+use mz_expr::GlobalId;
+use mz_ore::cast::CastFrom;
+use mz_repr::Timestamp;
+
+use crate::server::ComputeState;
+
+/// Unwraps the result of a step of the operator of a [`ResumableProgress`]
+/// sink, or logs the error, runs `$on_err` and returns from the operator logic
+/// so that it runs again after `$backoff`.
+///
+/// Can't use `?` when the return type of the operator logic is a `bool`. The
+/// sink state `$s` must have a `name` and an `activator`.
+macro_rules! retry_err {
+    ($s:ident, $expr:expr, $backoff:expr, $on_err:block) => {
+        match $expr {
+            Ok(val) => val,
+            Err(e) => {
+                let backoff = $backoff;
+                tracing::warn!(
+                    "error in sink {}, retrying in {:?}: {:#}",
+                    &$s.name,
+                    backoff,
+                    e
+                );
+                $on_err;
+                $s.activator.activate_after(backoff);
+                return true;
+            }
+        }
+    };
+}
+
 mod avro_ocf;
+mod http;
 mod kafka;
 mod metrics;
+mod parquet;
+mod postgres;
 mod tail;
 
 pub(crate) use metrics::KafkaBaseMetrics;
 pub use metrics::SinkBaseMetrics;
+
+/// The progress of a sink that writes out its updates from a single worker,
+/// and that resumes after the timestamps that a previous incarnation of the
+/// sink recorded as written in the external system.
+///
+/// Skipping those timestamps writes every update exactly once only if the
+/// sink's inputs replay the same updates at the same timestamps after a
+/// restart. The coordinator tracks the write frontier of the sink, and holding
+/// it back prevents compaction of the timestamp bindings of the sink's
+/// sources, so the write frontier must not pass a timestamp before it is
+/// recorded as written.
+pub(crate) struct ResumableProgress {
+    as_of: SinkAsOf,
+    /// The latest timestamp that a previous incarnation of the sink recorded
+    /// as written. Updates at or before this timestamp are skipped.
+    gate_ts: Option<Timestamp>,
+    /// Whether this worker writes out the updates of the sink.
+    is_active_worker: bool,
+    /// The frontier of timestamps that are recorded as written.
+    write_frontier: Rc<RefCell<Antichain<Timestamp>>>,
+}
+
+impl ResumableProgress {
+    /// Creates the progress of the sink `sink_id` on the worker of `scope`
+    /// and registers its write frontier with `compute_state`.
+    ///
+    /// The updates of the sink must be exchanged to the active worker by
+    /// hashing on `sink_id`.
+    pub(crate) fn new<G>(
+        compute_state: &mut ComputeState,
+        sink_id: GlobalId,
+        scope: &G,
+        as_of: SinkAsOf,
+    ) -> Self
+    where
+        G: Scope,
+    {
+        // Only the active worker ever holds back the write frontier, see the
+        // equivalent comment for Kafka sinks.
+        let is_active_worker = usize::cast_from(sink_id.hashed()) % scope.peers() == scope.index();
+        let write_frontier = Rc::new(RefCell::new(if is_active_worker {
+            Antichain::from_elem(Timestamp::minimum())
+        } else {
+            Antichain::new()
+        }));
+        compute_state
+            .sink_write_frontiers
+            .insert(sink_id, Rc::clone(&write_frontier));
+        ResumableProgress {
+            as_of,
+            gate_ts: None,
+            is_active_worker,
+            write_frontier,
+        }
+    }
+
+    /// Reports whether this worker writes out the updates of the sink.
+    pub(crate) fn is_active_worker(&self) -> bool {
+        self.is_active_worker
+    }
+
+    /// Sets the latest timestamp that a previous incarnation of the sink
+    /// recorded as written.
+    pub(crate) fn set_gate_ts(&mut self, gate_ts: Option<Timestamp>) {
+        self.gate_ts = gate_ts;
+    }
+
+    /// Reports whether an update at `time` must be written out, i.e. whether
+    /// it is beyond the as of of the sink and was not already written by a
+    /// previous incarnation of the sink.
+    pub(crate) fn should_write(&self, time: Timestamp) -> bool {
+        let beyond_as_of = if self.as_of.strict {
+            self.as_of.frontier.less_than(&time)
+        } else {
+            self.as_of.frontier.less_equal(&time)
+        };
+        beyond_as_of && Some(time) > self.gate_ts
+    }
+
+    /// Returns the latest timestamp up to which all updates are written, given
+    /// the input frontier and the earliest timestamp of the updates that are
+    /// not yet written, or `None` if the sink will never write more updates.
+    pub(crate) fn written_upto(
+        input_frontier: AntichainRef<Timestamp>,
+        min_pending_ts: Option<Timestamp>,
+    ) -> Option<Timestamp> {
+        // This only looks at the first entry of the antichain, see the
+        // equivalent comment for Kafka sinks.
+        let input_frontier = input_frontier
+            .iter()
+            .at_most_one()
+            .expect("more than one element in the frontier")
+            .cloned();
+        // A frontier of `t` means we still might receive updates with `t`, but
+        // the written timestamp is inclusive.
+        input_frontier
+            .into_iter()
+            .chain(min_pending_ts)
+            .min()
+            .map(|ts| ts.saturating_sub(1))
+    }
+
+    /// Advances the write frontier to `written`, as returned by
+    /// [`ResumableProgress::written_upto`], which must already be recorded as
+    /// written in the external system.
+    pub(crate) fn advance(&self, written: Option<Timestamp>) {
+        let mut write_frontier = self.write_frontier.borrow_mut();
+        match written {
+            Some(written) => {
+                // make sure we don't regress
+                assert!(write_frontier.less_equal(&written));
+                write_frontier.clear();
+                write_frontier.insert(written);
+            }
+            None => {
+                // We will never receive more data, so we will never write more data.
+                write_frontier.clear();
+            }
+        }
+    }
+
+    /// Indicates that the sink is closed to everyone else who might be
+    /// tracking its write frontier.
+    pub(crate) fn close(&self) {
+        self.write_frontier.borrow_mut().clear();
+    }
+}
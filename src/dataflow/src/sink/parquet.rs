@@ -0,0 +1,554 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::any::Any;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use aws_sdk_s3::{ByteStream, Client};
+use differential_dataflow::consolidation::consolidate_updates;
+use differential_dataflow::{Collection, Hashable};
+use serde::{Deserialize, Serialize};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use timely::dataflow::Scope;
+use timely::progress::Timestamp as _;
+use timely::scheduling::Activator;
+use tracing::{debug, warn};
+
+use mz_dataflow_types::sinks::{ParquetSinkConnector, ParquetSinkLocation, SinkDesc};
+use mz_expr::GlobalId;
+use mz_interchange::parquet::Encoder;
+use mz_ore::collections::CollectionExt;
+use mz_repr::{Diff, Row, Timestamp};
+use mz_timely_util::async_op;
+use mz_timely_util::operators_async_ext::OperatorBuilderExt;
+
+use super::ResumableProgress;
+use crate::render::sinks::{SinkRender, WriteProgress};
+
+/// How long to wait before retrying after an error writing files.
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The prefix of the names of the manifest segments.
+const MANIFEST_PREFIX: &str = "manifest-";
+
+/// The prefix of the names of the files that hold the updates.
+const PART_PREFIX: &str = "part-";
+
+impl<G> SinkRender<G> for ParquetSinkConnector
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    fn uses_keys(&self) -> bool {
+        false
+    }
+
+    fn get_key_indices(&self) -> Option<&[usize]> {
+        None
+    }
+
+    fn get_relation_key_indices(&self) -> Option<&[usize]> {
+        None
+    }
+
+    fn render_continuous_sink(
+        &self,
+        compute_state: &mut crate::server::ComputeState,
+        sink: &SinkDesc,
+        sink_id: GlobalId,
+        sinked_collection: Collection<G, (Option<Row>, Option<Row>), Diff>,
+        _write_progress: Option<WriteProgress>,
+    ) -> Option<Rc<dyn Any>>
+    where
+        G: Scope<Timestamp = Timestamp>,
+    {
+        let progress = ResumableProgress::new(
+            compute_state,
+            sink_id,
+            &sinked_collection.inner.scope(),
+            sink.as_of.clone(),
+        );
+
+        let token = parquet(sinked_collection, sink_id, self.clone(), progress);
+
+        Some(token)
+    }
+}
+
+struct ParquetSinkToken {
+    shutdown_flag: Arc<AtomicBool>,
+}
+
+impl Drop for ParquetSinkToken {
+    fn drop(&mut self) {
+        debug!("dropping parquet sink");
+        self.shutdown_flag.store(true, Ordering::SeqCst);
+    }
+}
+
+/// A completed file, as listed in the manifest.
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    /// The name of the file, relative to the directory of the sink.
+    file: String,
+    /// The inclusive lower bound of the timestamps of the updates in the file.
+    lower: Timestamp,
+    /// The exclusive upper bound of the timestamps of the updates in the file.
+    upper: Timestamp,
+    /// The number of updates in the file.
+    updates: usize,
+}
+
+/// A segment of the list of completed files of a sink, which lists the files
+/// that were written for one range of timestamps.
+///
+/// Each segment is written to its own file, named after the upper bound of
+/// its files, so that the manifest is never rewritten. The timestamp ranges of
+/// consecutive segments are adjacent, so the upper bound of the last segment
+/// is the timestamp up to which all updates are written out. A segment without
+/// files records that its range of timestamps holds no updates.
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestSegment {
+    files: Vec<ManifestEntry>,
+}
+
+/// Returns the name of the manifest segment whose files end at `upper`.
+///
+/// The upper bound is zero padded, so that the names of the segments sort in
+/// the order in which they were written.
+fn manifest_segment_name(upper: Timestamp) -> String {
+    format!("{}{:020}.json", MANIFEST_PREFIX, upper)
+}
+
+/// Returns the upper bound of the files of the manifest segment `name`.
+fn manifest_segment_upper(name: &str) -> Option<Timestamp> {
+    name.strip_prefix(MANIFEST_PREFIX)?
+        .strip_suffix(".json")?
+        .parse()
+        .ok()
+}
+
+/// Returns the lower bound of the timestamps of the part file `name`.
+fn part_lower(name: &str) -> Option<Timestamp> {
+    name.strip_prefix(PART_PREFIX)?
+        .split('-')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// The directory that the sink writes its files to.
+enum Store {
+    Local(PathBuf),
+    S3 {
+        client: Client,
+        bucket: String,
+        prefix: String,
+    },
+}
+
+impl Store {
+    async fn new(location: &ParquetSinkLocation) -> Store {
+        match location {
+            ParquetSinkLocation::Local(path) => Store::Local(path.clone()),
+            ParquetSinkLocation::S3 {
+                bucket,
+                prefix,
+                aws,
+            } => {
+                let config = aws.load().await;
+                Store::S3 {
+                    client: mz_aws_util::s3::client(&config),
+                    bucket: bucket.clone(),
+                    prefix: prefix.clone(),
+                }
+            }
+        }
+    }
+
+    /// Writes `bytes` to the file `name`, replacing any existing file.
+    ///
+    /// Readers never observe a partially written file.
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> Result<(), anyhow::Error> {
+        match self {
+            Store::Local(dir) => {
+                // Write to a hidden file first and then move it into place,
+                // which is atomic on POSIX filesystems.
+                let tmp_path = dir.join(format!(".{}.tmp", name));
+                let mut file = File::create(&tmp_path)?;
+                file.write_all(&bytes)?;
+                file.sync_all()?;
+                fs::rename(&tmp_path, dir.join(name))?;
+            }
+            Store::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                // S3 objects only become visible once they are fully uploaded.
+                let key = format!("{}/{}", prefix, name);
+                client
+                    .put_object()
+                    .bucket(bucket)
+                    .key(&key)
+                    .body(ByteStream::from(bytes))
+                    .send()
+                    .await
+                    .map_err(|e| anyhow!("writing s3://{}/{}: {}", bucket, key, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes the file `name`.
+    async fn delete(&self, name: &str) -> Result<(), anyhow::Error> {
+        match self {
+            Store::Local(dir) => {
+                let path = dir.join(name);
+                fs::remove_file(&path).with_context(|| format!("deleting {}", path.display()))
+            }
+            Store::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                let key = format!("{}/{}", prefix, name);
+                client
+                    .delete_object()
+                    .bucket(bucket)
+                    .key(&key)
+                    .send()
+                    .await
+                    .map_err(|e| anyhow!("deleting s3://{}/{}: {}", bucket, key, e))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Lists the names of the files whose names start with `name_prefix`, in
+    /// lexicographic order.
+    async fn list(&self, name_prefix: &str) -> Result<Vec<String>, anyhow::Error> {
+        let mut names = vec![];
+        match self {
+            Store::Local(dir) => {
+                let entries =
+                    fs::read_dir(dir).with_context(|| format!("listing {}", dir.display()))?;
+                for entry in entries {
+                    if let Some(name) = entry?.file_name().to_str() {
+                        if name.starts_with(name_prefix) {
+                            names.push(name.to_owned());
+                        }
+                    }
+                }
+            }
+            Store::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                let dir = format!("{}/", prefix);
+                let mut continuation_token = None;
+                loop {
+                    let response = client
+                        .list_objects_v2()
+                        .bucket(bucket)
+                        .prefix(format!("{}{}", dir, name_prefix))
+                        .set_continuation_token(continuation_token)
+                        .send()
+                        .await
+                        .map_err(|e| anyhow!("listing s3://{}/{}: {}", bucket, dir, e))?;
+                    for object in response.contents.unwrap_or_default() {
+                        if let Some(name) = object.key.as_deref().and_then(|k| k.strip_prefix(&dir))
+                        {
+                            names.push(name.to_owned());
+                        }
+                    }
+                    continuation_token = response.next_continuation_token;
+                    if continuation_token.is_none() {
+                        break;
+                    }
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+struct ParquetSinkState {
+    name: String,
+    connector: ParquetSinkConnector,
+    encoder: Encoder,
+    /// The directory of the sink, opened lazily and reset after errors.
+    store: Option<Store>,
+    /// Whether the files of previous incarnations of the sink have been read
+    /// from the manifest.
+    initialized: bool,
+    /// Updates at timestamps that the input frontier has not yet passed.
+    pending: Vec<(Row, Timestamp, Diff)>,
+    /// Updates at timestamps that the input frontier has passed, which go
+    /// into the next files.
+    complete: Vec<(Row, Timestamp, Diff)>,
+    /// The lower bound of the timestamps of the next files, which is the upper
+    /// bound of the previous files. Updates before it are already written out.
+    lower: Timestamp,
+    shutdown_flag: Arc<AtomicBool>,
+    activator: Activator,
+    /// The timestamps that are listed in the manifest.
+    progress: ResumableProgress,
+}
+
+impl ParquetSinkState {
+    async fn store(&mut self) -> &Store {
+        if self.store.is_none() {
+            self.store = Some(Store::new(&self.connector.location).await);
+        }
+        self.store.as_ref().expect("opened above")
+    }
+
+    /// Resumes after the files that a previous incarnation of the sink listed
+    /// in the manifest, and deletes the files that it wrote but never listed.
+    async fn initialize(&mut self) -> Result<(), anyhow::Error> {
+        if !self.connector.exactly_once {
+            // The sink writes to a fresh directory.
+            self.initialized = true;
+            return Ok(());
+        }
+        let store = self.store().await;
+        let lower = match store.list(MANIFEST_PREFIX).await?.last() {
+            Some(name) => manifest_segment_upper(name)
+                .ok_or_else(|| anyhow!("invalid manifest segment name {}", name))?,
+            None => Timestamp::minimum(),
+        };
+        // The files of a range of timestamps that was never listed start at
+        // or after the upper bound of the listed files.
+        for name in store.list(PART_PREFIX).await? {
+            if part_lower(&name).map_or(false, |part_lower| part_lower >= lower) {
+                store.delete(&name).await?;
+            }
+        }
+        self.lower = lower;
+        self.progress.set_gate_ts(lower.checked_sub(1));
+        self.initialized = true;
+        Ok(())
+    }
+
+    /// Writes the complete updates to files that cover the timestamps up to
+    /// `upper`, and then lists the files in the manifest.
+    ///
+    /// A new file is started whenever the row data in the current file
+    /// exceeds the maximum file size. If there are no complete updates, no
+    /// files are written, and the next files start at `upper`.
+    async fn write_files(&mut self, upper: Timestamp) -> Result<(), anyhow::Error> {
+        consolidate_updates(&mut self.complete);
+        if self.complete.is_empty() {
+            if self.connector.exactly_once {
+                // Record that the timestamps up to `upper` hold no updates, so
+                // that the sink resumes after them. Otherwise the write frontier
+                // would pass timestamps that restarts replay.
+                let bytes = serde_json::to_vec(&ManifestSegment { files: vec![] })?;
+                self.store()
+                    .await
+                    .put(&manifest_segment_name(upper), bytes)
+                    .await?;
+            }
+            self.lower = upper;
+            return Ok(());
+        }
+        self.complete
+            .sort_by(|(r1, t1, _), (r2, t2, _)| (t1, r1).cmp(&(t2, r2)));
+
+        let mut chunks = vec![];
+        let mut start = 0;
+        let mut size = 0;
+        for (i, (row, _, _)) in self.complete.iter().enumerate() {
+            if size >= self.connector.max_file_size {
+                chunks.push(start..i);
+                start = i;
+                size = 0;
+            }
+            size += row.data().len();
+        }
+        chunks.push(start..self.complete.len());
+
+        let result = async {
+            let mut entries = vec![];
+            for (i, chunk) in chunks.into_iter().enumerate() {
+                let file = format!("{}{}-{}-{}.parquet", PART_PREFIX, self.lower, upper, i);
+                let bytes = self.encoder.encode(&self.complete[chunk.clone()])?;
+                self.store().await.put(&file, bytes).await?;
+                entries.push(ManifestEntry {
+                    file,
+                    lower: self.lower,
+                    upper,
+                    updates: chunk.len(),
+                });
+            }
+            let segment = ManifestSegment { files: entries };
+            let bytes = serde_json::to_vec(&segment)?;
+            self.store()
+                .await
+                .put(&manifest_segment_name(upper), bytes)
+                .await
+        }
+        .await;
+        if let Err(e) = result {
+            // The updates are written to new files on retry, as the upper
+            // bound may have advanced by then, so the files that were written
+            // are not listed in the manifest and must not linger. Files that
+            // cannot be deleted now are deleted when the sink resumes.
+            let lower = self.lower;
+            let sink_name = self.name.clone();
+            let store = self.store().await;
+            for name in store.list(PART_PREFIX).await.unwrap_or_default() {
+                if part_lower(&name).map_or(false, |part_lower| part_lower >= lower) {
+                    if let Err(e) = store.delete(&name).await {
+                        warn!(
+                            "error deleting unlisted file in sink {}: {:#}",
+                            sink_name, e
+                        );
+                    }
+                }
+            }
+            return Err(e);
+        }
+
+        self.complete.clear();
+        self.lower = upper;
+        Ok(())
+    }
+
+    /// Advances the write frontier to the lower bound of the next files.
+    fn emit_progress(&mut self, upper: Timestamp) {
+        let written =
+            if upper == Timestamp::MAX && self.complete.is_empty() && self.pending.is_empty() {
+                // We will never receive more data, so we will never write more data.
+                None
+            } else {
+                // Only the timestamps before the lower bound of the next
+                // files are written.
+                Some(self.lower.saturating_sub(1))
+            };
+        self.progress.advance(written);
+    }
+}
+
+/// Writes a stream of updates to Parquet files, along with the timestamp and
+/// the diff of each update.
+///
+/// This operator exchanges all updates to a single worker by hashing on the
+/// given sink `id`. Every time the input frontier advances, the updates at the
+/// timestamps that it passed are written to one or more new files, which are
+/// then listed in a new manifest segment. Updates that the given
+/// [`ResumableProgress`] does not need written, because they are not beyond
+/// the as of of the sink or precede the files listed by a previous incarnation
+/// of the sink, are discarded.
+fn parquet<G>(
+    collection: Collection<G, (Option<Row>, Option<Row>)>,
+    id: GlobalId,
+    connector: ParquetSinkConnector,
+    progress: ResumableProgress,
+) -> Rc<dyn Any>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let name = format!("parquet-{}", id);
+
+    let stream = &collection.inner;
+    let scope = stream.scope();
+    let mut builder = OperatorBuilder::new(name.clone(), scope.clone());
+    let activator = scope.activator_for(&builder.operator_info().address[..]);
+
+    let shutdown_flag = Arc::new(AtomicBool::new(false));
+
+    let encoder = Encoder::new(&connector.value_desc)
+        .expect("parquet sink columns validated during planning");
+    let mut s = ParquetSinkState {
+        name,
+        connector,
+        encoder,
+        store: None,
+        initialized: false,
+        pending: vec![],
+        complete: vec![],
+        lower: Timestamp::minimum(),
+        shutdown_flag: Arc::clone(&shutdown_flag),
+        activator,
+        progress,
+    };
+
+    let mut vector = Vec::new();
+
+    // We want exactly one worker to write the files and the manifest.
+    let hashed_id = id.hashed();
+    let is_active_worker = s.progress.is_active_worker();
+
+    let mut input = builder.new_input(stream, Exchange::new(move |_| hashed_id));
+
+    builder.build_async(
+        scope,
+        async_op!(|_initial_capabilities, frontiers| {
+            if s.shutdown_flag.load(Ordering::SeqCst) {
+                debug!("shutting down sink: {}", &s.name);
+                s.progress.close();
+                return false;
+            }
+            if !is_active_worker {
+                return false;
+            }
+
+            if !s.initialized {
+                retry_err!(s, s.initialize().await, RETRY_BACKOFF, { s.store = None });
+            }
+
+            input.for_each(|_, rows| {
+                rows.swap(&mut vector);
+                for ((key, value), time, diff) in vector.drain(..) {
+                    if !s.progress.should_write(time) || diff == 0 {
+                        continue;
+                    }
+                    assert!(key.is_none(), "parquet sinks must not have keys");
+                    let value = value.expect("parquet sinks must have values");
+                    s.pending.push((value, time, diff));
+                }
+            });
+
+            // Panic if there's not exactly once element in the frontier like we expect.
+            let frontier = frontiers.clone().into_element();
+            // A closed input means that all updates are complete.
+            let upper = frontier
+                .elements()
+                .first()
+                .copied()
+                .unwrap_or(Timestamp::MAX);
+            let (complete, pending): (Vec<_>, Vec<_>) =
+                s.pending.drain(..).partition(|(_, time, _)| *time < upper);
+            s.pending = pending;
+            s.complete.extend(complete);
+
+            if upper > s.lower {
+                retry_err!(s, s.write_files(upper).await, RETRY_BACKOFF, {
+                    s.store = None
+                });
+            }
+            s.emit_progress(upper);
+
+            false
+        }),
+    );
+
+    Rc::new(ParquetSinkToken { shutdown_flag })
+}
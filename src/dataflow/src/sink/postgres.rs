@@ -0,0 +1,499 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use bytes::Bytes;
+use differential_dataflow::{Collection, Hashable};
+use futures::{pin_mut, SinkExt};
+use itertools::Itertools;
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use timely::dataflow::Scope;
+use timely::progress::frontier::AntichainRef;
+use timely::progress::Timestamp as _;
+use timely::scheduling::Activator;
+use tokio_postgres::types::Type as PgType;
+use tokio_postgres::{Client, Statement};
+use tracing::debug;
+
+use mz_dataflow_types::sinks::{PostgresSinkConnector, SinkDesc};
+use mz_expr::GlobalId;
+use mz_ore::collections::CollectionExt;
+use mz_postgres_util::quote_identifier;
+use mz_repr::{Datum, Diff, RelationType, Row, ScalarType, Timestamp};
+use mz_timely_util::async_op;
+use mz_timely_util::operators_async_ext::OperatorBuilderExt;
+
+use super::ResumableProgress;
+use crate::render::sinks::{SinkRender, WriteProgress};
+
+/// How long to wait before retrying after an error talking to the upstream database.
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+impl<G> SinkRender<G> for PostgresSinkConnector
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    fn uses_keys(&self) -> bool {
+        true
+    }
+
+    fn get_key_indices(&self) -> Option<&[usize]> {
+        Some(&self.key_indices)
+    }
+
+    fn get_relation_key_indices(&self) -> Option<&[usize]> {
+        None
+    }
+
+    fn render_continuous_sink(
+        &self,
+        compute_state: &mut crate::server::ComputeState,
+        sink: &SinkDesc,
+        sink_id: GlobalId,
+        sinked_collection: Collection<G, (Option<Row>, Option<Row>), Diff>,
+        _write_progress: Option<WriteProgress>,
+    ) -> Option<Rc<dyn Any>>
+    where
+        G: Scope<Timestamp = Timestamp>,
+    {
+        let progress = ResumableProgress::new(
+            compute_state,
+            sink_id,
+            &sinked_collection.inner.scope(),
+            sink.as_of.clone(),
+        );
+
+        let token = postgres(sinked_collection, sink_id, self.clone(), progress);
+
+        Some(token)
+    }
+}
+
+struct PostgresSinkToken {
+    shutdown_flag: Arc<AtomicBool>,
+}
+
+impl Drop for PostgresSinkToken {
+    fn drop(&mut self) {
+        debug!("dropping postgres sink");
+        self.shutdown_flag.store(true, Ordering::SeqCst);
+    }
+}
+
+/// The temporary table into which the updates of each timestamp are copied
+/// before they are applied to the upstream table.
+const STAGING_TABLE: &str = "mz_sink_staging";
+
+/// A connection to the upstream database and the statements the sink uses.
+struct PostgresConnection {
+    client: Client,
+    /// Copies the updates of a timestamp into the staging table.
+    copy: Statement,
+    /// Upserts the staged rows that are not deletions.
+    upsert: Statement,
+    /// Deletes the rows whose keys are staged as deletions.
+    delete: Statement,
+    progress: Statement,
+}
+
+struct PostgresSinkState {
+    name: String,
+    connector: PostgresSinkConnector,
+    /// The type of the rows of the staging table: the sinked columns followed
+    /// by whether the row is a deletion.
+    staging_type: RelationType,
+    /// The connection to the upstream database, established lazily and reset
+    /// after errors.
+    conn: Option<PostgresConnection>,
+    /// Whether the progress of previous incarnations of the sink has been
+    /// read from the progress table.
+    initialized: bool,
+    /// The latest timestamp recorded in the progress table.
+    latest_progress_ts: Timestamp,
+    pending_rows: HashMap<Timestamp, Vec<(Row, Option<Row>)>>,
+    shutdown_flag: Arc<AtomicBool>,
+    activator: Activator,
+    /// The timestamps that are recorded in the progress table.
+    progress: ResumableProgress,
+}
+
+impl PostgresSinkState {
+    fn new(
+        connector: PostgresSinkConnector,
+        name: String,
+        shutdown_flag: Arc<AtomicBool>,
+        activator: Activator,
+        progress: ResumableProgress,
+    ) -> Self {
+        let mut column_types = connector.value_desc.typ().column_types.clone();
+        column_types.push(ScalarType::Bool.nullable(false));
+        let staging_type = RelationType::new(column_types);
+        PostgresSinkState {
+            name,
+            connector,
+            staging_type,
+            conn: None,
+            initialized: false,
+            latest_progress_ts: Timestamp::minimum(),
+            pending_rows: HashMap::new(),
+            shutdown_flag,
+            activator,
+            progress,
+        }
+    }
+
+    async fn connect(&mut self) -> Result<&mut PostgresConnection, anyhow::Error> {
+        if self.conn.is_none() {
+            let client = mz_postgres_util::connect(&self.connector.conn)
+                .await
+                .context("connecting to upstream database")?;
+
+            let desc = &self.connector.value_desc;
+            let columns = desc
+                .iter_names()
+                .map(|name| quote_identifier(name.as_str()))
+                .collect::<Vec<_>>();
+            let keys = self
+                .connector
+                .key_indices
+                .iter()
+                .map(|&i| columns[i].as_str())
+                .collect::<Vec<_>>();
+            let updates = columns
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !self.connector.key_indices.contains(i))
+                .map(|(_, column)| format!("{column} = EXCLUDED.{column}"))
+                .collect::<Vec<_>>();
+            let on_conflict = if updates.is_empty() {
+                "DO NOTHING".to_owned()
+            } else {
+                format!("DO UPDATE SET {}", updates.join(", "))
+            };
+
+            // The staging table takes the column types of the upstream table,
+            // so that the upstream database converts the copied values to the
+            // types of the table columns. Its rows are deleted at the end of
+            // each transaction.
+            client
+                .batch_execute(&format!(
+                    "CREATE TEMPORARY TABLE {staging} ON COMMIT DELETE ROWS AS \
+                     SELECT {}, NULL::boolean AS mz_is_delete FROM {} WITH NO DATA",
+                    columns.join(", "),
+                    self.connector.table,
+                    staging = STAGING_TABLE,
+                ))
+                .await
+                .context("creating staging table")?;
+
+            let copy = format!("COPY {} FROM STDIN", STAGING_TABLE);
+            let upsert = format!(
+                "INSERT INTO {} ({columns}) SELECT {columns} FROM {} WHERE NOT mz_is_delete \
+                 ON CONFLICT ({}) {}",
+                self.connector.table,
+                STAGING_TABLE,
+                keys.join(", "),
+                on_conflict,
+                columns = columns.join(", "),
+            );
+            let delete = format!(
+                "DELETE FROM {} AS t USING {} AS s WHERE s.mz_is_delete AND {}",
+                self.connector.table,
+                STAGING_TABLE,
+                keys.iter()
+                    .map(|key| format!("t.{key} = s.{key}"))
+                    .join(" AND "),
+            );
+            let progress = format!(
+                "INSERT INTO {} (sink_id, ts) VALUES ($1, $2) \
+                 ON CONFLICT (sink_id) DO UPDATE SET ts = EXCLUDED.ts",
+                self.connector.progress_table,
+            );
+
+            let copy = client
+                .prepare(&copy)
+                .await
+                .context("preparing copy into staging table")?;
+            let upsert = client
+                .prepare(&upsert)
+                .await
+                .with_context(|| format!("preparing upsert into {}", self.connector.table))?;
+            let delete = client
+                .prepare(&delete)
+                .await
+                .with_context(|| format!("preparing delete from {}", self.connector.table))?;
+            let progress = client
+                .prepare_typed(&progress, &[PgType::TEXT, PgType::INT8])
+                .await
+                .with_context(|| {
+                    format!(
+                        "preparing progress update of {}",
+                        self.connector.progress_table
+                    )
+                })?;
+
+            self.conn = Some(PostgresConnection {
+                client,
+                copy,
+                upsert,
+                delete,
+                progress,
+            });
+        }
+        Ok(self.conn.as_mut().expect("connected above"))
+    }
+
+    /// Reads the latest timestamp that a previous incarnation of this sink
+    /// wrote out from the progress table.
+    async fn initialize(&mut self) -> Result<(), anyhow::Error> {
+        let query = format!(
+            "SELECT ts FROM {} WHERE sink_id = $1",
+            self.connector.progress_table
+        );
+        let progress_key = self.connector.progress_key.clone();
+        let conn = self.connect().await?;
+        let row = conn.client.query_opt(&query, &[&progress_key]).await?;
+        let gate_ts = match row {
+            Some(row) => {
+                let ts: i64 = row.get("ts");
+                Some(Timestamp::try_from(ts).map_err(|_| anyhow!("invalid progress {}", ts))?)
+            }
+            None => None,
+        };
+        self.progress.set_gate_ts(gate_ts);
+        if let Some(gate_ts) = gate_ts {
+            self.latest_progress_ts = gate_ts;
+        }
+        self.initialized = true;
+        Ok(())
+    }
+
+    /// Applies the updates at `ts` and records `ts` as written, in a single
+    /// transaction.
+    ///
+    /// The updates are copied into the staging table, from which they are
+    /// applied with one statement for the deletions and one for the upserts.
+    async fn write_timestamp(&mut self, ts: Timestamp) -> Result<(), anyhow::Error> {
+        let rows = self.pending_rows.remove(&ts).unwrap_or_default();
+        let data = self.encode_staging_rows(&rows);
+        let progress_key = self.connector.progress_key.clone();
+        let result = async {
+            let data = data?;
+            let conn = self.connect().await?;
+            let txn = conn.client.transaction().await?;
+            let sink = txn.copy_in(&conn.copy).await?;
+            pin_mut!(sink);
+            sink.send(Bytes::from(data)).await?;
+            sink.finish().await?;
+            txn.execute(&conn.delete, &[]).await?;
+            txn.execute(&conn.upsert, &[]).await?;
+            txn.execute(&conn.progress, &[&progress_key, &progress_ts(ts)])
+                .await?;
+            txn.commit().await?;
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+        if result.is_err() {
+            // Keep the updates around to retry the whole transaction.
+            self.pending_rows.insert(ts, rows);
+        } else {
+            self.latest_progress_ts = ts;
+        }
+        result
+    }
+
+    /// Encodes the latest update of each key in `rows` as the text format
+    /// rows of a `COPY` into the staging table.
+    ///
+    /// Deletions carry the key columns and NULL in all other columns.
+    fn encode_staging_rows(&self, rows: &[(Row, Option<Row>)]) -> Result<Vec<u8>, anyhow::Error> {
+        // The upsert fails if it affects the same row twice, so only the
+        // latest update of each key is applied.
+        let mut latest = HashMap::new();
+        for (i, (key, _)) in rows.iter().enumerate() {
+            latest.insert(key, i);
+        }
+        let arity = self.connector.value_desc.arity();
+        let mut data = Vec::new();
+        let mut row = Row::default();
+        for (i, (key, value)) in rows.iter().enumerate() {
+            if latest[key] != i {
+                continue;
+            }
+            row.clear();
+            match value {
+                Some(value) => {
+                    row.extend_by_row(value);
+                    row.push(Datum::False);
+                }
+                None => {
+                    let key = key.unpack();
+                    for column in 0..arity {
+                        match self.connector.key_indices.iter().position(|&k| k == column) {
+                            Some(k) => row.push(key[k]),
+                            None => row.push(Datum::Null),
+                        }
+                    }
+                    row.push(Datum::True);
+                }
+            }
+            mz_pgcopy::encode_copy_row_text(row.clone(), &self.staging_type, &mut data)?;
+        }
+        Ok(data)
+    }
+
+    /// Advances the write frontier to the input frontier and the pending
+    /// timestamps, recording the new frontier in the progress table first if
+    /// it moved past the latest recorded timestamp.
+    async fn maybe_emit_progress<'a>(
+        &mut self,
+        input_frontier: AntichainRef<'a, Timestamp>,
+    ) -> Result<(), anyhow::Error> {
+        let min_pending_ts = self.pending_rows.keys().min().cloned();
+        let written = ResumableProgress::written_upto(input_frontier, min_pending_ts);
+
+        if let Some(written) = written {
+            if written > self.latest_progress_ts {
+                let progress_key = self.connector.progress_key.clone();
+                let conn = self.connect().await?;
+                conn.client
+                    .execute(&conn.progress, &[&progress_key, &progress_ts(written)])
+                    .await?;
+                self.latest_progress_ts = written;
+            }
+        }
+
+        self.progress.advance(written);
+        Ok(())
+    }
+}
+
+fn progress_ts(ts: Timestamp) -> i64 {
+    i64::try_from(ts).expect("timestamp fits into an i64")
+}
+
+/// Applies a stream of keyed upserts to a table in an upstream Postgres
+/// database.
+///
+/// This operator exchanges all updates to a single worker by hashing on the
+/// given sink `id`. Once the input frontier has passed a timestamp, the
+/// updates at that timestamp are applied in a single transaction that also
+/// records the timestamp in the progress table. Updates that the given
+/// [`ResumableProgress`] does not need written are discarded.
+fn postgres<G>(
+    collection: Collection<G, (Option<Row>, Option<Row>)>,
+    id: GlobalId,
+    connector: PostgresSinkConnector,
+    progress: ResumableProgress,
+) -> Rc<dyn Any>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let name = format!("postgres-{}", id);
+
+    let stream = &collection.inner;
+    let scope = stream.scope();
+    let mut builder = OperatorBuilder::new(name.clone(), scope.clone());
+    let activator = scope.activator_for(&builder.operator_info().address[..]);
+
+    let shutdown_flag = Arc::new(AtomicBool::new(false));
+
+    let mut s = PostgresSinkState::new(
+        connector,
+        name,
+        Arc::clone(&shutdown_flag),
+        activator,
+        progress,
+    );
+
+    let mut vector = Vec::new();
+
+    // We want exactly one worker to write to the upstream database.
+    let hashed_id = id.hashed();
+    let is_active_worker = s.progress.is_active_worker();
+
+    let mut input = builder.new_input(stream, Exchange::new(move |_| hashed_id));
+
+    builder.build_async(
+        scope,
+        async_op!(|_initial_capabilities, frontiers| {
+            if s.shutdown_flag.load(Ordering::SeqCst) {
+                debug!("shutting down sink: {}", &s.name);
+                s.progress.close();
+                return false;
+            }
+            // Panic if there's not exactly once element in the frontier like we expect.
+            let frontier = frontiers.clone().into_element();
+
+            if is_active_worker && !s.initialized {
+                retry_err!(s, s.initialize().await, RETRY_BACKOFF, { s.conn = None });
+            }
+
+            input.for_each(|_, rows| {
+                assert!(is_active_worker);
+                rows.swap(&mut vector);
+                for ((key, value), time, diff) in vector.drain(..) {
+                    if !s.progress.should_write(time) {
+                        // Skip stale data for already written timestamps
+                        continue;
+                    }
+
+                    assert!(diff >= 0, "can't sink negative multiplicities");
+                    if diff == 0 {
+                        continue;
+                    }
+                    let key = key.expect("postgres sinks must have keys");
+                    s.pending_rows.entry(time).or_default().push((key, value));
+                }
+            });
+
+            // Write out newly closed timestamps in ascending order.
+            let mut closed_ts: Vec<Timestamp> = s
+                .pending_rows
+                .keys()
+                .filter(|ts| !frontier.less_equal(*ts))
+                .copied()
+                .collect();
+            closed_ts.sort_unstable();
+            for ts in closed_ts {
+                retry_err!(s, s.write_timestamp(ts).await, RETRY_BACKOFF, {
+                    s.conn = None
+                });
+            }
+
+            if is_active_worker {
+                retry_err!(
+                    s,
+                    s.maybe_emit_progress(frontier.borrow()).await,
+                    RETRY_BACKOFF,
+                    { s.conn = None }
+                );
+            }
+
+            if !s.pending_rows.is_empty() {
+                // We have some more rows that we need to wait for frontiers to
+                // advance before we can write them out.
+                s.activator.activate_after(Duration::from_millis(100));
+                return true;
+            }
+
+            false
+        }),
+    );
+
+    Rc::new(PostgresSinkToken { shutdown_flag })
+}
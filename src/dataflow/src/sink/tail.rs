@@ -9,6 +9,7 @@
 
 use std::any::Any;
 use std::cell::RefCell;
+use std::iter;
 use std::ops::DerefMut;
 use std::rc::Rc;
 
@@ -26,13 +27,15 @@ use timely::progress::Antichain;
 use timely::PartialOrder;
 
 use mz_dataflow_types::{
-    sinks::{SinkAsOf, SinkDesc, TailSinkConnector},
+    sinks::{SinkAsOf, SinkDesc, TailEnvelope, TailSinkConnector},
     TailResponse,
 };
 use mz_expr::GlobalId;
-use mz_repr::{Diff, Row, Timestamp};
+use mz_interchange::avro::DiffPair;
+use mz_interchange::envelopes::{combine_at_timestamp, dbz_format};
+use mz_repr::{Datum, DatumVec, Diff, Row, Timestamp};
 
-use crate::render::sinks::SinkRender;
+use crate::render::sinks::{SinkRender, WriteProgress};
 
 impl<G> SinkRender<G> for TailSinkConnector
 where
@@ -56,6 +59,7 @@ where
         sink: &SinkDesc,
         sink_id: GlobalId,
         sinked_collection: Collection<G, (Option<Row>, Option<Row>), Diff>,
+        _write_progress: Option<WriteProgress>,
     ) -> Option<Rc<dyn Any>>
     where
         G: Scope<Timestamp = Timestamp>,
@@ -78,8 +82,20 @@ where
         }));
         let tail_protocol_weak = Rc::downgrade(&tail_protocol_handle);
 
+        let rows = sinked_collection.map(|(k, v)| {
+            assert!(k.is_none(), "tail does not support keys");
+            v.expect("tail must have values")
+        });
+        let rows = match &self.envelope {
+            None => rows,
+            Some(TailEnvelope::Upsert { key_indices }) => {
+                upsert(rows, key_indices.clone(), sink.from_desc.arity())
+            }
+            Some(TailEnvelope::Debezium { key_indices }) => debezium(rows, key_indices.clone()),
+        };
+
         tail(
-            sinked_collection,
+            rows,
             sink_id,
             sink.as_of.clone(),
             tail_protocol_handle,
@@ -97,8 +113,97 @@ where
     }
 }
 
+/// Pairs up the changes to `rows` at each timestamp by the key formed by the
+/// columns at `key_indices`.
+fn combine_by_key<G>(
+    rows: Collection<G, Row, Diff>,
+    key_indices: Vec<usize>,
+) -> Collection<G, (Option<Row>, Vec<DiffPair<Row>>), Diff>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let mut datum_vec = DatumVec::new();
+    let keyed = rows.map(move |row| {
+        let key = {
+            let datums = datum_vec.borrow_with(&row);
+            Row::pack(key_indices.iter().map(|idx| datums[*idx]))
+        };
+        (Some(key), row)
+    });
+    combine_at_timestamp(keyed.arrange_by_key().stream)
+}
+
+/// Presents `rows`, which have `arity` columns, as one row per key and
+/// timestamp, consisting of the state of the key, the key columns, and the
+/// remaining columns of the row.
+///
+/// The state is `upsert` if the key has a new value, `delete` if the key was
+/// deleted, and `key_violation` if the key had more than one value at the
+/// timestamp. The remaining columns are `NULL` unless the state is `upsert`.
+fn upsert<G>(
+    rows: Collection<G, Row, Diff>,
+    key_indices: Vec<usize>,
+    arity: usize,
+) -> Collection<G, Row, Diff>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let combined = combine_by_key(rows, key_indices.clone());
+    let mut packer = Row::default();
+    let mut datum_vec = DatumVec::new();
+    combined.map(move |(key, pairs)| {
+        let key = key.expect("upsert tails are keyed");
+        let (state, value) = match &pairs[..] {
+            [DiffPair {
+                after: Some(after), ..
+            }] => ("upsert", Some(after)),
+            [DiffPair { after: None, .. }] => ("delete", None),
+            _ => ("key_violation", None),
+        };
+        packer.push(Datum::String(state));
+        packer.extend_by_row(&key);
+        match value {
+            Some(value) => {
+                let datums = datum_vec.borrow_with(value);
+                packer.extend(
+                    datums
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _)| !key_indices.contains(idx))
+                        .map(|(_, datum)| *datum),
+                );
+            }
+            None => {
+                packer.extend(iter::repeat(Datum::Null).take(arity - key_indices.len()));
+            }
+        }
+        packer.finish_and_reuse()
+    })
+}
+
+/// Presents `rows` as one row per change, consisting of the value of a row
+/// before and after the change. If `key_indices` is not empty, deletions and
+/// insertions at the same timestamp are paired up by key.
+fn debezium<G>(rows: Collection<G, Row, Diff>, key_indices: Vec<usize>) -> Collection<G, Row, Diff>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let combined = if key_indices.is_empty() {
+        combine_at_timestamp(rows.map(|row| (None, row)).arrange_by_key().stream)
+    } else {
+        combine_by_key(rows, key_indices)
+    };
+    let mut packer = Row::default();
+    combined.flat_map(move |(_key, pairs)| {
+        pairs
+            .into_iter()
+            .map(|pair| dbz_format(&mut packer, pair))
+            .collect::<Vec<_>>()
+    })
+}
+
 fn tail<G>(
-    sinked_collection: Collection<G, (Option<Row>, Option<Row>), Diff>,
+    rows: Collection<G, Row, Diff>,
     sink_id: GlobalId,
     as_of: SinkAsOf,
     tail_protocol_handle: Rc<RefCell<Option<TailProtocol>>>,
@@ -107,14 +212,7 @@ fn tail<G>(
     G: Scope<Timestamp = Timestamp>,
 {
     // make sure all data is routed to one worker by keying on the sink id
-    let batches = sinked_collection
-        .map(move |(k, v)| {
-            assert!(k.is_none(), "tail does not support keys");
-            let v = v.expect("tail must have values");
-            (sink_id, v)
-        })
-        .arrange_by_key()
-        .stream;
+    let batches = rows.map(move |row| (sink_id, row)).arrange_by_key().stream;
 
     // Initialize to the minimal input frontier.
     let mut input_frontier = Antichain::from_elem(<G::Timestamp as TimelyTimestamp>::minimum());
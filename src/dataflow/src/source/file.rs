@@ -233,6 +233,9 @@ pub fn read_file_task<Ctor, I, Err>(
     let file_stream: Box<dyn AvroRead + Send> = match compression {
         Compression::Gzip => Box::new(MultiGzDecoder::new(file_stream)),
         Compression::None => Box::new(file_stream),
+        Compression::Zstd | Compression::Bzip2 | Compression::Xz => {
+            unreachable!("planning only allows gzip compression for file sources")
+        }
     };
 
     let iter = iter_ctor(file_stream);
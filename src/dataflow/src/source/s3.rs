@@ -33,7 +33,7 @@ use std::default::Default;
 use std::fmt::Formatter;
 use std::ops::AddAssign;
 
-use async_compression::tokio::bufread::GzipDecoder;
+use async_compression::tokio::bufread::{BzDecoder, GzipDecoder, XzDecoder, ZstdDecoder};
 use aws_sdk_s3::error::{GetObjectError, ListObjectsV2Error};
 use aws_sdk_s3::{Client as S3Client, SdkError};
 use aws_sdk_sqs::model::{ChangeMessageVisibilityBatchRequestEntry, Message as SqsMessage};
@@ -699,8 +699,11 @@ async fn download_object(
         if let Some(s) = obj.content_encoding.as_deref() {
             match (s, compression) {
                 ("gzip", Compression::Gzip) => (),
+                ("zstd", Compression::Zstd) => (),
+                ("bzip2", Compression::Bzip2) => (),
+                ("xz", Compression::Xz) => (),
                 ("identity", Compression::None) => (),
-                ("identity" | "gzip", _) => {
+                ("identity" | "gzip" | "zstd" | "bzip2" | "xz", _) => {
                     tracing::debug!("object {} has mismatched Content-Encoding: {}", key, s)
                 }
                 _ => tracing::debug!("object {} has unrecognized Content-Encoding: {}", key, s),
@@ -731,6 +734,18 @@ async fn download_object(
             let decoder = GzipDecoder::new(reader);
             read_object_chunked(source_id, decoder, tx).await
         }
+        Compression::Zstd => {
+            let decoder = ZstdDecoder::new(reader);
+            read_object_chunked(source_id, decoder, tx).await
+        }
+        Compression::Bzip2 => {
+            let decoder = BzDecoder::new(reader);
+            read_object_chunked(source_id, decoder, tx).await
+        }
+        Compression::Xz => {
+            let decoder = XzDecoder::new(reader);
+            read_object_chunked(source_id, decoder, tx).await
+        }
     };
 
     tracing::debug!(
@@ -120,6 +120,16 @@ impl TimestampProposer {
     fn upper(&self) -> Timestamp {
         self.timestamp
     }
+
+    /// Ensures that all future proposals happen at times greater than `timestamp`.
+    ///
+    /// Bindings restored on restart may be ahead of the local clock, and new
+    /// offsets must never be bound to a time that was already closed.
+    fn advance_past(&mut self, timestamp: Timestamp) {
+        if self.timestamp <= timestamp {
+            self.timestamp = timestamp + 1;
+        }
+    }
 }
 
 /// This struct holds per partition timestamp binding state, as a ordered list of bindings (time, offset).
@@ -356,6 +366,7 @@ impl TimestampBindingBox {
 
         let partition = self.partitions.get_mut(&partition).expect("known to exist");
         partition.add_binding(timestamp, offset);
+        self.proposer.advance_past(timestamp);
     }
 
     fn downgrade(&self, cap: &mut Capability<Timestamp>, cursors: &HashMap<PartitionId, MzOffset>) {
@@ -1036,4 +1047,25 @@ mod tests {
         expected_updates.sort();
         assert_eq!(actual_updates, expected_updates);
     }
+
+    // Bindings restored on restart must keep their timestamps, and new offsets must be bound
+    // to later times even if the local clock is behind the restored bindings.
+    #[test]
+    fn restored_bindings_ahead_of_clock() {
+        let timestamp_histories = TimestampBindingRc::new(1000, (|| 50).into());
+
+        timestamp_histories.add_partition(PartitionId::Kafka(0), None);
+        timestamp_histories.add_binding(PartitionId::Kafka(0), 100, MzOffset { offset: 4 });
+
+        assert_eq!(
+            timestamp_histories
+                .get_or_propose_binding(&PartitionId::Kafka(0), MzOffset { offset: 2 }),
+            100
+        );
+        assert_eq!(
+            timestamp_histories
+                .get_or_propose_binding(&PartitionId::Kafka(0), MzOffset { offset: 4 }),
+            101
+        );
+    }
 }
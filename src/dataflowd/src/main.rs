@@ -157,6 +157,7 @@ async fn run(args: Args) -> Result<(), anyhow::Error> {
         metrics_registry: MetricsRegistry::new(),
         now: SYSTEM_TIME.clone(),
         persister: None,
+        spill_directory: None,
     })?;
 
     let mut conn = mz_dataflowd::tcp::framed_server(conn);
@@ -0,0 +1,398 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Generation of random `MirRelationExpr`s over randomly filled sources.
+//!
+//! Generated expressions are well typed, and only use scalar functions that
+//! cannot fail on the generated data, so that their results are defined. In
+//! particular, negations only appear beneath thresholds, and `TopK`s order
+//! by all of their columns so that their results are deterministic.
+
+use std::collections::HashMap;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use mz_expr::{
+    AggregateExpr, AggregateFunc, BinaryFunc, ColumnOrder, GlobalId, Id, LocalId, MirRelationExpr,
+    MirScalarExpr, UnaryFunc, VariadicFunc,
+};
+use mz_ore::id_gen::IdGen;
+use mz_repr::{ColumnType, Datum, Diff, RelationType, Row, ScalarType};
+
+use crate::interpret::Collection;
+use crate::TestCatalog;
+
+/// Generates random `MirRelationExpr`s.
+pub struct RelationGenerator<'a, R> {
+    rng: &'a mut R,
+    id_gen: &'a mut IdGen,
+    sources: Vec<(GlobalId, RelationType)>,
+    locals: Vec<(LocalId, RelationType)>,
+}
+
+impl<'a, R: Rng> RelationGenerator<'a, R> {
+    /// Registers between one and three sources of random types in `catalog`.
+    ///
+    /// Local ids are allocated from `id_gen`, which should be shared with the
+    /// transforms that are applied to the generated expressions.
+    pub fn new(rng: &'a mut R, id_gen: &'a mut IdGen, catalog: &mut TestCatalog) -> Self {
+        let mut sources = Vec::new();
+        for i in 0..rng.gen_range(1..=3) {
+            let typ = RelationType::new(
+                (0..rng.gen_range(1..=3))
+                    .map(|_| {
+                        let scalar_type = if rng.gen_bool(0.8) {
+                            ScalarType::Int64
+                        } else {
+                            ScalarType::Bool
+                        };
+                        scalar_type.nullable(rng.gen())
+                    })
+                    .collect(),
+            );
+            let id = catalog
+                .insert(&format!("s{}", i), typ.clone(), false)
+                .expect("source names are unique");
+            sources.push((id, typ));
+        }
+        RelationGenerator {
+            rng,
+            id_gen,
+            sources,
+            locals: Vec::new(),
+        }
+    }
+
+    /// Fills each source with up to five rows of random data.
+    pub fn contents(&mut self) -> HashMap<GlobalId, Collection> {
+        let mut contents = HashMap::new();
+        for (id, typ) in self.sources.clone() {
+            let rows = (0..self.rng.gen_range(0..=5))
+                .map(|_| {
+                    let row = Row::pack(typ.column_types.iter().map(|typ| self.datum(typ)));
+                    (row, self.rng.gen_range(1..=2) as Diff)
+                })
+                .collect();
+            contents.insert(id, rows);
+        }
+        contents
+    }
+
+    /// Generates an expression whose operators are nested at most `depth`
+    /// deep.
+    pub fn relation(&mut self, depth: usize) -> MirRelationExpr {
+        if depth == 0 {
+            return self.leaf();
+        }
+        match self.rng.gen_range(0..12) {
+            0 => {
+                let input = self.relation(depth - 1);
+                let mut typ = input.typ();
+                let mut scalars = Vec::new();
+                for _ in 0..self.rng.gen_range(1..=2) {
+                    let scalar = self.any_scalar(&typ, 2);
+                    typ.column_types.push(scalar.typ(&typ));
+                    scalars.push(scalar);
+                }
+                input.map(scalars)
+            }
+            1 => {
+                let input = self.relation(depth - 1);
+                let typ = input.typ();
+                let predicates = (0..self.rng.gen_range(1..=2))
+                    .map(|_| self.scalar(&typ, &ScalarType::Bool, 2))
+                    .collect::<Vec<_>>();
+                input.filter(predicates)
+            }
+            2 => {
+                let input = self.relation(depth - 1);
+                let arity = input.arity();
+                if arity == 0 {
+                    return input;
+                }
+                let outputs = (0..self.rng.gen_range(1..=arity + 1))
+                    .map(|_| self.rng.gen_range(0..arity))
+                    .collect();
+                input.project(outputs)
+            }
+            3 => self.join(depth),
+            4 => self.reduce(depth),
+            5 => self.relation(depth - 1).distinct(),
+            6 => self.top_k(depth),
+            7 => {
+                let left = self.relation(depth - 1);
+                let right = self.relation(depth - 1);
+                let right = self.conform(right, &left.typ());
+                left.union(right)
+            }
+            8 => {
+                let left = self.relation(depth - 1);
+                let right = self.relation(depth - 1);
+                let right = self.conform(right, &left.typ());
+                left.union(right.negate()).threshold()
+            }
+            9 => {
+                let value = self.relation(depth - 1);
+                let id = LocalId::new(self.id_gen.allocate_id());
+                self.locals.push((id, value.typ()));
+                let body = self.relation(depth - 1);
+                self.locals.pop();
+                MirRelationExpr::Let {
+                    id,
+                    value: Box::new(value),
+                    body: Box::new(body),
+                }
+            }
+            _ => self.relation(depth - 1),
+        }
+    }
+
+    fn leaf(&mut self) -> MirRelationExpr {
+        if !self.locals.is_empty() && self.rng.gen_bool(0.3) {
+            let (id, typ) = self.locals.choose(self.rng).expect("locals is not empty");
+            MirRelationExpr::Get {
+                id: Id::Local(*id),
+                typ: typ.clone(),
+            }
+        } else if self.rng.gen_bool(0.1) {
+            let typ = RelationType::new(vec![ScalarType::Int64.nullable(true)]);
+            let rows = (0..self.rng.gen_range(0..=2))
+                .map(|_| (vec![self.datum(&typ.column_types[0])], 1))
+                .collect();
+            MirRelationExpr::constant_diff(rows, typ)
+        } else {
+            let (id, typ) = self.sources.choose(self.rng).expect("sources is not empty");
+            MirRelationExpr::global_get(*id, typ.clone())
+        }
+    }
+
+    /// Joins two or three inputs on up to two equivalence classes of columns
+    /// of the same type.
+    fn join(&mut self, depth: usize) -> MirRelationExpr {
+        let inputs = (0..self.rng.gen_range(2..=3))
+            .map(|_| self.relation(depth - 1))
+            .collect::<Vec<_>>();
+        let column_types = inputs
+            .iter()
+            .flat_map(|input| input.typ().column_types)
+            .collect::<Vec<_>>();
+        let mut equivalences = Vec::new();
+        for _ in 0..self.rng.gen_range(0..=2) {
+            if column_types.is_empty() {
+                break;
+            }
+            let column = self.rng.gen_range(0..column_types.len());
+            let others = (0..column_types.len())
+                .filter(|other| {
+                    *other != column
+                        && column_types[*other].scalar_type == column_types[column].scalar_type
+                })
+                .collect::<Vec<_>>();
+            if let Some(other) = others.choose(self.rng) {
+                equivalences.push(vec![
+                    MirScalarExpr::Column(column),
+                    MirScalarExpr::Column(*other),
+                ]);
+            }
+        }
+        MirRelationExpr::join_scalars(inputs, equivalences)
+    }
+
+    fn reduce(&mut self, depth: usize) -> MirRelationExpr {
+        let input = self.relation(depth - 1);
+        let typ = input.typ();
+        let arity = typ.arity();
+        let group_key = if arity == 0 {
+            vec![]
+        } else {
+            (0..self.rng.gen_range(0..=2))
+                .map(|_| MirScalarExpr::Column(self.rng.gen_range(0..arity)))
+                .collect()
+        };
+        let aggregates = (0..self.rng.gen_range(0..=2))
+            .map(|_| {
+                let (func, expr) = match self.rng.gen_range(0..4) {
+                    0 => (AggregateFunc::Count, self.any_scalar(&typ, 1)),
+                    1 => (
+                        AggregateFunc::SumInt64,
+                        self.scalar(&typ, &ScalarType::Int64, 1),
+                    ),
+                    2 => (
+                        AggregateFunc::MaxInt64,
+                        self.scalar(&typ, &ScalarType::Int64, 1),
+                    ),
+                    _ => (
+                        AggregateFunc::MinInt64,
+                        self.scalar(&typ, &ScalarType::Int64, 1),
+                    ),
+                };
+                AggregateExpr {
+                    func,
+                    expr,
+                    distinct: self.rng.gen(),
+                }
+            })
+            .collect();
+        MirRelationExpr::Reduce {
+            input: Box::new(input),
+            group_key,
+            aggregates,
+            monotonic: false,
+            expected_group_size: None,
+        }
+    }
+
+    /// Orders by all columns, in a random order and direction, so that the
+    /// rows that are retained are unambiguous.
+    fn top_k(&mut self, depth: usize) -> MirRelationExpr {
+        let input = self.relation(depth - 1);
+        let arity = input.arity();
+        let group_key = (0..arity)
+            .filter(|_| self.rng.gen_bool(0.3))
+            .collect::<Vec<_>>();
+        let mut columns = (0..arity).collect::<Vec<_>>();
+        columns.shuffle(self.rng);
+        let order_key = columns
+            .into_iter()
+            .map(|column| ColumnOrder {
+                column,
+                desc: self.rng.gen(),
+            })
+            .collect();
+        let limit = if self.rng.gen() {
+            Some(self.rng.gen_range(0..=3))
+        } else {
+            None
+        };
+        let offset = self.rng.gen_range(0..=1);
+        input.top_k(group_key, order_key, limit, offset)
+    }
+
+    /// Reshapes `expr` to have the column types of `typ`, by picking one of
+    /// its columns of each type, or a null if it has none.
+    fn conform(&mut self, expr: MirRelationExpr, typ: &RelationType) -> MirRelationExpr {
+        let expr_typ = expr.typ();
+        let arity = expr_typ.arity();
+        let mut nulls = Vec::new();
+        let mut outputs = Vec::new();
+        for column_type in &typ.column_types {
+            let candidates = (0..arity)
+                .filter(|c| expr_typ.column_types[*c].scalar_type == column_type.scalar_type)
+                .collect::<Vec<_>>();
+            match candidates.choose(self.rng) {
+                Some(column) => outputs.push(*column),
+                None => {
+                    outputs.push(arity + nulls.len());
+                    nulls.push(MirScalarExpr::literal_null(column_type.scalar_type.clone()));
+                }
+            }
+        }
+        expr.map(nulls).project(outputs)
+    }
+
+    /// Generates a scalar expression of either type.
+    fn any_scalar(&mut self, typ: &RelationType, depth: usize) -> MirScalarExpr {
+        let scalar_type = if self.rng.gen_bool(0.7) {
+            ScalarType::Int64
+        } else {
+            ScalarType::Bool
+        };
+        self.scalar(typ, &scalar_type, depth)
+    }
+
+    /// Generates a scalar expression of type `scalar_type` over the columns of
+    /// `typ`.
+    fn scalar(
+        &mut self,
+        typ: &RelationType,
+        scalar_type: &ScalarType,
+        depth: usize,
+    ) -> MirScalarExpr {
+        let columns = (0..typ.arity())
+            .filter(|c| typ.column_types[*c].scalar_type == *scalar_type)
+            .collect::<Vec<_>>();
+        if !columns.is_empty() && (depth == 0 || self.rng.gen_bool(0.4)) {
+            return MirScalarExpr::Column(*columns.choose(self.rng).expect("columns is not empty"));
+        }
+        if depth == 0 || self.rng.gen_bool(0.2) {
+            let datum = self.datum(&scalar_type.clone().nullable(true));
+            return MirScalarExpr::literal_ok(datum, scalar_type.clone());
+        }
+        let depth = depth - 1;
+        match scalar_type {
+            ScalarType::Bool => match self.rng.gen_range(0..5) {
+                0 => {
+                    let func = [
+                        BinaryFunc::Eq,
+                        BinaryFunc::NotEq,
+                        BinaryFunc::Lt,
+                        BinaryFunc::Gte,
+                    ]
+                    .choose(self.rng)
+                    .expect("functions is not empty")
+                    .clone();
+                    let left = self.scalar(typ, &ScalarType::Int64, depth);
+                    let right = self.scalar(typ, &ScalarType::Int64, depth);
+                    left.call_binary(right, func)
+                }
+                1 => self
+                    .any_scalar(typ, depth)
+                    .call_unary(UnaryFunc::IsNull(mz_expr::func::IsNull)),
+                2 => self
+                    .scalar(typ, scalar_type, depth)
+                    .call_unary(UnaryFunc::Not(mz_expr::func::Not)),
+                3 => {
+                    let left = self.scalar(typ, scalar_type, depth);
+                    let right = self.scalar(typ, scalar_type, depth);
+                    left.call_binary(right, BinaryFunc::And)
+                }
+                _ => {
+                    let left = self.scalar(typ, scalar_type, depth);
+                    let right = self.scalar(typ, scalar_type, depth);
+                    left.call_binary(right, BinaryFunc::Or)
+                }
+            },
+            _ => match self.rng.gen_range(0..3) {
+                // The generated integers are small enough that sums of them
+                // cannot overflow.
+                0 => {
+                    let left = self.scalar(typ, scalar_type, depth);
+                    let right = self.scalar(typ, scalar_type, depth);
+                    left.call_binary(right, BinaryFunc::AddInt64)
+                }
+                1 => {
+                    let cond = self.scalar(typ, &ScalarType::Bool, depth);
+                    let then = self.scalar(typ, scalar_type, depth);
+                    let els = self.scalar(typ, scalar_type, depth);
+                    cond.if_then_else(then, els)
+                }
+                _ => MirScalarExpr::CallVariadic {
+                    func: VariadicFunc::Coalesce,
+                    exprs: vec![
+                        self.scalar(typ, scalar_type, depth),
+                        self.scalar(typ, scalar_type, depth),
+                    ],
+                },
+            },
+        }
+    }
+
+    /// Generates a datum of type `typ`, drawing integers from a small domain
+    /// so that joins and reductions find matching values.
+    fn datum(&mut self, typ: &ColumnType) -> Datum<'static> {
+        if typ.nullable && self.rng.gen_bool(0.2) {
+            return Datum::Null;
+        }
+        match typ.scalar_type {
+            ScalarType::Bool => Datum::from(self.rng.gen::<bool>()),
+            _ => Datum::Int64(self.rng.gen_range(-2..=2)),
+        }
+    }
+}
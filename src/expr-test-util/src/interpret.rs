@@ -0,0 +1,288 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A reference interpreter for `MirRelationExpr`s.
+//!
+//! The interpreter favors obviousness over efficiency: joins are evaluated as
+//! filtered cross products, and reductions by materializing each group.
+
+use std::collections::{BTreeMap, HashMap};
+
+use mz_expr::{compare_columns, GlobalId, Id, LocalId, MirRelationExpr, MirScalarExpr};
+use mz_repr::{Datum, Diff, Row, RowArena};
+
+/// The contents of a collection, as rows and their multiplicities.
+pub type Collection = Vec<(Row, Diff)>;
+
+/// Evaluates `expr` over the contents of the global collections in `sources`.
+///
+/// Returns the consolidated contents of the result, sorted by row. Errors if
+/// the result is not defined, which happens when a scalar expression fails to
+/// evaluate or when a reduction observes a row with a negative multiplicity.
+pub fn evaluate(
+    expr: &MirRelationExpr,
+    sources: &HashMap<GlobalId, Collection>,
+) -> Result<Collection, String> {
+    let mut interpreter = Interpreter {
+        sources,
+        locals: HashMap::new(),
+    };
+    interpreter.eval(expr).map(consolidate)
+}
+
+/// Sorts `collection` by row, sums the multiplicities of equal rows, and
+/// discards the rows whose multiplicities sum to zero.
+pub fn consolidate(collection: Collection) -> Collection {
+    let mut consolidated = BTreeMap::new();
+    for (row, diff) in collection {
+        *consolidated.entry(row).or_insert(0) += diff;
+    }
+    consolidated
+        .into_iter()
+        .filter(|(_, diff)| *diff != 0)
+        .collect()
+}
+
+struct Interpreter<'a> {
+    sources: &'a HashMap<GlobalId, Collection>,
+    locals: HashMap<LocalId, Collection>,
+}
+
+impl Interpreter<'_> {
+    fn eval(&mut self, expr: &MirRelationExpr) -> Result<Collection, String> {
+        match expr {
+            MirRelationExpr::Constant { rows, .. } => rows.clone().map_err(|e| e.to_string()),
+            MirRelationExpr::Get { id, .. } => match id {
+                Id::Local(id) => self.locals.get(id).cloned(),
+                Id::Global(id) => self.sources.get(id).cloned(),
+                Id::LocalBareSource => None,
+            }
+            .ok_or_else(|| format!("no contents for {}", id)),
+            MirRelationExpr::Let { id, value, body } => {
+                let value = self.eval(value)?;
+                let shadowed = self.locals.insert(*id, value);
+                let result = self.eval(body);
+                match shadowed {
+                    Some(shadowed) => self.locals.insert(*id, shadowed),
+                    None => self.locals.remove(id),
+                };
+                result
+            }
+            MirRelationExpr::Project { input, outputs } => Ok(self
+                .eval(input)?
+                .into_iter()
+                .map(|(row, diff)| {
+                    let datums = row.unpack();
+                    (Row::pack(outputs.iter().map(|i| datums[*i])), diff)
+                })
+                .collect()),
+            MirRelationExpr::Map { input, scalars } => {
+                let arena = RowArena::new();
+                let mut result = Vec::new();
+                for (row, diff) in self.eval(input)? {
+                    let mut datums = row.unpack();
+                    for scalar in scalars {
+                        let datum = scalar.eval(&datums, &arena).map_err(|e| e.to_string())?;
+                        datums.push(datum);
+                    }
+                    result.push((Row::pack_slice(&datums), diff));
+                }
+                Ok(result)
+            }
+            MirRelationExpr::FlatMap { input, func, exprs } => {
+                let arena = RowArena::new();
+                let mut result = Vec::new();
+                for (row, diff) in self.eval(input)? {
+                    let datums = row.unpack();
+                    let args = eval_all(exprs, &datums, &arena)?;
+                    for (output, output_diff) in
+                        func.eval(&args, &arena).map_err(|e| e.to_string())?
+                    {
+                        let mut row = row.clone();
+                        row.extend_by_row(&output);
+                        result.push((row, diff * output_diff));
+                    }
+                }
+                Ok(result)
+            }
+            MirRelationExpr::Filter { input, predicates } => {
+                let arena = RowArena::new();
+                let mut result = Vec::new();
+                for (row, diff) in self.eval(input)? {
+                    let mut retain = true;
+                    let datums = row.unpack();
+                    for predicate in predicates {
+                        let datum = predicate.eval(&datums, &arena).map_err(|e| e.to_string())?;
+                        if datum != Datum::True {
+                            retain = false;
+                            break;
+                        }
+                    }
+                    if retain {
+                        result.push((row.clone(), diff));
+                    }
+                }
+                Ok(result)
+            }
+            MirRelationExpr::Join {
+                inputs,
+                equivalences,
+                ..
+            } => {
+                let mut product = vec![(Row::default(), 1)];
+                for input in inputs {
+                    let input = self.eval(input)?;
+                    product = product
+                        .iter()
+                        .flat_map(|(left, left_diff)| {
+                            input.iter().map(move |(right, right_diff)| {
+                                let mut row = left.clone();
+                                row.extend_by_row(right);
+                                (row, left_diff * right_diff)
+                            })
+                        })
+                        .collect();
+                }
+                let arena = RowArena::new();
+                let mut result = Vec::new();
+                for (row, diff) in product {
+                    let mut retain = true;
+                    let datums = row.unpack();
+                    for class in equivalences {
+                        // Unlike the `=` operator, join equivalences consider
+                        // nulls equal to each other.
+                        let values = eval_all(class, &datums, &arena)?;
+                        if values.windows(2).any(|pair| pair[0] != pair[1]) {
+                            retain = false;
+                            break;
+                        }
+                    }
+                    if retain {
+                        result.push((row.clone(), diff));
+                    }
+                }
+                Ok(result)
+            }
+            MirRelationExpr::Reduce {
+                input,
+                group_key,
+                aggregates,
+                ..
+            } => {
+                let arena = RowArena::new();
+                let mut groups: BTreeMap<Row, Collection> = BTreeMap::new();
+                for (row, diff) in self.eval_positive(input)? {
+                    let key = Row::pack_slice(&eval_all(group_key, &row.unpack(), &arena)?);
+                    groups.entry(key).or_default().push((row, diff));
+                }
+                let mut result = Vec::new();
+                for (key, rows) in groups.iter() {
+                    let mut datums = key.unpack();
+                    for aggregate in aggregates {
+                        let mut values = Vec::new();
+                        for (row, diff) in rows {
+                            let value = aggregate
+                                .expr
+                                .eval(&row.unpack(), &arena)
+                                .map_err(|e| e.to_string())?;
+                            values.extend(std::iter::repeat(value).take(count(*diff)));
+                        }
+                        if aggregate.distinct {
+                            values.sort();
+                            values.dedup();
+                        }
+                        datums.push(aggregate.func.eval(values, &arena));
+                    }
+                    result.push((Row::pack_slice(&datums), 1));
+                }
+                Ok(result)
+            }
+            MirRelationExpr::TopK {
+                input,
+                group_key,
+                order_key,
+                limit,
+                offset,
+                ..
+            } => {
+                let mut groups: BTreeMap<Row, Collection> = BTreeMap::new();
+                for (row, diff) in self.eval_positive(input)? {
+                    let key = Row::pack(group_key.iter().map(|i| row.unpack()[*i]));
+                    groups.entry(key).or_default().push((row, diff));
+                }
+                let mut result = Vec::new();
+                for (_, mut rows) in groups {
+                    rows.sort_by(|(left, _), (right, _)| {
+                        compare_columns(order_key, &left.unpack(), &right.unpack(), || {
+                            left.cmp(right)
+                        })
+                    });
+                    let mut skip = *offset;
+                    let mut remaining = limit.unwrap_or(usize::MAX);
+                    for (row, diff) in rows {
+                        let mut copies = count(diff);
+                        let skipped = copies.min(skip);
+                        skip -= skipped;
+                        copies -= skipped;
+                        let taken = copies.min(remaining);
+                        remaining -= taken;
+                        if taken > 0 {
+                            let taken = Diff::try_from(taken).expect("taken from a Diff");
+                            result.push((row, taken));
+                        }
+                    }
+                }
+                Ok(result)
+            }
+            MirRelationExpr::Negate { input } => Ok(self
+                .eval(input)?
+                .into_iter()
+                .map(|(row, diff)| (row, -diff))
+                .collect()),
+            MirRelationExpr::Threshold { input } => Ok(consolidate(self.eval(input)?)
+                .into_iter()
+                .filter(|(_, diff)| *diff > 0)
+                .collect()),
+            MirRelationExpr::Union { base, inputs } => {
+                let mut result = self.eval(base)?;
+                for input in inputs {
+                    result.extend(self.eval(input)?);
+                }
+                Ok(result)
+            }
+            MirRelationExpr::ArrangeBy { input, .. }
+            | MirRelationExpr::DeclareKeys { input, .. } => self.eval(input),
+        }
+    }
+
+    /// Evaluates `expr`, which must not contain rows with negative
+    /// multiplicities.
+    fn eval_positive(&mut self, expr: &MirRelationExpr) -> Result<Collection, String> {
+        let collection = consolidate(self.eval(expr)?);
+        match collection.iter().find(|(_, diff)| *diff < 0) {
+            Some((row, diff)) => Err(format!("row {:?} has negative multiplicity {}", row, diff)),
+            None => Ok(collection),
+        }
+    }
+}
+
+fn eval_all<'a>(
+    exprs: &'a [MirScalarExpr],
+    datums: &[Datum<'a>],
+    arena: &'a RowArena,
+) -> Result<Vec<Datum<'a>>, String> {
+    exprs
+        .iter()
+        .map(|expr| expr.eval(datums, arena).map_err(|e| e.to_string()))
+        .collect()
+}
+
+fn count(diff: Diff) -> usize {
+    usize::try_from(diff).expect("multiplicity is positive")
+}
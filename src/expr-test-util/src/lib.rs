@@ -23,6 +23,12 @@ use mz_ore::str::separated;
 use mz_repr::{ColumnType, RelationType, Row, ScalarType};
 use mz_repr_test_util::*;
 
+mod generate;
+mod interpret;
+
+pub use generate::RelationGenerator;
+pub use interpret::{consolidate, evaluate, Collection};
+
 lazy_static! {
     pub static ref RTI: ReflectedTypeInfo = {
         let mut rti = ReflectedTypeInfo::default();
@@ -150,6 +156,11 @@ impl<'a> TestCatalog {
         self.objects.get(name)
     }
 
+    /// Looks up the id of the object named `name`.
+    pub fn get_source_id(&'a self, name: &str) -> Option<GlobalId> {
+        self.get(name).map(|(id, _)| *id)
+    }
+
     /// Looks up the name of the object referred to as `id`.
     pub fn get_source_name(&'a self, id: &GlobalId) -> Option<&'a String> {
         self.names.get(id)
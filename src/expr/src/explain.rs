@@ -418,6 +418,12 @@ impl<'a> ViewExplanation<'a> {
                 }
                 Ok(())
             }
+            JoinImplementation::AntiJoin(pos, key) => writeln!(
+                f,
+                "AntiJoin %{}.({})",
+                self.expr_chain(&join_inputs[*pos]),
+                separated(", ", key)
+            ),
             JoinImplementation::Unimplemented => writeln!(f, "Unimplemented"),
         }
     }
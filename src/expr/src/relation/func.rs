@@ -1006,6 +1006,54 @@ fn generate_subscripts_array(
     }
 }
 
+/// Merges the timestamps in `timestamps` into sessions, where each session
+/// extends `gap` past its latest timestamp.
+fn sessions(
+    timestamps: Datum,
+    gap: Interval,
+    el_typ: &ScalarType,
+) -> Result<impl Iterator<Item = (Row, Diff)>, EvalError> {
+    if gap.months != 0 || gap.duration <= 0 {
+        return Err(EvalError::InvalidParameterValue(
+            "session gap must be greater than zero and cannot contain months or years".to_owned(),
+        ));
+    }
+    let gap = gap.duration_as_chrono();
+    let (unwrap, conv): (
+        fn(Datum) -> NaiveDateTime,
+        fn(NaiveDateTime) -> Datum<'static>,
+    ) = match el_typ {
+        ScalarType::TimestampTz => (
+            |d| d.unwrap_timestamptz().naive_utc(),
+            |d| Datum::from(DateTime::<Utc>::from_utc(d, Utc)),
+        ),
+        _ => (|d| d.unwrap_timestamp(), Datum::from),
+    };
+
+    let mut timestamps = timestamps
+        .unwrap_list()
+        .iter()
+        .filter(|d| !d.is_null())
+        .map(unwrap)
+        .collect::<Vec<_>>();
+    timestamps.sort_unstable();
+
+    let mut sessions: Vec<(NaiveDateTime, NaiveDateTime)> = vec![];
+    for ts in timestamps {
+        let end = ts
+            .checked_add_signed(gap)
+            .ok_or(EvalError::TimestampOutOfRange)?;
+        match sessions.last_mut() {
+            Some((_, last_end)) if ts < *last_end => *last_end = end,
+            _ => sessions.push((ts, end)),
+        }
+    }
+
+    Ok(sessions
+        .into_iter()
+        .map(move |(start, end)| (Row::pack_slice(&[conv(start), conv(end)]), 1)))
+}
+
 fn unnest_array<'a>(a: Datum<'a>) -> impl Iterator<Item = (Row, Diff)> + 'a {
     a.unwrap_array()
         .elements()
@@ -1166,6 +1214,11 @@ pub enum TableFunc {
         width: usize,
     },
     GenerateSubscriptsArray,
+    /// Merges a list of timestamps into sessions separated by gaps of at least
+    /// the given interval, producing the start and end of each session.
+    Sessions {
+        el_typ: ScalarType,
+    },
 }
 
 impl TableFunc {
@@ -1236,6 +1289,11 @@ impl TableFunc {
             TableFunc::UnnestArray { .. } => Ok(Box::new(unnest_array(datums[0]))),
             TableFunc::UnnestList { .. } => Ok(Box::new(unnest_list(datums[0]))),
             TableFunc::Wrap { width, .. } => Ok(Box::new(wrap(&datums, *width))),
+            TableFunc::Sessions { el_typ } => Ok(Box::new(sessions(
+                datums[0],
+                datums[1].unwrap_interval(),
+                el_typ,
+            )?)),
         }
     }
 
@@ -1278,6 +1336,12 @@ impl TableFunc {
             TableFunc::UnnestArray { el_typ } => vec![el_typ.clone().nullable(true)],
             TableFunc::UnnestList { el_typ } => vec![el_typ.clone().nullable(true)],
             TableFunc::Wrap { types, .. } => types.clone(),
+            TableFunc::Sessions { el_typ } => {
+                vec![
+                    el_typ.clone().nullable(false),
+                    el_typ.clone().nullable(false),
+                ]
+            }
         })
     }
 
@@ -1297,6 +1361,7 @@ impl TableFunc {
             TableFunc::UnnestArray { .. } => 1,
             TableFunc::UnnestList { .. } => 1,
             TableFunc::Wrap { width, .. } => *width,
+            TableFunc::Sessions { .. } => 2,
         }
     }
 
@@ -1314,7 +1379,8 @@ impl TableFunc {
             | TableFunc::CsvExtract(_)
             | TableFunc::Repeat
             | TableFunc::UnnestArray { .. }
-            | TableFunc::UnnestList { .. } => true,
+            | TableFunc::UnnestList { .. }
+            | TableFunc::Sessions { .. } => true,
             TableFunc::Wrap { .. } => false,
         }
     }
@@ -1338,6 +1404,7 @@ impl TableFunc {
             TableFunc::UnnestArray { .. } => true,
             TableFunc::UnnestList { .. } => true,
             TableFunc::Wrap { .. } => true,
+            TableFunc::Sessions { .. } => true,
         }
     }
 }
@@ -1359,6 +1426,7 @@ impl fmt::Display for TableFunc {
             TableFunc::UnnestArray { .. } => f.write_str("unnest_array"),
             TableFunc::UnnestList { .. } => f.write_str("unnest_list"),
             TableFunc::Wrap { width, .. } => write!(f, "wrap{}", width),
+            TableFunc::Sessions { .. } => f.write_str("sessions"),
         }
     }
 }
@@ -2072,6 +2072,18 @@ pub enum JoinImplementation {
     /// Each plan starts from the corresponding index, and then in sequence joins
     /// against collections identified by index and with the specified arrangement key.
     DeltaQuery(Vec<Vec<(usize, Vec<MirScalarExpr>)>>),
+    /// Subtract from one input the records whose keys appear in the other.
+    ///
+    /// This implements joins of a collection `x` with the union of the
+    /// distinct keys of `x` and the negation of a collection `s` with at most
+    /// one record per key, the form that `NOT EXISTS` subqueries take. Rather
+    /// than joining `x` with each branch of the union, the records of `x`
+    /// whose keys match a record of `s` are looked up in an arrangement of `x`
+    /// and retracted from `x`.
+    ///
+    /// The arguments are the index of `x` and the key of the arrangement of
+    /// `x` to look its records up in.
+    AntiJoin(usize, Vec<MirScalarExpr>),
     /// No implementation yet selected.
     Unimplemented,
 }
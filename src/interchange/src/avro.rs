@@ -34,7 +34,7 @@ mod tests {
 
     use mz_avro::types::{DecimalValue, Value};
     use mz_repr::adt::numeric;
-    use mz_repr::{Datum, RelationDesc, ScalarType};
+    use mz_repr::{Datum, RelationDesc, Row, ScalarType};
 
     use super::*;
 
@@ -52,6 +52,66 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn decode_demanded_columns() -> anyhow::Result<()> {
+        let schema = r#"{
+            "type": "record",
+            "name": "test",
+            "fields": [
+                { "name": "f1", "type": "int" },
+                { "name": "f2", "type": ["null", "string", "long"] },
+                { "name": "f3", "type": "string" }
+            ]
+        }"#;
+        let bytes = mz_avro::to_avro_datum(
+            &parse_schema(schema)?,
+            Value::Record(vec![
+                ("f1".into(), Value::Int(1)),
+                (
+                    "f2".into(),
+                    Value::Union {
+                        index: 2,
+                        inner: Box::new(Value::Long(2)),
+                        n_variants: 3,
+                        null_variant: Some(0),
+                    },
+                ),
+                ("f3".into(), Value::String("three".into())),
+            ]),
+        )?;
+        let decode = |demanded: &[bool]| -> anyhow::Result<Row> {
+            let mut decoder = Decoder::new(schema, None, "test".into(), false)?;
+            decoder.demand_columns(demanded);
+            futures::executor::block_on(decoder.decode(&mut &bytes[..]))
+        };
+
+        // The union contributes two columns, one per non-null variant.
+        assert_eq!(
+            decode(&[true; 4])?,
+            Row::pack_slice(&[
+                Datum::Int32(1),
+                Datum::Null,
+                Datum::Int64(2),
+                Datum::String("three"),
+            ])
+        );
+        // Fields are skipped only if none of their columns are demanded.
+        assert_eq!(
+            decode(&[false, false, true, false])?,
+            Row::pack_slice(&[Datum::Null, Datum::Null, Datum::Int64(2), Datum::Null])
+        );
+        assert_eq!(
+            decode(&[true, false, false, true])?,
+            Row::pack_slice(&[
+                Datum::Int32(1),
+                Datum::Null,
+                Datum::Null,
+                Datum::String("three"),
+            ])
+        );
+        Ok(())
+    }
+
     #[test]
     fn basic_record() -> anyhow::Result<()> {
         let schema = r#"{
@@ -18,14 +18,15 @@ use uuid::Uuid;
 use mz_avro::error::{DecodeError, Error as AvroError};
 use mz_avro::{
     define_unexpected, give_value, AvroArrayAccess, AvroDecode, AvroDeserializer, AvroMapAccess,
-    AvroRead, AvroRecordAccess, GeneralDeserializer, StatefulAvroDecodable, ValueDecoder,
-    ValueOrReader,
+    AvroRead, AvroRecordAccess, GeneralDeserializer, StatefulAvroDecodable, TrivialDecoder,
+    ValueDecoder, ValueOrReader,
 };
 use mz_ore::result::ResultExt;
 use mz_repr::adt::jsonb::JsonbPacker;
 use mz_repr::adt::numeric;
 use mz_repr::{Datum, Row};
 
+use crate::avro::schema::field_column_counts;
 use crate::avro::ConfluentAvroResolver;
 
 /// Manages decoding of Avro-encoded bytes.
@@ -35,6 +36,8 @@ pub struct Decoder {
     debug_name: String,
     buf1: Vec<u8>,
     packer: Row,
+    /// The fields of the top-level record to decode, if not all of them.
+    projection: Option<Vec<FieldProjection>>,
 }
 
 impl Decoder {
@@ -58,22 +61,61 @@ impl Decoder {
             debug_name,
             buf1: vec![],
             packer: Default::default(),
+            projection: None,
         })
     }
 
+    /// Restricts decoding to the columns for which `demanded` is true.
+    ///
+    /// Fields of the top-level record whose columns are all undemanded are
+    /// skipped rather than decoded, and their columns are set to null. Schemas
+    /// that are not records are always decoded in full.
+    pub fn demand_columns(&mut self, demanded: &[bool]) {
+        let counts = match field_column_counts(self.csr_avro.reader_schema()) {
+            Some(counts) => counts,
+            None => return,
+        };
+        let mut start = 0;
+        let fields = counts
+            .into_iter()
+            .map(|columns| {
+                let demanded = demanded
+                    .get(start..start + columns)
+                    .map_or(true, |demanded| demanded.iter().any(|d| *d));
+                start += columns;
+                FieldProjection { demanded, columns }
+            })
+            .collect::<Vec<_>>();
+        if fields.iter().any(|field| !field.demanded) {
+            self.projection = Some(fields);
+        }
+    }
+
     /// Decodes Avro-encoded `bytes` into a `Row`.
     pub async fn decode(&mut self, bytes: &mut &[u8]) -> anyhow::Result<Row> {
         let (bytes2, resolved_schema) = self.csr_avro.resolve(bytes).await?;
         *bytes = bytes2;
-        let dec = AvroFlatDecoder {
-            packer: &mut self.packer,
-            buf: &mut self.buf1,
-            is_top: true,
-        };
         let dsr = GeneralDeserializer {
             schema: resolved_schema.top_node(),
         };
-        dsr.deserialize(bytes, dec)?;
+        match &self.projection {
+            None => {
+                let dec = AvroFlatDecoder {
+                    packer: &mut self.packer,
+                    buf: &mut self.buf1,
+                    is_top: true,
+                };
+                dsr.deserialize(bytes, dec)?;
+            }
+            Some(fields) => {
+                let dec = ProjectedRecordDecoder {
+                    packer: &mut self.packer,
+                    buf: &mut self.buf1,
+                    fields,
+                };
+                dsr.deserialize(bytes, dec)?;
+            }
+        }
         let result = self.packer.finish_and_reuse();
         tracing::trace!(
             "[customer-data] Decoded row {:?} in {}",
@@ -84,6 +126,82 @@ impl Decoder {
     }
 }
 
+/// Whether to decode a field of the top-level record.
+#[derive(Debug, Clone, Copy)]
+struct FieldProjection {
+    /// Whether any of the field's columns are demanded.
+    demanded: bool,
+    /// The number of columns the field decodes to.
+    columns: usize,
+}
+
+/// Decodes a top-level record like [`AvroFlatDecoder`], but skips the fields
+/// that are not demanded.
+struct ProjectedRecordDecoder<'a> {
+    packer: &'a mut Row,
+    buf: &'a mut Vec<u8>,
+    fields: &'a [FieldProjection],
+}
+
+impl<'a> AvroDecode for ProjectedRecordDecoder<'a> {
+    type Out = ();
+    fn record<R: AvroRead, A: AvroRecordAccess<R>>(
+        self,
+        a: &mut A,
+    ) -> Result<Self::Out, AvroError> {
+        let skip = |packer: &mut Row, field: &FieldProjection| {
+            for _ in 0..field.columns {
+                packer.push(Datum::Null);
+            }
+        };
+        // As in `AvroFlatDecoder::record`, fields that arrive out of order
+        // are stashed until the fields preceding them have been packed.
+        let mut expected = 0;
+        let mut stash = vec![];
+        while let Some((_name, idx, f)) = a.next_field()? {
+            let field = &self.fields[idx];
+            if !field.demanded {
+                f.decode_field(TrivialDecoder)?;
+                if idx == expected {
+                    expected += 1;
+                    skip(self.packer, field);
+                } else {
+                    stash.push((idx, None));
+                }
+            } else if idx == expected {
+                expected += 1;
+                f.decode_field(AvroFlatDecoder {
+                    packer: self.packer,
+                    buf: self.buf,
+                    is_top: false,
+                })?;
+            } else {
+                stash.push((idx, Some(f.decode_field(ValueDecoder)?)));
+            }
+        }
+        stash.sort_by_key(|(idx, _val)| *idx);
+        for (idx, val) in stash {
+            assert!(idx == expected);
+            expected += 1;
+            match val {
+                Some(val) => {
+                    let dec = AvroFlatDecoder {
+                        packer: self.packer,
+                        buf: self.buf,
+                        is_top: false,
+                    };
+                    give_value(dec, &val)?;
+                }
+                None => skip(self.packer, &self.fields[idx]),
+            }
+        }
+        Ok(())
+    }
+    define_unexpected! {
+        union_branch, array, map, enum_variant, scalar, decimal, bytes, string, json, uuid, fixed
+    }
+}
+
 pub struct AvroStringDecoder<'a> {
     pub buf: &'a mut Vec<u8>,
 }
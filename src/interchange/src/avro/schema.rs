@@ -40,6 +40,28 @@ pub fn schema_to_relationdesc(schema: Schema) -> Result<RelationDesc, anyhow::Er
     )?))
 }
 
+/// Returns the number of columns that each field of a record schema decodes
+/// to, or `None` if the schema is not a record.
+///
+/// This mirrors the flattening performed by [`schema_to_relationdesc`]: a
+/// union contributes one column per non-null variant, and any other field a
+/// single column.
+pub(super) fn field_column_counts(schema: &Schema) -> Option<Vec<usize>> {
+    let node = schema.top_node();
+    match node.inner {
+        SchemaPiece::Record { fields, .. } => Some(
+            fields
+                .iter()
+                .map(|f| match node.step(&f.schema).inner {
+                    SchemaPiece::Union(us) => us.variants().iter().filter(|v| !is_null(v)).count(),
+                    _ => 1,
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
 fn validate_schema_1(schema: SchemaNode) -> anyhow::Result<Vec<(ColumnName, ColumnType)>> {
     let mut columns = vec![];
     let mut seen_avro_nodes = Default::default();
@@ -250,6 +272,11 @@ impl ConfluentAvroResolver {
         })
     }
 
+    /// Returns the schema that records are decoded into.
+    pub(super) fn reader_schema(&self) -> &Schema {
+        &self.reader_schema
+    }
+
     pub async fn resolve<'a, 'b>(
         &'a mut self,
         mut bytes: &'b [u8],
@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use byteorder::{BigEndian, ByteOrder};
 
 /// Extracts the schema_id placed in front of the serialized message by the confluent stack
@@ -52,21 +52,57 @@ pub fn extract_avro_header(buf: &[u8]) -> Result<(i32, &[u8])> {
     extract_schema_id(buf, "avro")
 }
 
-pub fn extract_protobuf_header(buf: &[u8]) -> Result<(i32, &[u8])> {
-    let (schema_id, buf) = extract_schema_id(buf, "protobuf")?;
+/// Extracts the schema ID and the message indexes placed in front of a
+/// serialized Protobuf message by the Confluent stack.
+///
+/// The message indexes are the path through the (nested) message declarations
+/// of the schema that identifies the message that was serialized. They are
+/// encoded as a zigzag varint count followed by that many zigzag varints, with
+/// the common case of the first top-level message abbreviated to a count of
+/// zero.
+pub fn extract_protobuf_header(buf: &[u8]) -> Result<(i32, Vec<i32>, &[u8])> {
+    let (schema_id, mut buf) = extract_schema_id(buf, "protobuf")?;
 
-    match buf.get(0) {
-        Some(0) => Ok((schema_id, &buf[1..])),
-        Some(message_id) => bail!(
-            "unsupported Confluent-style protobuf message descriptor id: \
-                expected 0, but found: {}. \
-                See https://github.com/MaterializeInc/materialize/issues/9250",
-            message_id
-        ),
-        None => bail!(
-            "Confluent-style protobuf datum is too few bytes: expected a message id after magic \
-            and schema id, got a buffer of length {}",
-            buf.len()
-        ),
+    let count = decode_zigzag_varint(&mut buf)?;
+    let message_indexes = if count == 0 {
+        vec![0]
+    } else if count > 0 {
+        (0..count)
+            .map(|_| decode_zigzag_varint(&mut buf))
+            .collect::<Result<_>>()?
+    } else {
+        bail!(
+            "Confluent-style protobuf datum has invalid message index count {}",
+            count
+        )
+    };
+    Ok((schema_id, message_indexes, buf))
+}
+
+fn decode_zigzag_varint(buf: &mut &[u8]) -> Result<i32> {
+    let n = prost::encoding::decode_varint(buf)
+        .map_err(|e| anyhow!("decoding Confluent-style protobuf message indexes: {}", e))?;
+    let n = u32::try_from(n)
+        .map_err(|_| anyhow!("Confluent-style protobuf message index out of range: {}", n))?;
+    Ok(((n >> 1) as i32) ^ -((n & 1) as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protobuf_header() -> Result<()> {
+        // The abbreviated form for the first message.
+        let (schema_id, indexes, rest) = extract_protobuf_header(&[0, 0, 0, 0, 42, 0, 8])?;
+        assert_eq!((schema_id, indexes, rest), (42, vec![0], &[8][..]));
+
+        // Two indexes, [1, 2], zigzag encoded.
+        let (schema_id, indexes, rest) = extract_protobuf_header(&[0, 0, 0, 1, 0, 4, 2, 4, 8])?;
+        assert_eq!((schema_id, indexes, rest), (256, vec![1, 2], &[8][..]));
+
+        // Too few indexes.
+        assert!(extract_protobuf_header(&[0, 0, 0, 0, 42, 4, 2]).is_err());
+        Ok(())
     }
 }
@@ -0,0 +1,688 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Encoding and decoding of Apache Parquet objects.
+//!
+//! Parquet objects are decoded via their Arrow representation, so the mapping
+//! from Parquet logical types onto [`ScalarType`]s is the mapping from the
+//! Arrow types that `arrow2` chooses for each Parquet logical type. Encoding
+//! goes through Arrow as well, using the inverse of that mapping where one
+//! exists.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use arrow2::array::{
+    Array, BinaryArray, BooleanArray, FixedSizeBinaryArray, PrimitiveArray, Utf8Array,
+};
+use arrow2::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow2::io::parquet::read::{get_schema, read_metadata, RecordReader};
+use arrow2::io::parquet::write::RowGroupIterator;
+use arrow2::record_batch::RecordBatch;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use parquet2::compression::Compression;
+use parquet2::encoding::Encoding;
+use parquet2::write::{write_file, Version, WriteOptions};
+
+use mz_repr::adt::jsonb::JsonbRef;
+use mz_repr::adt::numeric::{Numeric, NUMERIC_DATUM_MAX_PRECISION};
+use mz_repr::{ColumnType, Datum, Diff, RelationDesc, Row, ScalarType, Timestamp};
+
+/// Infers the columns of a relation from the schema in the footer of the
+/// Parquet object `bytes`.
+pub fn parquet_columns(bytes: &[u8]) -> Result<Vec<(String, ColumnType)>, anyhow::Error> {
+    let metadata = read_metadata(&mut Cursor::new(bytes))?;
+    let schema = get_schema(&metadata)?;
+    schema
+        .fields()
+        .iter()
+        .map(|field| Ok((field.name().clone(), field_column_type(field)?)))
+        .collect()
+}
+
+fn field_column_type(field: &Field) -> Result<ColumnType, anyhow::Error> {
+    let scalar_type = match field.data_type() {
+        DataType::Boolean => ScalarType::Bool,
+        DataType::Int8 | DataType::Int16 | DataType::UInt8 => ScalarType::Int16,
+        DataType::Int32 | DataType::UInt16 => ScalarType::Int32,
+        DataType::Int64 | DataType::UInt32 => ScalarType::Int64,
+        DataType::UInt64 => ScalarType::Numeric { scale: Some(0) },
+        DataType::Float32 => ScalarType::Float32,
+        DataType::Float64 => ScalarType::Float64,
+        DataType::Decimal(precision, scale) => {
+            if *precision > usize::from(NUMERIC_DATUM_MAX_PRECISION) {
+                bail!(
+                    "decimal column {} has precision {}, which exceeds the maximum of {}",
+                    field.name(),
+                    precision,
+                    NUMERIC_DATUM_MAX_PRECISION
+                );
+            }
+            ScalarType::Numeric {
+                scale: Some(u8::try_from(*scale)?),
+            }
+        }
+        DataType::Utf8 | DataType::LargeUtf8 => ScalarType::String,
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+            ScalarType::Bytes
+        }
+        DataType::Date32 => ScalarType::Date,
+        DataType::Time32(_) | DataType::Time64(_) => ScalarType::Time,
+        DataType::Timestamp(_, None) => ScalarType::Timestamp,
+        DataType::Timestamp(_, Some(_)) => ScalarType::TimestampTz,
+        other => bail!(
+            "column {} has unsupported Parquet type {:?}",
+            field.name(),
+            other
+        ),
+    };
+    Ok(scalar_type.nullable(field.is_nullable()))
+}
+
+/// Decodes Parquet objects into rows with a fixed set of columns.
+#[derive(Debug)]
+pub struct Decoder {
+    columns: Vec<(String, ColumnType)>,
+}
+
+impl Decoder {
+    /// Constructs a decoder that produces rows with the specified columns.
+    pub fn new(columns: Vec<(String, ColumnType)>) -> Decoder {
+        Decoder { columns }
+    }
+
+    /// Prepares to decode the rows of the Parquet object `bytes`.
+    ///
+    /// Columns are matched by name, so objects may contain extra columns or
+    /// order their columns differently than the first object did. An object
+    /// that is missing a column, or whose column has a different type, is an
+    /// error.
+    pub fn decode(&self, bytes: Vec<u8>) -> Result<DecodedRows, anyhow::Error> {
+        let reader = RecordReader::try_new(Cursor::new(bytes), None, None, None, None)?;
+
+        let fields = reader.schema().fields().clone();
+        let mut projection = Vec::with_capacity(self.columns.len());
+        for (name, typ) in &self.columns {
+            let idx = fields
+                .iter()
+                .position(|f| f.name() == name)
+                .ok_or_else(|| anyhow!("Parquet object is missing column {}", name))?;
+            let actual = field_column_type(&fields[idx])?;
+            let compatible =
+                actual.scalar_type.base_eq(&typ.scalar_type) && (typ.nullable || !actual.nullable);
+            if !compatible {
+                bail!(
+                    "Parquet column {} has type {:?}, but the source expects {:?}",
+                    name,
+                    actual,
+                    typ
+                );
+            }
+            projection.push(idx);
+        }
+
+        Ok(DecodedRows {
+            reader,
+            projection,
+            batch: None,
+            next: 0,
+            row: Row::default(),
+        })
+    }
+}
+
+/// The rows of a Parquet object.
+///
+/// Rows are decoded one row group at a time, so at most one row group of
+/// the object is held in its decoded form.
+pub struct DecodedRows {
+    reader: RecordReader<Cursor<Vec<u8>>>,
+    projection: Vec<usize>,
+    /// The row group that rows are currently taken from.
+    batch: Option<RecordBatch>,
+    /// The index in `batch` of the next row.
+    next: usize,
+    row: Row,
+}
+
+impl fmt::Debug for DecodedRows {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DecodedRows")
+            .field("projection", &self.projection)
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Iterator for DecodedRows {
+    type Item = Result<Row, anyhow::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match &self.batch {
+                Some(batch) if self.next < batch.num_rows() => {
+                    let columns = batch.columns();
+                    for idx in &self.projection {
+                        match array_datum(columns[*idx].as_ref(), self.next) {
+                            Ok(datum) => self.row.push(datum),
+                            Err(e) => {
+                                self.row.clear();
+                                self.next += 1;
+                                return Some(Err(e));
+                            }
+                        }
+                    }
+                    self.next += 1;
+                    return Some(Ok(self.row.finish_and_reuse()));
+                }
+                _ => match self.reader.next()? {
+                    Ok(batch) => {
+                        self.batch = Some(batch);
+                        self.next = 0;
+                    }
+                    Err(e) => return Some(Err(e.into())),
+                },
+            }
+        }
+    }
+}
+
+fn downcast<T: 'static>(array: &dyn Array) -> &T {
+    array
+        .as_any()
+        .downcast_ref()
+        .expect("array type matches its data type")
+}
+
+/// Extracts the `i`th value of `array` as a [`Datum`].
+fn array_datum(array: &dyn Array, i: usize) -> Result<Datum, anyhow::Error> {
+    if array.is_null(i) {
+        return Ok(Datum::Null);
+    }
+    Ok(match array.data_type() {
+        DataType::Boolean => Datum::from(downcast::<BooleanArray>(array).value(i)),
+        DataType::Int8 => {
+            let v = downcast::<PrimitiveArray<i8>>(array).value(i);
+            Datum::from(i16::from(v))
+        }
+        DataType::Int16 => Datum::from(downcast::<PrimitiveArray<i16>>(array).value(i)),
+        DataType::UInt8 => {
+            let v = downcast::<PrimitiveArray<u8>>(array).value(i);
+            Datum::from(i16::from(v))
+        }
+        DataType::Int32 => Datum::from(downcast::<PrimitiveArray<i32>>(array).value(i)),
+        DataType::UInt16 => {
+            let v = downcast::<PrimitiveArray<u16>>(array).value(i);
+            Datum::from(i32::from(v))
+        }
+        DataType::Int64 => Datum::from(downcast::<PrimitiveArray<i64>>(array).value(i)),
+        DataType::UInt32 => {
+            let v = downcast::<PrimitiveArray<u32>>(array).value(i);
+            Datum::from(i64::from(v))
+        }
+        DataType::UInt64 => {
+            let v = downcast::<PrimitiveArray<u64>>(array).value(i);
+            Datum::from(Numeric::from(v))
+        }
+        DataType::Float32 => Datum::from(downcast::<PrimitiveArray<f32>>(array).value(i)),
+        DataType::Float64 => Datum::from(downcast::<PrimitiveArray<f64>>(array).value(i)),
+        DataType::Decimal(_, scale) => {
+            let v = downcast::<PrimitiveArray<i128>>(array).value(i);
+            // Decimals are bounded by `NUMERIC_DATUM_MAX_PRECISION` in
+            // `field_column_type`, so their unscaled values always fit.
+            let mut n = Numeric::try_from(v)?;
+            n.set_exponent(-i32::try_from(*scale)?);
+            Datum::from(n)
+        }
+        DataType::Utf8 => Datum::from(downcast::<Utf8Array<i32>>(array).value(i)),
+        DataType::LargeUtf8 => Datum::from(downcast::<Utf8Array<i64>>(array).value(i)),
+        DataType::Binary => Datum::from(downcast::<BinaryArray<i32>>(array).value(i)),
+        DataType::LargeBinary => Datum::from(downcast::<BinaryArray<i64>>(array).value(i)),
+        DataType::FixedSizeBinary(_) => {
+            Datum::from(downcast::<FixedSizeBinaryArray>(array).value(i))
+        }
+        DataType::Date32 => {
+            let days = downcast::<PrimitiveArray<i32>>(array).value(i);
+            Datum::from(NaiveDate::from_ymd(1970, 1, 1) + Duration::days(days.into()))
+        }
+        DataType::Time32(unit) => {
+            let v = downcast::<PrimitiveArray<i32>>(array).value(i);
+            Datum::from(time_from_unit(i64::from(v), unit)?)
+        }
+        DataType::Time64(unit) => {
+            let v = downcast::<PrimitiveArray<i64>>(array).value(i);
+            Datum::from(time_from_unit(v, unit)?)
+        }
+        DataType::Timestamp(unit, tz) => {
+            let v = downcast::<PrimitiveArray<i64>>(array).value(i);
+            let ts = timestamp_from_unit(v, unit)?;
+            match tz {
+                None => Datum::from(ts),
+                Some(_) => Datum::from(DateTime::<Utc>::from_utc(ts, Utc)),
+            }
+        }
+        other => bail!("unsupported Parquet type {:?}", other),
+    })
+}
+
+/// The name of the column that holds the timestamp of each encoded update.
+pub const TIMESTAMP_COLUMN: &str = "mz_timestamp";
+
+/// The name of the column that holds the diff of each encoded update.
+pub const DIFF_COLUMN: &str = "mz_diff";
+
+/// Returns the Arrow type that values of `typ` are encoded as, if they can be
+/// encoded at all.
+///
+/// Numeric values are encoded as their text representation, as Parquet
+/// decimals cannot represent every numeric value. JSON and UUID values are
+/// encoded as text, too.
+fn scalar_data_type(typ: &ScalarType) -> Option<DataType> {
+    Some(match typ {
+        ScalarType::Bool => DataType::Boolean,
+        ScalarType::Int16 => DataType::Int16,
+        ScalarType::Int32 => DataType::Int32,
+        ScalarType::Int64 => DataType::Int64,
+        ScalarType::Float32 => DataType::Float32,
+        ScalarType::Float64 => DataType::Float64,
+        ScalarType::Date => DataType::Date32,
+        ScalarType::Time => DataType::Time64(TimeUnit::Microsecond),
+        ScalarType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ScalarType::TimestampTz => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        ScalarType::Bytes => DataType::Binary,
+        ScalarType::String
+        | ScalarType::Char { .. }
+        | ScalarType::VarChar { .. }
+        | ScalarType::Numeric { .. }
+        | ScalarType::Jsonb
+        | ScalarType::Uuid => DataType::Utf8,
+        _ => return None,
+    })
+}
+
+/// Reports whether values of `typ` can be encoded into Parquet objects.
+pub fn is_encodable(typ: &ScalarType) -> bool {
+    scalar_data_type(typ).is_some()
+}
+
+/// Encodes updates into Parquet objects.
+///
+/// The columns of the objects are the columns of the relation, followed by
+/// the [`TIMESTAMP_COLUMN`] and [`DIFF_COLUMN`] of each update.
+#[derive(Debug)]
+pub struct Encoder {
+    types: Vec<ScalarType>,
+    schema: Arc<Schema>,
+}
+
+impl Encoder {
+    /// Constructs an encoder for updates to rows described by `desc`.
+    pub fn new(desc: &RelationDesc) -> Result<Encoder, anyhow::Error> {
+        Encoder::with_fields(
+            desc,
+            vec![
+                Field::new(TIMESTAMP_COLUMN, DataType::Int64, false),
+                Field::new(DIFF_COLUMN, DataType::Int64, false),
+            ],
+        )
+    }
+
+    /// Constructs an encoder for objects whose columns are the columns
+    /// described by `desc`, followed by `extra_fields`.
+    fn with_fields(
+        desc: &RelationDesc,
+        extra_fields: Vec<Field>,
+    ) -> Result<Encoder, anyhow::Error> {
+        let mut types = Vec::with_capacity(desc.arity());
+        let mut fields = Vec::with_capacity(desc.arity() + extra_fields.len());
+        for (name, typ) in desc.iter() {
+            let data_type = scalar_data_type(&typ.scalar_type).ok_or_else(|| {
+                anyhow!(
+                    "column {} has type {:?}, which cannot be encoded as Parquet",
+                    name,
+                    typ.scalar_type
+                )
+            })?;
+            types.push(typ.scalar_type.clone());
+            fields.push(Field::new(name.as_str(), data_type, typ.nullable));
+        }
+        fields.extend(extra_fields);
+        Ok(Encoder {
+            types,
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+
+    /// Encodes `updates` into a single Parquet object.
+    pub fn encode(&self, updates: &[(Row, Timestamp, Diff)]) -> Result<Vec<u8>, anyhow::Error> {
+        let mut columns = self.row_columns(updates.iter().map(|(row, _, _)| row))?;
+        let timestamps = updates
+            .iter()
+            .map(|(_, ts, _)| i64::try_from(*ts))
+            .collect::<Result<Vec<_>, _>>()?;
+        columns.push(Arc::new(PrimitiveArray::<i64>::from_slice(timestamps)));
+        let diffs = updates.iter().map(|(_, _, diff)| *diff).collect::<Vec<_>>();
+        columns.push(Arc::new(PrimitiveArray::<i64>::from_slice(diffs)));
+        self.write(columns)
+    }
+
+    /// Builds the Arrow arrays of the columns of `rows`.
+    fn row_columns<'a>(
+        &self,
+        rows: impl Iterator<Item = &'a Row>,
+    ) -> Result<Vec<Arc<dyn Array>>, anyhow::Error> {
+        let rows = rows.map(|row| row.unpack()).collect::<Vec<_>>();
+        let mut columns = Vec::with_capacity(self.schema.fields().len());
+        for (i, typ) in self.types.iter().enumerate() {
+            let datums = rows.iter().map(|datums| datums[i]).collect::<Vec<_>>();
+            columns.push(datum_array(typ, &datums)?);
+        }
+        Ok(columns)
+    }
+
+    /// Writes `columns` into a Parquet object with a single row group.
+    fn write(&self, columns: Vec<Arc<dyn Array>>) -> Result<Vec<u8>, anyhow::Error> {
+        let batch = RecordBatch::try_new(Arc::clone(&self.schema), columns)?;
+        let options = WriteOptions {
+            write_statistics: true,
+            compression: Compression::Snappy,
+            version: Version::V2,
+        };
+        let row_groups = RowGroupIterator::try_new(
+            std::iter::once(Ok(batch)),
+            &self.schema,
+            options,
+            vec![Encoding::Plain; self.schema.fields().len()],
+        )?;
+        let parquet_schema = row_groups.parquet_schema().clone();
+        let mut buf = vec![];
+        write_file(&mut buf, row_groups, parquet_schema, options, None, None)?;
+        Ok(buf)
+    }
+}
+
+/// Encodes `rows`, which are described by `desc`, into a single Parquet
+/// object.
+///
+/// Unlike the objects produced by an [`Encoder`], the object only contains
+/// the columns of the rows.
+pub fn encode_rows(desc: &RelationDesc, rows: &[Row]) -> Result<Vec<u8>, anyhow::Error> {
+    let encoder = Encoder::with_fields(desc, vec![])?;
+    encoder.write(encoder.row_columns(rows.iter())?)
+}
+
+/// Collects the non-null values of `datums` as produced by `f`.
+fn values<'a, T>(datums: &[Datum<'a>], f: impl Fn(Datum<'a>) -> T) -> Vec<Option<T>> {
+    datums
+        .iter()
+        .map(|datum| (!datum.is_null()).then(|| f(*datum)))
+        .collect()
+}
+
+/// Builds an Arrow array of `datums`, which are all of type `typ`.
+fn datum_array(typ: &ScalarType, datums: &[Datum]) -> Result<Arc<dyn Array>, anyhow::Error> {
+    let epoch = NaiveDate::from_ymd(1970, 1, 1);
+    Ok(match typ {
+        ScalarType::Bool => Arc::new(BooleanArray::from(values(datums, |d| d.unwrap_bool()))),
+        ScalarType::Int16 => Arc::new(PrimitiveArray::<i16>::from(values(datums, |d| {
+            d.unwrap_int16()
+        }))),
+        ScalarType::Int32 => Arc::new(PrimitiveArray::<i32>::from(values(datums, |d| {
+            d.unwrap_int32()
+        }))),
+        ScalarType::Int64 => Arc::new(PrimitiveArray::<i64>::from(values(datums, |d| {
+            d.unwrap_int64()
+        }))),
+        ScalarType::Float32 => Arc::new(PrimitiveArray::<f32>::from(values(datums, |d| {
+            d.unwrap_float32()
+        }))),
+        ScalarType::Float64 => Arc::new(PrimitiveArray::<f64>::from(values(datums, |d| {
+            d.unwrap_float64()
+        }))),
+        ScalarType::Date => {
+            let days = values(datums, |d| {
+                i32::try_from((d.unwrap_date() - epoch).num_days())
+                    .expect("dates fit into 32-bit day counts")
+            });
+            Arc::new(PrimitiveArray::<i32>::from(days).to(DataType::Date32))
+        }
+        ScalarType::Time => {
+            let micros = values(datums, |d| {
+                let t = d.unwrap_time();
+                i64::from(t.num_seconds_from_midnight()) * 1_000_000
+                    + i64::from(t.nanosecond() / 1_000)
+            });
+            Arc::new(
+                PrimitiveArray::<i64>::from(micros).to(DataType::Time64(TimeUnit::Microsecond)),
+            )
+        }
+        ScalarType::Timestamp | ScalarType::TimestampTz => {
+            let micros = values(datums, |d| {
+                let ts = match typ {
+                    ScalarType::Timestamp => d.unwrap_timestamp(),
+                    _ => d.unwrap_timestamptz().naive_utc(),
+                };
+                ts.timestamp() * 1_000_000 + i64::from(ts.timestamp_subsec_micros())
+            });
+            let data_type = scalar_data_type(typ).expect("timestamps are encodable");
+            Arc::new(PrimitiveArray::<i64>::from(micros).to(data_type))
+        }
+        ScalarType::Bytes => Arc::new(BinaryArray::<i32>::from(values(datums, |d| {
+            d.unwrap_bytes()
+        }))),
+        ScalarType::String | ScalarType::Char { .. } | ScalarType::VarChar { .. } => {
+            Arc::new(Utf8Array::<i32>::from(values(datums, |d| d.unwrap_str())))
+        }
+        ScalarType::Numeric { .. } => Arc::new(Utf8Array::<i32>::from(values(datums, |d| {
+            d.unwrap_numeric().0.to_standard_notation_string()
+        }))),
+        ScalarType::Jsonb => Arc::new(Utf8Array::<i32>::from(values(datums, |d| {
+            JsonbRef::from_datum(d).to_string()
+        }))),
+        ScalarType::Uuid => Arc::new(Utf8Array::<i32>::from(values(datums, |d| {
+            d.unwrap_uuid().to_string()
+        }))),
+        other => bail!("cannot encode values of type {:?} as Parquet", other),
+    })
+}
+
+/// Splits a count of `unit`s into whole seconds and remaining nanoseconds.
+fn split_unit(v: i64, unit: &TimeUnit) -> (i64, u32) {
+    let per_second = match unit {
+        TimeUnit::Second => 1,
+        TimeUnit::Millisecond => 1_000,
+        TimeUnit::Microsecond => 1_000_000,
+        TimeUnit::Nanosecond => 1_000_000_000,
+    };
+    let secs = v.div_euclid(per_second);
+    let nanos = v.rem_euclid(per_second) * (1_000_000_000 / per_second);
+    (secs, u32::try_from(nanos).expect("nanoseconds fit in u32"))
+}
+
+fn time_from_unit(v: i64, unit: &TimeUnit) -> Result<NaiveTime, anyhow::Error> {
+    let (secs, nanos) = split_unit(v, unit);
+    u32::try_from(secs)
+        .ok()
+        .and_then(|secs| NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos))
+        .ok_or_else(|| anyhow!("time out of range: {}", v))
+}
+
+fn timestamp_from_unit(v: i64, unit: &TimeUnit) -> Result<NaiveDateTime, anyhow::Error> {
+    let (secs, nanos) = split_unit(v, unit);
+    NaiveDateTime::from_timestamp_opt(secs, nanos)
+        .ok_or_else(|| anyhow!("timestamp out of range: {}", v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_unit() {
+        assert_eq!(split_unit(1_500, &TimeUnit::Millisecond), (1, 500_000_000));
+        assert_eq!(
+            split_unit(-1_500, &TimeUnit::Millisecond),
+            (-2, 500_000_000)
+        );
+        assert_eq!(split_unit(7, &TimeUnit::Second), (7, 0));
+        assert_eq!(split_unit(1_000_001, &TimeUnit::Microsecond), (1, 1_000));
+    }
+
+    #[test]
+    fn test_time_from_unit() {
+        assert_eq!(
+            time_from_unit(3_600_000_000, &TimeUnit::Microsecond).unwrap(),
+            NaiveTime::from_hms(1, 0, 0)
+        );
+        assert!(time_from_unit(-1, &TimeUnit::Second).is_err());
+    }
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let desc = RelationDesc::empty()
+            .with_column("a", ScalarType::Int32.nullable(false))
+            .with_column("b", ScalarType::String.nullable(true))
+            .with_column("c", ScalarType::Date.nullable(true));
+        let date = NaiveDate::from_ymd(2021, 3, 14);
+        let updates = vec![
+            (
+                Row::pack_slice(&[Datum::Int32(1), Datum::String("x"), Datum::Date(date)]),
+                5,
+                1,
+            ),
+            (
+                Row::pack_slice(&[Datum::Int32(2), Datum::Null, Datum::Null]),
+                6,
+                -1,
+            ),
+        ];
+        let bytes = Encoder::new(&desc).unwrap().encode(&updates).unwrap();
+
+        let columns = parquet_columns(&bytes).unwrap();
+        let names = columns
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "b", "c", TIMESTAMP_COLUMN, DIFF_COLUMN]);
+
+        let rows = Decoder::new(columns)
+            .decode(bytes)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                Row::pack_slice(&[
+                    Datum::Int32(1),
+                    Datum::String("x"),
+                    Datum::Date(date),
+                    Datum::Int64(5),
+                    Datum::Int64(1),
+                ]),
+                Row::pack_slice(&[
+                    Datum::Int32(2),
+                    Datum::Null,
+                    Datum::Null,
+                    Datum::Int64(6),
+                    Datum::Int64(-1),
+                ]),
+            ]
+        );
+    }
+
+    fn decode_all(
+        columns: Vec<(String, ColumnType)>,
+        bytes: Vec<u8>,
+    ) -> Result<Vec<Row>, anyhow::Error> {
+        Decoder::new(columns).decode(bytes)?.collect()
+    }
+
+    #[test]
+    fn test_decode_columns_by_name() {
+        let desc = RelationDesc::empty()
+            .with_column("a", ScalarType::Int32.nullable(false))
+            .with_column("b", ScalarType::String.nullable(true));
+        let updates = vec![(
+            Row::pack_slice(&[Datum::Int32(1), Datum::String("x")]),
+            5,
+            1,
+        )];
+        let bytes = Encoder::new(&desc).unwrap().encode(&updates).unwrap();
+
+        // Columns can be reordered and extra columns are ignored.
+        let columns = vec![
+            ("b".into(), ScalarType::String.nullable(true)),
+            ("a".into(), ScalarType::Int32.nullable(true)),
+        ];
+        assert_eq!(
+            decode_all(columns, bytes.clone()).unwrap(),
+            vec![Row::pack_slice(&[Datum::String("x"), Datum::Int32(1)])]
+        );
+
+        let missing = vec![("z".into(), ScalarType::Int32.nullable(true))];
+        let err = decode_all(missing, bytes.clone()).unwrap_err();
+        assert_eq!(err.to_string(), "Parquet object is missing column z");
+
+        let mistyped = vec![("a".into(), ScalarType::String.nullable(false))];
+        assert!(decode_all(mistyped, bytes.clone()).is_err());
+
+        // A nullable column cannot fill a non-nullable one.
+        let nullable = vec![("b".into(), ScalarType::String.nullable(false))];
+        assert!(decode_all(nullable, bytes).is_err());
+    }
+
+    #[test]
+    fn test_decode_row_groups() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let batches = [vec![1, 2], vec![3]]
+            .into_iter()
+            .map(|values| {
+                let column: Arc<dyn Array> = Arc::new(PrimitiveArray::<i64>::from_slice(values));
+                RecordBatch::try_new(Arc::clone(&schema), vec![column])
+            })
+            .collect::<Vec<_>>();
+        let options = WriteOptions {
+            write_statistics: false,
+            compression: Compression::Uncompressed,
+            version: Version::V2,
+        };
+        let row_groups =
+            RowGroupIterator::try_new(batches.into_iter(), &schema, options, vec![Encoding::Plain])
+                .unwrap();
+        let parquet_schema = row_groups.parquet_schema().clone();
+        let mut bytes = vec![];
+        write_file(&mut bytes, row_groups, parquet_schema, options, None, None).unwrap();
+
+        let columns = vec![("a".into(), ScalarType::Int64.nullable(false))];
+        assert_eq!(
+            decode_all(columns, bytes).unwrap(),
+            vec![
+                Row::pack_slice(&[Datum::Int64(1)]),
+                Row::pack_slice(&[Datum::Int64(2)]),
+                Row::pack_slice(&[Datum::Int64(3)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_object() {
+        let columns = vec![("a".into(), ScalarType::Int64.nullable(false))];
+        assert!(decode_all(columns, b"not parquet".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_encode_unsupported_type() {
+        let desc = RelationDesc::empty().with_column("i", ScalarType::Interval.nullable(true));
+        assert!(Encoder::new(&desc).is_err());
+    }
+}
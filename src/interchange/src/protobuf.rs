@@ -11,20 +11,29 @@ use std::collections::HashSet;
 
 use anyhow::{anyhow, bail, Context};
 
+use prost::Message;
 use prost_reflect::{
     Cardinality, DynamicMessage, FieldDescriptor, FileDescriptor, Kind, MessageDescriptor,
     ReflectMessage, Value,
 };
+use prost_types::{FileDescriptorProto, FileDescriptorSet};
 
 use mz_ore::str::StrExt;
 use mz_repr::{ColumnName, ColumnType, Datum, Row, ScalarType};
 
+mod encode;
+
+pub use self::encode::{ProtobufEncoder, ProtobufSchemaGenerator};
+
 /// A decoded description of the schema of a Protobuf message.
 #[derive(Debug, PartialEq)]
 pub struct DecodedDescriptors {
     message_descriptor: MessageDescriptor,
     columns: Vec<(ColumnName, ColumnType)>,
     message_name: String,
+    /// The path of indexes that identifies the message within the file that
+    /// declares it, as used by the Confluent wire format.
+    message_indexes: Option<Vec<i32>>,
 }
 
 impl DecodedDescriptors {
@@ -38,6 +47,11 @@ impl DecodedDescriptors {
                 message_name.quoted(),
             )
         })?;
+        let message_indexes = FileDescriptorSet::decode(bytes)
+            .context("decoding file descriptor set")?
+            .file
+            .iter()
+            .find_map(|file| message_indexes(file, &message_name));
         let mut seen_messages = HashSet::new();
         seen_messages.insert(message_descriptor.name().to_owned());
         let mut columns = vec![];
@@ -50,6 +64,7 @@ impl DecodedDescriptors {
             message_descriptor,
             columns,
             message_name,
+            message_indexes,
         })
     }
 
@@ -69,6 +84,8 @@ pub struct Decoder {
     descriptors: DecodedDescriptors,
     packer: Row,
     confluent_wire_format: bool,
+    /// Whether each top-level field is demanded, if not all of them are.
+    demanded: Option<Vec<bool>>,
 }
 
 impl Decoder {
@@ -81,9 +98,20 @@ impl Decoder {
             descriptors,
             packer: Default::default(),
             confluent_wire_format,
+            demanded: None,
         })
     }
 
+    /// Restricts decoding to the columns for which `demanded` is true.
+    ///
+    /// The values of undemanded fields are not packed, and their columns are
+    /// set to null instead.
+    pub fn demand_columns(&mut self, demanded: &[bool]) {
+        if demanded.iter().any(|d| !*d) {
+            self.demanded = Some(demanded.to_vec());
+        }
+    }
+
     /// Decodes the encoded Protobuf message into a [`Row`].
     pub async fn decode(&mut self, mut bytes: &[u8]) -> Result<Option<Row>, anyhow::Error> {
         if self.confluent_wire_format {
@@ -103,15 +131,107 @@ impl Decoder {
             // allocations).
             //
             // [0]: https://developers.google.com/protocol-buffers/docs/overview
-            let (_schema_id, adjusted_bytes) = crate::confluent::extract_protobuf_header(bytes)?;
+            let (_schema_id, message_indexes, adjusted_bytes) =
+                crate::confluent::extract_protobuf_header(bytes)?;
+            // The message indexes, on the other hand, must identify the
+            // message we expect. Decoding a different message would silently
+            // produce garbage.
+            if let Some(expected) = &self.descriptors.message_indexes {
+                if *expected != message_indexes {
+                    bail!(
+                        "Confluent-style protobuf datum has message indexes {:?}, \
+                         but message {} has message indexes {:?}",
+                        message_indexes,
+                        self.descriptors.message_name.quoted(),
+                        expected
+                    );
+                }
+            }
             bytes = adjusted_bytes;
         }
         let message = DynamicMessage::decode(self.descriptors.message_descriptor.clone(), bytes)?;
-        pack_message(&mut self.packer, &message)?;
+        pack_message(&mut self.packer, &message, self.demanded.as_deref())?;
         Ok(Some(self.packer.finish_and_reuse()))
     }
 }
 
+/// Determines the fully qualified name of the message to decode from a
+/// schema.
+///
+/// `bytes` is an encoded `FileDescriptorSet` that contains the schema's file,
+/// named `file_name`, and its dependencies. If `message_name` is `None`, the
+/// file must declare exactly one top-level message. Otherwise `message_name`
+/// may name any message declared in the file, including nested messages,
+/// either by its fully qualified name or relative to the file's package.
+pub fn resolve_message_name(
+    bytes: &[u8],
+    file_name: &str,
+    message_name: Option<&str>,
+) -> Result<String, anyhow::Error> {
+    let fds = FileDescriptorSet::decode(bytes).context("decoding file descriptor set")?;
+    let file = fds
+        .file
+        .iter()
+        .find(|file| file.name() == file_name)
+        .ok_or_else(|| anyhow!("file {} not found in schema", file_name.quoted()))?;
+    let qualify = |name: &str| match file.package() {
+        "" => format!(".{}", name),
+        package => format!(".{}.{}", package, name),
+    };
+    match message_name {
+        None => match file.message_type.as_slice() {
+            [message] => Ok(qualify(message.name())),
+            [] => bail!(
+                "Protobuf schema {} contains no messages",
+                file_name.quoted()
+            ),
+            _ => bail!(
+                "Protobuf schema {} contains multiple messages; \
+                 use MESSAGE to specify which message to decode",
+                file_name.quoted()
+            ),
+        },
+        Some(name) => {
+            let candidates = match name.strip_prefix('.') {
+                Some(_) => vec![name.to_owned()],
+                None => vec![qualify(name), format!(".{}", name)],
+            };
+            candidates
+                .into_iter()
+                .find(|candidate| message_indexes(file, candidate).is_some())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "protobuf message {} not found in schema {}",
+                        name.quoted(),
+                        file_name.quoted()
+                    )
+                })
+        }
+    }
+}
+
+/// Computes the path of indexes through the (nested) message declarations of
+/// `file` that leads to the message named `message_name`, if `file` declares
+/// that message.
+fn message_indexes(file: &FileDescriptorProto, message_name: &str) -> Option<Vec<i32>> {
+    let name = message_name.strip_prefix('.').unwrap_or(message_name);
+    let relative = match file.package() {
+        "" => name,
+        package => name.strip_prefix(package)?.strip_prefix('.')?,
+    };
+    let mut indexes = vec![];
+    let mut messages = &file.message_type;
+    for part in relative.split('.') {
+        let (index, message) = messages
+            .iter()
+            .enumerate()
+            .find(|(_, message)| message.name() == part)?;
+        indexes.push(i32::try_from(index).ok()?);
+        messages = &message.nested_type;
+    }
+    Some(indexes)
+}
+
 fn derive_column_type(
     seen_messages: &mut HashSet<String>,
     field: &FieldDescriptor,
@@ -172,19 +292,28 @@ fn derive_inner_type(
     }
 }
 
-fn pack_message(packer: &mut Row, message: &DynamicMessage) -> Result<(), anyhow::Error> {
-    for field_desc in message.descriptor().fields() {
-        if !message.has_field(&field_desc) {
-            if field_desc.cardinality() == Cardinality::Required {
-                bail!(
-                    "protobuf message missing required field {}",
-                    field_desc.name()
-                );
-            }
-            if field_desc.kind().as_message().is_some() && !field_desc.is_list() {
-                packer.push(Datum::Null);
-                continue;
-            }
+fn pack_message(
+    packer: &mut Row,
+    message: &DynamicMessage,
+    demanded: Option<&[bool]>,
+) -> Result<(), anyhow::Error> {
+    for (i, field_desc) in message.descriptor().fields().enumerate() {
+        if !message.has_field(&field_desc) && field_desc.cardinality() == Cardinality::Required {
+            bail!(
+                "protobuf message missing required field {}",
+                field_desc.name()
+            );
+        }
+        if !demanded.map_or(true, |demanded| demanded.get(i).copied().unwrap_or(true)) {
+            packer.push(Datum::Null);
+            continue;
+        }
+        if !message.has_field(&field_desc)
+            && field_desc.kind().as_message().is_some()
+            && !field_desc.is_list()
+        {
+            packer.push(Datum::Null);
+            continue;
         }
         let value = message.get_field(&field_desc);
         pack_value(packer, &field_desc, &*value)?;
@@ -223,7 +352,7 @@ fn pack_value(
             })?;
             packer.push(Datum::String(value.name()));
         }
-        Value::Message(m) => packer.push_list_with(|packer| pack_message(packer, m))?,
+        Value::Message(m) => packer.push_list_with(|packer| pack_message(packer, m, None))?,
         Value::List(values) => {
             packer.push_list_with(|packer| {
                 for value in values {
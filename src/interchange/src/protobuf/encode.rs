@@ -0,0 +1,612 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+
+use anyhow::bail;
+use byteorder::{NetworkEndian, WriteBytesExt};
+use prost::encoding::{encode_key, encode_varint, WireType};
+
+use mz_ore::collections::CollectionExt;
+use mz_repr::adt::jsonb::JsonbRef;
+use mz_repr::{strconv, ColumnName, ColumnType, Datum, RelationDesc, Row, ScalarType};
+
+use crate::encode::{column_names_and_types, Encode};
+
+/// Generates Protobuf schemas that describe the keys and values of a sink.
+///
+/// Each schema is a `proto2` file containing a single top-level message,
+/// `Key` or `Value`, with one optional field per column, numbered in column
+/// order. Records become nested messages and lists become repeated fields.
+/// Types without a Protobuf equivalent, like numerics and timestamps, are
+/// encoded as strings in their SQL text format.
+#[derive(Debug)]
+pub struct ProtobufSchemaGenerator {
+    key: Option<MessageSchema>,
+    value: MessageSchema,
+}
+
+impl ProtobufSchemaGenerator {
+    /// Generates schemas for the given key and value descriptions.
+    ///
+    /// If `include_transaction` is set, the value message gains a
+    /// `transaction` field that carries the ID of the transaction that
+    /// produced the update.
+    pub fn new(
+        key_desc: Option<RelationDesc>,
+        value_desc: RelationDesc,
+        include_transaction: bool,
+    ) -> Result<Self, anyhow::Error> {
+        let key = match key_desc {
+            Some(desc) => Some(MessageSchema::new(
+                "Key",
+                &column_names_and_types(desc),
+                false,
+            )?),
+            None => None,
+        };
+        let value = MessageSchema::new(
+            "Value",
+            &column_names_and_types(value_desc),
+            include_transaction,
+        )?;
+        Ok(ProtobufSchemaGenerator { key, value })
+    }
+
+    /// Renders the schema of the key message as a `.proto` file, if the sink
+    /// has a key.
+    pub fn key_schema(&self) -> Option<String> {
+        self.key.as_ref().map(render_file)
+    }
+
+    /// Renders the schema of the value message as a `.proto` file.
+    pub fn value_schema(&self) -> String {
+        render_file(&self.value)
+    }
+}
+
+/// Encodes rows as Protobuf messages in the Confluent wire format.
+#[derive(Debug)]
+pub struct ProtobufEncoder {
+    schema_generator: ProtobufSchemaGenerator,
+    key_schema_id: Option<i32>,
+    value_schema_id: i32,
+}
+
+impl ProtobufEncoder {
+    pub fn new(
+        schema_generator: ProtobufSchemaGenerator,
+        key_schema_id: Option<i32>,
+        value_schema_id: i32,
+    ) -> Self {
+        ProtobufEncoder {
+            schema_generator,
+            key_schema_id,
+            value_schema_id,
+        }
+    }
+}
+
+impl Encode for ProtobufEncoder {
+    fn get_format_name(&self) -> &str {
+        "protobuf"
+    }
+
+    fn encode_key_unchecked(&self, row: Row) -> Vec<u8> {
+        let schema = self
+            .schema_generator
+            .key
+            .as_ref()
+            .expect("key schema must exist");
+        encode_message_unchecked(self.key_schema_id.unwrap(), schema, row)
+    }
+
+    fn encode_value_unchecked(&self, row: Row) -> Vec<u8> {
+        encode_message_unchecked(self.value_schema_id, &self.schema_generator.value, row)
+    }
+}
+
+fn encode_message_unchecked(schema_id: i32, schema: &MessageSchema, row: Row) -> Vec<u8> {
+    let mut buf = vec![];
+    // The Confluent wire format: a magic byte, the big endian schema ID, and
+    // the message indexes of the encoded message. Our schemas only contain
+    // one top-level message, so the indexes are always the abbreviated `[0]`.
+    //
+    // https://docs.confluent.io/platform/current/schema-registry/serdes-develop/index.html#wire-format
+    buf.write_u8(0).expect("writing to vec cannot fail");
+    buf.write_i32::<NetworkEndian>(schema_id)
+        .expect("writing to vec cannot fail");
+    buf.write_u8(0).expect("writing to vec cannot fail");
+    encode_fields(&mut buf, schema, row.iter());
+    buf
+}
+
+/// The schema of a generated Protobuf message.
+#[derive(Debug, Clone, PartialEq)]
+struct MessageSchema {
+    name: String,
+    /// The name that the message was generated with, before it was made
+    /// unique within its enclosing message.
+    full_name: String,
+    fields: Vec<FieldSchema>,
+    nested: Vec<MessageSchema>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FieldSchema {
+    name: String,
+    repeated: bool,
+    kind: FieldKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FieldKind {
+    Bool,
+    Int32,
+    Int64,
+    Float,
+    Double,
+    String,
+    Bytes,
+    /// A SQL type that is encoded as a string in its text format.
+    Text(ScalarType),
+    /// A message declared in the enclosing message's `nested` list.
+    Message(String),
+    /// The ID of the transaction that produced the update.
+    Transaction,
+}
+
+impl MessageSchema {
+    fn new(
+        name: &str,
+        columns: &[(ColumnName, ColumnType)],
+        include_transaction: bool,
+    ) -> Result<Self, anyhow::Error> {
+        let mut message = MessageSchema {
+            name: name.into(),
+            full_name: name.into(),
+            fields: vec![],
+            nested: vec![],
+        };
+        let mut names = HashSet::new();
+        if include_transaction {
+            names.insert("Transaction".to_string());
+        }
+        for (column_name, column_type) in columns {
+            let name = unique_identifier(&mut names, column_name.as_str());
+            let (repeated, scalar_type) = match &column_type.scalar_type {
+                ScalarType::List { element_type, .. } => (true, &**element_type),
+                scalar_type => (false, scalar_type),
+            };
+            let kind = message.field_kind(&mut names, &name, scalar_type)?;
+            message.fields.push(FieldSchema {
+                name,
+                repeated,
+                kind,
+            });
+        }
+        if include_transaction {
+            let name = unique_identifier(&mut names, "transaction");
+            message.fields.push(FieldSchema {
+                name,
+                repeated: false,
+                kind: FieldKind::Transaction,
+            });
+        }
+        Ok(message)
+    }
+
+    fn field_kind(
+        &mut self,
+        names: &mut HashSet<String>,
+        field_name: &str,
+        scalar_type: &ScalarType,
+    ) -> Result<FieldKind, anyhow::Error> {
+        Ok(match scalar_type {
+            ScalarType::Bool => FieldKind::Bool,
+            ScalarType::Int16
+            | ScalarType::Int32
+            | ScalarType::Oid
+            | ScalarType::RegClass
+            | ScalarType::RegProc
+            | ScalarType::RegType => FieldKind::Int32,
+            ScalarType::Int64 => FieldKind::Int64,
+            ScalarType::Float32 => FieldKind::Float,
+            ScalarType::Float64 => FieldKind::Double,
+            ScalarType::String | ScalarType::Char { .. } | ScalarType::VarChar { .. } => {
+                FieldKind::String
+            }
+            ScalarType::Bytes => FieldKind::Bytes,
+            ScalarType::Numeric { .. }
+            | ScalarType::Date
+            | ScalarType::Time
+            | ScalarType::Timestamp
+            | ScalarType::TimestampTz
+            | ScalarType::Interval
+            | ScalarType::Uuid
+            | ScalarType::Jsonb => FieldKind::Text(scalar_type.clone()),
+            ScalarType::Record {
+                fields,
+                custom_name,
+                ..
+            } => {
+                let stem = custom_name.as_deref().unwrap_or(field_name);
+                let mut nested = MessageSchema::new(&pascal_case(stem), fields, false)?;
+                // Reuse an identical message, e.g. for the `before` and `after`
+                // fields of the Debezium envelope.
+                let existing = self
+                    .nested
+                    .iter()
+                    .find(|m| m.full_name == nested.full_name && m.same_shape(&nested));
+                match existing {
+                    Some(existing) => FieldKind::Message(existing.name.clone()),
+                    None => {
+                        nested.name = unique_identifier(names, &nested.name);
+                        let name = nested.name.clone();
+                        self.nested.push(nested);
+                        FieldKind::Message(name)
+                    }
+                }
+            }
+            ScalarType::List { .. } => bail!("Protobuf sinks do not support nested lists"),
+            ScalarType::Array(_) => bail!("Protobuf sinks do not support array columns"),
+            ScalarType::Map { .. } => bail!("Protobuf sinks do not support map columns"),
+        })
+    }
+
+    fn same_shape(&self, other: &MessageSchema) -> bool {
+        self.fields == other.fields && self.nested == other.nested
+    }
+}
+
+/// Converts `name` into a valid Protobuf identifier that is not yet in
+/// `names`, and records it there.
+fn unique_identifier(names: &mut HashSet<String>, name: &str) -> String {
+    let mut stem: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !stem.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        stem.insert(0, '_');
+    }
+    let mut name = stem.clone();
+    let mut i = 1;
+    while names.contains(&name) {
+        name = format!("{}{}", stem, i);
+        i += 1;
+    }
+    names.insert(name.clone());
+    name
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().expect("part is not empty");
+            first.to_ascii_uppercase().to_string() + chars.as_str()
+        })
+        .collect()
+}
+
+fn render_file(message: &MessageSchema) -> String {
+    let mut buf = String::from("syntax = \"proto2\";\n\n");
+    render_message(&mut buf, message, 0).expect("writing to string cannot fail");
+    buf
+}
+
+fn render_message(buf: &mut String, message: &MessageSchema, depth: usize) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    writeln!(buf, "{}message {} {{", indent, message.name)?;
+    for nested in &message.nested {
+        render_message(buf, nested, depth + 1)?;
+    }
+    if message
+        .fields
+        .iter()
+        .any(|f| f.kind == FieldKind::Transaction)
+    {
+        writeln!(buf, "{}  message Transaction {{", indent)?;
+        writeln!(buf, "{}    optional string id = 1;", indent)?;
+        writeln!(buf, "{}  }}", indent)?;
+    }
+    for (i, field) in message.fields.iter().enumerate() {
+        let typ = match &field.kind {
+            FieldKind::Bool => "bool",
+            FieldKind::Int32 => "int32",
+            FieldKind::Int64 => "int64",
+            FieldKind::Float => "float",
+            FieldKind::Double => "double",
+            FieldKind::String | FieldKind::Text(_) => "string",
+            FieldKind::Bytes => "bytes",
+            FieldKind::Message(name) => name,
+            FieldKind::Transaction => "Transaction",
+        };
+        let label = if field.repeated {
+            "repeated"
+        } else {
+            "optional"
+        };
+        writeln!(
+            buf,
+            "{}  {} {} {} = {};",
+            indent,
+            label,
+            typ,
+            field.name,
+            i + 1
+        )?;
+    }
+    writeln!(buf, "{}}}", indent)
+}
+
+fn encode_fields<'a, I>(buf: &mut Vec<u8>, message: &MessageSchema, datums: I)
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    for (i, (field, datum)) in message.fields.iter().zip(datums).enumerate() {
+        let tag = u32::try_from(i + 1).expect("field count fits in u32");
+        // Absent fields represent SQL NULLs.
+        if datum.is_null() {
+            continue;
+        }
+        if field.repeated {
+            // Protobuf has no way to represent a NULL element, so those are
+            // encoded as the element type's default value.
+            for element in datum.unwrap_list().iter() {
+                encode_field(buf, message, tag, &field.kind, element);
+            }
+        } else {
+            encode_field(buf, message, tag, &field.kind, datum);
+        }
+    }
+}
+
+fn encode_field(
+    buf: &mut Vec<u8>,
+    message: &MessageSchema,
+    tag: u32,
+    kind: &FieldKind,
+    datum: Datum,
+) {
+    let is_null = datum.is_null();
+    match kind {
+        FieldKind::Bool => {
+            encode_key(tag, WireType::Varint, buf);
+            encode_varint(u64::from(!is_null && datum.unwrap_bool()), buf);
+        }
+        FieldKind::Int32 => {
+            let i = if is_null {
+                0
+            } else {
+                match datum {
+                    Datum::Int16(i) => i32::from(i),
+                    datum => datum.unwrap_int32(),
+                }
+            };
+            encode_key(tag, WireType::Varint, buf);
+            // Negative `int32`s are sign extended to 64 bits on the wire.
+            encode_varint(i64::from(i) as u64, buf);
+        }
+        FieldKind::Int64 => {
+            let i = if is_null { 0 } else { datum.unwrap_int64() };
+            encode_key(tag, WireType::Varint, buf);
+            encode_varint(i as u64, buf);
+        }
+        FieldKind::Float => {
+            let f = if is_null { 0.0 } else { datum.unwrap_float32() };
+            encode_key(tag, WireType::ThirtyTwoBit, buf);
+            buf.extend_from_slice(&f.to_le_bytes());
+        }
+        FieldKind::Double => {
+            let f = if is_null { 0.0 } else { datum.unwrap_float64() };
+            encode_key(tag, WireType::SixtyFourBit, buf);
+            buf.extend_from_slice(&f.to_le_bytes());
+        }
+        FieldKind::String => {
+            let s = if is_null { "" } else { datum.unwrap_str() };
+            encode_length_delimited(buf, tag, s.as_bytes());
+        }
+        FieldKind::Bytes => {
+            let b = if is_null {
+                &[][..]
+            } else {
+                datum.unwrap_bytes()
+            };
+            encode_length_delimited(buf, tag, b);
+        }
+        FieldKind::Text(scalar_type) => {
+            let mut s = String::new();
+            if !is_null {
+                format_text(&mut s, scalar_type, datum);
+            }
+            encode_length_delimited(buf, tag, s.as_bytes());
+        }
+        FieldKind::Message(name) => {
+            let nested = message
+                .nested
+                .iter()
+                .find(|m| m.name == *name)
+                .expect("nested message declared");
+            let mut nested_buf = vec![];
+            if !is_null {
+                encode_fields(&mut nested_buf, nested, datum.unwrap_list().iter());
+            }
+            encode_length_delimited(buf, tag, &nested_buf);
+        }
+        FieldKind::Transaction => {
+            let id = datum.unwrap_list().into_first();
+            let mut nested_buf = vec![];
+            encode_length_delimited(&mut nested_buf, 1, id.unwrap_str().as_bytes());
+            encode_length_delimited(buf, tag, &nested_buf);
+        }
+    }
+}
+
+fn encode_length_delimited(buf: &mut Vec<u8>, tag: u32, bytes: &[u8]) {
+    encode_key(tag, WireType::LengthDelimited, buf);
+    encode_varint(u64::try_from(bytes.len()).expect("usize fits in u64"), buf);
+    buf.extend_from_slice(bytes);
+}
+
+fn format_text(buf: &mut String, scalar_type: &ScalarType, datum: Datum) {
+    match scalar_type {
+        ScalarType::Numeric { .. } => {
+            buf.push_str(&datum.unwrap_numeric().0.to_standard_notation_string());
+        }
+        ScalarType::Date => {
+            strconv::format_date(buf, datum.unwrap_date());
+        }
+        ScalarType::Time => {
+            strconv::format_time(buf, datum.unwrap_time());
+        }
+        ScalarType::Timestamp => {
+            strconv::format_timestamp(buf, datum.unwrap_timestamp());
+        }
+        ScalarType::TimestampTz => {
+            strconv::format_timestamptz(buf, datum.unwrap_timestamptz());
+        }
+        ScalarType::Interval => {
+            strconv::format_interval(buf, datum.unwrap_interval());
+        }
+        ScalarType::Uuid => {
+            strconv::format_uuid(buf, datum.unwrap_uuid());
+        }
+        ScalarType::Jsonb => {
+            strconv::format_jsonb(buf, JsonbRef::from_datum(datum));
+        }
+        _ => unreachable!("{:?} is not encoded as text", scalar_type),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_schema() -> Result<(), anyhow::Error> {
+        let row = ScalarType::Record {
+            fields: vec![
+                ("a".into(), ScalarType::Int64.nullable(false)),
+                (
+                    "b c".into(),
+                    ScalarType::Numeric { scale: None }.nullable(true),
+                ),
+            ],
+            custom_oid: None,
+            custom_name: Some("row".into()),
+        };
+        let desc = RelationDesc::empty()
+            .with_column("before", row.clone().nullable(true))
+            .with_column("after", row.nullable(true))
+            .with_column(
+                "tags",
+                ScalarType::List {
+                    element_type: Box::new(ScalarType::String),
+                    custom_oid: None,
+                }
+                .nullable(false),
+            );
+        let generator = ProtobufSchemaGenerator::new(None, desc, true)?;
+        assert_eq!(generator.key_schema(), None);
+        assert_eq!(
+            generator.value_schema(),
+            r#"syntax = "proto2";
+
+message Value {
+  message Row {
+    optional int64 a = 1;
+    optional string b_c = 2;
+  }
+  message Transaction {
+    optional string id = 1;
+  }
+  optional Row before = 1;
+  optional Row after = 2;
+  repeated string tags = 3;
+  optional Transaction transaction = 4;
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn generate_schema_reuses_messages_by_name() -> Result<(), anyhow::Error> {
+        let record = |name: &str, scalar_type: ScalarType| ScalarType::Record {
+            fields: vec![("a".into(), scalar_type.nullable(true))],
+            custom_oid: None,
+            custom_name: Some(name.into()),
+        };
+        let desc = RelationDesc::empty()
+            .with_column("w", record("row_type", ScalarType::Int64).nullable(true))
+            .with_column("x", record("row", ScalarType::Int64).nullable(true))
+            .with_column("y", record("row", ScalarType::String).nullable(true))
+            .with_column("z", record("row", ScalarType::String).nullable(true));
+        let generator = ProtobufSchemaGenerator::new(None, desc, false)?;
+        assert_eq!(
+            generator.value_schema(),
+            r#"syntax = "proto2";
+
+message Value {
+  message RowType {
+    optional int64 a = 1;
+  }
+  message Row {
+    optional int64 a = 1;
+  }
+  message Row1 {
+    optional string a = 1;
+  }
+  optional RowType w = 1;
+  optional Row x = 2;
+  optional Row1 y = 3;
+  optional Row1 z = 4;
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn encode_row() -> Result<(), anyhow::Error> {
+        let desc = RelationDesc::empty()
+            .with_column("a", ScalarType::Int32.nullable(true))
+            .with_column("b", ScalarType::String.nullable(true))
+            .with_column(
+                "c",
+                ScalarType::List {
+                    element_type: Box::new(ScalarType::Int64),
+                    custom_oid: None,
+                }
+                .nullable(false),
+            );
+        let generator = ProtobufSchemaGenerator::new(None, desc, false)?;
+        let encoder = ProtobufEncoder::new(generator, None, 7);
+
+        let mut row = Row::default();
+        row.push(Datum::Int32(-1));
+        row.push(Datum::Null);
+        row.push_list(vec![Datum::Int64(1), Datum::Int64(300)]);
+        assert_eq!(
+            encoder.encode_value_unchecked(row),
+            vec![
+                // Magic byte, schema ID, and message indexes.
+                0, 0, 0, 0, 7, 0, //
+                // a = -1, sign extended to ten bytes.
+                0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, //
+                // c = [1, 300], not packed.
+                0x18, 0x01, 0x18, 0xac, 0x02,
+            ]
+        );
+        Ok(())
+    }
+}
@@ -117,6 +117,7 @@ async fn run(args: Args) -> Result<(), anyhow::Error> {
         metrics_registry: metrics_registry.clone(),
         persister,
         now: SYSTEM_TIME.clone(),
+        cost_based_join_ordering: false,
     })
     .await?;
 
@@ -94,6 +94,12 @@ struct Args {
 
     #[clap(long, env = "MZ_DISABLE_USER_INDEXES")]
     disable_user_indexes: bool,
+    /// [EXPERIMENTAL] Order joins using size estimates of their inputs.
+    ///
+    /// Estimates are gathered from maintained indexes and ingested source
+    /// offsets, so plans may change as the data does.
+    #[clap(long, env = "MZ_COST_BASED_JOIN_ORDERING")]
+    cost_based_join_ordering: bool,
 
     /// The address on which metrics visible to "third parties" get exposed.
     ///
@@ -777,6 +783,7 @@ dataflow workers: {workers}",
         experimental_mode: args.experimental,
         disable_user_indexes: args.disable_user_indexes,
         safe_mode: args.safe,
+        cost_based_join_ordering: args.cost_based_join_ordering,
         telemetry,
         aws_external_id: args.aws_external_id,
         introspection_frequency: args
@@ -11,7 +11,8 @@
 //!
 //! materialized embeds an HTTP server for introspection into the running
 //! process. At the moment, its primary exports are Prometheus metrics, heap
-//! profiles, and catalog dumps.
+//! profiles, and catalog dumps. It also receives the requests that feed
+//! webhook sources.
 
 use std::net::SocketAddr;
 use std::pin::Pin;
@@ -40,6 +41,7 @@ mod prof;
 mod root;
 mod sql;
 mod util;
+mod webhook;
 
 const SYSTEM_USER: &str = "mz_system";
 
@@ -195,6 +197,9 @@ impl Server {
                     (&Method::GET, "/internal/catalog") => {
                         catalog::handle_internal_catalog(req, &mut coord_client).await
                     }
+                    (&Method::POST, path) if path.starts_with(webhook::WEBHOOK_PATH_PREFIX) => {
+                        webhook::handle_webhook(req, &mut coord_client).await
+                    }
                     _ => root::handle_static(req, &mut coord_client),
                 };
                 coord_client.terminate().await;
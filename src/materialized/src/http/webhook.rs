@@ -0,0 +1,129 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Ingestion endpoint for webhook sources.
+
+use std::collections::HashMap;
+
+use hyper::body::HttpBody;
+use hyper::header::CONTENT_LENGTH;
+use hyper::{Body, Request, Response, StatusCode};
+
+use mz_coord::session::EndTransactionAction;
+use mz_coord::CoordError;
+use mz_sql::names::PartialName;
+
+use crate::http::util;
+
+/// The path prefix under which webhook sources receive requests.
+pub const WEBHOOK_PATH_PREFIX: &str = "/api/webhook/";
+
+/// The size of the largest request body that webhook sources accept, in bytes.
+///
+/// Bodies are held in memory until they are appended to the source, so
+/// without a limit a single request could exhaust the memory of the process.
+const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Appends the body of a request to
+/// `/api/webhook/<database>/<schema>/<source>` to the named webhook source.
+///
+/// The response is only sent once the append has been committed, so a
+/// successful response means that the request has been durably recorded if
+/// the source is persisted.
+pub async fn handle_webhook(
+    req: Request<Body>,
+    coord_client: &mut mz_coord::SessionClient,
+) -> Result<Response<Body>, anyhow::Error> {
+    let name = match parse_webhook_path(req.uri().path()) {
+        Some(name) => name,
+        None => {
+            return Ok(util::error_response(
+                StatusCode::NOT_FOUND,
+                "expected path of the form /api/webhook/<database>/<schema>/<source>",
+            ))
+        }
+    };
+
+    // Header names are always lowercase in hyper, which is how webhook
+    // sources store the headers they capture.
+    let mut headers = HashMap::new();
+    for (name, value) in req.headers() {
+        headers
+            .entry(name.as_str().to_owned())
+            .or_insert_with(|| String::from_utf8_lossy(value.as_bytes()).into_owned());
+    }
+    let too_large = || {
+        util::error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("request body exceeds the limit of {} bytes", MAX_BODY_SIZE),
+        )
+    };
+    let content_length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .and_then(|len| len.parse::<u64>().ok());
+    if content_length.map_or(false, |len| len > MAX_BODY_SIZE as u64) {
+        return Ok(too_large());
+    }
+    // The content length is only advisory, so the limit must also be enforced
+    // while reading the body.
+    let body = match read_body(req.into_body()).await? {
+        Some(body) => body,
+        None => return Ok(too_large()),
+    };
+
+    let res = async {
+        coord_client.start_transaction(Some(1)).await?;
+        coord_client.append_webhook(name, headers, body).await?;
+        coord_client
+            .end_transaction(EndTransactionAction::Commit)
+            .await?;
+        Ok::<_, CoordError>(())
+    }
+    .await;
+    match res {
+        Ok(()) => Ok(Response::new(Body::empty())),
+        Err(e @ CoordError::InvalidWebhookSignature(_)) => Ok(util::error_response(
+            StatusCode::UNAUTHORIZED,
+            e.to_string(),
+        )),
+        Err(e) => Ok(util::error_response(StatusCode::BAD_REQUEST, e.to_string())),
+    }
+}
+
+/// Reads `body`, unless it is larger than [`MAX_BODY_SIZE`].
+async fn read_body(mut body: Body) -> Result<Option<Vec<u8>>, hyper::Error> {
+    let mut buf = vec![];
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if buf.len() + chunk.len() > MAX_BODY_SIZE {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(Some(buf))
+}
+
+fn parse_webhook_path(path: &str) -> Option<PartialName> {
+    let path = path.strip_prefix(WEBHOOK_PATH_PREFIX)?;
+    let mut parts = path.split('/');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(database), Some(schema), Some(item), None)
+            if !database.is_empty() && !schema.is_empty() && !item.is_empty() =>
+        {
+            Some(PartialName {
+                database: Some(database.into()),
+                schema: Some(schema.into()),
+                item: item.into(),
+            })
+        }
+        _ => None,
+    }
+}
@@ -133,6 +133,8 @@ pub struct Config {
     pub disable_user_indexes: bool,
     /// Whether to run in safe mode.
     pub safe_mode: bool,
+    /// Whether to order joins using size estimates of their inputs.
+    pub cost_based_join_ordering: bool,
     /// Telemetry configuration.
     pub telemetry: Option<TelemetryConfig>,
     /// The place where the server's metrics will be reported from.
@@ -272,6 +274,7 @@ pub async fn serve(config: Config) -> Result<Server, anyhow::Error> {
         now: SYSTEM_TIME.clone(),
         metrics_registry: config.metrics_registry.clone(),
         persister: persister.runtime.clone(),
+        spill_directory: Some(config.data_directory.join("spill")),
     })?;
 
     // Initialize coordinator.
@@ -289,6 +292,7 @@ pub async fn serve(config: Config) -> Result<Server, anyhow::Error> {
         metrics_registry: config.metrics_registry.clone(),
         persister,
         now: SYSTEM_TIME.clone(),
+        cost_based_join_ordering: config.cost_based_join_ordering,
     })
     .await?;
 
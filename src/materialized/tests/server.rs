@@ -210,7 +210,10 @@ fn test_safe_mode() -> Result<(), Box<dyn Error>> {
     let err = client
         .batch_execute("CREATE SINK snk FROM mz_sources INTO FILE '/ignored' FORMAT BYTES")
         .unwrap_db_error();
-    assert_eq!(err.message(), "Expected one of KAFKA or AVRO, found FILE");
+    assert_eq!(
+        err.message(),
+        "Expected one of KAFKA or AVRO or POSTGRES or PARQUET or HTTP, found FILE"
+    );
 
     // No Avro OCF sources or sinks.
     let err = client
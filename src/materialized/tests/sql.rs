@@ -18,7 +18,7 @@ use std::fs::File;
 use std::io::Read;
 use std::io::Write;
 use std::net::Shutdown;
-use std::net::TcpListener;
+use std::net::{TcpListener, TcpStream};
 use std::path::Path;
 use std::thread;
 use std::thread::sleep;
@@ -928,6 +928,108 @@ fn test_temporary_views() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Reads an HTTP request from `stream`, returning its head and its body.
+fn read_http_request(stream: &mut TcpStream) -> Result<(String, String), anyhow::Error> {
+    let mut buf = vec![0; 1024];
+    let mut input = vec![];
+    let head_len = loop {
+        if let Some(pos) = input.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        let len_read = stream.read(&mut buf)?;
+        assert!(len_read > 0);
+        input.extend_from_slice(&buf[0..len_read]);
+    };
+    let head = String::from_utf8(input[..head_len].to_vec())?;
+    let content_length = http_header(&head, "content-length")
+        .map(|len| len.parse())
+        .transpose()?
+        .unwrap_or(0);
+    let mut body = input[head_len..].to_vec();
+    while body.len() < content_length {
+        let len_read = stream.read(&mut buf)?;
+        assert!(len_read > 0);
+        body.extend_from_slice(&buf[0..len_read]);
+    }
+    Ok((head, String::from_utf8(body)?))
+}
+
+/// Returns the value of the header `name` in the head of an HTTP request.
+fn http_header(head: &str, name: &str) -> Option<String> {
+    head.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        if key.eq_ignore_ascii_case(name) {
+            Some(value.trim().to_owned())
+        } else {
+            None
+        }
+    })
+}
+
+#[test]
+fn test_http_sink() -> Result<(), anyhow::Error> {
+    mz_ore::test::init_logging();
+
+    mz_ore::test::timeout(Duration::from_secs(30), || {
+        // Create a listener that will simulate the endpoint of the sink.
+        let listener = TcpListener::bind("localhost:0")?;
+        let listener_port = listener.local_addr()?.port();
+
+        let server = util::start_server(util::Config::default())?;
+        let mut client = server.connect(postgres::NoTls)?;
+
+        let mut file = NamedTempFile::new()?;
+        file.write_all(b"line 1\n")?;
+        file.as_file_mut().sync_all()?;
+        client.batch_execute(&*format!(
+            "CREATE SOURCE f FROM FILE '{}' FORMAT TEXT",
+            file.path().display()
+        ))?;
+        client.batch_execute(&*format!(
+            "CREATE SINK s FROM f INTO HTTP 'http://localhost:{}/hook'",
+            listener_port
+        ))?;
+        let sink_id: String = client
+            .query_one("SELECT id FROM mz_sinks WHERE name = 's'", &[])?
+            .get("id");
+
+        // Fail the first request, which the sink must then retry.
+        let (mut stream, _) = listener.accept()?;
+        let (head, body) = read_http_request(&mut stream)?;
+        write!(
+            stream,
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        )?;
+        stream.shutdown(Shutdown::Both)?;
+
+        assert!(head.starts_with("POST /hook "));
+        assert_eq!(
+            http_header(&head, "content-type").as_deref(),
+            Some("application/json")
+        );
+        let idempotency_key = http_header(&head, "idempotency-key").unwrap();
+        let update: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(
+            idempotency_key,
+            format!("{}-{}", sink_id, update["timestamp"])
+        );
+        let updates = update["updates"].as_array().unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0]["key"], serde_json::Value::Null);
+        assert_eq!(updates[0]["value"]["before"], serde_json::Value::Null);
+        assert_eq!(updates[0]["value"]["after"]["row"]["text"], "line 1");
+
+        // The retry carries the same updates and idempotency key.
+        let (mut stream, _) = listener.accept()?;
+        let (head, retried_body) = read_http_request(&mut stream)?;
+        write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")?;
+        assert_eq!(http_header(&head, "idempotency-key"), Some(idempotency_key));
+        assert_eq!(retried_body, body);
+
+        Ok(())
+    })
+}
+
 // This test attempts to observe a linearizability violation by creating a set of
 // sources which are constantly being appended to, then creating a materialized
 // view of each of their sizes, then repeatedly reading from some subset of
@@ -42,6 +42,7 @@ pub struct Config {
     tls: Option<materialized::TlsConfig>,
     experimental_mode: bool,
     safe_mode: bool,
+    cost_based_join_ordering: bool,
     workers: usize,
     logical_compaction_window: Option<Duration>,
 }
@@ -55,6 +56,7 @@ impl Default for Config {
             tls: None,
             experimental_mode: false,
             safe_mode: false,
+            cost_based_join_ordering: false,
             workers: 1,
             logical_compaction_window: None,
         }
@@ -96,6 +98,11 @@ impl Config {
         self
     }
 
+    pub fn cost_based_join_ordering(mut self) -> Self {
+        self.cost_based_join_ordering = true;
+        self
+    }
+
     pub fn workers(mut self, workers: usize) -> Self {
         self.workers = workers;
         self
@@ -140,6 +147,7 @@ pub fn start_server(config: Config) -> Result<Server, anyhow::Error> {
         tls: config.tls,
         experimental_mode: config.experimental_mode,
         safe_mode: config.safe_mode,
+        cost_based_join_ordering: config.cost_based_join_ordering,
         disable_user_indexes: false,
         telemetry: None,
         introspection_frequency: Duration::from_secs(1),
@@ -170,6 +170,26 @@ pub fn decode_trace_arrow<R: Read + Seek>(r: &mut R) -> Result<BlobTraceBatch, E
     Ok(ret)
 }
 
+/// Encodes updates into the Arrow file format, without any batch metadata.
+///
+/// This is intended for data that is written and read back by the same
+/// process, like arrangement batches spilled to disk.
+pub fn encode_arrow_records<W: Write>(w: &mut W, updates: &[ColumnarRecords]) -> Result<(), Error> {
+    let options = WriteOptions { compression: None };
+    let mut writer = FileWriter::try_new(w, &SCHEMA_ARROW_KVTD, options)?;
+    for records in updates.iter() {
+        writer.write(&encode_arrow_batch_kvtd(records))?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Decodes updates written by [encode_arrow_records].
+pub fn decode_arrow_records<R: Read + Seek>(r: &mut R) -> Result<Vec<ColumnarRecords>, Error> {
+    let file_meta = read_file_metadata(r)?;
+    decode_arrow_file_kvtd(r, file_meta)
+}
+
 fn decode_arrow_file_kvtd<R: Read + Seek>(
     r: &mut R,
     file_meta: FileMetadata,
@@ -78,4 +78,10 @@ pub const FUNC_MZ_DATE_BIN_HOPPING_UNIX_EPOCH_TSTZ_OID: u32 = 16_450;
 pub const FUNC_MZ_DATE_BIN_HOPPING_TS_OID: u32 = 16_451;
 pub const FUNC_MZ_DATE_BIN_HOPPING_TSTZ_OID: u32 = 16_452;
 pub const FUNC_MZ_TYPE_NAME: u32 = 16_453;
-// next ID: 16_454
+pub const FUNC_MZ_TUMBLE_TS_OID: u32 = 16_454;
+pub const FUNC_MZ_TUMBLE_TSTZ_OID: u32 = 16_455;
+pub const FUNC_MZ_HOP_TS_OID: u32 = 16_456;
+pub const FUNC_MZ_HOP_TSTZ_OID: u32 = 16_457;
+pub const FUNC_MZ_SESSION_OID: u32 = 16_458;
+pub const FUNC_MZ_SESSIONS_OID: u32 = 16_459;
+// next ID: 16_460
@@ -995,6 +995,7 @@ where
             ExecuteResponse::DroppedSchema => command_complete!("DROP SCHEMA"),
             ExecuteResponse::DroppedRole => command_complete!("DROP ROLE"),
             ExecuteResponse::DroppedSource => command_complete!("DROP SOURCE"),
+            ExecuteResponse::DroppedSubscription => command_complete!("DROP SUBSCRIPTION"),
             ExecuteResponse::DroppedIndex => command_complete!("DROP INDEX"),
             ExecuteResponse::DroppedSink => command_complete!("DROP SINK"),
             ExecuteResponse::DroppedTable => command_complete!("DROP TABLE"),
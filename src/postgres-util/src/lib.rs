@@ -142,6 +142,11 @@ pub struct TableInfo {
     pub schema: Vec<PgColumn>,
 }
 
+/// Quotes `name` as a Postgres identifier, doubling any embedded quotes.
+pub fn quote_identifier(name: &str) -> String {
+    Ident::new(name).to_ast_string_stable()
+}
+
 /// Creates a TLS connector for the given [`Config`].
 pub fn make_tls(config: &Config) -> Result<MakeTlsConnector, anyhow::Error> {
     let mut builder = SslConnector::builder(SslMethod::tls_client())?;
@@ -393,3 +398,12 @@ pub async fn connect_replication(conn: &str) -> Result<Client, anyhow::Error> {
     );
     Ok(client)
 }
+
+/// Starts a regular (non-replication) connection to the upstream database
+pub async fn connect(conn: &str) -> Result<Client, anyhow::Error> {
+    let config: Config = conn.parse()?;
+    let tls = make_tls(&config)?;
+    let (client, connection) = config.connect(tls).await?;
+    task::spawn(|| format!("postgres_connect:{conn}"), connection);
+    Ok(client)
+}
@@ -92,6 +92,8 @@ impl_display_t!(AvroSchema);
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ProtobufSchema<T: AstInfo> {
     Csr {
+        /// The message to decode, if the schema declares more than one.
+        message_name: Option<String>,
         csr_connector: CsrConnectorProto<T>,
     },
     InlineSchema {
@@ -103,7 +105,15 @@ pub enum ProtobufSchema<T: AstInfo> {
 impl<T: AstInfo> AstDisplay for ProtobufSchema<T> {
     fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
         match self {
-            Self::Csr { csr_connector } => {
+            Self::Csr {
+                message_name,
+                csr_connector,
+            } => {
+                if let Some(message_name) = message_name {
+                    f.write_str("MESSAGE '");
+                    f.write_node(&display::escape_single_quote_string(message_name));
+                    f.write_str("' ");
+                }
                 f.write_node(csr_connector);
             }
             Self::InlineSchema {
@@ -287,6 +297,7 @@ pub enum Format<T: AstInfo> {
         delimiter: char,
     },
     Json,
+    Parquet,
     Text,
 }
 
@@ -414,6 +425,7 @@ impl<T: AstInfo> AstDisplay for Format<T> {
                 }
             }
             Self::Json => f.write_str("JSON"),
+            Self::Parquet => f.write_str("PARQUET"),
             Self::Text => f.write_str("TEXT"),
         }
     }
@@ -423,6 +435,9 @@ impl_display_t!(Format);
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Compression {
     Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
     None,
 }
 
@@ -430,6 +445,9 @@ impl AstDisplay for Compression {
     fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
         match self {
             Self::Gzip => f.write_str("GZIP"),
+            Self::Zstd => f.write_str("ZSTD"),
+            Self::Bzip2 => f.write_str("BZIP2"),
+            Self::Xz => f.write_str("XZ"),
             Self::None => f.write_str("NONE"),
         }
     }
@@ -494,6 +512,12 @@ pub enum CreateSourceConnector {
         /// The PubNub channel to subscribe to
         channel: String,
     },
+    Webhook {
+        /// The request headers whose values are appended to each row
+        headers: Vec<String>,
+        /// How to verify the signature of each request, if at all
+        validation: Option<WebhookValidation>,
+    },
 }
 
 impl AstDisplay for CreateSourceConnector {
@@ -570,6 +594,27 @@ impl AstDisplay for CreateSourceConnector {
                 f.write_str(&display::escape_single_quote_string(channel));
                 f.write_str("'");
             }
+            CreateSourceConnector::Webhook {
+                headers,
+                validation,
+            } => {
+                f.write_str("WEBHOOK");
+                if !headers.is_empty() {
+                    f.write_str(" HEADERS (");
+                    for (i, header) in headers.iter().enumerate() {
+                        if i > 0 {
+                            f.write_str(", ");
+                        }
+                        f.write_str("'");
+                        f.write_str(&display::escape_single_quote_string(header));
+                        f.write_str("'");
+                    }
+                    f.write_str(")");
+                }
+                if let Some(validation) = validation {
+                    f.write_node(validation);
+                }
+            }
         }
     }
 }
@@ -580,6 +625,10 @@ impl<T: AstInfo> From<&CreateSinkConnector<T>> for ConnectorType {
         match connector {
             CreateSinkConnector::Kafka { .. } => ConnectorType::Kafka,
             CreateSinkConnector::AvroOcf { .. } => ConnectorType::AvroOcf,
+            CreateSinkConnector::Postgres { .. } => ConnectorType::Postgres,
+            CreateSinkConnector::Parquet { path } if path.starts_with("s3://") => ConnectorType::S3,
+            CreateSinkConnector::Parquet { .. } => ConnectorType::File,
+            CreateSinkConnector::Http { .. } => ConnectorType::Webhook,
         }
     }
 }
@@ -590,11 +639,33 @@ pub enum CreateSinkConnector<T: AstInfo> {
     Kafka {
         broker: String,
         topic: String,
-        key: Option<KafkaSinkKey>,
+        key: Option<SinkKey>,
+        /// An expression over the key columns that picks the partition of
+        /// each message
+        partition_by: Option<Expr<T>>,
+        /// Expressions over the key columns that become the headers of each
+        /// message
+        headers: Vec<KafkaSinkHeader<T>>,
         consistency: Option<KafkaConsistency<T>>,
     },
     /// Avro Object Container File
     AvroOcf { path: String },
+    Postgres {
+        /// The postgres connection string
+        conn: String,
+        /// The upstream table that the sink writes to
+        table: UnresolvedObjectName,
+        /// The columns that identify rows in the upstream table
+        key: SinkKey,
+    },
+    /// Apache Parquet files in a local directory or an S3 prefix
+    Parquet { path: String },
+    Http {
+        /// The URL that the sink POSTs updates to
+        url: String,
+        /// The columns that identify the updated rows, if any
+        key: Option<SinkKey>,
+    },
 }
 
 impl<T: AstInfo> AstDisplay for CreateSinkConnector<T> {
@@ -604,6 +675,8 @@ impl<T: AstInfo> AstDisplay for CreateSinkConnector<T> {
                 broker,
                 topic,
                 key,
+                partition_by,
+                headers,
                 consistency,
             } => {
                 f.write_str("KAFKA BROKER '");
@@ -615,6 +688,15 @@ impl<T: AstInfo> AstDisplay for CreateSinkConnector<T> {
                 if let Some(key) = key.as_ref() {
                     f.write_node(key);
                 }
+                if let Some(partition_by) = partition_by.as_ref() {
+                    f.write_str(" PARTITION BY ");
+                    f.write_node(partition_by);
+                }
+                if !headers.is_empty() {
+                    f.write_str(" HEADERS (");
+                    f.write_node(&display::comma_separated(headers));
+                    f.write_str(")");
+                }
                 if let Some(consistency) = consistency.as_ref() {
                     f.write_node(consistency);
                 }
@@ -624,6 +706,26 @@ impl<T: AstInfo> AstDisplay for CreateSinkConnector<T> {
                 f.write_node(&display::escape_single_quote_string(path));
                 f.write_str("'");
             }
+            CreateSinkConnector::Postgres { conn, table, key } => {
+                f.write_str("POSTGRES CONNECTION '");
+                f.write_node(&display::escape_single_quote_string(conn));
+                f.write_str("' TABLE ");
+                f.write_node(table);
+                f.write_node(key);
+            }
+            CreateSinkConnector::Parquet { path } => {
+                f.write_str("PARQUET '");
+                f.write_node(&display::escape_single_quote_string(path));
+                f.write_str("'");
+            }
+            CreateSinkConnector::Http { url, key } => {
+                f.write_str("HTTP '");
+                f.write_node(&display::escape_single_quote_string(url));
+                f.write_str("'");
+                if let Some(key) = key.as_ref() {
+                    f.write_node(key);
+                }
+            }
         }
     }
 }
@@ -650,13 +752,29 @@ impl<T: AstInfo> AstDisplay for KafkaConsistency<T> {
 }
 impl_display_t!(KafkaConsistency);
 
+/// A header that a Kafka sink attaches to each message.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct KafkaSinkKey {
+pub struct KafkaSinkHeader<T: AstInfo> {
+    pub name: Ident,
+    pub value: Expr<T>,
+}
+
+impl<T: AstInfo> AstDisplay for KafkaSinkHeader<T> {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_node(&self.name);
+        f.write_str(" = ");
+        f.write_node(&self.value);
+    }
+}
+impl_display_t!(KafkaSinkHeader);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SinkKey {
     pub key_columns: Vec<Ident>,
     pub not_enforced: bool,
 }
 
-impl AstDisplay for KafkaSinkKey {
+impl AstDisplay for SinkKey {
     fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
         f.write_str(" KEY (");
         f.write_node(&display::comma_separated(&self.key_columns));
@@ -719,6 +837,31 @@ impl AstDisplay for S3KeySource {
 }
 impl_display!(S3KeySource);
 
+/// The `VALIDATE HMAC` clause of a webhook source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WebhookValidation {
+    /// The hash algorithm, as accepted by the `hmac` function, e.g. `sha256`
+    pub algorithm: String,
+    /// The environment variable that holds the key shared with the sender of
+    /// the webhooks
+    pub key_env: String,
+    /// The request header that carries the signature
+    pub header: String,
+}
+
+impl AstDisplay for WebhookValidation {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_str(" VALIDATE HMAC '");
+        f.write_str(&display::escape_single_quote_string(&self.algorithm));
+        f.write_str("' KEY ENV '");
+        f.write_str(&display::escape_single_quote_string(&self.key_env));
+        f.write_str("' HEADER '");
+        f.write_str(&display::escape_single_quote_string(&self.header));
+        f.write_str("'");
+    }
+}
+impl_display!(WebhookValidation);
+
 /// A table-level constraint, specified in a `CREATE TABLE` or an
 /// `ALTER TABLE ADD <constraint>` statement.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
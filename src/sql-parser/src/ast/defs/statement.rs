@@ -22,9 +22,9 @@ use std::fmt;
 
 use crate::ast::display::{self, AstDisplay, AstFormatter};
 use crate::ast::{
-    AstInfo, ColumnDef, CreateSinkConnector, CreateSourceConnector, CreateSourceFormat, Envelope,
-    Expr, Format, Ident, KeyConstraint, Query, SourceIncludeMetadata, TableAlias, TableConstraint,
-    TableWithJoins, UnresolvedObjectName, Value,
+    AstInfo, ColumnDef, CreateSinkConnector, CreateSourceConnector, CreateSourceFormat, CsrSeed,
+    Envelope, Expr, Format, Ident, KeyConstraint, Query, SourceIncludeMetadata, TableAlias,
+    TableConstraint, TableWithJoins, UnresolvedObjectName, Value,
 };
 
 /// A top-level statement (SELECT, INSERT, CREATE, etc.)
@@ -48,9 +48,11 @@ pub enum Statement<T: AstInfo> {
     CreateRole(CreateRoleStatement),
     AlterObjectRename(AlterObjectRenameStatement),
     AlterIndex(AlterIndexStatement),
+    AlterSource(AlterSourceStatement),
     Discard(DiscardStatement),
     DropDatabase(DropDatabaseStatement),
     DropObjects(DropObjectsStatement),
+    DropSubscription(DropSubscriptionStatement),
     SetVariable(SetVariableStatement),
     ShowDatabases(ShowDatabasesStatement<T>),
     ShowObjects(ShowObjectsStatement<T>),
@@ -96,9 +98,11 @@ impl<T: AstInfo> AstDisplay for Statement<T> {
             Statement::CreateType(stmt) => f.write_node(stmt),
             Statement::AlterObjectRename(stmt) => f.write_node(stmt),
             Statement::AlterIndex(stmt) => f.write_node(stmt),
+            Statement::AlterSource(stmt) => f.write_node(stmt),
             Statement::Discard(stmt) => f.write_node(stmt),
             Statement::DropDatabase(stmt) => f.write_node(stmt),
             Statement::DropObjects(stmt) => f.write_node(stmt),
+            Statement::DropSubscription(stmt) => f.write_node(stmt),
             Statement::SetVariable(stmt) => f.write_node(stmt),
             Statement::ShowDatabases(stmt) => f.write_node(stmt),
             Statement::ShowObjects(stmt) => f.write_node(stmt),
@@ -873,6 +877,44 @@ impl AstDisplay for AlterIndexStatement {
 
 impl_display!(AlterIndexStatement);
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AlterSourceAction {
+    /// `REFRESH SCHEMA`. Purification fills in the seed with the latest
+    /// schemas from the source's schema registry.
+    RefreshSchema { seed: Option<CsrSeed> },
+}
+
+/// `ALTER SOURCE ... REFRESH SCHEMA`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlterSourceStatement {
+    pub source_name: UnresolvedObjectName,
+    pub if_exists: bool,
+    pub action: AlterSourceAction,
+}
+
+impl AstDisplay for AlterSourceStatement {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_str("ALTER SOURCE ");
+        if self.if_exists {
+            f.write_str("IF EXISTS ");
+        }
+        f.write_node(&self.source_name);
+        f.write_str(" ");
+
+        match &self.action {
+            AlterSourceAction::RefreshSchema { seed } => {
+                f.write_str("REFRESH SCHEMA");
+                if let Some(seed) = seed {
+                    f.write_str(" ");
+                    f.write_node(seed);
+                }
+            }
+        }
+    }
+}
+
+impl_display!(AlterSourceStatement);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DiscardStatement {
     pub target: DiscardTarget,
@@ -959,6 +1001,24 @@ impl AstDisplay for DropObjectsStatement {
 }
 impl_display!(DropObjectsStatement);
 
+/// `DROP SUBSCRIPTION`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DropSubscriptionStatement {
+    pub name: Ident,
+    pub if_exists: bool,
+}
+
+impl AstDisplay for DropSubscriptionStatement {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_str("DROP SUBSCRIPTION ");
+        if self.if_exists {
+            f.write_str("IF EXISTS ");
+        }
+        f.write_node(&self.name);
+    }
+}
+impl_display!(DropSubscriptionStatement);
+
 /// `SET <variable>`
 ///
 /// Note: this is not a standard SQL statement, but it is supported by at
@@ -1263,6 +1323,7 @@ impl_display!(RollbackStatement);
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TailStatement<T: AstInfo> {
     pub relation: TailRelation<T>,
+    pub envelope: Option<TailEnvelope>,
     pub options: Vec<WithOption>,
     pub as_of: Option<Expr<T>>,
 }
@@ -1271,6 +1332,10 @@ impl<T: AstInfo> AstDisplay for TailStatement<T> {
     fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
         f.write_str("TAIL ");
         f.write_node(&self.relation);
+        if let Some(envelope) = &self.envelope {
+            f.write_str(" ENVELOPE ");
+            f.write_node(envelope);
+        }
         if !self.options.is_empty() {
             f.write_str(" WITH (");
             f.write_node(&display::comma_separated(&self.options));
@@ -1304,6 +1369,37 @@ impl<T: AstInfo> AstDisplay for TailRelation<T> {
 }
 impl_display_t!(TailRelation);
 
+/// The shape in which `TAIL` presents updates.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TailEnvelope {
+    /// One row per key and timestamp with the new value of the key, or a
+    /// marker that the key was deleted.
+    Upsert { key_columns: Vec<Ident> },
+    /// One row per changed row with its value before and after the change.
+    Debezium { key_columns: Vec<Ident> },
+}
+
+impl AstDisplay for TailEnvelope {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        let key_columns = match self {
+            TailEnvelope::Upsert { key_columns } => {
+                f.write_str("UPSERT");
+                key_columns
+            }
+            TailEnvelope::Debezium { key_columns } => {
+                f.write_str("DEBEZIUM");
+                key_columns
+            }
+        };
+        if !key_columns.is_empty() {
+            f.write_str(" (KEY (");
+            f.write_node(&display::comma_separated(key_columns));
+            f.write_str("))");
+        }
+    }
+}
+impl_display!(TailEnvelope);
+
 /// `EXPLAIN ...`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ExplainStatement<T: AstInfo> {
@@ -1315,16 +1411,27 @@ pub struct ExplainStatement<T: AstInfo> {
 impl<T: AstInfo> AstDisplay for ExplainStatement<T> {
     fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
         f.write_str("EXPLAIN ");
+        let mut options = vec![];
         if self.options.timing {
-            f.write_str("(TIMING ");
-            f.write_str(self.options.timing);
+            options.push("TIMING true");
+        }
+        if self.options.statistics {
+            options.push("STATISTICS true");
+        }
+        if !options.is_empty() {
+            f.write_str("(");
+            f.write_str(options.join(", "));
             f.write_str(") ");
         }
         if self.options.typed {
             f.write_str("TYPED ");
         }
         f.write_node(&self.stage);
-        f.write_str(" FOR ");
+        if self.stage == ExplainStage::Analyze {
+            f.write_str(" ");
+        } else {
+            f.write_str(" FOR ");
+        }
         f.write_node(&self.explainee);
     }
 }
@@ -1588,6 +1695,9 @@ pub enum ExplainStage {
     OptimizedPlan,
     /// The render::plan::Plan
     PhysicalPlan,
+    /// The render::plan::Plan of a running dataflow, annotated with its
+    /// runtime behavior
+    Analyze,
 }
 
 impl AstDisplay for ExplainStage {
@@ -1599,6 +1709,7 @@ impl AstDisplay for ExplainStage {
             ExplainStage::DecorrelatedPlan => f.write_str("DECORRELATED PLAN"),
             ExplainStage::OptimizedPlan => f.write_str("OPTIMIZED PLAN"),
             ExplainStage::PhysicalPlan => f.write_str("PHYSICAL PLAN"),
+            ExplainStage::Analyze => f.write_str("ANALYZE"),
         }
     }
 }
@@ -1608,12 +1719,15 @@ impl_display!(ExplainStage);
 pub enum Explainee<T: AstInfo> {
     View(UnresolvedObjectName),
     Query(Query<T>),
+    Index(UnresolvedObjectName),
+    Sink(UnresolvedObjectName),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ExplainOptions {
     pub typed: bool,
     pub timing: bool,
+    pub statistics: bool,
 }
 
 impl<T: AstInfo> AstDisplay for Explainee<T> {
@@ -1624,6 +1738,14 @@ impl<T: AstInfo> AstDisplay for Explainee<T> {
                 f.write_node(&name);
             }
             Explainee::Query(query) => f.write_node(query),
+            Explainee::Index(name) => {
+                f.write_str("INDEX ");
+                f.write_node(&name);
+            }
+            Explainee::Sink(name) => {
+                f.write_str("SINK ");
+                f.write_node(&name);
+            }
         }
     }
 }
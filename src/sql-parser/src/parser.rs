@@ -1619,6 +1619,8 @@ impl<'a> Parser<'a> {
             Format::Csv { columns, delimiter }
         } else if self.parse_keyword(JSON) {
             Format::Json
+        } else if self.parse_keyword(PARQUET) {
+            Format::Parquet
         } else if self.parse_keyword(TEXT) {
             Format::Text
         } else if self.parse_keyword(BYTES) {
@@ -1626,7 +1628,7 @@ impl<'a> Parser<'a> {
         } else {
             return self.expected(
                 self.peek_pos(),
-                "AVRO, PROTOBUF, REGEX, CSV, JSON, TEXT, or BYTES",
+                "AVRO, PROTOBUF, REGEX, CSV, JSON, PARQUET, TEXT, or BYTES",
                 self.peek_token(),
             );
         };
@@ -1665,10 +1667,20 @@ impl<'a> Parser<'a> {
     fn parse_protobuf_schema(&mut self) -> Result<ProtobufSchema<Raw>, ParserError> {
         if self.parse_keywords(&[USING, CONFLUENT, SCHEMA, REGISTRY]) {
             let csr_connector = self.parse_csr_connector_proto()?;
-            Ok(ProtobufSchema::Csr { csr_connector })
+            Ok(ProtobufSchema::Csr {
+                message_name: None,
+                csr_connector,
+            })
         } else if self.parse_keyword(MESSAGE) {
             let message_name = self.parse_literal_string()?;
             self.expect_keyword(USING)?;
+            if self.parse_keywords(&[CONFLUENT, SCHEMA, REGISTRY]) {
+                let csr_connector = self.parse_csr_connector_proto()?;
+                return Ok(ProtobufSchema::Csr {
+                    message_name: Some(message_name),
+                    csr_connector,
+                });
+            }
             let schema = self.parse_schema()?;
             Ok(ProtobufSchema::InlineSchema {
                 message_name,
@@ -1683,25 +1695,28 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_csr_connector_avro(&mut self) -> Result<CsrConnectorAvro<Raw>, ParserError> {
-        let url = self.parse_literal_string()?;
-
-        let seed = if self.parse_keyword(SEED) {
-            let key_schema = if self.parse_keyword(KEY) {
-                self.expect_keyword(SCHEMA)?;
-                Some(self.parse_literal_string()?)
-            } else {
-                None
-            };
-            self.expect_keywords(&[VALUE, SCHEMA])?;
-            let value_schema = self.parse_literal_string()?;
-            Some(CsrSeed {
-                key_schema,
-                value_schema,
-            })
+    fn parse_opt_csr_seed(&mut self) -> Result<Option<CsrSeed>, ParserError> {
+        if !self.parse_keyword(SEED) {
+            return Ok(None);
+        }
+        let key_schema = if self.parse_keyword(KEY) {
+            self.expect_keyword(SCHEMA)?;
+            Some(self.parse_literal_string()?)
         } else {
             None
         };
+        self.expect_keywords(&[VALUE, SCHEMA])?;
+        let value_schema = self.parse_literal_string()?;
+        Ok(Some(CsrSeed {
+            key_schema,
+            value_schema,
+        }))
+    }
+
+    fn parse_csr_connector_avro(&mut self) -> Result<CsrConnectorAvro<Raw>, ParserError> {
+        let url = self.parse_literal_string()?;
+
+        let seed = self.parse_opt_csr_seed()?;
 
         // Look ahead to avoid erroring on `WITH SNAPSHOT`; we only want to
         // accept `WITH (...)` here.
@@ -1818,8 +1833,18 @@ impl<'a> Parser<'a> {
             Compression::None
         } else if self.parse_keyword(GZIP) {
             Compression::Gzip
+        } else if self.parse_keyword(ZSTD) {
+            Compression::Zstd
+        } else if self.parse_keyword(BZIP2) {
+            Compression::Bzip2
+        } else if self.parse_keyword(XZ) {
+            Compression::Xz
         } else {
-            return self.expected(self.peek_pos(), "NONE or GZIP", self.peek_token());
+            return self.expected(
+                self.peek_pos(),
+                "NONE, GZIP, ZSTD, BZIP2, or XZ",
+                self.peek_token(),
+            );
         };
         Ok(compression)
     }
@@ -1979,7 +2004,38 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_create_source_connector(&mut self) -> Result<CreateSourceConnector, ParserError> {
-        match self.expect_one_of_keywords(&[FILE, KAFKA, KINESIS, AVRO, S3, POSTGRES, PUBNUB])? {
+        match self
+            .expect_one_of_keywords(&[FILE, KAFKA, KINESIS, AVRO, S3, POSTGRES, PUBNUB, WEBHOOK])?
+        {
+            WEBHOOK => {
+                let headers = if self.parse_keyword(HEADERS) {
+                    self.expect_token(&Token::LParen)?;
+                    let headers = self.parse_comma_separated(Parser::parse_literal_string)?;
+                    self.expect_token(&Token::RParen)?;
+                    headers
+                } else {
+                    vec![]
+                };
+                let validation = if self.parse_keywords(&[VALIDATE, HMAC]) {
+                    let algorithm = self.parse_literal_string()?;
+                    self.expect_keywords(&[KEY, ENV])?;
+                    let key_env = self.parse_literal_string()?;
+                    self.expect_keyword(HEADER)?;
+                    let header = self.parse_literal_string()?;
+                    Some(WebhookValidation {
+                        algorithm,
+                        key_env,
+                        header,
+                    })
+                } else {
+                    None
+                };
+
+                Ok(CreateSourceConnector::Webhook {
+                    headers,
+                    validation,
+                })
+            }
             PUBNUB => {
                 self.expect_keywords(&[SUBSCRIBE, KEY])?;
                 let subscribe_key = self.parse_literal_string()?;
@@ -2095,7 +2151,7 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_create_sink_connector(&mut self) -> Result<CreateSinkConnector<Raw>, ParserError> {
-        match self.expect_one_of_keywords(&[KAFKA, AVRO])? {
+        match self.expect_one_of_keywords(&[KAFKA, AVRO, POSTGRES, PARQUET, HTTP])? {
             KAFKA => {
                 self.expect_keyword(BROKER)?;
                 let broker = self.parse_literal_string()?;
@@ -2107,27 +2163,30 @@ impl<'a> Parser<'a> {
                 let key = if self.peek_keyword(KEY)
                     && self.peek_nth_token(1) != Some(Token::Keyword(FORMAT))
                 {
-                    let _ = self.expect_keyword(KEY);
-                    let key_columns = self.parse_parenthesized_column_list(Mandatory)?;
-
-                    let not_enforced = if self.peek_keywords(&[NOT, ENFORCED]) {
-                        let _ = self.expect_keywords(&[NOT, ENFORCED])?;
-                        true
-                    } else {
-                        false
-                    };
-                    Some(KafkaSinkKey {
-                        key_columns,
-                        not_enforced,
-                    })
+                    Some(self.parse_sink_key()?)
+                } else {
+                    None
+                };
+                let partition_by = if self.parse_keywords(&[PARTITION, BY]) {
+                    Some(self.parse_expr()?)
                 } else {
                     None
                 };
+                let headers = if self.parse_keyword(HEADERS) {
+                    self.expect_token(&Token::LParen)?;
+                    let headers = self.parse_comma_separated(Parser::parse_kafka_sink_header)?;
+                    self.expect_token(&Token::RParen)?;
+                    headers
+                } else {
+                    vec![]
+                };
                 let consistency = self.parse_kafka_consistency()?;
                 Ok(CreateSinkConnector::Kafka {
                     broker,
                     topic,
                     key,
+                    partition_by,
+                    headers,
                     consistency,
                 })
             }
@@ -2136,10 +2195,53 @@ impl<'a> Parser<'a> {
                 let path = self.parse_literal_string()?;
                 Ok(CreateSinkConnector::AvroOcf { path })
             }
+            POSTGRES => {
+                self.expect_keyword(CONNECTION)?;
+                let conn = self.parse_literal_string()?;
+                self.expect_keyword(TABLE)?;
+                let table = self.parse_object_name()?;
+                let key = self.parse_sink_key()?;
+                Ok(CreateSinkConnector::Postgres { conn, table, key })
+            }
+            PARQUET => {
+                let path = self.parse_literal_string()?;
+                Ok(CreateSinkConnector::Parquet { path })
+            }
+            HTTP => {
+                let url = self.parse_literal_string()?;
+                let key = if self.peek_keyword(KEY) {
+                    Some(self.parse_sink_key()?)
+                } else {
+                    None
+                };
+                Ok(CreateSinkConnector::Http { url, key })
+            }
             _ => unreachable!(),
         }
     }
 
+    fn parse_sink_key(&mut self) -> Result<SinkKey, ParserError> {
+        self.expect_keyword(KEY)?;
+        let key_columns = self.parse_parenthesized_column_list(Mandatory)?;
+        let not_enforced = if self.peek_keywords(&[NOT, ENFORCED]) {
+            let _ = self.expect_keywords(&[NOT, ENFORCED])?;
+            true
+        } else {
+            false
+        };
+        Ok(SinkKey {
+            key_columns,
+            not_enforced,
+        })
+    }
+
+    fn parse_kafka_sink_header(&mut self) -> Result<KafkaSinkHeader<Raw>, ParserError> {
+        let name = self.parse_identifier()?;
+        self.expect_token(&Token::Eq)?;
+        let value = self.parse_expr()?;
+        Ok(KafkaSinkHeader { name, value })
+    }
+
     fn parse_kafka_consistency(&mut self) -> Result<Option<KafkaConsistency<Raw>>, ParserError> {
         if self.parse_keyword(CONSISTENCY) {
             // We would prefer for all consistency parameters to be
@@ -2423,7 +2525,17 @@ impl<'a> Parser<'a> {
         let materialized = self.parse_keyword(MATERIALIZED);
 
         let object_type = match self.parse_one_of_keywords(&[
-            DATABASE, INDEX, ROLE, SCHEMA, SINK, SOURCE, TABLE, TYPE, USER, VIEW,
+            DATABASE,
+            INDEX,
+            ROLE,
+            SCHEMA,
+            SINK,
+            SOURCE,
+            SUBSCRIPTION,
+            TABLE,
+            TYPE,
+            USER,
+            VIEW,
         ]) {
             Some(DATABASE) => {
                 let if_exists = self.parse_if_exists()?;
@@ -2438,6 +2550,14 @@ impl<'a> Parser<'a> {
                     restrict,
                 }));
             }
+            Some(SUBSCRIPTION) => {
+                let if_exists = self.parse_if_exists()?;
+                let name = self.parse_identifier()?;
+                return Ok(Statement::DropSubscription(DropSubscriptionStatement {
+                    if_exists,
+                    name,
+                }));
+            }
             Some(INDEX) => ObjectType::Index,
             Some(ROLE) | Some(USER) => ObjectType::Role,
             Some(SCHEMA) => ObjectType::Schema,
@@ -2450,7 +2570,7 @@ impl<'a> Parser<'a> {
                 return self.expected(
                     self.peek_pos(),
                     "DATABASE, INDEX, ROLE, SCHEMA, SINK, SOURCE, \
-                     TABLE, TYPE, USER, VIEW after DROP",
+                     SUBSCRIPTION, TABLE, TYPE, USER, VIEW after DROP",
                     self.peek_token(),
                 );
             }
@@ -2739,7 +2859,19 @@ impl<'a> Parser<'a> {
         let if_exists = self.parse_if_exists()?;
         let name = self.parse_object_name()?;
 
-        // We support `ALTER INDEX ... {RESET, SET} and `ALTER <object type> RENAME
+        // We support `ALTER INDEX ... {RESET, SET}`, `ALTER SOURCE ... REFRESH
+        // SCHEMA`, and `ALTER <object type> RENAME`
+        if object_type == ObjectType::Source && self.parse_keyword(REFRESH) {
+            self.expect_keyword(SCHEMA)?;
+            // The seed is always fetched from the schema registry during
+            // purification, so users cannot specify one.
+            return Ok(Statement::AlterSource(AlterSourceStatement {
+                source_name: name,
+                if_exists,
+                action: AlterSourceAction::RefreshSchema { seed: None },
+            }));
+        }
+
         if object_type == ObjectType::Index {
             let action = match self.parse_one_of_keywords(&[RESET, SET]) {
                 Some(RESET) => {
@@ -4266,35 +4398,73 @@ impl<'a> Parser<'a> {
         } else {
             TailRelation::Name(self.parse_object_name()?)
         };
+        let envelope = if self.parse_keyword(ENVELOPE) {
+            Some(self.parse_tail_envelope()?)
+        } else {
+            None
+        };
         let options = self.parse_opt_with_options()?;
         let as_of = self.parse_optional_as_of()?;
         Ok(Statement::Tail(TailStatement {
             relation,
+            envelope,
             options,
             as_of,
         }))
     }
 
+    /// Parses the envelope of a `TAIL` statement, assuming that the `ENVELOPE`
+    /// token has already been consumed.
+    fn parse_tail_envelope(&mut self) -> Result<TailEnvelope, ParserError> {
+        let upsert = match self.expect_one_of_keywords(&[UPSERT, DEBEZIUM])? {
+            UPSERT => true,
+            DEBEZIUM => false,
+            _ => unreachable!(),
+        };
+        let key_columns = if self.consume_token(&Token::LParen) {
+            self.expect_keyword(KEY)?;
+            let key_columns = self.parse_parenthesized_column_list(Mandatory)?;
+            self.expect_token(&Token::RParen)?;
+            key_columns
+        } else if upsert {
+            return self.expected(self.peek_pos(), "(KEY (...))", self.peek_token());
+        } else {
+            vec![]
+        };
+        Ok(if upsert {
+            TailEnvelope::Upsert { key_columns }
+        } else {
+            TailEnvelope::Debezium { key_columns }
+        })
+    }
+
     /// Parse an `EXPLAIN` statement, assuming that the `EXPLAIN` token
     /// has already been consumed.
     fn parse_explain(&mut self) -> Result<Statement<Raw>, ParserError> {
         // (TYPED)?
         let typed = self.parse_keyword(TYPED);
         let mut timing = false;
+        let mut statistics = false;
 
-        // options: ( '(' TIMING (true|false) ')' )?
+        // options: ( '(' { TIMING | STATISTICS } (true|false) [, ...] ')' )?
         if let Some(Token::LParen) = self.peek_token() {
             // Check whether a valid option is after the parentheses, since the
             // parentheses may belong to the actual query to be explained.
             match self.peek_nth_token(1) {
-                Some(Token::Keyword(TIMING)) => {
+                Some(Token::Keyword(TIMING)) | Some(Token::Keyword(STATISTICS)) => {
                     self.next_token(); // Consume the LParen
-                    self.parse_comma_separated(|s| match s.expect_one_of_keywords(&[TIMING])? {
-                        TIMING => {
-                            timing = s.parse_boolean_value()?;
-                            Ok(())
+                    self.parse_comma_separated(|s| {
+                        match s.expect_one_of_keywords(&[TIMING, STATISTICS])? {
+                            TIMING => {
+                                timing = s.parse_boolean_value()?;
+                                Ok(())
+                            }
+                            STATISTICS => {
+                                statistics = s.parse_boolean_value()?;
+                                Ok(())
+                            }
+                            _ => unreachable!(),
                         }
-                        _ => unreachable!(),
                     })?;
                     self.expect_token(&Token::RParen)?;
                 }
@@ -4302,6 +4472,27 @@ impl<'a> Parser<'a> {
             }
         }
 
+        let options = ExplainOptions {
+            typed,
+            timing,
+            statistics,
+        };
+
+        // ANALYZE (INDEX | SINK | VIEW) object_name
+        if self.parse_keyword(ANALYZE) {
+            let explainee = match self.expect_one_of_keywords(&[INDEX, SINK, VIEW])? {
+                INDEX => Explainee::Index(self.parse_object_name()?),
+                SINK => Explainee::Sink(self.parse_object_name()?),
+                VIEW => Explainee::View(self.parse_object_name()?),
+                _ => unreachable!(),
+            };
+            return Ok(Statement::Explain(ExplainStatement {
+                stage: ExplainStage::Analyze,
+                explainee,
+                options,
+            }));
+        }
+
         // (RAW | DECORRELATED | OPTIMIZED | PHYSICAL)? PLAN
         let stage = match self.parse_one_of_keywords(&[
             RAW,
@@ -4351,7 +4542,6 @@ impl<'a> Parser<'a> {
             Explainee::Query(self.parse_query()?)
         };
 
-        let options = ExplainOptions { typed, timing };
         Ok(Statement::Explain(ExplainStatement {
             stage,
             explainee,
@@ -1494,7 +1494,7 @@ macro_rules! impl_def {
     // Return type can be automatically determined as a function of the
     // parameters.
     ($params:expr, $op:expr, $oid:expr) => {{
-        let pcx = crate::plan::PlanContext::new(chrono::MIN_DATETIME, false);
+        let pcx = crate::plan::PlanContext::new(chrono::MIN_DATETIME, false, false);
         let scx = StatementContext::new(None, &crate::catalog::DummyCatalog);
         // This lifetime is compatible with more functions.
         let qcx = QueryContext::root(&scx, QueryLifetime::OneShot(&pcx));
@@ -2534,6 +2534,37 @@ lazy_static! {
             "current_timestamp" => Scalar {
                 params!() => Operation::nullary(|ecx| plan_current_timestamp(ecx, "current_timestamp")), oid::FUNC_CURRENT_TIMESTAMP_OID;
             },
+            // `hop`, `session` and `tumble` only produce the windows that are
+            // open as of `mz_logical_timestamp()`. Their temporal filters
+            // retract each window once it closes, which bounds the state of
+            // any reduction over the windows.
+            //
+            // `hop` starts its windows at multiples of the hop, from the first
+            // one after `$1 - $3` up to `$1`. The `$1 < window_start + $3`
+            // check drops the extra window that `date_bin` yields when `$1 - $3`
+            // is a multiple of the hop before the origin.
+            "hop" => Table {
+                // (timestamp, hop, width)
+                params!(Timestamp, Interval, Interval) => sql_impl_table_func("
+                    SELECT window_start, window_start + $3 AS window_end
+                    FROM pg_catalog.generate_series(
+                        pg_catalog.date_bin($2, $1 - $3, '1970-01-01') + $2, $1, $2
+                    ) AS w(window_start)
+                    WHERE $1 < window_start + $3
+                      AND mz_catalog.mz_logical_timestamp() >= extract(epoch FROM window_start) * 1000
+                      AND mz_catalog.mz_logical_timestamp() < extract(epoch FROM window_start + $3) * 1000
+                ") => ReturnType::set_of(RecordAny), oid::FUNC_MZ_HOP_TS_OID;
+                // (timestamp, hop, width)
+                params!(TimestampTz, Interval, Interval) => sql_impl_table_func("
+                    SELECT window_start, window_start + $3 AS window_end
+                    FROM pg_catalog.generate_series(
+                        pg_catalog.date_bin($2, $1 - $3, '1970-01-01') + $2, $1, $2
+                    ) AS w(window_start)
+                    WHERE $1 < window_start + $3
+                      AND mz_catalog.mz_logical_timestamp() >= extract(epoch FROM window_start) * 1000
+                      AND mz_catalog.mz_logical_timestamp() < extract(epoch FROM window_start + $3) * 1000
+                ") => ReturnType::set_of(RecordAny), oid::FUNC_MZ_HOP_TSTZ_OID;
+            },
             "list_agg" => Aggregate {
                 params!(Any) => Operation::unary_ordered(|ecx, e, order_by| {
                     if let ScalarType::Char {.. }  = ecx.scalar_type(&e) {
@@ -2628,6 +2659,31 @@ lazy_static! {
                     })
                 }), oid::FUNC_REPEAT_OID;
             },
+            "session" => Table {
+                // (timestamps, gap)
+                params!(ListAny, Interval) => sql_impl_table_func("
+                    SELECT session_start, session_end
+                    FROM mz_internal.mz_sessions($1, $2)
+                    WHERE mz_catalog.mz_logical_timestamp() >= extract(epoch FROM session_start) * 1000
+                      AND mz_catalog.mz_logical_timestamp() < extract(epoch FROM session_end) * 1000
+                ") => ReturnType::set_of(RecordAny), oid::FUNC_MZ_SESSION_OID;
+            },
+            "tumble" => Table {
+                // (timestamp, width)
+                params!(Timestamp, Interval) => sql_impl_table_func("
+                    SELECT window_start, window_start + $2 AS window_end
+                    FROM (SELECT pg_catalog.date_bin($2, $1, '1970-01-01') AS window_start) AS w
+                    WHERE mz_catalog.mz_logical_timestamp() >= extract(epoch FROM window_start) * 1000
+                      AND mz_catalog.mz_logical_timestamp() < extract(epoch FROM window_start + $2) * 1000
+                ") => ReturnType::set_of(RecordAny), oid::FUNC_MZ_TUMBLE_TS_OID;
+                // (timestamp, width)
+                params!(TimestampTz, Interval) => sql_impl_table_func("
+                    SELECT window_start, window_start + $2 AS window_end
+                    FROM (SELECT pg_catalog.date_bin($2, $1, '1970-01-01') AS window_start) AS w
+                    WHERE mz_catalog.mz_logical_timestamp() >= extract(epoch FROM window_start) * 1000
+                      AND mz_catalog.mz_logical_timestamp() < extract(epoch FROM window_start + $2) * 1000
+                ") => ReturnType::set_of(RecordAny), oid::FUNC_MZ_TUMBLE_TSTZ_OID;
+            },
             "unnest" => Table {
                 vec![ArrayAny] => Operation::unary(move |ecx, e| {
                     let el_typ = ecx.scalar_type(&e).unwrap_array_element_type().clone();
@@ -2710,6 +2766,24 @@ lazy_static! {
             "mz_session_id" => Scalar {
                 params!() => Operation::nullary(mz_session_id), oid::FUNC_MZ_SESSION_ID_OID;
             },
+            "mz_sessions" => Table {
+                params!(ListAny, Interval) => Operation::binary(move |ecx, timestamps, gap| {
+                    let el_typ = ecx.scalar_type(&timestamps).unwrap_list_element_type().clone();
+                    if !matches!(el_typ, Timestamp | TimestampTz) {
+                        sql_bail!(
+                            "session requires a list of timestamps, not {}",
+                            ecx.humanize_scalar_type(&ecx.scalar_type(&timestamps))
+                        );
+                    }
+                    Ok(TableFuncPlan {
+                        expr: HirRelationExpr::CallTable {
+                            func: TableFunc::Sessions { el_typ },
+                            exprs: vec![timestamps, gap],
+                        },
+                        column_names: vec!["session_start".into(), "session_end".into()],
+                    })
+                }) => ReturnType::set_of(RecordAny), oid::FUNC_MZ_SESSIONS_OID;
+            },
             "mz_sleep" => Scalar {
                 params!(Float64) => UnaryFunc::Sleep(func::Sleep), oid::FUNC_MZ_SLEEP_OID;
             },
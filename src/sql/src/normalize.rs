@@ -24,7 +24,7 @@ use mz_repr::ColumnName;
 use mz_sql_parser::ast::display::AstDisplay;
 use mz_sql_parser::ast::visit_mut::{self, VisitMut};
 use mz_sql_parser::ast::{
-    AstInfo, CreateIndexStatement, CreateSinkStatement, CreateSourceStatement,
+    AstInfo, CreateIndexStatement, CreateSinkConnector, CreateSinkStatement, CreateSourceStatement,
     CreateTableStatement, CreateTypeStatement, CreateViewStatement, Function, FunctionArgs, Ident,
     IfExistsBehavior, Op, Query, Raw, SqlOption, Statement, TableFactor, TableFunction,
     UnresolvedObjectName, Value, ViewDefinition,
@@ -312,7 +312,7 @@ pub fn create_statement(
         Statement::CreateSink(CreateSinkStatement {
             name,
             from,
-            connector: _,
+            connector,
             with_options: _,
             format: _,
             envelope: _,
@@ -322,6 +322,23 @@ pub fn create_statement(
         }) => {
             *name = allocate_name(name)?;
             *from = resolve_item(from)?;
+            if let CreateSinkConnector::Kafka {
+                partition_by,
+                headers,
+                ..
+            } = connector
+            {
+                let mut normalizer = QueryNormalizer::new(scx);
+                if let Some(partition_by) = partition_by {
+                    normalizer.visit_expr_mut(partition_by);
+                }
+                for header in headers {
+                    normalizer.visit_expr_mut(&mut header.value);
+                }
+                if let Some(err) = normalizer.err {
+                    return Err(err.into());
+                }
+            }
             *if_not_exists = false;
         }
 
@@ -426,6 +443,17 @@ macro_rules! with_option_type {
             _ => ::anyhow::bail!("expected Interval"),
         }
     };
+    ($name:ident, u64) => {
+        match $name {
+            Some(crate::ast::WithOptionValue::Value(crate::ast::Value::Number(value))) => {
+                match value.parse::<u64>() {
+                    Ok(value) => value,
+                    Err(_) => ::anyhow::bail!("expected u64"),
+                }
+            }
+            _ => ::anyhow::bail!("expected u64"),
+        }
+    };
 }
 
 /// Ensures that the given set of options are empty, useful for validating that
@@ -461,6 +489,7 @@ pub(crate) fn ensure_empty_options<V>(
 ///   option which will be interpreted as true: (`WITH (name)`.
 /// - `Interval`: expects either a SQL interval or string that can be parsed as
 ///   an interval.
+/// - `u64`: expects a SQL number that is a non-negative integer.
 macro_rules! with_options {
   (struct $name:ident {
         $($field_name:ident: $field_type:ident,)*
@@ -34,7 +34,7 @@ use enum_kinds::EnumKind;
 use serde::{Deserialize, Serialize};
 
 use mz_dataflow_types::{
-    sinks::SinkConnectorBuilder, sinks::SinkEnvelope, sources::SourceConnector,
+    sinks::SinkConnectorBuilder, sinks::SinkEnvelope, sinks::TailEnvelope, sources::SourceConnector,
 };
 use mz_expr::{GlobalId, MirRelationExpr, RowSetFinishing};
 use mz_ore::now::{self, NOW_ZERO};
@@ -85,6 +85,7 @@ pub enum Plan {
     DropSchema(DropSchemaPlan),
     DropRoles(DropRolesPlan),
     DropItems(DropItemsPlan),
+    DropSubscription(DropSubscriptionPlan),
     EmptyQuery,
     ShowAllVariables,
     ShowVariable(ShowVariablePlan),
@@ -97,6 +98,7 @@ pub enum Plan {
     SendRows(SendRowsPlan),
     CopyFrom(CopyFromPlan),
     Explain(ExplainPlan),
+    ExplainAnalyze(ExplainAnalyzePlan),
     SendDiffs(SendDiffsPlan),
     Insert(InsertPlan),
     AlterNoop(AlterNoopPlan),
@@ -104,6 +106,7 @@ pub enum Plan {
     AlterIndexResetOptions(AlterIndexResetOptionsPlan),
     AlterIndexEnable(AlterIndexEnablePlan),
     AlterItemRename(AlterItemRenamePlan),
+    AlterSourceRefreshSchema(AlterSourceRefreshSchemaPlan),
     Declare(DeclarePlan),
     Fetch(FetchPlan),
     Close(ClosePlan),
@@ -212,6 +215,12 @@ pub struct DropItemsPlan {
     pub ty: ObjectType,
 }
 
+#[derive(Debug)]
+pub struct DropSubscriptionPlan {
+    pub name: String,
+    pub if_exists: bool,
+}
+
 #[derive(Debug)]
 pub struct ShowVariablePlan {
     pub name: String,
@@ -239,6 +248,16 @@ pub struct TailPlan {
     pub ts: Option<Timestamp>,
     pub copy_to: Option<CopyFormat>,
     pub emit_progress: bool,
+    pub subscription: Option<TailSubscription>,
+    pub envelope: Option<TailEnvelope>,
+}
+
+/// A durable, named position in a `TAIL` that a client can resume from.
+#[derive(Debug)]
+pub struct TailSubscription {
+    pub name: String,
+    /// The maximum amount of history the subscription may retain.
+    pub max_retention: Duration,
 }
 
 #[derive(Debug)]
@@ -271,6 +290,12 @@ pub struct ExplainPlan {
     pub options: ExplainOptions,
 }
 
+#[derive(Debug)]
+pub struct ExplainAnalyzePlan {
+    /// The index, sink, or indexed view whose running dataflow to explain.
+    pub id: GlobalId,
+}
+
 #[derive(Debug)]
 pub struct SendDiffsPlan {
     pub id: GlobalId,
@@ -323,6 +348,14 @@ pub struct AlterItemRenamePlan {
     pub object_type: ObjectType,
 }
 
+#[derive(Debug)]
+pub struct AlterSourceRefreshSchemaPlan {
+    pub id: GlobalId,
+    /// The source as planned with the refreshed schemas. Its description
+    /// extends the description of the existing source with nullable columns.
+    pub source: Source,
+}
+
 #[derive(Debug)]
 pub struct DeclarePlan {
     pub name: String,
@@ -381,7 +414,7 @@ pub struct Sink {
     pub create_sql: String,
     pub from: GlobalId,
     pub connector_builder: SinkConnectorBuilder,
-    pub envelope: SinkEnvelope,
+    pub envelope: Option<SinkEnvelope>,
     pub depends_on: Vec<GlobalId>,
 }
 
@@ -392,6 +425,7 @@ pub struct View {
     pub column_names: Vec<ColumnName>,
     pub temporary: bool,
     pub depends_on: Vec<GlobalId>,
+    pub outer_join_anti_joins: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -400,6 +434,11 @@ pub struct Index {
     pub on: GlobalId,
     pub keys: Vec<mz_expr::MirScalarExpr>,
     pub depends_on: Vec<GlobalId>,
+    /// Whether the index may spill its state to disk.
+    pub spill: bool,
+    /// The number of updates at which the batches of the index spill, if
+    /// not the default.
+    pub spill_threshold: Option<usize>,
 }
 
 #[derive(Clone, Debug)]
@@ -491,23 +530,30 @@ impl Params {
 pub struct PlanContext {
     pub wall_time: DateTime<Utc>,
     pub qgm_optimizations: bool,
+    pub outer_join_anti_joins: bool,
 }
 
 impl PlanContext {
-    pub fn new(wall_time: DateTime<Utc>, qgm_optimizations: bool) -> Self {
+    pub fn new(
+        wall_time: DateTime<Utc>,
+        qgm_optimizations: bool,
+        outer_join_anti_joins: bool,
+    ) -> Self {
         Self {
             wall_time,
             qgm_optimizations,
+            outer_join_anti_joins,
         }
     }
 
-    /// Return a PlanContext with zero values. This should only be used when
-    /// planning is required but unused (like in `plan_create_table()`) or in
-    /// tests.
+    /// Return a PlanContext with zero values and the default feature flags.
+    /// This should only be used when planning is required but unused (like in
+    /// `plan_create_table()`) or in tests.
     pub fn zero() -> Self {
         PlanContext {
             wall_time: now::to_datetime(NOW_ZERO()),
             qgm_optimizations: false,
+            outer_join_anti_joins: false,
         }
     }
 }
@@ -50,6 +50,7 @@ use crate::plan::expr::{
     AggregateExpr, ColumnOrder, ColumnRef, HirRelationExpr, HirScalarExpr, JoinKind, WindowExprType,
 };
 use crate::plan::transform_expr;
+use crate::plan::OptimizerConfig;
 
 /// Maps a leveled column reference to a specific column.
 ///
@@ -130,7 +131,7 @@ struct CteDesc {
 impl HirRelationExpr {
     /// Rewrite `self` into a `mz_expr::MirRelationExpr`.
     /// This requires rewriting all correlated subqueries (nested `HirRelationExpr`s) into flat queries
-    pub fn lower(self) -> mz_expr::MirRelationExpr {
+    pub fn lower(self, config: &OptimizerConfig) -> mz_expr::MirRelationExpr {
         match self {
             // We directly rewrite a Constant into the corresponding `MirRelationExpr::Constant`
             // to ensure that the downstream optimizer can easily bypass most
@@ -151,7 +152,13 @@ impl HirRelationExpr {
                 mz_expr::MirRelationExpr::constant(vec![vec![]], RelationType::new(vec![])).let_in(
                     &mut id_gen,
                     |id_gen, get_outer| {
-                        other.applied_to(id_gen, get_outer, &ColumnMap::empty(), &mut CteMap::new())
+                        other.applied_to(
+                            id_gen,
+                            get_outer,
+                            &ColumnMap::empty(),
+                            &mut CteMap::new(),
+                            config,
+                        )
                     },
                 )
             }
@@ -176,6 +183,7 @@ impl HirRelationExpr {
         get_outer: mz_expr::MirRelationExpr,
         col_map: &ColumnMap,
         cte_map: &mut CteMap,
+        config: &OptimizerConfig,
     ) -> mz_expr::MirRelationExpr {
         maybe_grow(|| {
             use self::HirRelationExpr::*;
@@ -260,7 +268,8 @@ impl HirRelationExpr {
                     value,
                     body,
                 } => {
-                    let value = value.applied_to(id_gen, get_outer.clone(), col_map, cte_map);
+                    let value =
+                        value.applied_to(id_gen, get_outer.clone(), col_map, cte_map, config);
                     value.let_in(id_gen, |id_gen, get_value| {
                         let (new_id, typ) = if let mz_expr::MirRelationExpr::Get {
                             id: mz_expr::Id::Local(id),
@@ -285,7 +294,7 @@ impl HirRelationExpr {
                                 outer_relation: get_outer.clone(),
                             },
                         );
-                        let body = body.applied_to(id_gen, get_outer, col_map, cte_map);
+                        let body = body.applied_to(id_gen, get_outer, col_map, cte_map, config);
                         if let Some(old_value) = old_value {
                             cte_map.insert(id, old_value);
                         } else {
@@ -297,7 +306,8 @@ impl HirRelationExpr {
                 Project { input, outputs } => {
                     // Projections should be applied to the decorrelated `inner`, and to its columns,
                     // which means rebasing `outputs` to start `get_outer.arity()` columns later.
-                    let input = input.applied_to(id_gen, get_outer.clone(), col_map, cte_map);
+                    let input =
+                        input.applied_to(id_gen, get_outer.clone(), col_map, cte_map, config);
                     let outputs = (0..get_outer.arity())
                         .chain(outputs.into_iter().map(|i| get_outer.arity() + i))
                         .collect::<Vec<_>>();
@@ -305,7 +315,7 @@ impl HirRelationExpr {
                 }
                 Map { input, mut scalars } => {
                     // Scalar expressions may contain correlated subqueries. We must be cautious!
-                    let mut input = input.applied_to(id_gen, get_outer, col_map, cte_map);
+                    let mut input = input.applied_to(id_gen, get_outer, col_map, cte_map, config);
 
                     // Lower subqueries in maximally sized batches, such as no subquery in the current
                     // batch depends on columns from the same batch.
@@ -330,7 +340,7 @@ impl HirRelationExpr {
 
                         let scalars = scalars.drain(0..end_idx).collect_vec();
                         let (with_subqueries, subquery_map) = HirScalarExpr::lower_subqueries(
-                            &scalars, id_gen, col_map, cte_map, input,
+                            &scalars, id_gen, col_map, cte_map, config, input,
                         );
                         input = with_subqueries;
 
@@ -348,6 +358,7 @@ impl HirRelationExpr {
                                 id_gen,
                                 col_map,
                                 cte_map,
+                                config,
                                 &mut input,
                                 &Some(&subquery_map),
                             );
@@ -392,11 +403,11 @@ impl HirRelationExpr {
                     // Filter expressions may contain correlated subqueries.
                     // We extend `get_outer` with sufficient values to determine the value of the predicate,
                     // then filter the results, then strip off any columns that were added for this purpose.
-                    let mut input = input.applied_to(id_gen, get_outer, col_map, cte_map);
+                    let mut input = input.applied_to(id_gen, get_outer, col_map, cte_map, config);
                     for predicate in predicates {
                         let old_arity = input.arity();
-                        let predicate =
-                            predicate.applied_to(id_gen, col_map, cte_map, &mut input, &None);
+                        let predicate = predicate
+                            .applied_to(id_gen, col_map, cte_map, config, &mut input, &None);
                         let new_arity = input.arity();
                         input = input.filter(vec![predicate]);
                         if old_arity != new_arity {
@@ -423,7 +434,7 @@ impl HirRelationExpr {
 
                     assert!(kind.can_be_correlated());
 
-                    let left = left.applied_to(id_gen, get_outer, col_map, cte_map);
+                    let left = left.applied_to(id_gen, get_outer, col_map, cte_map, config);
                     left.let_in(id_gen, |id_gen, get_left| {
                         let apply_requires_distinct_outer = false;
                         let mut join = branch(
@@ -434,13 +445,13 @@ impl HirRelationExpr {
                             *right,
                             apply_requires_distinct_outer,
                             |id_gen, right, get_left, col_map, cte_map| {
-                                right.applied_to(id_gen, get_left, col_map, cte_map)
+                                right.applied_to(id_gen, get_left, col_map, cte_map, config)
                             },
                         );
 
                         // Plan the `on` predicate.
                         let old_arity = join.arity();
-                        let on = on.applied_to(id_gen, col_map, cte_map, &mut join, &None);
+                        let on = on.applied_to(id_gen, col_map, cte_map, config, &mut join, &None);
                         join = join.filter(vec![on]);
                         let new_arity = join.arity();
                         if old_arity != new_arity {
@@ -481,13 +492,18 @@ impl HirRelationExpr {
                     // against the records present in the left and right (decorrelated) inputs,
                     // depending on the type of join.
                     let oa = get_outer.arity();
-                    let left = left.applied_to(id_gen, get_outer.clone(), col_map, cte_map);
+                    let left = left.applied_to(id_gen, get_outer.clone(), col_map, cte_map, config);
                     let lt = left.typ();
                     let la = left.arity() - oa;
                     left.let_in(id_gen, |id_gen, get_left| {
                         let right_col_map = col_map.enter_scope(0);
-                        let right =
-                            right.applied_to(id_gen, get_outer.clone(), &right_col_map, cte_map);
+                        let right = right.applied_to(
+                            id_gen,
+                            get_outer.clone(),
+                            &right_col_map,
+                            cte_map,
+                            config,
+                        );
                         let rt = right.typ();
                         let ra = right.arity() - oa;
                         right.let_in(id_gen, |id_gen, get_right| {
@@ -502,7 +518,14 @@ impl HirRelationExpr {
                                     .collect(),
                             );
                             let old_arity = product.arity();
-                            let on = on.applied_to(id_gen, col_map, cte_map, &mut product, &None);
+                            let on = on.applied_to(
+                                id_gen,
+                                col_map,
+                                cte_map,
+                                config,
+                                &mut product,
+                                &None,
+                            );
 
                             // Attempt an efficient equijoin implementation, in which outer joins are
                             // more efficiently rendered than in general. This can return `None` if
@@ -515,6 +538,7 @@ impl HirRelationExpr {
                                 kind.clone(),
                                 oa,
                                 id_gen,
+                                config,
                             ) {
                                 return joined;
                             }
@@ -583,11 +607,18 @@ impl HirRelationExpr {
                             get_outer.clone(),
                             col_map,
                             cte_map,
+                            config,
                         )),
                         inputs: inputs
                             .into_iter()
                             .map(|input| {
-                                input.applied_to(id_gen, get_outer.clone(), col_map, cte_map)
+                                input.applied_to(
+                                    id_gen,
+                                    get_outer.clone(),
+                                    col_map,
+                                    cte_map,
+                                    config,
+                                )
                             })
                             .collect(),
                     }
@@ -601,7 +632,8 @@ impl HirRelationExpr {
                     // Reduce may contain expressions with correlated subqueries.
                     // In addition, here an empty reduction key signifies that we need to supply default values
                     // in the case that there are no results (as in a SQL aggregation without an explicit GROUP BY).
-                    let mut input = input.applied_to(id_gen, get_outer.clone(), col_map, cte_map);
+                    let mut input =
+                        input.applied_to(id_gen, get_outer.clone(), col_map, cte_map, config);
                     let applied_group_key = (0..get_outer.arity())
                         .chain(group_key.iter().map(|i| get_outer.arity() + i))
                         .collect();
@@ -632,7 +664,7 @@ impl HirRelationExpr {
                 Distinct { input } => {
                     // Distinct is uncomplicated.
                     input
-                        .applied_to(id_gen, get_outer, col_map, cte_map)
+                        .applied_to(id_gen, get_outer, col_map, cte_map, config)
                         .distinct()
                 }
                 TopK {
@@ -643,7 +675,8 @@ impl HirRelationExpr {
                     offset,
                 } => {
                     // TopK is uncomplicated, except that we must group by the columns of `get_outer` as well.
-                    let input = input.applied_to(id_gen, get_outer.clone(), col_map, cte_map);
+                    let input =
+                        input.applied_to(id_gen, get_outer.clone(), col_map, cte_map, config);
                     let applied_group_key = (0..get_outer.arity())
                         .chain(group_key.iter().map(|i| get_outer.arity() + i))
                         .collect();
@@ -659,17 +692,17 @@ impl HirRelationExpr {
                 Negate { input } => {
                     // Negate is uncomplicated.
                     input
-                        .applied_to(id_gen, get_outer, col_map, cte_map)
+                        .applied_to(id_gen, get_outer, col_map, cte_map, config)
                         .negate()
                 }
                 Threshold { input } => {
                     // Threshold is uncomplicated.
                     input
-                        .applied_to(id_gen, get_outer, col_map, cte_map)
+                        .applied_to(id_gen, get_outer, col_map, cte_map, config)
                         .threshold()
                 }
                 DeclareKeys { input, keys } => input
-                    .applied_to(id_gen, get_outer, col_map, cte_map)
+                    .applied_to(id_gen, get_outer, col_map, cte_map, config)
                     .declare_keys(keys),
             }
         })
@@ -692,6 +725,7 @@ impl HirScalarExpr {
         id_gen: &mut mz_ore::id_gen::IdGen,
         col_map: &ColumnMap,
         cte_map: &mut CteMap,
+        config: &OptimizerConfig,
         inner: &mut mz_expr::MirRelationExpr,
         subquery_map: &Option<&HashMap<HirScalarExpr, usize>>,
     ) -> mz_expr::MirScalarExpr {
@@ -712,7 +746,14 @@ impl HirScalarExpr {
                 CallNullary(func) => SS::CallNullary(func),
                 CallUnary { func, expr } => SS::CallUnary {
                     func,
-                    expr: Box::new(expr.applied_to(id_gen, col_map, cte_map, inner, subquery_map)),
+                    expr: Box::new(expr.applied_to(
+                        id_gen,
+                        col_map,
+                        cte_map,
+                        config,
+                        inner,
+                        subquery_map,
+                    )),
                 },
                 CallBinary { func, expr1, expr2 } => SS::CallBinary {
                     func,
@@ -720,6 +761,7 @@ impl HirScalarExpr {
                         id_gen,
                         col_map,
                         cte_map,
+                        config,
                         inner,
                         subquery_map,
                     )),
@@ -727,6 +769,7 @@ impl HirScalarExpr {
                         id_gen,
                         col_map,
                         cte_map,
+                        config,
                         inner,
                         subquery_map,
                     )),
@@ -760,7 +803,8 @@ impl HirScalarExpr {
                     // and we would benefit from not introducing the complexity.
 
                     let inner_arity = inner.arity();
-                    let cond_expr = cond.applied_to(id_gen, col_map, cte_map, inner, subquery_map);
+                    let cond_expr =
+                        cond.applied_to(id_gen, col_map, cte_map, config, inner, subquery_map);
 
                     // Defensive copies, in case we mangle these in decorrelation.
                     let inner_clone = inner.clone();
@@ -768,8 +812,10 @@ impl HirScalarExpr {
                     let else_clone = els.clone();
 
                     let cond_arity = inner.arity();
-                    let then_expr = then.applied_to(id_gen, col_map, cte_map, inner, subquery_map);
-                    let else_expr = els.applied_to(id_gen, col_map, cte_map, inner, subquery_map);
+                    let then_expr =
+                        then.applied_to(id_gen, col_map, cte_map, config, inner, subquery_map);
+                    let else_expr =
+                        els.applied_to(id_gen, col_map, cte_map, config, inner, subquery_map);
 
                     if cond_arity == inner.arity() {
                         // If no additional columns were added, we simply return the
@@ -792,6 +838,7 @@ impl HirScalarExpr {
                                 id_gen,
                                 col_map,
                                 cte_map,
+                                config,
                                 &mut then_inner,
                                 subquery_map,
                             );
@@ -817,6 +864,7 @@ impl HirScalarExpr {
                                 id_gen,
                                 col_map,
                                 cte_map,
+                                config,
                                 &mut else_inner,
                                 subquery_map,
                             );
@@ -851,6 +899,7 @@ impl HirScalarExpr {
                         inner.take_dangerous(),
                         col_map,
                         cte_map,
+                        config,
                         *expr,
                         apply_requires_distinct_outer,
                     );
@@ -864,6 +913,7 @@ impl HirScalarExpr {
                         inner.take_dangerous(),
                         col_map,
                         cte_map,
+                        config,
                         *expr,
                         apply_requires_distinct_outer,
                     );
@@ -888,6 +938,7 @@ impl HirScalarExpr {
                                                     id_gen,
                                                     col_map,
                                                     cte_map,
+                                                    config,
                                                     &mut get_inner,
                                                     subquery_map,
                                                 )
@@ -912,6 +963,7 @@ impl HirScalarExpr {
                                                 id_gen,
                                                 col_map,
                                                 cte_map,
+                                                config,
                                                 &mut get_inner,
                                                 subquery_map,
                                             );
@@ -1064,6 +1116,7 @@ impl HirScalarExpr {
         id_gen: &mut mz_ore::id_gen::IdGen,
         col_map: &ColumnMap,
         cte_map: &mut CteMap,
+        config: &OptimizerConfig,
         inner: mz_expr::MirRelationExpr,
     ) -> (mz_expr::MirRelationExpr, HashMap<HirScalarExpr, usize>) {
         let mut subquery_map = HashMap::new();
@@ -1086,6 +1139,7 @@ impl HirScalarExpr {
                                 distinct_inner.clone(),
                                 col_map,
                                 cte_map,
+                                config,
                                 (**expr).clone(),
                                 apply_requires_distinct_outer,
                             );
@@ -1099,6 +1153,7 @@ impl HirScalarExpr {
                                 distinct_inner.clone(),
                                 col_map,
                                 cte_map,
+                                config,
                                 (**expr).clone(),
                                 apply_requires_distinct_outer,
                             );
@@ -1392,6 +1447,7 @@ fn apply_scalar_subquery(
     outer: mz_expr::MirRelationExpr,
     col_map: &ColumnMap,
     cte_map: &mut CteMap,
+    config: &OptimizerConfig,
     scalar_subquery: HirRelationExpr,
     apply_requires_distinct_outer: bool,
 ) -> mz_expr::MirRelationExpr {
@@ -1405,7 +1461,7 @@ fn apply_scalar_subquery(
         |id_gen, expr, get_inner, col_map, cte_map| {
             let select = expr
                 // compute for every row in get_inner
-                .applied_to(id_gen, get_inner.clone(), col_map, cte_map);
+                .applied_to(id_gen, get_inner.clone(), col_map, cte_map, config);
             let col_type = select.typ().column_types.into_last();
 
             let inner_arity = get_inner.arity();
@@ -1449,6 +1505,7 @@ fn apply_existential_subquery(
     outer: mz_expr::MirRelationExpr,
     col_map: &ColumnMap,
     cte_map: &mut CteMap,
+    config: &OptimizerConfig,
     subquery_expr: HirRelationExpr,
     apply_requires_distinct_outer: bool,
 ) -> mz_expr::MirRelationExpr {
@@ -1462,7 +1519,7 @@ fn apply_existential_subquery(
         |id_gen, expr, get_inner, col_map, cte_map| {
             let exists = expr
                 // compute for every row in get_inner
-                .applied_to(id_gen, get_inner.clone(), col_map, cte_map)
+                .applied_to(id_gen, get_inner.clone(), col_map, cte_map, config)
                 // throw away actual values and just remember whether or not there were __any__ rows
                 .distinct_by((0..get_inner.arity()).collect())
                 // Append true to anything that returned any rows. This
@@ -1487,6 +1544,7 @@ impl AggregateExpr {
         id_gen: &mut mz_ore::id_gen::IdGen,
         col_map: &ColumnMap,
         cte_map: &mut CteMap,
+        config: &OptimizerConfig,
         inner: &mut mz_expr::MirRelationExpr,
     ) -> mz_expr::AggregateExpr {
         let AggregateExpr {
@@ -1497,7 +1555,7 @@ impl AggregateExpr {
 
         mz_expr::AggregateExpr {
             func: func.into_expr(),
-            expr: expr.applied_to(id_gen, col_map, cte_map, inner, &None),
+            expr: expr.applied_to(id_gen, col_map, cte_map, config, inner, &None),
             distinct,
         }
     }
@@ -1511,6 +1569,7 @@ fn attempt_outer_join(
     kind: JoinKind,
     oa: usize,
     id_gen: &mut mz_ore::id_gen::IdGen,
+    config: &OptimizerConfig,
 ) -> Option<mz_expr::MirRelationExpr> {
     use mz_expr::BinaryFunc;
 
@@ -1594,10 +1653,25 @@ fn attempt_outer_join(
 
             // We'll want to re-use the results of the join multiple times.
             join.let_in(id_gen, |id_gen, get_join| {
-                let mut result = get_join.clone();
+                let left_outer = matches!(kind, JoinKind::LeftOuter { .. } | JoinKind::FullOuter);
+                let right_outer = matches!(kind, JoinKind::RightOuter | JoinKind::FullOuter);
+
+                if config.outer_join_anti_joins {
+                    // Find the rows of `left` and `right` that are not matched
+                    // in the inner equijoin by anti-joins with the keys of the
+                    // other side, so that the output of the inner join is only
+                    // read once. This is an experimental rewrite of the plan
+                    // below, which is still rendered with the usual operators.
+                    let left_absent = left_outer
+                        .then(|| unmatched_rows(&get_left, &get_right, &l_keys, &r_keys, oa, la));
+                    let right_absent = right_outer
+                        .then(|| unmatched_rows(&get_right, &get_left, &r_keys, &l_keys, oa, ra));
+                    return pad_unmatched(get_join, left_absent, right_absent, lt, rt, oa);
+                }
 
                 // A collection of keys present in both left and right collections.
                 let both_keys = get_join
+                    .clone()
                     .project((0..oa).chain(l_keys.clone()).collect::<Vec<_>>())
                     .distinct();
 
@@ -1606,7 +1680,8 @@ fn attempt_outer_join(
                 // remains with nulls, and fold them in to `result`.
 
                 both_keys.let_in(id_gen, |_id_gen, get_both| {
-                    if let JoinKind::LeftOuter { .. } | JoinKind::FullOuter = kind {
+                    // Rows in `left` that are not matched in the inner equijoin.
+                    let left_absent = left_outer.then(|| {
                         // Rows in `left` that are matched in the inner equijoin.
                         let left_present = mz_expr::MirRelationExpr::join(
                             vec![get_left.clone(), get_both.clone()],
@@ -1617,24 +1692,11 @@ fn attempt_outer_join(
                                 .collect::<Vec<_>>(),
                         )
                         .project((0..(oa + la)).collect());
+                        left_present.negate().union(get_left.clone())
+                    });
 
-                        // Determine the types of nulls to use as filler.
-                        let right_fill = rt
-                            .column_types
-                            .into_iter()
-                            .skip(oa)
-                            .map(|typ| mz_expr::MirScalarExpr::literal_null(typ.scalar_type))
-                            .collect();
-
-                        // Add to `result` absent elements, filled with typed nulls.
-                        result = left_present
-                            .negate()
-                            .union(get_left.clone())
-                            .map(right_fill)
-                            .union(result);
-                    }
-
-                    if let JoinKind::RightOuter | JoinKind::FullOuter = kind {
+                    // Rows in `right` that are not matched in the inner equijoin.
+                    let right_absent = right_outer.then(|| {
                         // Rows in `right` that are matched in the inner equijoin.
                         let right_present = mz_expr::MirRelationExpr::join(
                             vec![get_right.clone(), get_both],
@@ -1645,34 +1707,160 @@ fn attempt_outer_join(
                                 .collect::<Vec<_>>(),
                         )
                         .project((0..(oa + ra)).collect());
+                        right_present.negate().union(get_right.clone())
+                    });
 
-                        // Determine the types of nulls to use as filler.
-                        let left_fill = lt
-                            .column_types
-                            .into_iter()
-                            .skip(oa)
-                            .map(|typ| mz_expr::MirScalarExpr::literal_null(typ.scalar_type))
-                            .collect();
-
-                        // Add to `result` absent elemetns, prepended with typed nulls.
-                        result = right_present
-                            .negate()
-                            .union(get_right.clone())
-                            .map(left_fill)
-                            // Permute left fill before right values.
-                            .project(
-                                (0..oa)
-                                    .chain(oa + ra..oa + ra + la)
-                                    .chain(oa..oa + ra)
-                                    .collect(),
-                            )
-                            .union(result)
-                    }
-
-                    result
+                    pad_unmatched(get_join, left_absent, right_absent, lt, rt, oa)
                 })
             })
         })
     });
     Some(result)
 }
+
+/// Returns the rows of `get_this` that have no match in `get_other`, in an
+/// equijoin of the `this_keys` columns of the former with the `other_keys`
+/// columns of the latter, within each assignment of values to the first `oa`
+/// columns of both.
+///
+/// The matched rows are found by a semi-join of `get_this` with the distinct
+/// non-null keys of `get_other`, rather than with the keys of the inner join.
+fn unmatched_rows(
+    get_this: &mz_expr::MirRelationExpr,
+    get_other: &mz_expr::MirRelationExpr,
+    this_keys: &[usize],
+    other_keys: &[usize],
+    oa: usize,
+    arity: usize,
+) -> mz_expr::MirRelationExpr {
+    // Keys that contain nulls match nothing under `=`, but would match each
+    // other in a join, so they must not be looked up.
+    let other_keys = get_other
+        .clone()
+        .filter(other_keys.iter().map(|c| {
+            mz_expr::MirScalarExpr::Column(*c)
+                .call_unary(mz_expr::UnaryFunc::IsNull(mz_expr::func::IsNull))
+                .call_unary(mz_expr::UnaryFunc::Not(mz_expr::func::Not))
+        }))
+        .project((0..oa).chain(other_keys.iter().cloned()).collect())
+        .distinct();
+    let matched = mz_expr::MirRelationExpr::join(
+        vec![get_this.clone(), other_keys],
+        (0..oa)
+            .chain(this_keys.iter().cloned())
+            .enumerate()
+            .map(|(i, c)| vec![(0, c), (1, i)])
+            .collect(),
+    )
+    .project((0..(oa + arity)).collect());
+    matched.negate().union(get_this.clone())
+}
+
+/// Folds the rows of `left_absent` and `right_absent`, if present, padded with
+/// typed nulls for the columns of the other side, into the inner join
+/// `get_join` of relations of types `lt` and `rt` that share their first `oa`
+/// columns.
+fn pad_unmatched(
+    get_join: mz_expr::MirRelationExpr,
+    left_absent: Option<mz_expr::MirRelationExpr>,
+    right_absent: Option<mz_expr::MirRelationExpr>,
+    lt: RelationType,
+    rt: RelationType,
+    oa: usize,
+) -> mz_expr::MirRelationExpr {
+    let la = lt.arity() - oa;
+    let ra = rt.arity() - oa;
+    let mut result = get_join;
+
+    if let Some(left_absent) = left_absent {
+        // Determine the types of nulls to use as filler.
+        let right_fill = rt
+            .column_types
+            .into_iter()
+            .skip(oa)
+            .map(|typ| mz_expr::MirScalarExpr::literal_null(typ.scalar_type))
+            .collect();
+
+        // Add to `result` absent elements, filled with typed nulls.
+        result = left_absent.map(right_fill).union(result);
+    }
+
+    if let Some(right_absent) = right_absent {
+        // Determine the types of nulls to use as filler.
+        let left_fill = lt
+            .column_types
+            .into_iter()
+            .skip(oa)
+            .map(|typ| mz_expr::MirScalarExpr::literal_null(typ.scalar_type))
+            .collect();
+
+        // Add to `result` absent elemetns, prepended with typed nulls.
+        result = right_absent
+            .map(left_fill)
+            // Permute left fill before right values.
+            .project(
+                (0..oa)
+                    .chain(oa + ra..oa + ra + la)
+                    .chain(oa..oa + ra)
+                    .collect(),
+            )
+            .union(result)
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use mz_expr::{BinaryFunc, Id, MirRelationExpr, MirScalarExpr};
+    use mz_ore::id_gen::IdGen;
+
+    use super::*;
+
+    /// Returns the number of reads of the inner join in the lowering of a left
+    /// outer join of two relations of two columns on their first columns.
+    fn inner_join_reads(outer_join_anti_joins: bool) -> usize {
+        let typ = RelationType::new(vec![ScalarType::Int32.nullable(true); 2]);
+        let left = MirRelationExpr::global_get(GlobalId::User(1), typ.clone());
+        let right = MirRelationExpr::global_get(GlobalId::User(2), typ);
+        let on = MirScalarExpr::Column(0).call_binary(MirScalarExpr::Column(2), BinaryFunc::Eq);
+        let config = OptimizerConfig {
+            qgm_optimizations: false,
+            outer_join_anti_joins,
+        };
+        let lowered = attempt_outer_join(
+            left,
+            right,
+            on,
+            JoinKind::LeftOuter,
+            0,
+            &mut IdGen::default(),
+            &config,
+        )
+        .expect("equijoins are lowered efficiently");
+        let (join_id, body) = match lowered {
+            MirRelationExpr::Let { id, body, .. } => (id, body),
+            _ => panic!("the inner join is bound"),
+        };
+        let mut reads = 0;
+        body.visit_post(&mut |expr| {
+            if let MirRelationExpr::Get {
+                id: Id::Local(id), ..
+            } = expr
+            {
+                if *id == join_id {
+                    reads += 1;
+                }
+            }
+        });
+        reads
+    }
+
+    #[test]
+    fn anti_joins_do_not_arrange_inner_join() {
+        // Subtracting the matched rows reads the inner join a second time, to
+        // arrange its distinct keys.
+        assert_eq!(inner_join_reads(false), 2);
+        assert_eq!(inner_join_reads(true), 1);
+    }
+}
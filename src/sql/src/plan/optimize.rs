@@ -17,6 +17,7 @@ use super::StatementContext;
 #[derive(Debug)]
 pub struct OptimizerConfig {
     pub qgm_optimizations: bool,
+    pub outer_join_anti_joins: bool,
 }
 
 /// Convert a reference to a [`StatementContext`] to an [`OptimizerConfig`].
@@ -28,9 +29,11 @@ impl<'a> From<&StatementContext<'a>> for OptimizerConfig {
         match scx.pcx() {
             Ok(pcx) => OptimizerConfig {
                 qgm_optimizations: pcx.qgm_optimizations,
+                outer_join_anti_joins: pcx.outer_join_anti_joins,
             },
             Err(..) => OptimizerConfig {
                 qgm_optimizations: false,
+                outer_join_anti_joins: false,
             },
         }
     }
@@ -52,7 +55,7 @@ impl HirRelationExpr {
             model.into()
         } else {
             // directly decorrelate and lower into a MirRelationExpr
-            self.lower()
+            self.lower(config)
         }
     }
 }
@@ -28,23 +28,29 @@ use tracing::debug;
 
 use mz_dataflow_types::{
     sinks::{
-        AvroOcfSinkConnectorBuilder, KafkaSinkConnectorBuilder, KafkaSinkConnectorRetention,
-        KafkaSinkFormat, SinkConnectorBuilder, SinkEnvelope,
+        AvroOcfCodec, AvroOcfSinkConnectorBuilder, HttpSinkConnectorBuilder,
+        KafkaSinkConnectorBuilder, KafkaSinkConnectorRetention, KafkaSinkFormat,
+        ParquetSinkConnectorBuilder, ParquetSinkLocation, PostgresSinkConnectorBuilder,
+        SinkConnectorBuilder, SinkEnvelope,
     },
     sources::{
         encoding::{
             included_column_desc, AvroEncoding, AvroOcfEncoding, ColumnSpec, CsvEncoding,
-            DataEncoding, ProtobufEncoding, RegexEncoding, SourceDataEncoding,
+            DataEncoding, ParquetEncoding, ProtobufEncoding, RegexEncoding, SourceDataEncoding,
         },
         provide_default_metadata, DebeziumDedupProjection, DebeziumEnvelope, DebeziumMode,
         DebeziumSourceProjection, ExternalSourceConnector, FileSourceConnector, IncludedColumnPos,
         KafkaSourceConnector, KeyEnvelope, KinesisSourceConnector, PostgresSourceConnector,
         PubNubSourceConnector, S3SourceConnector, SourceConnector, SourceEnvelope, Timeline,
+        WebhookSourceConnector, WebhookValidation,
     },
 };
-use mz_expr::GlobalId;
+use mz_expr::{GlobalId, MirScalarExpr};
 use mz_interchange::avro::{self, AvroSchemaGenerator};
 use mz_interchange::envelopes;
+use mz_interchange::parquet;
+use mz_interchange::protobuf::ProtobufSchemaGenerator;
+use mz_ore::cast::CastFrom;
 use mz_ore::collections::CollectionExt;
 use mz_ore::str::StrExt;
 use mz_repr::{strconv, ColumnName, RelationDesc, RelationType, ScalarType};
@@ -52,16 +58,17 @@ use mz_sql_parser::ast::{CsrSeedCompiledOrLegacy, SourceIncludeMetadata};
 
 use crate::ast::display::AstDisplay;
 use crate::ast::{
-    AlterIndexAction, AlterIndexStatement, AlterObjectRenameStatement, AvroSchema, ColumnOption,
-    Compression, CreateDatabaseStatement, CreateIndexStatement, CreateRoleOption,
-    CreateRoleStatement, CreateSchemaStatement, CreateSinkConnector, CreateSinkStatement,
-    CreateSourceConnector, CreateSourceFormat, CreateSourceStatement, CreateTableStatement,
-    CreateTypeAs, CreateTypeStatement, CreateViewStatement, CreateViewsDefinitions,
-    CreateViewsStatement, CsrConnectorAvro, CsrConnectorProto, CsrSeedCompiled, CsvColumns,
-    DbzMode, DropDatabaseStatement, DropObjectsStatement, Envelope, Expr, Format, Ident,
-    IfExistsBehavior, KafkaConsistency, KeyConstraint, ObjectType, ProtobufSchema, Raw,
-    SourceIncludeMetadataType, SqlOption, Statement, TableConstraint, UnresolvedObjectName, Value,
-    ViewDefinition, WithOption,
+    AlterIndexAction, AlterIndexStatement, AlterObjectRenameStatement, AlterSourceAction,
+    AlterSourceStatement, AvroSchema, ColumnOption, Compression, CreateDatabaseStatement,
+    CreateIndexStatement, CreateRoleOption, CreateRoleStatement, CreateSchemaStatement,
+    CreateSinkConnector, CreateSinkStatement, CreateSourceConnector, CreateSourceFormat,
+    CreateSourceStatement, CreateTableStatement, CreateTypeAs, CreateTypeStatement,
+    CreateViewStatement, CreateViewsDefinitions, CreateViewsStatement, CsrConnectorAvro,
+    CsrConnectorProto, CsrSeedCompiled, CsvColumns, DbzMode, DropDatabaseStatement,
+    DropObjectsStatement, DropSubscriptionStatement, Envelope, Expr, Format, Ident,
+    IfExistsBehavior, KafkaConsistency, KafkaSinkHeader, KeyConstraint, ObjectType, ProtobufSchema,
+    Raw, SourceIncludeMetadataType, SqlOption, Statement, TableConstraint, UnresolvedObjectName,
+    Value, ViewDefinition, WithOption,
 };
 use crate::catalog::{CatalogItem, CatalogItemType};
 use crate::kafka_util;
@@ -72,11 +79,12 @@ use crate::plan::query::{resolve_names_data_type, QueryLifetime, ResolvedDataTyp
 use crate::plan::statement::{StatementContext, StatementDesc};
 use crate::plan::{
     plan_utils, query, AlterIndexEnablePlan, AlterIndexResetOptionsPlan, AlterIndexSetOptionsPlan,
-    AlterItemRenamePlan, AlterNoopPlan, CreateDatabasePlan, CreateIndexPlan, CreateRolePlan,
-    CreateSchemaPlan, CreateSinkPlan, CreateSourcePlan, CreateTablePlan, CreateTypePlan,
-    CreateViewPlan, CreateViewsPlan, DropDatabasePlan, DropItemsPlan, DropRolesPlan,
-    DropSchemaPlan, HirRelationExpr, Index, IndexOption, IndexOptionName, Params, Plan, Sink,
-    Source, Table, Type, TypeInner, View,
+    AlterItemRenamePlan, AlterNoopPlan, AlterSourceRefreshSchemaPlan, CreateDatabasePlan,
+    CreateIndexPlan, CreateRolePlan, CreateSchemaPlan, CreateSinkPlan, CreateSourcePlan,
+    CreateTablePlan, CreateTypePlan, CreateViewPlan, CreateViewsPlan, DropDatabasePlan,
+    DropItemsPlan, DropRolesPlan, DropSchemaPlan, DropSubscriptionPlan, HirRelationExpr, Index,
+    IndexOption, IndexOptionName, OptimizerConfig, Params, Plan, Sink, Source, Table, Type,
+    TypeInner, View,
 };
 use crate::pure::Schema;
 
@@ -447,6 +455,9 @@ pub fn plan_create_source(
                 compression: match compression {
                     Compression::Gzip => mz_dataflow_types::sources::Compression::Gzip,
                     Compression::None => mz_dataflow_types::sources::Compression::None,
+                    Compression::Zstd | Compression::Bzip2 | Compression::Xz => {
+                        bail_unsupported!(format!("COMPRESSION {} for file sources", compression))
+                    }
                 },
                 tail,
             });
@@ -494,6 +505,9 @@ pub fn plan_create_source(
                 aws,
                 compression: match compression {
                     Compression::Gzip => mz_dataflow_types::sources::Compression::Gzip,
+                    Compression::Zstd => mz_dataflow_types::sources::Compression::Zstd,
+                    Compression::Bzip2 => mz_dataflow_types::sources::Compression::Bzip2,
+                    Compression::Xz => mz_dataflow_types::sources::Compression::Xz,
                     Compression::None => mz_dataflow_types::sources::Compression::None,
                 },
             });
@@ -501,6 +515,14 @@ pub fn plan_create_source(
             if matches!(encoding, SourceDataEncoding::KeyValue { .. }) {
                 bail!("S3 sources do not support key decoding");
             }
+            if matches!(encoding.value_ref(), DataEncoding::Parquet(_)) {
+                // Parquet compresses column chunks itself, and its footer
+                // must be readable without decompressing the whole object.
+                if *compression != Compression::None {
+                    bail!("FORMAT PARQUET requires COMPRESSION NONE");
+                }
+                with_options.remove("parquet_schema");
+            }
             (connector, encoding)
         }
         CreateSourceConnector::Postgres {
@@ -535,6 +557,59 @@ pub fn plan_create_source(
             });
             (connector, SourceDataEncoding::Single(DataEncoding::Text))
         }
+        CreateSourceConnector::Webhook {
+            headers,
+            validation,
+        } => {
+            if !*materialized {
+                bail!("webhook sources must be materialized");
+            }
+            if *envelope != Envelope::None {
+                bail_unsupported!("ENVELOPE with webhook sources");
+            }
+            if with_options.contains_key("timeline") {
+                bail!("webhook sources cannot specify a timeline");
+            }
+            let encoding = match format {
+                CreateSourceFormat::None | CreateSourceFormat::Bare(Format::Bytes) => {
+                    DataEncoding::Bytes
+                }
+                CreateSourceFormat::Bare(Format::Text) => DataEncoding::Text,
+                CreateSourceFormat::Bare(Format::Json) => DataEncoding::Json,
+                _ => bail!("CREATE SOURCE ... WEBHOOK must specify FORMAT BYTES, TEXT, or JSON"),
+            };
+
+            // HTTP header names are case insensitive.
+            let headers: Vec<_> = headers.iter().map(|h| h.to_lowercase()).collect();
+            if let Some(dup) = headers.iter().duplicates().next() {
+                bail!("header {} specified more than once", dup.quoted());
+            }
+
+            let validation = match validation {
+                None => None,
+                Some(validation) => {
+                    let algorithm = validation.algorithm.to_lowercase();
+                    if !["md5", "sha1", "sha224", "sha256", "sha384", "sha512"]
+                        .contains(&algorithm.as_str())
+                    {
+                        bail!("invalid hash algorithm {}", validation.algorithm.quoted());
+                    }
+                    Some(WebhookValidation {
+                        algorithm,
+                        key_env: validation.key_env.clone(),
+                        header: validation.header.to_lowercase(),
+                    })
+                }
+            };
+
+            let connector = ExternalSourceConnector::Webhook(WebhookSourceConnector {
+                headers,
+                validation,
+                // Assigned by the coordinator, which owns persistence.
+                persisted_name: None,
+            });
+            (connector, SourceDataEncoding::Single(encoding))
+        }
         CreateSourceConnector::AvroOcf { path, .. } => {
             let tail = match with_options.remove("tail") {
                 None => false,
@@ -563,6 +638,20 @@ pub fn plan_create_source(
             (connector, encoding)
         }
     };
+    if matches!(encoding.value_ref(), DataEncoding::Parquet(_))
+        && !matches!(connector, CreateSourceConnector::S3 { .. })
+    {
+        bail!("FORMAT PARQUET is only supported for S3 sources");
+    }
+    let is_json = |encoding: &DataEncoding| matches!(encoding, DataEncoding::Json);
+    if (is_json(encoding.value_ref()) || encoding.key_ref().map_or(false, is_json))
+        && !matches!(
+            connector,
+            CreateSourceConnector::S3 { .. } | CreateSourceConnector::Webhook { .. }
+        )
+    {
+        bail_unsupported!("JSON sources other than S3 sources");
+    }
     let (key_desc, value_desc) = encoding.desc()?;
 
     let key_envelope = get_key_envelope(include_metadata, envelope, &encoding)?;
@@ -821,7 +910,7 @@ pub fn plan_create_source(
         id: mz_expr::Id::LocalBareSource,
         typ: desc.typ().clone(),
     }
-    .lower();
+    .lower(&scx.into());
 
     let source = Source {
         create_sql,
@@ -1123,6 +1212,7 @@ fn get_encoding_inner<T: mz_sql_parser::ast::AstInfo>(
                         seed,
                         with_options: ccsr_options,
                     },
+                ..
             } => {
                 if let Some(CsrSeedCompiledOrLegacy::Compiled(CsrSeedCompiled { key, value })) =
                     seed
@@ -1204,7 +1294,16 @@ fn get_encoding_inner<T: mz_sql_parser::ast::AstInfo>(
                 },
             })
         }
-        Format::Json => bail_unsupported!("JSON sources"),
+        Format::Json => DataEncoding::Json,
+        Format::Parquet => {
+            let columns = match normalize::options(with_options).remove("parquet_schema") {
+                Some(Value::String(schema)) => serde_json::from_str(&schema)
+                    .map_err(|e| anyhow!("invalid parquet_schema: {}", e))?,
+                Some(_) => bail!("parquet_schema option must be a string"),
+                None => bail!("FORMAT PARQUET is only supported for S3 sources"),
+            };
+            DataEncoding::Parquet(ParquetEncoding { columns })
+        }
         Format::Text => DataEncoding::Text,
     }))
 }
@@ -1231,10 +1330,12 @@ fn get_key_envelope(
                 // Otherwise it gets the names of the columns in the type
                 if let SourceDataEncoding::KeyValue { key, value: _ } = encoding {
                     let is_composite = match key {
-                        DataEncoding::AvroOcf { .. } | DataEncoding::Postgres => {
+                        DataEncoding::AvroOcf { .. }
+                        | DataEncoding::Parquet(_)
+                        | DataEncoding::Postgres => {
                             bail!("{} sources cannot use INCLUDE KEY", key.op_name())
                         }
-                        DataEncoding::Bytes | DataEncoding::Text => false,
+                        DataEncoding::Bytes | DataEncoding::Text | DataEncoding::Json => false,
                         DataEncoding::Avro(_)
                         | DataEncoding::Csv(_)
                         | DataEncoding::Protobuf(_)
@@ -1299,7 +1400,8 @@ pub fn plan_view(
     expr.bind_parameters(&params)?;
     //TODO: materialize#724 - persist finishing information with the view?
     expr.finish(finishing);
-    let relation_expr = expr.optimize_and_lower(&scx.into());
+    let config = OptimizerConfig::from(scx);
+    let relation_expr = expr.optimize_and_lower(&config);
 
     let name = if temporary {
         scx.allocate_temporary_name(normalize::unresolved_object_name(name.to_owned())?)
@@ -1320,6 +1422,7 @@ pub fn plan_view(
         column_names: names,
         temporary,
         depends_on,
+        outer_join_anti_joins: config.outer_join_anti_joins,
     };
 
     Ok((name, view))
@@ -1395,6 +1498,10 @@ pub fn plan_create_views(
     }
 }
 
+/// The number of updates that a rate-limited Kafka sink reads ahead of its
+/// producer by default.
+const DEFAULT_MAX_READ_AHEAD: u64 = 100_000;
+
 #[allow(clippy::too_many_arguments)]
 fn kafka_sink_builder(
     format: Option<Format<Raw>>,
@@ -1405,6 +1512,9 @@ fn kafka_sink_builder(
     relation_key_indices: Option<Vec<usize>>,
     key_desc_and_indices: Option<(RelationDesc, Vec<usize>)>,
     value_desc: RelationDesc,
+    partition_by: Option<MirScalarExpr>,
+    headers: Vec<(String, MirScalarExpr)>,
+    envelope: Option<SinkEnvelope>,
     topic_suffix_nonce: String,
     root_dependencies: &[&dyn CatalogItem],
 ) -> Result<SinkConnectorBuilder, anyhow::Error> {
@@ -1493,6 +1603,54 @@ fn kafka_sink_builder(
                 ccsr_config,
             }
         }
+        Some(Format::Protobuf(ProtobufSchema::Csr {
+            message_name,
+            csr_connector:
+                CsrConnectorProto {
+                    url,
+                    seed,
+                    with_options,
+                },
+        })) => {
+            if seed.is_some() {
+                bail!("SEED option does not make sense with sinks");
+            }
+            if message_name.is_some() {
+                bail!("MESSAGE option does not make sense with sinks");
+            }
+            if avro_key_fullname.is_some() || avro_value_fullname.is_some() {
+                bail!("avro_key_fullname and avro_value_fullname require FORMAT AVRO");
+            }
+            let mut ccsr_with_options = normalize::options(&with_options);
+
+            let schema_registry_url = url.parse::<Url>()?;
+            let ccsr_config = kafka_util::generate_ccsr_client_config(
+                schema_registry_url.clone(),
+                &config_options,
+                &mut ccsr_with_options,
+            )?;
+
+            let include_transaction =
+                reuse_topic || consistency_topic.is_some() || consistency.is_some();
+            let schema_generator = ProtobufSchemaGenerator::new(
+                key_desc_and_indices
+                    .as_ref()
+                    .map(|(desc, _indices)| desc.clone()),
+                value_desc.clone(),
+                include_transaction,
+            )?;
+            let value_schema = schema_generator.value_schema();
+            let key_schema = schema_generator.key_schema();
+
+            normalize::ensure_empty_options(&ccsr_with_options, "CONFLUENT SCHEMA REGISTRY")?;
+
+            KafkaSinkFormat::Protobuf {
+                schema_registry_url,
+                key_schema,
+                value_schema,
+                ccsr_config,
+            }
+        }
         Some(Format::Json) => KafkaSinkFormat::Json,
         Some(format) => bail_unsupported!(format!("sink format {:?}", format)),
         None => bail_unsupported!("sink without format"),
@@ -1509,21 +1667,19 @@ fn kafka_sink_builder(
 
     let broker_addrs = broker.parse()?;
 
+    // Whether the sources and tables are durable, i.e. whether they replay the
+    // same updates at the same timestamps after a restart, is only known to the
+    // coordinator, which checks it before creating the sink.
     let transitive_source_dependencies: Vec<_> = if reuse_topic {
         for item in root_dependencies.iter() {
-            if item.item_type() == CatalogItemType::Source {
-                if !item.source_connector()?.yields_stable_input() {
-                    bail!(
-                    "reuse_topic requires that sink input dependencies are replayable, {} is not",
-                    item.name()
-                );
-                }
-            } else if item.item_type() != CatalogItemType::Source {
-                bail!(
-                    "reuse_topic requires that sink input dependencies are sources, {} is not",
+            match item.item_type() {
+                CatalogItemType::Source | CatalogItemType::Table => (),
+                _ => bail!(
+                    "reuse_topic requires that sink input dependencies are sources or tables, {} \
+                     is not",
                     item.name()
-                );
-            };
+                ),
+            }
         }
 
         root_dependencies.iter().map(|i| i.id()).collect()
@@ -1581,6 +1737,42 @@ fn kafka_sink_builder(
         bytes: retention_bytes,
     };
 
+    let coalesce_interval = match with_options.remove("coalesce_interval") {
+        None => None,
+        Some(Value::String(s)) => {
+            let interval = mz_repr::util::parse_duration(&s)?;
+            if interval.as_millis() == 0 {
+                bail!("coalesce_interval must be at least 1ms");
+            }
+            Some(interval)
+        }
+        Some(_) => bail!("coalesce_interval must be a string"),
+    };
+    if coalesce_interval.is_some() && envelope != Some(SinkEnvelope::Upsert) {
+        bail!("coalesce_interval requires ENVELOPE UPSERT");
+    }
+
+    let mut positive_integer = |name: &str| -> Result<Option<u64>, anyhow::Error> {
+        match with_options.remove(name) {
+            None => Ok(None),
+            Some(Value::Number(n)) => match n.parse::<u64>() {
+                Ok(limit) if limit > 0 => Ok(Some(limit)),
+                _ => bail!("{} must be a positive integer", name),
+            },
+            Some(_) => bail!("{} must be an integer", name),
+        }
+    };
+    let max_messages_per_second = positive_integer("max_messages_per_second")?;
+    let max_bytes_per_second = positive_integer("max_bytes_per_second")?;
+    // Only sinks that are rate limited or bound their read-ahead pace their
+    // input, which costs buffering it.
+    let max_read_ahead = match positive_integer("max_read_ahead")? {
+        None if max_messages_per_second.is_some() || max_bytes_per_second.is_some() => {
+            Some(DEFAULT_MAX_READ_AHEAD)
+        }
+        max_read_ahead => max_read_ahead,
+    };
+
     let consistency_topic = consistency_config.clone().map(|config| config.0);
     let consistency_format = consistency_config.map(|config| config.1);
 
@@ -1598,12 +1790,107 @@ fn kafka_sink_builder(
         relation_key_indices,
         key_desc_and_indices,
         value_desc,
+        partition_by,
+        headers,
+        coalesce_interval,
+        max_messages_per_second,
+        max_bytes_per_second,
+        max_read_ahead,
         reuse_topic,
         transitive_source_dependencies,
         retention,
     }))
 }
 
+/// Plans the `PARTITION BY` and `HEADERS` expressions of a Kafka sink.
+///
+/// The expressions may only refer to the columns of the sink key, so that
+/// every message of the sink can be routed, including the tombstones of upsert
+/// sinks, which have no value. The planned expressions refer to the columns of
+/// the key rather than to the columns of `desc`.
+fn plan_kafka_sink_routing(
+    scx: &StatementContext,
+    desc: &RelationDesc,
+    key_indices: Option<&[usize]>,
+    partition_by: Option<Expr<Raw>>,
+    headers: Vec<KafkaSinkHeader<Raw>>,
+) -> Result<
+    (
+        Option<MirScalarExpr>,
+        Vec<(String, MirScalarExpr)>,
+        Vec<GlobalId>,
+    ),
+    anyhow::Error,
+> {
+    let mut depends_on = vec![];
+    let mut plan_key_expr =
+        |clause: &str, expr: Expr<Raw>| -> Result<(MirScalarExpr, ScalarType), anyhow::Error> {
+            let key_indices = match key_indices {
+                Some(key_indices) => key_indices,
+                None => bail!("{} requires a KEY", clause),
+            };
+            let (exprs, expr_depends_on) = query::plan_index_exprs(scx, desc, vec![expr])?;
+            depends_on.extend(expr_depends_on);
+            let mut expr = exprs.into_element();
+            if expr.contains_temporal() {
+                bail!("{} cannot use mz_logical_timestamp()", clause);
+            }
+            let mut permutation = HashMap::new();
+            for column in expr.support() {
+                match key_indices.iter().position(|index| *index == column) {
+                    Some(position) => {
+                        permutation.insert(column, position);
+                    }
+                    None => bail!(
+                        "{} can only refer to columns of the sink key, but {} is not a key column",
+                        clause,
+                        desc.get_name(column).as_str().quoted()
+                    ),
+                }
+            }
+            let scalar_type = expr.typ(desc.typ()).scalar_type;
+            expr.permute_map(&permutation);
+            Ok((expr, scalar_type))
+        };
+
+    let partition_by = match partition_by {
+        Some(expr) => {
+            let (expr, scalar_type) = plan_key_expr("PARTITION BY", expr)?;
+            match scalar_type {
+                ScalarType::Int16 | ScalarType::Int32 | ScalarType::Int64 => Some(expr),
+                _ => bail!(
+                    "PARTITION BY expression must have type integer, not {}",
+                    scx.humanize_scalar_type(&scalar_type)
+                ),
+            }
+        }
+        None => None,
+    };
+
+    let mut names = HashSet::new();
+    let mut planned_headers = vec![];
+    for KafkaSinkHeader { name, value } in headers {
+        let name = normalize::ident(name);
+        if !names.insert(name.clone()) {
+            bail!("header {} specified more than once", name.quoted());
+        }
+        let (expr, scalar_type) = plan_key_expr("HEADERS", value)?;
+        match scalar_type {
+            ScalarType::String
+            | ScalarType::Char { .. }
+            | ScalarType::VarChar { .. }
+            | ScalarType::Bytes => planned_headers.push((name, expr)),
+            _ => bail!(
+                "header {} must have type text or bytea, not {}",
+                name.quoted(),
+                scx.humanize_scalar_type(&scalar_type)
+            ),
+        }
+    }
+
+    Ok((partition_by, planned_headers, depends_on))
+}
+
 /// Determines the consistency configuration (topic and format) that should be used for a Kafka
 /// sink based on the given configuration items.
 ///
@@ -1657,6 +1944,21 @@ fn get_kafka_sink_consistency_config(
                 // If a CONSISTENCY FORMAT is not provided, default to the FORMAT of the sink.
                 match sink_format {
                     format @ KafkaSinkFormat::Avro { .. } => Some((topic, format.clone())),
+                    // The consistency topic is always Avro-formatted, but it
+                    // can share the schema registry of a Protobuf sink.
+                    KafkaSinkFormat::Protobuf {
+                        schema_registry_url,
+                        ccsr_config,
+                        ..
+                    } => Some((
+                        topic,
+                        KafkaSinkFormat::Avro {
+                            schema_registry_url: schema_registry_url.clone(),
+                            key_schema: None,
+                            value_schema: avro::get_debezium_transaction_schema().canonical_form(),
+                            ccsr_config: ccsr_config.clone(),
+                        },
+                    )),
                     KafkaSinkFormat::Json => bail_unsupported!("CONSISTENCY FORMAT JSON"),
                 }
             }
@@ -1671,6 +1973,11 @@ fn get_kafka_sink_consistency_config(
                         schema_registry_url,
                         ccsr_config,
                         ..
+                    }
+                    | KafkaSinkFormat::Protobuf {
+                        schema_registry_url,
+                        ccsr_config,
+                        ..
                     } => {
                         let consistency_topic = match consistency_topic {
                             Some(topic) => topic,
@@ -1708,6 +2015,7 @@ fn get_kafka_sink_consistency_config(
 
 fn avro_ocf_sink_builder(
     format: Option<Format<Raw>>,
+    with_options: &mut BTreeMap<String, Value>,
     path: String,
     file_name_suffix: String,
     value_desc: RelationDesc,
@@ -1722,10 +2030,271 @@ fn avro_ocf_sink_builder(
         bail!("avro ocf sink cannot write to a directory");
     }
 
+    let codec = match with_options.remove("codec") {
+        None => AvroOcfCodec::Null,
+        Some(Value::String(s)) => match s.to_lowercase().as_str() {
+            "null" => AvroOcfCodec::Null,
+            "deflate" => AvroOcfCodec::Deflate,
+            "snappy" => AvroOcfCodec::Snappy,
+            _ => bail!(
+                "invalid codec {}: must be one of 'null', 'deflate' or 'snappy'",
+                s.quoted()
+            ),
+        },
+        Some(_) => bail!("codec must be a string"),
+    };
+
+    let max_file_size = match with_options.remove("max_file_size") {
+        None => None,
+        Some(Value::Number(n)) => match n.parse::<usize>() {
+            Ok(size) if size > 0 => Some(size),
+            _ => bail!("max_file_size must be a positive integer"),
+        },
+        Some(_) => bail!("max_file_size must be an integer"),
+    };
+
+    let rotate_interval = match with_options.remove("rotate_interval") {
+        None => None,
+        Some(Value::String(s)) => {
+            let interval = mz_repr::util::parse_duration(&s)?;
+            if interval.as_millis() == 0 {
+                bail!("rotate_interval must be at least 1ms");
+            }
+            Some(interval)
+        }
+        Some(_) => bail!("rotate_interval must be a string"),
+    };
+
     Ok(SinkConnectorBuilder::AvroOcf(AvroOcfSinkConnectorBuilder {
         path,
         file_name_suffix,
+        codec,
+        max_file_size,
+        rotate_interval,
+        value_desc,
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn postgres_sink_builder(
+    scx: &StatementContext,
+    format: Option<Format<Raw>>,
+    with_options: &mut BTreeMap<String, Value>,
+    conn: String,
+    table: UnresolvedObjectName,
+    envelope: Option<SinkEnvelope>,
+    key_desc_and_indices: Option<(RelationDesc, Vec<usize>)>,
+    value_desc: RelationDesc,
+    root_dependencies: &[&dyn CatalogItem],
+) -> Result<SinkConnectorBuilder, anyhow::Error> {
+    if format.is_some() {
+        bail!("postgres sinks cannot specify a format");
+    }
+    if envelope != Some(SinkEnvelope::Upsert) {
+        bail_unsupported!("postgres sinks with ENVELOPE DEBEZIUM");
+    }
+
+    let progress_table = match with_options.remove("progress_table") {
+        None => "mz_sink_progress".to_owned(),
+        Some(Value::String(name)) => name,
+        Some(_) => bail!("progress_table must be a string"),
+    };
+
+    let (_key_desc, key_indices) = key_desc_and_indices.expect("postgres sinks always have a key");
+    for &idx in &key_indices {
+        if value_desc.typ().column_types[idx].nullable {
+            bail!(
+                "postgres sink key column {} must not be nullable",
+                value_desc.get_name(idx).as_str().quoted()
+            );
+        }
+    }
+    for (name, typ) in value_desc.iter() {
+        if let ScalarType::List { .. } | ScalarType::Map { .. } | ScalarType::Record { .. } =
+            typ.scalar_type
+        {
+            bail_unsupported!(format!(
+                "postgres sink column {} of type {}",
+                name.as_str().quoted(),
+                scx.humanize_scalar_type(&typ.scalar_type)
+            ));
+        }
+    }
+
+    // Updates are applied exactly once across restarts by skipping the
+    // timestamps that the progress table records as written, which requires
+    // that the inputs are replayed with the same timestamps. Whether the
+    // sources and tables are durable is only known to the coordinator, which
+    // checks it before creating the sink.
+    for item in root_dependencies {
+        match item.item_type() {
+            CatalogItemType::Source | CatalogItemType::Table => (),
+            _ => bail!(
+                "postgres sinks require that sink input dependencies are sources or tables, {} \
+                 is not",
+                item.name()
+            ),
+        }
+    }
+    let transitive_source_dependencies = root_dependencies.iter().map(|i| i.id()).collect();
+
+    // The names are quoted here so that they are not subject to the
+    // identifier case folding of the upstream database.
+    let table = table.to_ast_string_stable();
+    let progress_table = Ident::new(progress_table).to_ast_string_stable();
+
+    Ok(SinkConnectorBuilder::Postgres(
+        PostgresSinkConnectorBuilder {
+            conn,
+            table,
+            key_indices,
+            value_desc,
+            progress_table,
+            cluster_id: scx.catalog.config().cluster_id,
+            transitive_source_dependencies,
+        },
+    ))
+}
+
+fn parquet_sink_builder(
+    scx: &StatementContext,
+    format: Option<Format<Raw>>,
+    with_options: &mut BTreeMap<String, Value>,
+    path: String,
+    directory_suffix: String,
+    value_desc: RelationDesc,
+    root_dependencies: &[&dyn CatalogItem],
+) -> Result<SinkConnectorBuilder, anyhow::Error> {
+    if format.is_some() {
+        bail!("parquet sinks cannot specify a format");
+    }
+
+    let reuse_directory = match with_options.remove("reuse_directory") {
+        Some(Value::Boolean(b)) => b,
+        None => false,
+        Some(_) => bail!("reuse_directory must be a boolean"),
+    };
+
+    let max_file_size = match with_options.remove("max_file_size") {
+        None => 64 << 20,
+        Some(Value::Number(n)) => n.parse::<usize>()?,
+        Some(_) => bail!("max_file_size must be an integer"),
+    };
+    if max_file_size == 0 {
+        bail!("max_file_size must be greater than zero");
+    }
+
+    for (name, typ) in value_desc.iter() {
+        if name.as_str() == parquet::TIMESTAMP_COLUMN || name.as_str() == parquet::DIFF_COLUMN {
+            bail!(
+                "parquet sink column name {} is reserved",
+                name.as_str().quoted()
+            );
+        }
+        if !parquet::is_encodable(&typ.scalar_type) {
+            bail_unsupported!(format!(
+                "parquet sink column {} of type {}",
+                name.as_str().quoted(),
+                scx.humanize_scalar_type(&typ.scalar_type)
+            ));
+        }
+    }
+
+    let location = match path.strip_prefix("s3://") {
+        Some(bucket_and_prefix) => {
+            let (bucket, prefix) = bucket_and_prefix
+                .split_once('/')
+                .unwrap_or((bucket_and_prefix, ""));
+            if bucket.is_empty() {
+                bail!("parquet sink path {} does not name a bucket", path.quoted());
+            }
+            let external_id = scx.catalog.config().aws_external_id.clone();
+            ParquetSinkLocation::S3 {
+                bucket: bucket.into(),
+                prefix: prefix.trim_end_matches('/').into(),
+                aws: normalize::aws_config(with_options, None, external_id)?,
+            }
+        }
+        None => {
+            let path = PathBuf::from(path);
+            if path.exists() && !path.is_dir() {
+                bail!("parquet sink path {} is not a directory", path.display());
+            }
+            ParquetSinkLocation::Local(path)
+        }
+    };
+
+    // A sink that reuses its directory resumes after the files listed in the
+    // manifest, which requires that the inputs are replayed with the same
+    // timestamps. Whether the sources and tables are durable is only known to
+    // the coordinator, which checks it before creating the sink.
+    let transitive_source_dependencies = if reuse_directory {
+        for item in root_dependencies {
+            match item.item_type() {
+                CatalogItemType::Source | CatalogItemType::Table => (),
+                _ => bail!(
+                    "reuse_directory requires that sink input dependencies are sources or \
+                     tables, {} is not",
+                    item.name()
+                ),
+            }
+        }
+        root_dependencies.iter().map(|i| i.id()).collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(SinkConnectorBuilder::Parquet(ParquetSinkConnectorBuilder {
+        location,
+        directory_suffix,
+        reuse_directory,
+        max_file_size,
         value_desc,
+        transitive_source_dependencies,
+    }))
+}
+
+fn http_sink_builder(
+    format: Option<Format<Raw>>,
+    url: String,
+    relation_key_indices: Option<Vec<usize>>,
+    key_desc_and_indices: Option<(RelationDesc, Vec<usize>)>,
+    value_desc: RelationDesc,
+    root_dependencies: &[&dyn CatalogItem],
+) -> Result<SinkConnectorBuilder, anyhow::Error> {
+    match format {
+        None | Some(Format::Json) => (),
+        Some(_) => bail!("http sinks only support FORMAT JSON"),
+    }
+
+    let url = match Url::parse(&url) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => url,
+        Ok(_) => bail!("http sink url {} must use http or https", url.quoted()),
+        Err(e) => bail!("invalid http sink url {}: {}", url.quoted(), e),
+    };
+
+    // HTTP sinks skip the timestamps that they have already delivered after a
+    // restart, which requires that the inputs are replayed with the same
+    // timestamps. Whether the sources and tables are durable is only known to
+    // the coordinator, which checks it before creating the sink.
+    for item in root_dependencies {
+        match item.item_type() {
+            CatalogItemType::Source | CatalogItemType::Table => (),
+            _ => bail!(
+                "http sinks require that sink input dependencies are sources or tables, {} is not",
+                item.name()
+            ),
+        }
+    }
+    let transitive_source_dependencies = root_dependencies.iter().map(|i| i.id()).collect();
+
+    Ok(SinkConnectorBuilder::Http(HttpSinkConnectorBuilder {
+        url,
+        key_desc_and_indices,
+        relation_key_indices,
+        value_desc,
+        cluster_id: scx.catalog.config().cluster_id,
+        transitive_source_dependencies,
     }))
 }
 
@@ -1754,10 +2323,21 @@ pub fn plan_create_sink(
     } = stmt;
 
     let envelope = match envelope {
+        // Parquet sinks write out every update along with its timestamp and
+        // diff, so they do not use an envelope.
+        None if matches!(connector, CreateSinkConnector::Parquet { .. }) => None,
+        Some(_) if matches!(connector, CreateSinkConnector::Parquet { .. }) => {
+            bail!("parquet sinks cannot specify an envelope")
+        }
+        // Postgres sinks apply changes to a keyed table, so they default to
+        // upsert semantics.
+        None if matches!(connector, CreateSinkConnector::Postgres { .. }) => {
+            Some(SinkEnvelope::Upsert)
+        }
         None | Some(Envelope::Debezium(mz_sql_parser::ast::DbzMode::Plain)) => {
-            SinkEnvelope::Debezium
+            Some(SinkEnvelope::Debezium)
         }
-        Some(Envelope::Upsert) => SinkEnvelope::Upsert,
+        Some(Envelope::Upsert) => Some(SinkEnvelope::Upsert),
         Some(Envelope::CdcV2) => bail_unsupported!("CDCv2 sinks"),
         Some(Envelope::Debezium(mz_sql_parser::ast::DbzMode::Upsert)) => {
             bail_unsupported!("UPSERT doesn't make sense for sinks")
@@ -1775,53 +2355,56 @@ pub fn plan_create_sink(
     let mut with_options = normalize::options(&with_options);
 
     let desc = from.desc()?;
-    let key_indices = match &connector {
-        CreateSinkConnector::Kafka { key, .. } => {
-            if let Some(key) = key.clone() {
-                let key_columns = key
-                    .key_columns
-                    .into_iter()
-                    .map(normalize::column_name)
-                    .collect::<Vec<_>>();
-                let mut uniq = HashSet::new();
-                for col in key_columns.iter() {
-                    if !uniq.insert(col) {
-                        bail!("Repeated column name in sink key: {}", col);
-                    }
-                }
-                let indices = key_columns
-                    .iter()
-                    .map(|col| -> anyhow::Result<usize> {
-                        let name_idx = desc
-                            .get_by_name(col)
-                            .map(|(idx, _type)| idx)
-                            .ok_or_else(|| anyhow!("No such column: {}", col))?;
-                        if desc.get_unambiguous_name(name_idx).is_none() {
-                            bail!("Ambiguous column: {}", col);
-                        }
-                        Ok(name_idx)
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-                let is_valid_key =
-                    desc.typ().keys.iter().any(|key_columns| {
-                        key_columns.iter().all(|column| indices.contains(column))
-                    });
-                if key.not_enforced && envelope == SinkEnvelope::Upsert {
-                    // TODO: We should report a warning notice back to the user via the pgwire
-                    // protocol. See https://github.com/MaterializeInc/materialize/issues/9333.
-                    tracing::warn!(
-                        "Verification of upsert key disabled for sink '{}' via 'NOT ENFORCED'. This is potentially dangerous and can lead to crashing materialize when the specified key is not in fact a unique key of the sinked view.",
-                        name
-                    );
-                } else if !is_valid_key && envelope == SinkEnvelope::Upsert {
-                    return Err(invalid_upsert_key_err(&desc, &key_columns));
+    let key = match &connector {
+        CreateSinkConnector::Kafka { key, .. } => key.clone(),
+        CreateSinkConnector::Postgres { key, .. } => Some(key.clone()),
+        CreateSinkConnector::Http { key, .. } => key.clone(),
+        CreateSinkConnector::AvroOcf { .. } | CreateSinkConnector::Parquet { .. } => None,
+    };
+    let key_indices = match key {
+        Some(key) => {
+            let key_columns = key
+                .key_columns
+                .into_iter()
+                .map(normalize::column_name)
+                .collect::<Vec<_>>();
+            let mut uniq = HashSet::new();
+            for col in key_columns.iter() {
+                if !uniq.insert(col) {
+                    bail!("Repeated column name in sink key: {}", col);
                 }
-                Some(indices)
-            } else {
-                None
             }
+            let indices = key_columns
+                .iter()
+                .map(|col| -> anyhow::Result<usize> {
+                    let name_idx = desc
+                        .get_by_name(col)
+                        .map(|(idx, _type)| idx)
+                        .ok_or_else(|| anyhow!("No such column: {}", col))?;
+                    if desc.get_unambiguous_name(name_idx).is_none() {
+                        bail!("Ambiguous column: {}", col);
+                    }
+                    Ok(name_idx)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let is_valid_key = desc
+                .typ()
+                .keys
+                .iter()
+                .any(|key_columns| key_columns.iter().all(|column| indices.contains(column)));
+            if key.not_enforced && envelope == Some(SinkEnvelope::Upsert) {
+                // TODO: We should report a warning notice back to the user via the pgwire
+                // protocol. See https://github.com/MaterializeInc/materialize/issues/9333.
+                tracing::warn!(
+                    "Verification of upsert key disabled for sink '{}' via 'NOT ENFORCED'. This is potentially dangerous and can lead to crashing materialize when the specified key is not in fact a unique key of the sinked view.",
+                    name
+                );
+            } else if !is_valid_key && envelope == Some(SinkEnvelope::Upsert) {
+                return Err(invalid_upsert_key_err(&desc, &key_columns));
+            }
+            Some(indices)
         }
-        CreateSinkConnector::AvroOcf { .. } => None,
+        None => None,
     };
 
     // pick the first valid natural relation key, if any
@@ -1835,13 +2418,30 @@ pub fn plan_create_sink(
         (RelationDesc::new(typ, names), key_indices)
     });
 
-    if key_desc_and_indices.is_none() && envelope == SinkEnvelope::Upsert {
+    if key_desc_and_indices.is_none() && envelope == Some(SinkEnvelope::Upsert) {
         return Err(PlanError::UpsertSinkWithoutKey.into());
     }
 
+    let (partition_by, headers, routing_depends_on) = match &connector {
+        CreateSinkConnector::Kafka {
+            partition_by,
+            headers,
+            ..
+        } => plan_kafka_sink_routing(
+            scx,
+            &desc,
+            key_desc_and_indices
+                .as_ref()
+                .map(|(_desc, indices)| indices.as_slice()),
+            partition_by.clone(),
+            headers.clone(),
+        )?,
+        _ => (None, vec![], vec![]),
+    };
+
     let value_desc = match envelope {
-        SinkEnvelope::Debezium => envelopes::dbz_desc(desc.clone()),
-        SinkEnvelope::Upsert => desc.clone(),
+        Some(SinkEnvelope::Debezium) => envelopes::dbz_desc(desc.clone()),
+        Some(SinkEnvelope::Upsert) | None => desc.clone(),
     };
 
     if as_of.is_some() {
@@ -1850,6 +2450,7 @@ pub fn plan_create_sink(
 
     let mut depends_on = vec![from.id()];
     depends_on.extend(from.uses());
+    depends_on.extend(routing_depends_on);
 
     let root_user_dependencies = get_root_dependencies(scx, &depends_on);
 
@@ -1868,12 +2469,43 @@ pub fn plan_create_sink(
             relation_key_indices,
             key_desc_and_indices,
             value_desc,
+            partition_by,
+            headers,
+            envelope,
             suffix_nonce,
             &root_user_dependencies,
         )?,
         CreateSinkConnector::AvroOcf { path } => {
-            avro_ocf_sink_builder(format, path, suffix_nonce, value_desc)?
+            avro_ocf_sink_builder(format, &mut with_options, path, suffix_nonce, value_desc)?
         }
+        CreateSinkConnector::Postgres { conn, table, .. } => postgres_sink_builder(
+            scx,
+            format,
+            &mut with_options,
+            conn,
+            table,
+            envelope,
+            key_desc_and_indices,
+            value_desc,
+            &root_user_dependencies,
+        )?,
+        CreateSinkConnector::Parquet { path } => parquet_sink_builder(
+            scx,
+            format,
+            &mut with_options,
+            path,
+            suffix_nonce,
+            value_desc,
+            &root_user_dependencies,
+        )?,
+        CreateSinkConnector::Http { url, .. } => http_sink_builder(
+            format,
+            url,
+            relation_key_indices,
+            key_desc_and_indices,
+            value_desc,
+            &root_user_dependencies,
+        )?,
     };
 
     normalize::ensure_empty_options(&with_options, "CREATE SINK")?;
@@ -2055,7 +2687,12 @@ pub fn plan_create_index(
         }
     };
 
-    let options = plan_index_options(with_options.clone())?;
+    let (options, spill) = plan_index_options(with_options.clone())?;
+    let spill_threshold = match spill.threshold {
+        Some(0) => bail!("spill_threshold must be greater than zero"),
+        Some(_) if spill.spill != Some(true) => bail!("spill_threshold requires spill = true"),
+        threshold => threshold.map(usize::cast_from),
+    };
 
     // Normalize `stmt`.
     *name = Some(Ident::new(index_name.item.clone()));
@@ -2072,6 +2709,8 @@ pub fn plan_create_index(
             on: on.id(),
             keys,
             depends_on,
+            spill: spill.spill.unwrap_or(false),
+            spill_threshold,
         },
         options,
         if_not_exists,
@@ -2455,9 +3094,30 @@ pub fn plan_drop_item(
     Ok(Some(catalog_entry.id()))
 }
 
+pub fn describe_drop_subscription(
+    _: &StatementContext,
+    _: DropSubscriptionStatement,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_drop_subscription(
+    _: &StatementContext,
+    DropSubscriptionStatement { name, if_exists }: DropSubscriptionStatement,
+) -> Result<Plan, anyhow::Error> {
+    // Subscriptions are not catalog items, so the coordinator checks whether
+    // the subscription exists.
+    Ok(Plan::DropSubscription(DropSubscriptionPlan {
+        name: normalize::ident(name),
+        if_exists,
+    }))
+}
+
 with_options! {
     struct IndexWithOptions {
         logical_compaction_window: String,
+        spill: bool,
+        spill_threshold: u64,
     }
 }
 
@@ -2468,7 +3128,17 @@ pub fn describe_alter_index_options(
     Ok(StatementDesc::new(None))
 }
 
-fn plan_index_options(with_opts: Vec<WithOption>) -> Result<Vec<IndexOption>, anyhow::Error> {
+/// The options of an index that determine whether and how it spills, which are
+/// fixed when the index is created.
+struct IndexSpillOptions {
+    spill: Option<bool>,
+    threshold: Option<u64>,
+}
+
+/// Plans the options of an index, returning its spill options separately.
+fn plan_index_options(
+    with_opts: Vec<WithOption>,
+) -> Result<(Vec<IndexOption>, IndexSpillOptions), anyhow::Error> {
     let with_opts = IndexWithOptions::try_from(with_opts)?;
     let mut out = vec![];
 
@@ -2481,7 +3151,11 @@ fn plan_index_options(with_opts: Vec<WithOption>) -> Result<Vec<IndexOption>, an
         }
     };
 
-    Ok(out)
+    let spill = IndexSpillOptions {
+        spill: with_opts.spill,
+        threshold: with_opts.spill_threshold,
+    };
+    Ok((out, spill))
 }
 
 pub fn plan_alter_index_options(
@@ -2525,7 +3199,10 @@ pub fn plan_alter_index_options(
             }))
         }
         AlterIndexAction::SetOptions(options) => {
-            let options = plan_index_options(options)?;
+            let (options, spill) = plan_index_options(options)?;
+            if spill.spill.is_some() || spill.threshold.is_some() {
+                bail!("cannot change the spill options of an existing index");
+            }
             Ok(Plan::AlterIndexSetOptions(AlterIndexSetOptionsPlan {
                 id,
                 options,
@@ -2581,3 +3258,115 @@ pub fn plan_alter_object_rename(
         object_type,
     }))
 }
+
+pub fn describe_alter_source(
+    _: &StatementContext,
+    _: AlterSourceStatement,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_alter_source(
+    scx: &StatementContext,
+    AlterSourceStatement {
+        source_name,
+        if_exists,
+        action,
+    }: AlterSourceStatement,
+) -> Result<Plan, anyhow::Error> {
+    let entry = match scx.resolve_item(source_name) {
+        Ok(source) => source,
+        Err(_) if if_exists => {
+            // TODO(benesch): generate a notice indicating this source does not
+            // exist.
+            return Ok(Plan::AlterNoop(AlterNoopPlan {
+                object_type: ObjectType::Source,
+            }));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if entry.item_type() != CatalogItemType::Source {
+        bail!("{} is a {} not a source", entry.name(), entry.item_type())
+    }
+
+    match action {
+        AlterSourceAction::RefreshSchema { seed } => {
+            // Replan the source's definition with the new schemas in place of
+            // the ones it was created with.
+            let mut stmt =
+                match mz_sql_parser::parser::parse_statements(entry.create_sql())?.into_element() {
+                    Statement::CreateSource(stmt) => stmt,
+                    _ => unreachable!("source created by non-CREATE SOURCE statement"),
+                };
+            match &mut stmt.format {
+                CreateSourceFormat::Bare(Format::Avro(AvroSchema::Csr { csr_connector })) => {
+                    match seed {
+                        Some(seed) => csr_connector.seed = Some(seed),
+                        None => bail!("ALTER SOURCE ... REFRESH SCHEMA was not purified"),
+                    }
+                }
+                _ => bail!(
+                    "{} does not use FORMAT AVRO USING CONFLUENT SCHEMA REGISTRY; \
+                     only such sources support REFRESH SCHEMA",
+                    entry.name()
+                ),
+            }
+            let source = match plan_create_source(scx, stmt)? {
+                Plan::CreateSource(CreateSourcePlan { source, .. }) => source,
+                _ => unreachable!("CREATE SOURCE planned as something else"),
+            };
+
+            check_refreshed_source_desc(scx, entry.desc()?, &source.desc)?;
+
+            Ok(Plan::AlterSourceRefreshSchema(
+                AlterSourceRefreshSchemaPlan {
+                    id: entry.id(),
+                    source,
+                },
+            ))
+        }
+    }
+}
+
+/// Ensures that a source with description `new` can transparently replace a
+/// source with description `old`, i.e., that `new` keeps all of the existing
+/// columns as they are and only appends nullable columns.
+fn check_refreshed_source_desc(
+    scx: &StatementContext,
+    old: &RelationDesc,
+    new: &RelationDesc,
+) -> Result<(), anyhow::Error> {
+    if new.arity() < old.arity() {
+        bail!(
+            "refreshed schema removes {} column(s) from the source",
+            old.arity() - new.arity()
+        );
+    }
+    for ((old_name, old_typ), (new_name, new_typ)) in old.iter().zip(new.iter()) {
+        if old_name != new_name {
+            bail!(
+                "refreshed schema replaces column {} with column {}; \
+                 new columns may only be added after all existing columns",
+                old_name.as_str().quoted(),
+                new_name.as_str().quoted()
+            );
+        }
+        if old_typ != new_typ {
+            bail!(
+                "refreshed schema changes the type of column {} from {} to {}",
+                old_name.as_str().quoted(),
+                scx.humanize_column_type(old_typ),
+                scx.humanize_column_type(new_typ)
+            );
+        }
+    }
+    for (name, typ) in new.iter().skip(old.arity()) {
+        if !typ.nullable {
+            bail!(
+                "refreshed schema adds non-nullable column {}; added columns must be nullable",
+                name.as_str().quoted()
+            );
+        }
+    }
+    Ok(())
+}
@@ -13,25 +13,33 @@
 //! `INSERT`, `SELECT`, `TAIL`, and `COPY`.
 
 use std::collections::HashMap;
+use std::iter;
+use std::time::Duration;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 
+use mz_dataflow_types::sinks::TailEnvelope;
 use mz_expr::MirRelationExpr;
+use mz_interchange::envelopes::dbz_desc;
 use mz_ore::collections::CollectionExt;
-use mz_repr::{RelationDesc, ScalarType};
+use mz_repr::adt::interval::Interval;
+use mz_repr::{ColumnName, ColumnType, RelationDesc, ScalarType};
 
 use crate::ast::{
-    CopyDirection, CopyRelation, CopyStatement, CopyTarget, CreateViewStatement, DeleteStatement,
-    ExplainStage, ExplainStatement, Explainee, Ident, InsertStatement, Query, Raw, SelectStatement,
-    Statement, TailRelation, TailStatement, UnresolvedObjectName, UpdateStatement, ViewDefinition,
+    self, CopyDirection, CopyRelation, CopyStatement, CopyTarget, CreateViewStatement,
+    DeleteStatement, ExplainStage, ExplainStatement, Explainee, Ident, InsertStatement, Query, Raw,
+    SelectStatement, Statement, TailRelation, TailStatement, UnresolvedObjectName, UpdateStatement,
+    Value, ViewDefinition,
 };
 use crate::catalog::CatalogItemType;
+use crate::normalize;
 use crate::plan::query;
 use crate::plan::query::QueryLifetime;
 use crate::plan::statement::{StatementContext, StatementDesc};
 use crate::plan::{
-    CopyFormat, CopyFromPlan, CopyParams, ExplainPlan, InsertPlan, MutationKind, Params, PeekPlan,
-    PeekWhen, Plan, ReadThenWritePlan, TailFrom, TailPlan,
+    CopyFormat, CopyFromPlan, CopyParams, ExplainAnalyzePlan, ExplainPlan, InsertPlan,
+    MutationKind, Params, PeekPlan, PeekWhen, Plan, ReadThenWritePlan, TailFrom, TailPlan,
+    TailSubscription,
 };
 
 // TODO(benesch): currently, describing a `SELECT` or `INSERT` query
@@ -178,6 +186,7 @@ pub fn describe_explain(
             ExplainStage::DecorrelatedPlan => "Decorrelated Plan",
             ExplainStage::OptimizedPlan { .. } => "Optimized Plan",
             ExplainStage::PhysicalPlan => "Physical Plan",
+            ExplainStage::Analyze => "Analyzed Plan",
         },
         ScalarType::String.nullable(false),
     )))
@@ -205,6 +214,9 @@ pub fn plan_explain(
     }: ExplainStatement<Raw>,
     params: &Params,
 ) -> Result<Plan, anyhow::Error> {
+    if stage == ExplainStage::Analyze {
+        return plan_explain_analyze(scx, explainee);
+    }
     let is_view = matches!(explainee, Explainee::View(_));
     let query = match explainee {
         Explainee::View(name) => {
@@ -224,6 +236,9 @@ pub fn plan_explain(
             query
         }
         Explainee::Query(query) => query,
+        Explainee::Index(_) | Explainee::Sink(_) => {
+            bail!("only EXPLAIN ANALYZE can explain indexes and sinks")
+        }
     };
     // Previouly we would bail here for ORDER BY and LIMIT; this has been relaxed to silently
     // report the plan without the ORDER BY and LIMIT decorations (which are done in post).
@@ -251,6 +266,28 @@ pub fn plan_explain(
     }))
 }
 
+fn plan_explain_analyze(
+    scx: &StatementContext,
+    explainee: Explainee<Raw>,
+) -> Result<Plan, anyhow::Error> {
+    let (name, expected_type) = match explainee {
+        Explainee::Index(name) => (name, CatalogItemType::Index),
+        Explainee::Sink(name) => (name, CatalogItemType::Sink),
+        Explainee::View(name) => (name, CatalogItemType::View),
+        Explainee::Query(_) => bail!("EXPLAIN ANALYZE cannot explain queries"),
+    };
+    let item = scx.resolve_item(name.clone())?;
+    if item.item_type() != expected_type {
+        bail!(
+            "Expected {} to be a {}, not a {}",
+            name,
+            expected_type,
+            item.item_type()
+        );
+    }
+    Ok(Plan::ExplainAnalyze(ExplainAnalyzePlan { id: item.id() }))
+}
+
 /// Plans and decorrelates a `Query`. Like `query::plan_root_query`, but returns
 /// an `mz_expr::MirRelationExpr`, which cannot include correlated expressions.
 pub fn plan_query(
@@ -278,13 +315,21 @@ with_options! {
     struct TailOptions {
         snapshot: bool,
         progress: bool,
+        name: String,
+        max_retention: Interval,
      }
 }
 
+/// The amount of history a named subscription retains by default.
+const DEFAULT_SUBSCRIPTION_MAX_RETENTION: Duration = Duration::from_secs(60 * 60);
+
 pub fn describe_tail(
     scx: &StatementContext,
     TailStatement {
-        relation, options, ..
+        relation,
+        envelope,
+        options,
+        ..
     }: TailStatement<Raw>,
 ) -> Result<StatementDesc, anyhow::Error> {
     let relation_desc = match relation {
@@ -295,6 +340,7 @@ pub fn describe_tail(
             desc
         }
     };
+    let envelope = plan_tail_envelope(&relation_desc, envelope)?;
     let options = TailOptions::try_from(options)?;
     let progress = options.progress.unwrap_or(false);
     let mut desc = RelationDesc::empty().with_column(
@@ -304,8 +350,27 @@ pub fn describe_tail(
     if progress {
         desc = desc.with_column("mz_progressed", ScalarType::Bool.nullable(false));
     }
-    desc = desc.with_column("mz_diff", ScalarType::Int64.nullable(true));
-    for (name, mut ty) in relation_desc.into_iter() {
+    let columns: Vec<(ColumnName, ColumnType)> = match &envelope {
+        None => {
+            desc = desc.with_column("mz_diff", ScalarType::Int64.nullable(true));
+            relation_desc.into_iter().collect()
+        }
+        Some(TailEnvelope::Upsert { key_indices }) => {
+            let columns: Vec<_> = relation_desc.into_iter().collect();
+            let keys = key_indices.iter().map(|i| columns[*i].clone());
+            let values = columns
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !key_indices.contains(i))
+                .map(|(_, (name, ty))| (name.clone(), ty.clone().nullable(true)));
+            iter::once(("mz_state".into(), ScalarType::String.nullable(false)))
+                .chain(keys)
+                .chain(values)
+                .collect()
+        }
+        Some(TailEnvelope::Debezium { .. }) => dbz_desc(relation_desc).into_iter().collect(),
+    };
+    for (name, mut ty) in columns {
         if progress {
             ty.nullable = true;
         }
@@ -314,21 +379,57 @@ pub fn describe_tail(
     Ok(StatementDesc::new(Some(desc)))
 }
 
+/// Resolves the key columns of the envelope of a `TAIL` of a relation
+/// described by `desc`.
+fn plan_tail_envelope(
+    desc: &RelationDesc,
+    envelope: Option<ast::TailEnvelope>,
+) -> Result<Option<TailEnvelope>, anyhow::Error> {
+    let envelope = match envelope {
+        None => return Ok(None),
+        Some(envelope) => envelope,
+    };
+    let key_columns = match &envelope {
+        ast::TailEnvelope::Upsert { key_columns } | ast::TailEnvelope::Debezium { key_columns } => {
+            key_columns
+        }
+    };
+    let mut key_indices = vec![];
+    for col in key_columns.iter().cloned().map(normalize::column_name) {
+        let idx = desc
+            .get_by_name(&col)
+            .map(|(idx, _type)| idx)
+            .ok_or_else(|| anyhow!("No such column: {}", col))?;
+        if desc.get_unambiguous_name(idx).is_none() {
+            bail!("Ambiguous column: {}", col);
+        }
+        if key_indices.contains(&idx) {
+            bail!("Repeated column name in TAIL key: {}", col);
+        }
+        key_indices.push(idx);
+    }
+    Ok(Some(match envelope {
+        ast::TailEnvelope::Upsert { .. } => TailEnvelope::Upsert { key_indices },
+        ast::TailEnvelope::Debezium { .. } => TailEnvelope::Debezium { key_indices },
+    }))
+}
+
 pub fn plan_tail(
     scx: &StatementContext,
     TailStatement {
         relation,
+        envelope,
         options,
         as_of,
     }: TailStatement<Raw>,
     copy_to: Option<CopyFormat>,
 ) -> Result<Plan, anyhow::Error> {
-    let from = match relation {
+    let (from, desc) = match relation {
         TailRelation::Name(name) => {
             let entry = scx.resolve_item(name)?;
             match entry.item_type() {
                 CatalogItemType::Table | CatalogItemType::Source | CatalogItemType::View => {
-                    TailFrom::Id(entry.id())
+                    (TailFrom::Id(entry.id()), entry.desc()?.clone())
                 }
                 CatalogItemType::Func
                 | CatalogItemType::Index
@@ -353,22 +454,48 @@ pub fn plan_tail(
                 QueryLifetime::OneShot(scx.pcx()?),
             )?;
             assert!(query.finishing.is_trivial(query.desc.arity()));
-            TailFrom::Query {
+            let desc = query.desc.clone();
+            let from = TailFrom::Query {
                 expr: query.expr,
                 desc: query.desc,
                 depends_on: query.depends_on,
-            }
+            };
+            (from, desc)
         }
     };
+    let envelope = plan_tail_envelope(&desc, envelope)?;
 
     let ts = as_of.map(|e| query::eval_as_of(scx, e)).transpose()?;
     let options = TailOptions::try_from(options)?;
+    let subscription = match options.name {
+        Some(name) => {
+            if !matches!(from, TailFrom::Id(_)) {
+                bail!("TAIL with a subscription name requires a named relation, not a query");
+            }
+            let max_retention = match options.max_retention {
+                Some(interval) => interval.duration()?,
+                None => DEFAULT_SUBSCRIPTION_MAX_RETENTION,
+            };
+            Some(TailSubscription {
+                name,
+                max_retention,
+            })
+        }
+        None => {
+            if options.max_retention.is_some() {
+                bail!("max_retention can only be specified for a named TAIL");
+            }
+            None
+        }
+    };
     Ok(Plan::Tail(TailPlan {
         from,
         ts,
         with_snapshot: options.snapshot.unwrap_or(true),
         copy_to,
         emit_progress: options.progress.unwrap_or(false),
+        subscription,
+        envelope,
     }))
 }
 
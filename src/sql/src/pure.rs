@@ -20,8 +20,9 @@ use std::sync::Arc;
 use anyhow::{anyhow, bail, ensure, Context};
 use aws_arn::ARN;
 use csv::ReaderBuilder;
+use globset::GlobBuilder;
 use itertools::Itertools;
-use mz_ccsr::Client;
+use mz_ccsr::{Client, GetBySubjectError};
 use mz_sql_parser::ast::{CsrSeedCompiledOrLegacy, Op};
 use reqwest::Url;
 use tokio::fs::File;
@@ -31,21 +32,23 @@ use uuid::Uuid;
 
 use mz_dataflow_types::sources::AwsConfig;
 use mz_dataflow_types::sources::{
-    ExternalSourceConnector, PostgresSourceConnector, SourceConnector,
+    ExternalSourceConnector, PostgresSourceConnector, SourceConnector, WEBHOOK_KEY_ENV_PREFIX,
 };
-use mz_repr::strconv;
+use mz_ore::collections::CollectionExt;
+use mz_ore::str::StrExt;
+use mz_repr::{strconv, ColumnType};
 use mz_sql_parser::parser::parse_columns;
 use protobuf_native::compiler::{SourceTreeDescriptorDatabase, VirtualSourceTree};
 use protobuf_native::MessageLite;
 
 use crate::ast::{
-    display::AstDisplay, AvroSchema, CreateSourceConnector, CreateSourceFormat,
-    CreateSourceStatement, CreateViewsDefinitions, CreateViewsSourceTarget, CreateViewsStatement,
-    CsrConnectorAvro, CsrConnectorProto, CsrSeed, CsrSeedCompiled, CsrSeedCompiledEncoding,
-    CsvColumns, DbzMode, Envelope, Expr, Format, Ident, ProtobufSchema, Query, Raw, RawName,
-    Select, SelectItem, SetExpr, SourceIncludeMetadata, SourceIncludeMetadataType, SqlOption,
-    Statement, TableFactor, TableWithJoins, UnresolvedObjectName, Value, ViewDefinition,
-    WithOption, WithOptionValue,
+    display::AstDisplay, AlterSourceAction, AlterSourceStatement, AvroSchema,
+    CreateSourceConnector, CreateSourceFormat, CreateSourceStatement, CreateViewsDefinitions,
+    CreateViewsSourceTarget, CreateViewsStatement, CsrConnectorAvro, CsrConnectorProto, CsrSeed,
+    CsrSeedCompiled, CsrSeedCompiledEncoding, CsvColumns, DbzMode, Envelope, Expr, Format, Ident,
+    ProtobufSchema, Query, Raw, RawName, S3KeySource, Select, SelectItem, SetExpr,
+    SourceIncludeMetadata, SourceIncludeMetadataType, SqlOption, Statement, TableFactor,
+    TableWithJoins, UnresolvedObjectName, Value, ViewDefinition, WithOption, WithOptionValue,
 };
 use crate::catalog::SessionCatalog;
 use crate::kafka_util;
@@ -84,6 +87,20 @@ pub fn purify(
         Err(anyhow!("SQL statement does not refer to a source"))
     };
 
+    // Similarly, refreshing the schema of a source requires the source's
+    // definition, which determines where to fetch the new schema from.
+    let refreshed_source_sql = match &stmt {
+        Statement::AlterSource(AlterSourceStatement {
+            source_name,
+            action: AlterSourceAction::RefreshSchema { .. },
+            ..
+        }) => normalize::unresolved_object_name(source_name.clone())
+            .ok()
+            .and_then(|name| catalog.resolve_item(&name).ok())
+            .map(|item| item.create_sql().to_owned()),
+        _ => None,
+    };
+
     let now = catalog.now();
     let aws_external_id = catalog.config().aws_external_id.clone();
 
@@ -175,10 +192,28 @@ pub fn purify(
                     }
                     file = Some(f);
                 }
-                CreateSourceConnector::S3 { .. } => {
+                CreateSourceConnector::S3 {
+                    key_sources,
+                    pattern,
+                    ..
+                } => {
                     let aws_config =
                         normalize::aws_config(&mut with_options_map, None, aws_external_id)?;
                     validate_aws_credentials(&aws_config).await?;
+
+                    if matches!(format, CreateSourceFormat::Bare(Format::Parquet))
+                        && !with_options_map.contains_key("parquet_schema")
+                    {
+                        let columns =
+                            infer_parquet_columns(&aws_config, key_sources, pattern.as_deref())
+                                .await?;
+                        with_options.push(mz_sql_parser::ast::SqlOption::Value {
+                            name: mz_sql_parser::ast::Ident::new("parquet_schema"),
+                            value: mz_sql_parser::ast::Value::String(serde_json::to_string(
+                                &columns,
+                            )?),
+                        });
+                    }
                 }
                 CreateSourceConnector::Kinesis { arn } => {
                     let region = arn
@@ -212,6 +247,25 @@ pub fn purify(
                     let _ = mz_postgres_util::publication_info(&conn, &publication).await?;
                 }
                 CreateSourceConnector::PubNub { .. } => (),
+                CreateSourceConnector::Webhook { validation, .. } => {
+                    // The key is only read when requests arrive, so catch
+                    // typos in the name of its variable early.
+                    if let Some(validation) = validation {
+                        if !validation.key_env.starts_with(WEBHOOK_KEY_ENV_PREFIX) {
+                            bail!(
+                                "environment variable {} does not start with {}",
+                                validation.key_env.quoted(),
+                                WEBHOOK_KEY_ENV_PREFIX.quoted()
+                            );
+                        }
+                        if std::env::var_os(&validation.key_env).is_none() {
+                            bail!(
+                                "environment variable {} is not set",
+                                validation.key_env.quoted()
+                            );
+                        }
+                    }
+                }
             }
 
             purify_source_format(
@@ -386,6 +440,40 @@ pub fn purify(
                 }
             }
         }
+
+        if let Statement::AlterSource(AlterSourceStatement {
+            action: AlterSourceAction::RefreshSchema { seed },
+            ..
+        }) = &mut stmt
+        {
+            // Sources that are not seeded from a schema registry are rejected
+            // during planning.
+            if let Some(create_sql) = refreshed_source_sql {
+                let source_stmt = mz_sql_parser::parser::parse_statements(&create_sql)?;
+                if let Statement::CreateSource(CreateSourceStatement {
+                    mut connector,
+                    format:
+                        CreateSourceFormat::Bare(Format::Avro(AvroSchema::Csr { csr_connector })),
+                    envelope,
+                    with_options,
+                    ..
+                }) = source_stmt.into_element()
+                {
+                    let mut csr_connector = csr_connector;
+                    let mut with_options_map = normalize::options(&with_options);
+                    let config_options = kafka_util::extract_config(&mut with_options_map)?;
+                    csr_connector.seed = None;
+                    purify_csr_connector_avro(
+                        &mut connector,
+                        &mut csr_connector,
+                        &envelope,
+                        &config_options,
+                    )
+                    .await?;
+                    *seed = csr_connector.seed;
+                }
+            }
+        }
         Ok(stmt)
     }
 }
@@ -508,9 +596,18 @@ async fn purify_source_format_single(
             _ => {}
         },
         Format::Protobuf(schema) => match schema {
-            ProtobufSchema::Csr { csr_connector } => {
-                purify_csr_connector_proto(connector, csr_connector, envelope, with_options)
-                    .await?;
+            ProtobufSchema::Csr {
+                message_name,
+                csr_connector,
+            } => {
+                purify_csr_connector_proto(
+                    connector,
+                    message_name.as_deref(),
+                    csr_connector,
+                    envelope,
+                    with_options,
+                )
+                .await?;
             }
             ProtobufSchema::InlineSchema {
                 message_name: _,
@@ -530,13 +627,14 @@ async fn purify_source_format_single(
         } => {
             purify_csv(file, connector, *delimiter, columns).await?;
         }
-        Format::Bytes | Format::Regex(_) | Format::Json | Format::Text => (),
+        Format::Bytes | Format::Regex(_) | Format::Json | Format::Parquet | Format::Text => (),
     }
     Ok(())
 }
 
 async fn purify_csr_connector_proto(
     connector: &mut CreateSourceConnector,
+    message_name: Option<&str>,
     csr_connector: &mut CsrConnectorProto<Raw>,
     envelope: &Envelope,
     with_options: &Vec<SqlOption<Raw>>,
@@ -562,11 +660,22 @@ async fn purify_csr_connector_proto(
                 &mut normalize::options(&ccsr_options),
             )?;
 
-            let value =
-                compile_proto(&format!("{}-value", topic), ccsr_config.clone().build()?).await?;
-            let key = compile_proto(&format!("{}-key", topic), ccsr_config.build()?)
-                .await
-                .ok();
+            let value = compile_proto(
+                &format!("{}-value", topic),
+                message_name,
+                ccsr_config.clone().build()?,
+            )
+            .await?;
+            let key_subject = format!("{}-key", topic);
+            let key = match compile_proto(&key_subject, None, ccsr_config.build()?).await {
+                Ok(key) => Some(key),
+                // Topics are not required to have a key schema, but a key
+                // schema that exists must be valid.
+                Err(e) if is_subject_not_found(&e) => None,
+                Err(e) => {
+                    return Err(e.context(format!("compiling key schema {}", key_subject.quoted())))
+                }
+            };
 
             if matches!(envelope, Envelope::Debezium(DbzMode::Upsert)) && key.is_none() {
                 bail!("Key schema is required for ENVELOPE DEBEZIUM UPSERT");
@@ -795,8 +904,12 @@ async fn get_remote_csr_schema(
 }
 
 /// Collect protobuf message descriptor from CSR and compile the descriptor.
+///
+/// If `message_name` is not specified, the subject's schema must contain
+/// exactly one top-level message.
 async fn compile_proto(
     subject_name: &String,
+    message_name: Option<&str>,
     ccsr_client: Client,
 ) -> Result<CsrSeedCompiledEncoding, anyhow::Error> {
     let (primary_subject, dependency_subjects) =
@@ -815,17 +928,14 @@ async fn compile_proto(
         .as_mut()
         .build_file_descriptor_set(&[Path::new(&primary_subject.name)])?;
 
-    // Ensure there is exactly one message in the file.
-    let primary_fd = fds.file(0);
-    let message_name = match primary_fd.message_type_size() {
-        1 => String::from_utf8_lossy(primary_fd.message_type(0).name()).into_owned(),
-        0 => bail_unsupported!(9598, "Protobuf schemas with no messages"),
-        _ => bail_unsupported!(9598, "Protobuf schemas with multiple messages"),
-    };
+    // Determine which message in the primary file to decode.
+    let fds = fds.serialize()?;
+    let message_name =
+        mz_interchange::protobuf::resolve_message_name(&fds, &primary_subject.name, message_name)?;
 
     // Encode the file descriptor set into a SQL byte string.
     let mut schema = String::new();
-    strconv::format_bytes(&mut schema, &fds.serialize()?);
+    strconv::format_bytes(&mut schema, &fds);
 
     Ok(CsrSeedCompiledEncoding {
         schema,
@@ -833,6 +943,86 @@ async fn compile_proto(
     })
 }
 
+/// Reports whether `e` indicates that a subject is absent from the schema
+/// registry.
+fn is_subject_not_found(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<GetBySubjectError>(),
+        Some(GetBySubjectError::SubjectNotFound)
+    )
+}
+
+/// Infers the columns of a Parquet S3 source from the first object that a
+/// bucket scan would discover.
+async fn infer_parquet_columns(
+    config: &AwsConfig,
+    key_sources: &[S3KeySource],
+    pattern: Option<&str>,
+) -> Result<Vec<(String, ColumnType)>, anyhow::Error> {
+    let bucket = key_sources
+        .iter()
+        .find_map(|ks| match ks {
+            S3KeySource::Scan { bucket } => Some(bucket),
+            S3KeySource::SqsNotifications { .. } => None,
+        })
+        .ok_or_else(|| {
+            anyhow!("FORMAT PARQUET requires a BUCKET SCAN to determine the source's columns")
+        })?;
+    let matcher = pattern
+        .map(|p| {
+            GlobBuilder::new(p)
+                .literal_separator(true)
+                .backslash_escape(true)
+                .build()
+                .map(|g| g.compile_matcher())
+        })
+        .transpose()?;
+
+    let config = config.load().await;
+    let client = mz_aws_util::s3::client(&config);
+    let mut continuation_token = None;
+    let key = loop {
+        let response = client
+            .list_objects_v2()
+            .bucket(bucket)
+            .set_continuation_token(continuation_token)
+            .send()
+            .await
+            .with_context(|| format!("Unable to list S3 bucket {}", bucket))?;
+        let key = response
+            .contents
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|o| o.key)
+            .find(|key| matcher.as_ref().map_or(true, |m| m.is_match(key)));
+        match (key, response.next_continuation_token) {
+            (Some(key), _) => break key,
+            (None, Some(token)) => continuation_token = Some(token),
+            (None, None) => bail!(
+                "FORMAT PARQUET requires at least one object in bucket {} to determine the \
+                 source's columns",
+                bucket
+            ),
+        }
+    };
+
+    let object = client
+        .get_object()
+        .bucket(bucket)
+        .key(&key)
+        .send()
+        .await
+        .with_context(|| format!("Unable to get S3 object {}/{}", bucket, key))?;
+    let bytes = object
+        .body
+        .collect()
+        .await
+        .with_context(|| format!("Unable to read S3 object {}/{}", bucket, key))?
+        .into_bytes();
+    mz_interchange::parquet::parquet_columns(&bytes)
+        .with_context(|| format!("Unable to read Parquet schema from {}/{}", bucket, key))
+}
+
 /// Makes an always-valid AWS API call to perform a basic sanity check of
 /// whether the specified AWS configuration is valid.
 async fn validate_aws_credentials(config: &AwsConfig) -> Result<(), anyhow::Error> {
@@ -555,6 +555,7 @@ impl Runner {
             experimental_mode: true,
             disable_user_indexes: false,
             safe_mode: false,
+            cost_based_join_ordering: false,
             telemetry: None,
             introspection_frequency: Duration::from_secs(1),
             metrics_registry: MetricsRegistry::new(),
@@ -531,6 +531,9 @@ pub(crate) async fn build(
                     "s3-add-notifications" => {
                         Box::new(s3::build_add_notifications(builtin).map_err(wrap_err)?)
                     }
+                    "s3-wait-for-object" => {
+                        Box::new(s3::build_wait_for_object(builtin).map_err(wrap_err)?)
+                    }
                     "set-regex" => Box::new(set::build_regex(builtin).map_err(wrap_err)?),
                     "set-sql-timeout" => {
                         Box::new(set::build_sql_timeout(builtin).map_err(wrap_err)?)
@@ -118,18 +118,24 @@ where
 
 pub struct VerifyAction {
     sink: String,
+    file: usize,
     expected: Vec<String>,
 }
 
 pub fn build_verify(mut cmd: BuiltinCommand) -> Result<VerifyAction, anyhow::Error> {
     let sink = cmd.args.string("sink")?;
+    let file = cmd.args.opt_parse("file")?.unwrap_or(0);
     let expected = cmd.input;
     cmd.args.done()?;
     if sink.contains(path::MAIN_SEPARATOR) {
         // The goal isn't security, but preventing mistakes.
         bail!("separators in file sink names are forbidden");
     }
-    Ok(VerifyAction { sink, expected })
+    Ok(VerifyAction {
+        sink,
+        file,
+        expected,
+    })
 }
 
 #[async_trait]
@@ -158,27 +164,46 @@ impl Action for VerifyAction {
             .await
             .context("retrieving path")?;
 
+        // The files that the sink starts when it rotates have `-<n>` appended
+        // to the file stem of the first file.
+        let path = if self.file == 0 {
+            path
+        } else {
+            let mut name = path.file_stem().unwrap_or_default().to_owned();
+            name.push(format!("-{}", self.file));
+            if let Some(extension) = path.extension() {
+                name.push(".");
+                name.push(extension);
+            }
+            path.with_file_name(name)
+        };
+
         println!("Verifying results in file {}", path.display());
 
         // Get the rows from this file. There is no async `mz_avro::Reader`, so
-        // we drop into synchronous code here.
+        // we drop into synchronous code here. The sink writes the file, and
+        // starts rotated files, asynchronously, so retry until it matches.
         tokio::task::block_in_place(|| {
-            let file = File::open(&path)
-                .with_context(|| format!("reading sink file {}", path.display()))?;
-            let reader = Reader::new(file).context("creating avro reader")?;
-            let schema = reader.writer_schema().clone();
-            let actual = reader
-                .map(|res| res.map(|val| (None, Some(val))))
-                .collect::<Result<Vec<_>, _>>()
-                .context("reading avro values from file")?;
-            avro::validate_sink(
-                None,
-                &schema,
-                &self.expected,
-                &actual,
-                &state.regex,
-                &state.regex_replacement,
-            )
+            Retry::default()
+                .max_duration(state.default_timeout)
+                .retry(|_| {
+                    let file = File::open(&path)
+                        .with_context(|| format!("reading sink file {}", path.display()))?;
+                    let reader = Reader::new(file).context("creating avro reader")?;
+                    let schema = reader.writer_schema().clone();
+                    let actual = reader
+                        .map(|res| res.map(|val| (None, Some(val))))
+                        .collect::<Result<Vec<_>, _>>()
+                        .context("reading avro values from file")?;
+                    avro::validate_sink(
+                        None,
+                        &schema,
+                        &self.expected,
+                        &actual,
+                        &state.regex,
+                        &state.regex_replacement,
+                    )
+                })
         })?;
 
         Ok(ControlFlow::Continue)
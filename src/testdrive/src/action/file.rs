@@ -11,7 +11,7 @@ use std::path;
 use std::str::FromStr;
 
 use anyhow::bail;
-use async_compression::tokio::write::GzipEncoder;
+use async_compression::tokio::write::{GzipEncoder, ZstdEncoder};
 use async_trait::async_trait;
 use tokio::fs::{self, OpenOptions};
 use tokio::io::{AsyncWrite, AsyncWriteExt};
@@ -28,6 +28,7 @@ pub struct AppendAction {
 
 pub enum Compression {
     Gzip,
+    Zstd,
     None,
 }
 
@@ -37,6 +38,7 @@ impl FromStr for Compression {
     fn from_str(s: &str) -> Result<Self, anyhow::Error> {
         match s {
             "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
             "none" => Ok(Compression::None),
             f => bail!("unknown compression format: {}", f),
         }
@@ -99,6 +101,7 @@ impl Action for AppendAction {
 
         let mut file: Box<dyn AsyncWrite + Unpin + Send> = match self.compression {
             Compression::Gzip => Box::new(GzipEncoder::new(file)),
+            Compression::Zstd => Box::new(ZstdEncoder::new(file)),
             Compression::None => Box::new(file),
         };
 
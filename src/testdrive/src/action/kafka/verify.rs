@@ -11,11 +11,14 @@ use std::cmp;
 use std::str;
 use std::time::Duration;
 
-use anyhow::{bail, Context};
+use anyhow::{anyhow, bail, Context};
 use async_trait::async_trait;
 use byteorder::{BigEndian, ByteOrder};
+use prost::Message as _;
+use prost_reflect::{DynamicMessage, FileDescriptor, MessageDescriptor};
 use rdkafka::consumer::{Consumer, StreamConsumer};
-use rdkafka::message::Message;
+use rdkafka::message::{Headers, Message};
+use tokio::fs;
 use tokio::pin;
 use tokio_stream::StreamExt;
 
@@ -25,7 +28,14 @@ use crate::parser::BuiltinCommand;
 
 pub enum SinkFormat {
     Avro,
-    Json { key: bool },
+    Json {
+        key: bool,
+    },
+    Protobuf {
+        descriptor_file: String,
+        key_message: Option<String>,
+        message: String,
+    },
 }
 
 pub enum SinkConsistencyFormat {
@@ -39,6 +49,7 @@ pub struct VerifyAction {
     sort_messages: bool,
     expected_messages: Vec<String>,
     partial_search: Option<usize>,
+    metadata: bool,
 }
 
 pub fn build_verify(mut cmd: BuiltinCommand) -> Result<VerifyAction, anyhow::Error> {
@@ -47,6 +58,11 @@ pub fn build_verify(mut cmd: BuiltinCommand) -> Result<VerifyAction, anyhow::Err
         "json" => SinkFormat::Json {
             key: cmd.args.parse("key")?,
         },
+        "protobuf" => SinkFormat::Protobuf {
+            descriptor_file: cmd.args.string("descriptor-file")?,
+            key_message: cmd.args.opt_string("key-message"),
+            message: cmd.args.string("message")?,
+        },
         f => bail!("unknown format: {}", f),
     };
     let sink = cmd.args.string("sink")?;
@@ -64,6 +80,10 @@ pub fn build_verify(mut cmd: BuiltinCommand) -> Result<VerifyAction, anyhow::Err
         bail!("kafka-verify requires a non-empty list of expected messages");
     }
     let partial_search = cmd.args.opt_parse("partial-search")?;
+    let metadata = cmd.args.opt_bool("metadata")?.unwrap_or(false);
+    if metadata && !matches!(format, SinkFormat::Json { .. }) {
+        bail!("kafka-verify metadata=true is only supported for json formatted sinks");
+    }
     cmd.args.done()?;
     Ok(VerifyAction {
         sink,
@@ -72,6 +92,7 @@ pub fn build_verify(mut cmd: BuiltinCommand) -> Result<VerifyAction, anyhow::Err
         sort_messages,
         expected_messages,
         partial_search,
+        metadata,
     })
 }
 
@@ -100,6 +121,49 @@ fn avro_from_bytes(
     Ok(datum)
 }
 
+/// Decodes a Protobuf message in the Confluent wire format, verifying that it
+/// was encoded with the schema `schema_id` and that its message indexes refer
+/// to the first message of the schema.
+fn protobuf_from_bytes(
+    message: &MessageDescriptor,
+    schema_id: i32,
+    bytes: &[u8],
+) -> Result<serde_json::Value, anyhow::Error> {
+    if bytes.len() < 6 {
+        bail!(
+            "protobuf message is too few bytes: expected at least 6 bytes, got {}",
+            bytes.len()
+        );
+    }
+    let magic = bytes[0];
+    if magic != 0 {
+        bail!(
+            "wrong protobuf serialization magic: expected 0, got {}",
+            magic
+        );
+    }
+    let actual_schema_id = BigEndian::read_i32(&bytes[1..5]);
+    if actual_schema_id != schema_id {
+        bail!(
+            "wrong protobuf schema ID: expected {}, got {}",
+            schema_id,
+            actual_schema_id
+        );
+    }
+    // The abbreviated encoding of the message indexes `[0]`.
+    if bytes[5] != 0 {
+        bail!(
+            "wrong protobuf message indexes: expected [0], got a list of {} indexes",
+            bytes[5]
+        );
+    }
+    let mut datum = DynamicMessage::new(message.clone());
+    datum
+        .merge(&bytes[6..])
+        .context("decoding protobuf message")?;
+    serde_json::to_value(&datum).context("converting protobuf message to json")
+}
+
 async fn get_topic(
     sink: &str,
     topic_field: &str,
@@ -163,9 +227,23 @@ impl Action for VerifyAction {
                     consumer
                         .store_offset_from_message(&message)
                         .context("storing message offset")?;
+                    let headers = message
+                        .headers()
+                        .map(|headers| {
+                            (0..headers.count())
+                                .filter_map(|i| headers.get(i))
+                                .map(|(name, value)| {
+                                    let value = String::from_utf8_lossy(value).into_owned();
+                                    (name.to_owned(), serde_json::Value::String(value))
+                                })
+                                .collect::<serde_json::Map<_, _>>()
+                        })
+                        .unwrap_or_default();
                     actual_bytes.push((
                         message.key().and_then(|bytes| Some(bytes.to_owned())),
                         message.payload().and_then(|bytes| Some(bytes.to_owned())),
+                        message.partition(),
+                        headers,
                     ));
                 }
                 Some(Err(e)) => {
@@ -202,7 +280,7 @@ impl Action for VerifyAction {
                 let value_schema = &value_schema;
 
                 let mut actual_messages = vec![];
-                for (key, value) in actual_bytes {
+                for (key, value, _partition, _headers) in actual_bytes {
                     let key_datum = key_schema
                         .as_ref()
                         .map(|key_schema| {
@@ -240,7 +318,7 @@ impl Action for VerifyAction {
                     "partial search not yet implemented for json formatted sinks"
                 );
                 let mut actual_messages = vec![];
-                for (key, value) in actual_bytes {
+                for (key, value, partition, headers) in actual_bytes {
                     let key_datum = match key {
                         None => None,
                         Some(bytes) => {
@@ -253,6 +331,15 @@ impl Action for VerifyAction {
                             Some(serde_json::from_slice(&bytes).context("decoding json")?)
                         }
                     };
+                    let value_datum = if self.metadata {
+                        Some(serde_json::json!({
+                            "partition": partition,
+                            "headers": headers,
+                            "value": value_datum,
+                        }))
+                    } else {
+                        value_datum
+                    };
 
                     actual_messages.push((key_datum, value_datum));
                 }
@@ -269,6 +356,78 @@ impl Action for VerifyAction {
                     &state.regex_replacement,
                 )?
             }
+            SinkFormat::Protobuf {
+                descriptor_file,
+                key_message,
+                message,
+            } => {
+                assert!(
+                    self.partial_search.is_none(),
+                    "partial search not yet implemented for protobuf formatted sinks"
+                );
+                let bytes = fs::read(state.temp_path.join(descriptor_file))
+                    .await
+                    .context("reading protobuf descriptor file")?;
+                let fd =
+                    FileDescriptor::decode(&*bytes).context("parsing protobuf descriptor file")?;
+                let get_message = |name: &str| {
+                    fd.get_message_by_name(name)
+                        .ok_or_else(|| anyhow!("unknown message name {}", name))
+                };
+                let ccsr_client = &state.ccsr_client;
+                let get_schema_id = |subject: String| async move {
+                    ccsr_client
+                        .get_schema_by_subject(&subject)
+                        .await
+                        .with_context(|| format!("fetching schema for subject {}", subject))
+                        .map(|schema| schema.id)
+                };
+
+                let value_message = get_message(message)?;
+                let value_schema_id = get_schema_id(format!("{}-value", topic)).await?;
+                let key_message = match key_message {
+                    None => None,
+                    Some(key_message) => Some((
+                        get_message(key_message)?,
+                        get_schema_id(format!("{}-key", topic)).await?,
+                    )),
+                };
+
+                let mut actual_messages = vec![];
+                for (key, value, _partition, _headers) in actual_bytes {
+                    let key_datum = match &key_message {
+                        None => None,
+                        Some((key_message, key_schema_id)) => {
+                            let bytes = match key {
+                                Some(key) => key,
+                                None => bail!("empty message key"),
+                            };
+                            Some(protobuf_from_bytes(key_message, *key_schema_id, &bytes)?)
+                        }
+                    };
+                    let value_datum = match value {
+                        None => None,
+                        Some(bytes) => Some(protobuf_from_bytes(
+                            &value_message,
+                            value_schema_id,
+                            &bytes,
+                        )?),
+                    };
+                    actual_messages.push((key_datum, value_datum));
+                }
+
+                if self.sort_messages {
+                    actual_messages.sort_by_key(|k| format!("{:?}", k.1));
+                }
+
+                json::validate_sink(
+                    key_message.is_some(),
+                    &self.expected_messages,
+                    &actual_messages,
+                    &state.regex,
+                    &state.regex_replacement,
+                )?
+            }
         }
         Ok(ControlFlow::Continue)
     }
@@ -12,6 +12,7 @@ use std::io::Write;
 use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, Context};
+use async_compression::tokio::write::ZstdEncoder;
 use async_trait::async_trait;
 use aws_sdk_s3::error::{CreateBucketError, CreateBucketErrorKind};
 use aws_sdk_s3::model::{
@@ -22,6 +23,12 @@ use aws_sdk_s3::{ByteStream, SdkError};
 use aws_sdk_sqs::model::{DeleteMessageBatchRequestEntry, QueueAttributeName};
 use flate2::write::GzEncoder;
 use flate2::Compression as Flate2Compression;
+use globset::{Glob, GlobMatcher};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+
+use mz_ore::retry::Retry;
+use mz_repr::{Datum, RelationDesc, Row, ScalarType};
 
 use crate::action::file::{build_compression, Compression};
 use crate::action::{Action, ControlFlow, State};
@@ -83,15 +90,20 @@ pub struct PutObjectAction {
     bucket_prefix: String,
     key: String,
     compression: Compression,
-    contents: String,
+    contents: Vec<u8>,
 }
 
 pub fn build_put_object(mut cmd: BuiltinCommand) -> Result<PutObjectAction, anyhow::Error> {
     let bucket_prefix = format!("testdrive-{}", cmd.args.string("bucket")?);
     let key = cmd.args.string("key")?;
     let compression = build_compression(&mut cmd)?;
-    let contents = cmd.input.join("\n");
+    let format = cmd.args.opt_string("format");
     cmd.args.done()?;
+    let contents = match format.as_deref() {
+        None | Some("text") => cmd.input.join("\n").into_bytes(),
+        Some("parquet") => parquet_object(&cmd.input)?,
+        Some(f) => bail!("unknown object format: {}", f),
+    };
     Ok(PutObjectAction {
         bucket_prefix,
         key,
@@ -100,6 +112,62 @@ pub fn build_put_object(mut cmd: BuiltinCommand) -> Result<PutObjectAction, anyh
     })
 }
 
+/// Encodes JSON objects, one per line, as the rows of a Parquet object.
+///
+/// The columns of the object are the keys of the first JSON object. The type
+/// of each column is determined by its first non-null value: integers become
+/// `int8` columns, other numbers `float8` columns, and strings and booleans
+/// `text` and `bool` columns.
+fn parquet_object(lines: &[String]) -> Result<Vec<u8>, anyhow::Error> {
+    let objects = lines
+        .iter()
+        .map(|line| serde_json::from_str::<serde_json::Map<String, Value>>(line))
+        .collect::<Result<Vec<_>, _>>()
+        .context("parsing JSON object")?;
+    let names = match objects.first() {
+        Some(object) => object.keys().cloned().collect::<Vec<_>>(),
+        None => bail!("Parquet objects require at least one row"),
+    };
+
+    let mut desc = RelationDesc::empty();
+    for name in &names {
+        let values = || objects.iter().map(|o| o.get(name).unwrap_or(&Value::Null));
+        let scalar_type = match values().find(|v| !v.is_null()) {
+            Some(Value::Bool(_)) => ScalarType::Bool,
+            Some(Value::Number(n)) if n.is_i64() => ScalarType::Int64,
+            Some(Value::Number(_)) => ScalarType::Float64,
+            Some(Value::String(_)) => ScalarType::String,
+            Some(other) => bail!("unsupported value in column {}: {}", name, other),
+            None => bail!("column {} has no non-null values", name),
+        };
+        let nullable = values().any(|v| v.is_null());
+        desc = desc.with_column(name.as_str(), scalar_type.nullable(nullable));
+    }
+
+    let mut rows = vec![];
+    let mut row = Row::default();
+    for object in &objects {
+        for (name, typ) in names.iter().zip(desc.iter_types()) {
+            let datum = match (object.get(name).unwrap_or(&Value::Null), &typ.scalar_type) {
+                (Value::Null, _) => Datum::Null,
+                (Value::Bool(b), ScalarType::Bool) => Datum::from(*b),
+                (Value::Number(n), ScalarType::Int64) if n.is_i64() => Datum::from(n.as_i64()),
+                (Value::Number(n), ScalarType::Float64) => Datum::from(n.as_f64()),
+                (Value::String(s), ScalarType::String) => Datum::from(s.as_str()),
+                (value, typ) => bail!(
+                    "value {} in column {} does not have type {:?}",
+                    value,
+                    name,
+                    typ
+                ),
+            };
+            row.push(datum);
+        }
+        rows.push(row.finish_and_reuse());
+    }
+    mz_interchange::parquet::encode_rows(&desc, &rows)
+}
+
 #[async_trait]
 impl Action for PutObjectAction {
     async fn undo(&self, _state: &mut State) -> Result<(), anyhow::Error> {
@@ -110,7 +178,7 @@ impl Action for PutObjectAction {
         let bucket = format!("{}-{}", self.bucket_prefix, state.seed);
         println!("Put S3 object {}/{}", bucket, self.key);
 
-        let buffer = self.contents.clone().into_bytes();
+        let buffer = self.contents.clone();
         let contents = match self.compression {
             Compression::None => Ok(buffer),
             Compression::Gzip => {
@@ -120,6 +188,18 @@ impl Action for PutObjectAction {
                     .context("writing to gzip encoder")?;
                 encoder.finish().context("writing to gzip encoder")
             }
+            Compression::Zstd => {
+                let mut encoder = ZstdEncoder::new(Vec::new());
+                encoder
+                    .write_all(buffer.as_ref())
+                    .await
+                    .context("writing to zstd encoder")?;
+                encoder
+                    .shutdown()
+                    .await
+                    .context("writing to zstd encoder")?;
+                Ok(encoder.into_inner())
+            }
         }?;
 
         state
@@ -131,6 +211,7 @@ impl Action for PutObjectAction {
             .set_content_encoding(match self.compression {
                 Compression::None => None,
                 Compression::Gzip => Some("gzip".to_string()),
+                Compression::Zstd => Some("zstd".to_string()),
             })
             .key(&self.key)
             .send()
@@ -187,6 +268,69 @@ impl Action for DeleteObjectAction {
     }
 }
 
+pub struct WaitForObjectAction {
+    bucket_prefix: String,
+    key: GlobMatcher,
+}
+
+pub fn build_wait_for_object(
+    mut cmd: BuiltinCommand,
+) -> Result<WaitForObjectAction, anyhow::Error> {
+    let bucket_prefix = format!("testdrive-{}", cmd.args.string("bucket")?);
+    let key = cmd.args.string("key")?;
+    cmd.args.done()?;
+    let key = Glob::new(&key)
+        .with_context(|| format!("parsing key glob {}", key))?
+        .compile_matcher();
+    Ok(WaitForObjectAction { bucket_prefix, key })
+}
+
+#[async_trait]
+impl Action for WaitForObjectAction {
+    async fn undo(&self, _state: &mut State) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    async fn redo(&self, state: &mut State) -> Result<ControlFlow, anyhow::Error> {
+        let bucket = format!("{}-{}", self.bucket_prefix, state.seed);
+        println!(
+            "Waiting for S3 object {}/{}",
+            bucket,
+            self.key.glob().glob()
+        );
+        let s3_client = &state.s3_client;
+        Retry::default()
+            .max_duration(state.default_timeout)
+            .retry_async(|_| async {
+                let mut continuation_token = None;
+                loop {
+                    let response = s3_client
+                        .list_objects_v2()
+                        .bucket(&bucket)
+                        .set_continuation_token(continuation_token)
+                        .send()
+                        .await
+                        .context("listing S3 objects")?;
+                    let found = response
+                        .contents
+                        .unwrap_or_default()
+                        .iter()
+                        .filter_map(|object| object.key.as_deref())
+                        .any(|key| self.key.is_match(key));
+                    if found {
+                        return Ok(());
+                    }
+                    continuation_token = response.next_continuation_token;
+                    if continuation_token.is_none() {
+                        bail!("no object in bucket {} matches {}", bucket, self.key.glob());
+                    }
+                }
+            })
+            .await?;
+        Ok(ControlFlow::Continue)
+    }
+}
+
 pub struct AddBucketNotifications {
     bucket_prefix: String,
     queue_prefix: String,
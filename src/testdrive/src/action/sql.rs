@@ -116,10 +116,10 @@ impl Action for SqlAction {
             CreateDatabase(_) | CreateSchema(_) | CreateSource(_) | CreateSink(_)
             | CreateView(_) | CreateViews(_) | CreateTable(_) | CreateIndex(_) | CreateType(_)
             | CreateRole(_) | AlterObjectRename(_) | AlterIndex(_) | Discard(_)
-            | DropDatabase(_) | DropObjects(_) | SetVariable(_) | ShowDatabases(_)
-            | ShowObjects(_) | ShowIndexes(_) | ShowColumns(_) | ShowCreateView(_)
-            | ShowCreateSource(_) | ShowCreateTable(_) | ShowCreateSink(_) | ShowCreateIndex(_)
-            | ShowVariable(_) => false,
+            | DropDatabase(_) | DropObjects(_) | DropSubscription(_) | SetVariable(_)
+            | ShowDatabases(_) | ShowObjects(_) | ShowIndexes(_) | ShowColumns(_)
+            | ShowCreateView(_) | ShowCreateSource(_) | ShowCreateTable(_) | ShowCreateSink(_)
+            | ShowCreateIndex(_) | ShowVariable(_) => false,
             _ => true,
         };
 
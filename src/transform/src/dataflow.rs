@@ -14,7 +14,7 @@
 //! pushdown can be applied across views once we understand the context
 //! in which the views will be executed.
 
-use mz_dataflow_types::{DataflowDesc, LinearOperator};
+use mz_dataflow_types::{CollectionStatistics, DataflowDesc, LinearOperator};
 use mz_expr::{GlobalId, Id, LocalId, MirRelationExpr, MirScalarExpr};
 use mz_ore::id_gen::IdGen;
 use std::collections::{BTreeSet, HashMap, HashSet};
@@ -24,17 +24,24 @@ use crate::{monotonic::MonotonicFlag, Optimizer, TransformError};
 /// Optimizes the implementation of each dataflow.
 ///
 /// Inlines views, performs a full optimization pass including physical
-/// planning using the supplied indexes, propagates filtering and projection
-/// information to dataflow sources and lifts monotonicity information.
+/// planning using the supplied indexes and size estimates, propagates
+/// filtering and projection information to dataflow sources and lifts
+/// monotonicity information.
 pub fn optimize_dataflow(
     dataflow: &mut DataflowDesc,
     indexes: &HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+    statistics: &HashMap<GlobalId, CollectionStatistics>,
 ) -> Result<(), TransformError> {
     // Inline views that are used in only one other view.
     inline_views(dataflow)?;
 
     // Logical optimization pass after view inlining
-    optimize_dataflow_relations(dataflow, indexes, &Optimizer::logical_optimizer())?;
+    optimize_dataflow_relations(
+        dataflow,
+        indexes,
+        statistics,
+        &Optimizer::logical_optimizer(),
+    )?;
 
     optimize_dataflow_filters(dataflow)?;
     // TODO: when the linear operator contract ensures that propagated
@@ -47,10 +54,20 @@ pub fn optimize_dataflow(
 
     // A smaller logical optimization pass after projections and filters are
     // pushed down across views.
-    optimize_dataflow_relations(dataflow, indexes, &Optimizer::logical_cleanup_pass())?;
+    optimize_dataflow_relations(
+        dataflow,
+        indexes,
+        statistics,
+        &Optimizer::logical_cleanup_pass(),
+    )?;
 
     // Physical optimization pass
-    optimize_dataflow_relations(dataflow, indexes, &Optimizer::physical_optimizer())?;
+    optimize_dataflow_relations(
+        dataflow,
+        indexes,
+        statistics,
+        &Optimizer::physical_optimizer(),
+    )?;
 
     optimize_dataflow_monotonic(dataflow)?;
 
@@ -158,10 +175,11 @@ fn inline_views(dataflow: &mut DataflowDesc) -> Result<(), TransformError> {
 }
 
 /// Performs either the logical or the physical optimization pass on the
-/// dataflow using the supplied set of indexes.
+/// dataflow using the supplied set of indexes and size estimates.
 fn optimize_dataflow_relations(
     dataflow: &mut DataflowDesc,
     indexes: &HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+    statistics: &HashMap<GlobalId, CollectionStatistics>,
     optimizer: &Optimizer,
 ) -> Result<(), TransformError> {
     // Re-optimize each dataflow
@@ -172,7 +190,7 @@ fn optimize_dataflow_relations(
         // Re-name bindings to accommodate other analyses, specifically
         // `InlineLet` which probably wants a reworking in any case.
         // Re-run all optimizations on the composite views.
-        optimizer.transform(object.view.as_inner_mut(), &indexes)?;
+        optimizer.transform(object.view.as_inner_mut(), &indexes, statistics)?;
     }
 
     Ok(())
@@ -37,6 +37,7 @@
 //! Filter.transform(&mut expr, TransformArgs {
 //!   id_gen: &mut Default::default(),
 //!   indexes: &std::collections::HashMap::new(),
+//!   statistics: &std::collections::HashMap::new(),
 //! });
 //!
 //! let correct = input.filter(vec![predicate0]);
@@ -14,13 +14,24 @@
 //!
 //! This includes determining the type of join (e.g. differential linear, or delta queries),
 //! determining the orders of collections, lifting predicates if useful arrangements exist,
-//! and identifying opportunities to use indexes to replace filters.
+//! and identifying opportunities to use indexes to replace filters. When size estimates
+//! are available for the joined collections, they break ties between otherwise equally
+//! appealing orders in favor of those that join fewer records per key.
+//!
+//! Joins of the form that `NOT EXISTS` subqueries take, a collection joined with the
+//! union of its distinct keys and the negation of the keys to exclude, are implemented
+//! as anti-joins, which retract the excluded records from the collection rather than
+//! joining it with the union. `EXISTS` subqueries need no special treatment, as they
+//! already take the form of joins with the distinct keys to retain, which the plans
+//! below probe in their arrangements.
 
 use std::collections::HashMap;
 
 use crate::TransformArgs;
+use mz_dataflow_types::CollectionStatistics;
 use mz_expr::{
-    Id, JoinInputMapper, MapFilterProject, MirRelationExpr, MirScalarExpr, RECURSION_LIMIT,
+    GlobalId, Id, JoinInputMapper, MapFilterProject, MirRelationExpr, MirScalarExpr,
+    RECURSION_LIMIT,
 };
 use mz_ore::stack::{CheckedRecursion, RecursionGuard};
 
@@ -57,7 +68,8 @@ impl crate::Transform for JoinImplementation {
             let keys = idxs.iter().map(|(_id, keys)| keys.clone()).collect();
             arranged.insert(Id::Global(*on_id), keys);
         }
-        self.action_recursive(relation, &mut arranged)
+        let mut distinct_keys = HashMap::new();
+        self.action_recursive(relation, &mut arranged, &mut distinct_keys, args.statistics)
     }
 }
 
@@ -65,14 +77,18 @@ impl JoinImplementation {
     /// Pre-order visitor for each `MirRelationExpr` to find join operators.
     ///
     /// This method accumulates state about let-bound arrangements, so that
-    /// join operators can more accurately assess their available arrangements.
+    /// join operators can more accurately assess their available arrangements,
+    /// and about let-bound collections of the distinct values of columns of
+    /// other collections, so that join operators can recognize anti-joins.
     pub fn action_recursive(
         &self,
         relation: &mut MirRelationExpr,
         arranged: &mut HashMap<Id, Vec<Vec<MirScalarExpr>>>,
+        distinct_keys: &mut HashMap<Id, (Id, Vec<usize>)>,
+        statistics: &HashMap<GlobalId, CollectionStatistics>,
     ) -> Result<(), crate::TransformError> {
         if let MirRelationExpr::Let { id, value, body } = relation {
-            self.action_recursive(value, arranged)?;
+            self.action_recursive(value, arranged, distinct_keys, statistics)?;
             match &**value {
                 MirRelationExpr::ArrangeBy { keys, .. } => {
                     arranged.insert(Id::Local(*id), keys.clone());
@@ -85,12 +101,18 @@ impl JoinImplementation {
                 }
                 _ => {}
             }
-            self.action_recursive(body, arranged)?;
+            if let Some(summary) = anti_join::distinct_columns(value, distinct_keys) {
+                distinct_keys.insert(Id::Local(*id), summary);
+            }
+            self.action_recursive(body, arranged, distinct_keys, statistics)?;
             arranged.remove(&Id::Local(*id));
+            distinct_keys.remove(&Id::Local(*id));
             Ok(())
         } else {
-            relation.try_visit_mut_children(|e| self.action_recursive(e, arranged))?;
-            self.action(relation, arranged);
+            relation.try_visit_mut_children(|e| {
+                self.action_recursive(e, arranged, distinct_keys, statistics)
+            })?;
+            self.action(relation, arranged, distinct_keys, statistics);
             Ok(())
         }
     }
@@ -100,6 +122,8 @@ impl JoinImplementation {
         &self,
         relation: &mut MirRelationExpr,
         indexes: &HashMap<Id, Vec<Vec<MirScalarExpr>>>,
+        distinct_keys: &HashMap<Id, (Id, Vec<usize>)>,
+        statistics: &HashMap<GlobalId, CollectionStatistics>,
     ) {
         if let MirRelationExpr::Join {
             inputs,
@@ -127,6 +151,7 @@ impl JoinImplementation {
                 .map(|typ| typ.keys)
                 .collect::<Vec<_>>();
             let mut available_arrangements = vec![Vec::new(); inputs.len()];
+            let mut input_statistics = vec![None; inputs.len()];
             for index in 0..inputs.len() {
                 // We can work around mfps, as we can lift the mfps into the join execution.
                 let (mfp, input) = MapFilterProject::extract_non_errors_from_expr(&inputs[index]);
@@ -137,6 +162,7 @@ impl JoinImplementation {
                         if let Some(keys) = indexes.get(id) {
                             available_arrangements[index].extend(keys.clone());
                         }
+                        input_statistics[index] = global_statistics(statistics, id);
                     }
                     MirRelationExpr::ArrangeBy { input, keys } => {
                         // We may use any presented arrangement keys.
@@ -145,6 +171,7 @@ impl JoinImplementation {
                             if let Some(keys) = indexes.get(id) {
                                 available_arrangements[index].extend(keys.clone());
                             }
+                            input_statistics[index] = global_statistics(statistics, id);
                         }
                     }
                     MirRelationExpr::Reduce { group_key, .. } => {
@@ -173,6 +200,19 @@ impl JoinImplementation {
                         key.permute_map(&reverse_project);
                     }
                 }
+                // Treat distinct counts the same way. The record count remains a
+                // valid upper bound, as the MFP can only filter records out.
+                if let Some(statistics) = &mut input_statistics[index] {
+                    statistics.distinct_keys.retain(|(key, _)| {
+                        key.iter()
+                            .all(|k| k.support().iter().all(|c| reverse_project.contains_key(c)))
+                    });
+                    for (key, _) in statistics.distinct_keys.iter_mut() {
+                        for k in key.iter_mut() {
+                            k.permute_map(&reverse_project);
+                        }
+                    }
+                }
                 // Currently we only support using arrangements all of whose
                 // keys can be found in some equivalence.
                 // Note: because `order_input` currently only finds arrangements
@@ -189,6 +229,18 @@ impl JoinImplementation {
                 });
             }
 
+            // Anti-joins are preferable to both alternatives below whenever they apply, as
+            // they neither arrange nor join the distinct keys of the collection.
+            if let Some(anti_join_plan) = anti_join::plan(
+                relation,
+                &input_mapper,
+                &available_arrangements,
+                distinct_keys,
+            ) {
+                *relation = anti_join_plan;
+                return;
+            }
+
             // Determine if we can perform delta queries with the existing arrangements.
             // We could defer the execution if we are sure we know we want one input,
             // but we could imagine wanting the best from each and then comparing the two.
@@ -197,12 +249,14 @@ impl JoinImplementation {
                 &input_mapper,
                 &available_arrangements,
                 &unique_keys,
+                &input_statistics,
             );
             let differential_plan = differential::plan(
                 relation,
                 &input_mapper,
                 &available_arrangements,
                 &unique_keys,
+                &input_statistics,
             );
 
             *relation = delta_query_plan
@@ -214,6 +268,7 @@ impl JoinImplementation {
 
 mod delta_queries {
 
+    use mz_dataflow_types::CollectionStatistics;
     use mz_expr::{JoinImplementation, JoinInputMapper, MirRelationExpr, MirScalarExpr};
 
     /// Creates a delta query plan, and any predicates that need to be lifted.
@@ -224,6 +279,7 @@ mod delta_queries {
         input_mapper: &JoinInputMapper,
         available: &[Vec<Vec<MirScalarExpr>>],
         unique_keys: &[Vec<Vec<usize>>],
+        statistics: &[Option<CollectionStatistics>],
     ) -> Option<MirRelationExpr> {
         let mut new_join = join.clone();
 
@@ -243,7 +299,13 @@ mod delta_queries {
             }
 
             // Determine a viable order for each relation, or return `None` if none found.
-            let orders = super::optimize_orders(equivalences, available, unique_keys, input_mapper);
+            let orders = super::optimize_orders(
+                equivalences,
+                available,
+                unique_keys,
+                statistics,
+                input_mapper,
+            );
 
             // A viable delta query requires that, for every order,
             // there is an arrangement for every input except for
@@ -284,6 +346,7 @@ mod delta_queries {
 
 mod differential {
 
+    use mz_dataflow_types::CollectionStatistics;
     use mz_expr::{JoinImplementation, JoinInputMapper, MirRelationExpr, MirScalarExpr};
 
     /// Creates a linear differential plan, and any predicates that need to be lifted.
@@ -292,6 +355,7 @@ mod differential {
         input_mapper: &JoinInputMapper,
         available: &[Vec<Vec<MirScalarExpr>>],
         unique_keys: &[Vec<Vec<usize>>],
+        statistics: &[Option<CollectionStatistics>],
     ) -> Option<MirRelationExpr> {
         let mut new_join = join.clone();
 
@@ -306,8 +370,13 @@ mod differential {
             // Important, we should choose something stable under re-ordering, to converge under fixed
             // point iteration; we choose to start with the first input optimizing our criteria, which
             // should remain stable even when promoted to the first position.
-            let mut orders =
-                super::optimize_orders(equivalences, available, unique_keys, input_mapper);
+            let mut orders = super::optimize_orders(
+                equivalences,
+                available,
+                unique_keys,
+                statistics,
+                input_mapper,
+            );
 
             // For differential join, it is not as important for the starting
             // input to have good characteristics because the other ones
@@ -371,6 +440,207 @@ mod differential {
     }
 }
 
+mod anti_join {
+
+    use std::collections::HashMap;
+
+    use mz_expr::{Id, JoinImplementation, JoinInputMapper, MirRelationExpr, MirScalarExpr};
+
+    /// Creates an anti-join plan, if the join has the form that `NOT EXISTS`
+    /// subqueries take.
+    ///
+    /// That form is a join of a collection `x` with the union of the distinct
+    /// values of some columns of `x` and the negation of a collection `s` with
+    /// at most one record per key, equating each column of the union with the
+    /// corresponding column of `x`. Because `s` contains each key at most once,
+    /// the records of the join are exactly the records of `x`, extended with the
+    /// values of the columns, less those whose values appear in `s`.
+    ///
+    /// The method returns `None` if the join does not have this form.
+    pub fn plan(
+        join: &MirRelationExpr,
+        input_mapper: &JoinInputMapper,
+        available: &[Vec<Vec<MirScalarExpr>>],
+        distinct_keys: &HashMap<Id, (Id, Vec<usize>)>,
+    ) -> Option<MirRelationExpr> {
+        let mut new_join = join.clone();
+
+        if let MirRelationExpr::Join {
+            inputs,
+            equivalences,
+            implementation,
+        } = &mut new_join
+        {
+            if inputs.len() != 2 {
+                return None;
+            }
+
+            for x in 0..2 {
+                let union = 1 - x;
+                let mut x_input = &inputs[x];
+                while let MirRelationExpr::ArrangeBy { input, .. } = x_input {
+                    x_input = input;
+                }
+                let x_id = match x_input {
+                    MirRelationExpr::Get { id, .. } => *id,
+                    _ => continue,
+                };
+                let columns = match excluded_columns(&inputs[union], distinct_keys) {
+                    Some((id, columns)) if id == x_id => columns,
+                    _ => continue,
+                };
+
+                // Each column of the union must be equated with the corresponding
+                // column of `x`, and nothing else.
+                let mut expected = columns
+                    .iter()
+                    .enumerate()
+                    .map(|(union_column, x_column)| {
+                        let mut class = vec![
+                            MirScalarExpr::Column(input_mapper.map_column_to_global(*x_column, x)),
+                            MirScalarExpr::Column(
+                                input_mapper.map_column_to_global(union_column, union),
+                            ),
+                        ];
+                        class.sort();
+                        class
+                    })
+                    .collect::<Vec<_>>();
+                expected.sort();
+                let mut actual = equivalences.clone();
+                for class in actual.iter_mut() {
+                    class.sort();
+                }
+                actual.sort();
+                if actual != expected {
+                    continue;
+                }
+
+                // Prefer an existing arrangement of `x` by the columns, in any order.
+                let key = available[x]
+                    .iter()
+                    .find(|key| {
+                        key.len() == columns.len()
+                            && columns
+                                .iter()
+                                .all(|c| key.contains(&MirScalarExpr::Column(*c)))
+                    })
+                    .cloned()
+                    .unwrap_or_else(|| columns.iter().map(|c| MirScalarExpr::Column(*c)).collect());
+
+                // Implement the arrangement of `x`.
+                let lifted_mfp =
+                    super::implement_arrangements(inputs, available, [(x, key.clone())].iter());
+
+                *implementation = JoinImplementation::AntiJoin(x, key);
+
+                super::install_lifted_mfp(&mut new_join, lifted_mfp);
+
+                return Some(new_join);
+            }
+            None
+        } else {
+            panic!("anti_join::plan call on non-join expression.")
+        }
+    }
+
+    /// If `union` is the union of the distinct values of some columns of a
+    /// collection and the negation of a collection with at most one record per
+    /// key, returns the identifier of the former collection and the columns.
+    fn excluded_columns(
+        union: &MirRelationExpr,
+        distinct_keys: &HashMap<Id, (Id, Vec<usize>)>,
+    ) -> Option<(Id, Vec<usize>)> {
+        if let MirRelationExpr::Union { base, inputs } = union {
+            if inputs.len() != 1 {
+                return None;
+            }
+            let (distinct, negated) = match (&**base, &inputs[0]) {
+                (MirRelationExpr::Negate { input }, other)
+                | (other, MirRelationExpr::Negate { input }) => (other, &**input),
+                _ => return None,
+            };
+            let typ = negated.typ();
+            if typ.keys.is_empty() {
+                return None;
+            }
+            distinct_columns(distinct, distinct_keys)
+                .filter(|(_id, columns)| columns.len() == typ.arity())
+        } else {
+            None
+        }
+    }
+
+    /// If `relation` evaluates to the distinct values of some columns of a
+    /// collection, returns the identifier of the collection and the columns.
+    ///
+    /// `distinct_keys` records the let-bound collections known to do so.
+    pub fn distinct_columns(
+        relation: &MirRelationExpr,
+        distinct_keys: &HashMap<Id, (Id, Vec<usize>)>,
+    ) -> Option<(Id, Vec<usize>)> {
+        match relation {
+            MirRelationExpr::Get { id, typ } => {
+                if let Some(summary) = distinct_keys.get(id) {
+                    Some(summary.clone())
+                } else if !typ.keys.is_empty() {
+                    Some((*id, (0..typ.arity()).collect()))
+                } else {
+                    None
+                }
+            }
+            MirRelationExpr::Project { input, outputs } => match &**input {
+                // The projection of a collection onto columns that include
+                // one of its keys has no duplicates.
+                MirRelationExpr::Get { id, typ }
+                    if typ
+                        .keys
+                        .iter()
+                        .any(|key| key.iter().all(|c| outputs.contains(c))) =>
+                {
+                    Some((*id, outputs.clone()))
+                }
+                _ => None,
+            },
+            MirRelationExpr::Reduce {
+                input,
+                group_key,
+                aggregates,
+                ..
+            } if aggregates.is_empty() => {
+                let (id, outputs) = match &**input {
+                    MirRelationExpr::Get { id, .. } => (*id, None),
+                    MirRelationExpr::Project { input, outputs } => match &**input {
+                        MirRelationExpr::Get { id, .. } => (*id, Some(outputs)),
+                        _ => return None,
+                    },
+                    _ => return None,
+                };
+                let columns = group_key
+                    .iter()
+                    .map(|expr| match expr {
+                        MirScalarExpr::Column(c) => Some(outputs.map_or(*c, |o| o[*c])),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some((id, columns))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Looks up the size estimates for the collection `id`, if it is a global collection.
+fn global_statistics(
+    statistics: &HashMap<GlobalId, CollectionStatistics>,
+    id: &Id,
+) -> Option<CollectionStatistics> {
+    match id {
+        Id::Global(id) => statistics.get(id).cloned(),
+        _ => None,
+    }
+}
+
 /// Modify `inputs` to ensure specified arrangements are available.
 ///
 /// Lift filter predicates when all needed arrangements are otherwise available.
@@ -487,9 +757,16 @@ fn optimize_orders(
     equivalences: &[Vec<MirScalarExpr>],
     available: &[Vec<Vec<MirScalarExpr>>],
     unique_keys: &[Vec<Vec<usize>>],
+    statistics: &[Option<CollectionStatistics>],
     input_mapper: &JoinInputMapper,
 ) -> Vec<Vec<(Characteristics, Vec<MirScalarExpr>, usize)>> {
-    let mut orderer = Orderer::new(equivalences, available, unique_keys, input_mapper);
+    let mut orderer = Orderer::new(
+        equivalences,
+        available,
+        unique_keys,
+        statistics,
+        input_mapper,
+    );
     (0..available.len())
         .map(move |i| orderer.optimize_order_for(i))
         .collect::<Vec<_>>()
@@ -499,15 +776,18 @@ fn optimize_orders(
 ///
 /// A candidate is described by a collection and a key, and may have various liabilities.
 /// Primarily, the candidate may risk substantial inflation of records, which is something
-/// that concerns us greatly. Additionally the candidate may be unarranged, and we would
-/// prefer candidates that do not require additional memory. Finally, we prefer lower id
-/// collections in the interest of consistent tie-breaking.
+/// that concerns us greatly. When size estimates are available, we prefer candidates that
+/// are estimated to match fewer records for each key. Additionally the candidate may be
+/// unarranged, and we would prefer candidates that do not require additional memory.
+/// Finally, we prefer lower id collections in the interest of consistent tie-breaking.
 #[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone)]
 pub struct Characteristics {
     // An excellent indication that record count will not increase.
     unique_key: bool,
     // A weaker signal that record count will not increase.
     key_length: usize,
+    // An estimate of how much the record count may increase, or `u64::MAX` if unknown.
+    records_per_key: std::cmp::Reverse<u64>,
     // Indicates that there will be no additional in-memory footprint.
     arranged: bool,
     // We want to prefer input earlier in the input list, for stability of ordering.
@@ -515,10 +795,17 @@ pub struct Characteristics {
 }
 
 impl Characteristics {
-    fn new(unique_key: bool, key_length: usize, arranged: bool, input: usize) -> Self {
+    fn new(
+        unique_key: bool,
+        key_length: usize,
+        records_per_key: u64,
+        arranged: bool,
+        input: usize,
+    ) -> Self {
         Self {
             unique_key,
             key_length,
+            records_per_key: std::cmp::Reverse(records_per_key),
             arranged,
             input: std::cmp::Reverse(input),
         }
@@ -530,6 +817,7 @@ struct Orderer<'a> {
     equivalences: &'a [Vec<MirScalarExpr>],
     arrangements: &'a [Vec<Vec<MirScalarExpr>>],
     unique_keys: &'a [Vec<Vec<usize>>],
+    statistics: &'a [Option<CollectionStatistics>],
     input_mapper: &'a JoinInputMapper,
     reverse_equivalences: Vec<Vec<(usize, usize)>>,
     unique_arrangement: Vec<Vec<bool>>,
@@ -547,6 +835,7 @@ impl<'a> Orderer<'a> {
         equivalences: &'a [Vec<MirScalarExpr>],
         arrangements: &'a [Vec<Vec<MirScalarExpr>>],
         unique_keys: &'a [Vec<Vec<usize>>],
+        statistics: &'a [Option<CollectionStatistics>],
         input_mapper: &'a JoinInputMapper,
     ) -> Self {
         let inputs = arrangements.len();
@@ -581,6 +870,7 @@ impl<'a> Orderer<'a> {
             equivalences,
             arrangements,
             unique_keys,
+            statistics,
             input_mapper,
             reverse_equivalences,
             unique_arrangement,
@@ -593,6 +883,17 @@ impl<'a> Orderer<'a> {
         }
     }
 
+    /// Estimates the number of records of `input` that share each value of `key`.
+    ///
+    /// Returns `u64::MAX` if there are no size estimates for `input`, so that
+    /// inputs with estimates are preferred over those without.
+    fn records_per_key(&self, input: usize, key: &[MirScalarExpr]) -> u64 {
+        match &self.statistics[input] {
+            Some(statistics) => statistics.records_per_key(key),
+            None => u64::MAX,
+        }
+    }
+
     fn optimize_order_for(
         &mut self,
         start: usize,
@@ -611,19 +912,20 @@ impl<'a> Orderer<'a> {
         // Introduce cross joins as a possibility.
         for input in 0..self.inputs {
             let is_unique = self.unique_keys[input].iter().any(|cols| cols.is_empty());
+            let records_per_key = self.records_per_key(input, &[]);
             if let Some(pos) = self.arrangements[input]
                 .iter()
                 .position(|key| key.is_empty())
             {
                 self.arrangement_active[input].push(pos);
                 self.priority_queue.push((
-                    Characteristics::new(is_unique, 0, true, input),
+                    Characteristics::new(is_unique, 0, records_per_key, true, input),
                     vec![],
                     input,
                 ));
             } else {
                 self.priority_queue.push((
-                    Characteristics::new(is_unique, 0, false, input),
+                    Characteristics::new(is_unique, 0, records_per_key, false, input),
                     vec![],
                     input,
                 ));
@@ -648,7 +950,11 @@ impl<'a> Orderer<'a> {
 
         // calculate characteristics of an arrangement, if any on the starting input
         // by default, there is no arrangement on the starting input
-        let mut start_tuple = (Characteristics::new(false, 0, false, start), vec![], start);
+        let mut start_tuple = (
+            Characteristics::new(false, 0, self.records_per_key(start, &[]), false, start),
+            vec![],
+            start,
+        );
         // use an arrangement if there exists one that lines up with the keys of
         // the second input
         if let Some((_, key, second)) = self.order.get(0) {
@@ -669,8 +975,15 @@ impl<'a> Orderer<'a> {
                     .position(|k| k == &candidate_start_key)
                 {
                     let is_unique = self.unique_arrangement[start][pos];
+                    let records_per_key = self.records_per_key(start, &candidate_start_key);
                     start_tuple = (
-                        Characteristics::new(is_unique, candidate_start_key.len(), true, start),
+                        Characteristics::new(
+                            is_unique,
+                            candidate_start_key.len(),
+                            records_per_key,
+                            true,
+                            start,
+                        ),
                         candidate_start_key,
                         start,
                     );
@@ -737,10 +1050,12 @@ impl<'a> Orderer<'a> {
                                             self.arrangement_active[rel].push(pos);
                                             // TODO: This could be pre-computed, as it is independent of the order.
                                             let is_unique = self.unique_arrangement[rel][pos];
+                                            let records_per_key = self.records_per_key(rel, keys);
                                             self.priority_queue.push((
                                                 Characteristics::new(
                                                     is_unique,
                                                     keys.len(),
+                                                    records_per_key,
                                                     true,
                                                     rel,
                                                 ),
@@ -755,10 +1070,12 @@ impl<'a> Orderer<'a> {
                                         self.bound[rel].contains(&MirScalarExpr::Column(*c))
                                     })
                                 });
+                                let records_per_key = self.records_per_key(rel, &self.bound[rel]);
                                 self.priority_queue.push((
                                     Characteristics::new(
                                         is_unique,
                                         self.bound[rel].len(),
+                                        records_per_key,
                                         false,
                                         rel,
                                     ),
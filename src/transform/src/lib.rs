@@ -25,6 +25,7 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 
+use mz_dataflow_types::CollectionStatistics;
 use mz_expr::GlobalId;
 use mz_expr::MirRelationExpr;
 use mz_expr::MirScalarExpr;
@@ -64,6 +65,8 @@ pub struct TransformArgs<'a> {
     pub id_gen: &'a mut IdGen,
     /// The indexes accessible.
     pub indexes: &'a HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+    /// Size estimates for the collections accessible.
+    pub statistics: &'a HashMap<GlobalId, CollectionStatistics>,
 }
 
 /// Types capable of transforming relation expressions.
@@ -81,6 +84,14 @@ pub trait Transform: std::fmt::Debug {
     fn debug(&self) -> String {
         format!("{:?}", self)
     }
+    /// The transforms that this transform applies, if it only sequences
+    /// other transforms.
+    ///
+    /// This allows tests to attribute a change to the innermost transform
+    /// that made it.
+    fn components(&self) -> &[Box<dyn Transform>] {
+        &[]
+    }
 }
 
 /// Errors that can occur during a transformation.
@@ -137,6 +148,7 @@ impl Transform for Fixpoint {
                         TransformArgs {
                             id_gen: args.id_gen,
                             indexes: args.indexes,
+                            statistics: args.statistics,
                         },
                     )?;
                 }
@@ -156,6 +168,7 @@ impl Transform for Fixpoint {
                 TransformArgs {
                     id_gen: args.id_gen,
                     indexes: args.indexes,
+                    statistics: args.statistics,
                 },
             )?;
         }
@@ -165,6 +178,10 @@ impl Transform for Fixpoint {
             relation.pretty()
         )))
     }
+
+    fn components(&self) -> &[Box<dyn Transform>] {
+        &self.transforms
+    }
 }
 
 /// A sequence of transformations that simplify the `MirRelationExpr`
@@ -232,11 +249,16 @@ impl Transform for FuseAndCollapse {
                 TransformArgs {
                     id_gen: args.id_gen,
                     indexes: args.indexes,
+                    statistics: args.statistics,
                 },
             )?;
         }
         Ok(())
     }
+
+    fn components(&self) -> &[Box<dyn Transform>] {
+        &self.transforms
+    }
 }
 
 /// A naive optimizer for relation expressions.
@@ -374,11 +396,12 @@ impl Optimizer {
         &mut self,
         mut relation: MirRelationExpr,
     ) -> Result<mz_expr::OptimizedMirRelationExpr, TransformError> {
-        self.transform(&mut relation, &HashMap::new())?;
+        self.transform(&mut relation, &HashMap::new(), &HashMap::new())?;
         Ok(mz_expr::OptimizedMirRelationExpr(relation))
     }
 
-    /// Optimizes the supplied relation expression in place, using available arrangements.
+    /// Optimizes the supplied relation expression in place, using available arrangements
+    /// and size estimates.
     ///
     /// This method should only be called with non-empty `indexes` when optimizing a dataflow,
     /// as the optimizations may lock in the use of arrangements that may cease to exist.
@@ -386,6 +409,7 @@ impl Optimizer {
         &self,
         relation: &mut MirRelationExpr,
         indexes: &HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+        statistics: &HashMap<GlobalId, CollectionStatistics>,
     ) -> Result<(), TransformError> {
         let mut id_gen = Default::default();
         for transform in self.transforms.iter() {
@@ -394,6 +418,7 @@ impl Optimizer {
                 TransformArgs {
                     id_gen: &mut id_gen,
                     indexes,
+                    statistics,
                 },
             )?;
         }
@@ -0,0 +1,336 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! This module checks that transforms preserve the results of random MIRs.
+//!
+//! Each plan is evaluated with the reference interpreter of `mz_expr_test_util`
+//! before and after every transform that the optimizer applies. When a
+//! transform changes the result of a plan, the test reports the innermost
+//! transform that changed it along with the smallest plan and source contents
+//! that it could find that still reproduce the change.
+//!
+//! The plans are generated from the seed in `MZ_DIFFERENTIAL_SEED`, which
+//! defaults to 0, and their number is read from `MZ_DIFFERENTIAL_PLANS`.
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::env;
+    use std::fmt::Write;
+
+    use mz_expr::{GlobalId, MirRelationExpr};
+    use mz_expr_test_util::{
+        evaluate, generate_explanation, json_to_spec, Collection, RelationGenerator, TestCatalog,
+    };
+    use mz_ore::id_gen::IdGen;
+    use mz_transform::{Optimizer, Transform, TransformArgs, TransformError};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// The number of plans to check if `MZ_DIFFERENTIAL_PLANS` is not set.
+    const DEFAULT_PLANS: u64 = 200;
+    /// How deep the operators of the generated plans are nested.
+    const DEPTH: usize = 3;
+    /// The number of passes after which a sequence of transforms is assumed
+    /// not to converge when looking for the transform that caused a failure.
+    const PASS_LIMIT: usize = 100;
+
+    thread_local! {
+        static FULL_TRANSFORM_LIST: Vec<Box<dyn Transform>> =
+            Optimizer::logical_optimizer()
+                .transforms
+                .into_iter()
+                .chain(std::iter::once(
+                    Box::new(mz_transform::projection_pushdown::ProjectionPushdown)
+                        as Box<dyn Transform>,
+                ))
+                .chain(std::iter::once(
+                    Box::new(mz_transform::update_let::UpdateLet::default()) as Box<dyn Transform>
+                ))
+                .chain(Optimizer::logical_cleanup_pass().transforms.into_iter())
+                .chain(Optimizer::physical_optimizer().transforms.into_iter())
+                .collect::<Vec<_>>();
+    }
+
+    /// A transform that changed the result of a plan.
+    struct Failure<'a> {
+        transform: &'a dyn Transform,
+        input: MirRelationExpr,
+        contents: HashMap<GlobalId, Collection>,
+        expected: Collection,
+        got: Result<Collection, String>,
+    }
+
+    #[test]
+    fn differential() {
+        let seed = env::var("MZ_DIFFERENTIAL_SEED").map_or(0, |s| s.parse().unwrap());
+        let plans = env::var("MZ_DIFFERENTIAL_PLANS").map_or(DEFAULT_PLANS, |s| s.parse().unwrap());
+        FULL_TRANSFORM_LIST.with(|transforms| {
+            for plan_seed in seed..seed + plans {
+                if let Err(report) = check_plan(plan_seed, transforms) {
+                    panic!("{}", report);
+                }
+            }
+        });
+    }
+
+    /// Generates a plan from `seed` and checks that none of `transforms`
+    /// changes its result.
+    fn check_plan(seed: u64, transforms: &[Box<dyn Transform>]) -> Result<(), String> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut id_gen = IdGen::default();
+        let mut catalog = TestCatalog::default();
+        let mut generator = RelationGenerator::new(&mut rng, &mut id_gen, &mut catalog);
+        let mut relation = generator.relation(DEPTH);
+        let contents = generator.contents();
+
+        // Plans whose results are not defined, e.g. because they divide by
+        // zero, are allowed to be transformed into any plan.
+        let expected = match evaluate(&relation, &contents) {
+            Ok(expected) => expected,
+            Err(_) => return Ok(()),
+        };
+        for transform in transforms {
+            let mut output = relation.clone();
+            let got = transform_and_evaluate(&**transform, &mut output, &contents, &mut id_gen);
+            if got.as_ref() != Ok(&expected) {
+                let failure = blame(
+                    Failure {
+                        transform: &**transform,
+                        input: relation,
+                        contents,
+                        expected,
+                        got,
+                    },
+                    &mut id_gen,
+                );
+                return Err(report(&shrink(failure, &mut id_gen), &catalog, seed));
+            }
+            relation = output;
+        }
+        Ok(())
+    }
+
+    /// Applies `transform` to `relation` and evaluates the result.
+    fn transform_and_evaluate(
+        transform: &dyn Transform,
+        relation: &mut MirRelationExpr,
+        contents: &HashMap<GlobalId, Collection>,
+        id_gen: &mut IdGen,
+    ) -> Result<Collection, String> {
+        apply(transform, relation, id_gen)
+            .map_err(|e| e.to_string())
+            .and_then(|()| evaluate(relation, contents))
+    }
+
+    fn apply(
+        transform: &dyn Transform,
+        relation: &mut MirRelationExpr,
+        id_gen: &mut IdGen,
+    ) -> Result<(), TransformError> {
+        transform.transform(
+            relation,
+            TransformArgs {
+                id_gen,
+                indexes: &HashMap::new(),
+                statistics: &HashMap::new(),
+            },
+        )
+    }
+
+    /// Attributes `failure` to the innermost transform that causes it.
+    ///
+    /// Transforms that sequence other transforms are replayed one component at
+    /// a time, in passes, until the plan stops changing. If none of the
+    /// components is to blame, e.g. because the sequence does not converge,
+    /// the failure stays attributed to the sequence.
+    fn blame<'a>(failure: Failure<'a>, id_gen: &mut IdGen) -> Failure<'a> {
+        let components = failure.transform.components();
+        if components.is_empty() {
+            return failure;
+        }
+        let mut relation = failure.input.clone();
+        for _ in 0..PASS_LIMIT {
+            let original = relation.clone();
+            for component in components {
+                let mut output = relation.clone();
+                let got =
+                    transform_and_evaluate(&**component, &mut output, &failure.contents, id_gen);
+                if got.as_ref() != Ok(&failure.expected) {
+                    return blame(
+                        Failure {
+                            transform: &**component,
+                            input: relation,
+                            got,
+                            ..failure
+                        },
+                        id_gen,
+                    );
+                }
+                relation = output;
+            }
+            if relation == original {
+                break;
+            }
+        }
+        failure
+    }
+
+    /// Greedily removes parts of the plan and rows of the sources of `failure`
+    /// for as long as its transform still changes the result.
+    fn shrink<'a>(mut failure: Failure<'a>, id_gen: &mut IdGen) -> Failure<'a> {
+        'shrink: loop {
+            for input in smaller_plans(&failure.input) {
+                if let Some(smaller) = reproduce(&failure, input, failure.contents.clone(), id_gen)
+                {
+                    failure = smaller;
+                    continue 'shrink;
+                }
+            }
+            for contents in smaller_contents(&failure.contents) {
+                if let Some(smaller) = reproduce(&failure, failure.input.clone(), contents, id_gen)
+                {
+                    failure = smaller;
+                    continue 'shrink;
+                }
+            }
+            return failure;
+        }
+    }
+
+    /// Checks whether the transform of `failure` also changes the result of
+    /// `input` over `contents`.
+    fn reproduce<'a>(
+        failure: &Failure<'a>,
+        input: MirRelationExpr,
+        contents: HashMap<GlobalId, Collection>,
+        id_gen: &mut IdGen,
+    ) -> Option<Failure<'a>> {
+        // Candidates that refer to locals bound outside of them fail to
+        // evaluate, and are thus discarded here.
+        let expected = evaluate(&input, &contents).ok()?;
+        let mut output = input.clone();
+        let got = transform_and_evaluate(failure.transform, &mut output, &contents, id_gen);
+        if got.as_ref() == Ok(&expected) {
+            return None;
+        }
+        Some(Failure {
+            transform: failure.transform,
+            input,
+            contents,
+            expected,
+            got,
+        })
+    }
+
+    /// Returns the subexpressions of `relation`, followed by the copies of
+    /// `relation` with one filter predicate or one union input removed.
+    fn smaller_plans(relation: &MirRelationExpr) -> Vec<MirRelationExpr> {
+        let mut plans = Vec::new();
+        relation.visit_children(|child| {
+            child.visit_pre(&mut |expr| plans.push(expr.clone()));
+        });
+
+        let mut removals = Vec::new();
+        relation.visit_pre(&mut |expr| {
+            removals.push(match expr {
+                MirRelationExpr::Filter { predicates, .. } if predicates.len() > 1 => {
+                    predicates.len()
+                }
+                MirRelationExpr::Union { inputs, .. } => inputs.len() + 1,
+                _ => 0,
+            })
+        });
+        for (node, count) in removals.into_iter().enumerate() {
+            for removal in 0..count {
+                let mut plan = relation.clone();
+                let mut visited = 0;
+                plan.visit_mut_pre(&mut |expr| {
+                    if visited == node {
+                        remove(expr, removal);
+                    }
+                    visited += 1;
+                });
+                plans.push(plan);
+            }
+        }
+        plans
+    }
+
+    /// Removes the `index`th predicate of a filter, or the `index`th input of
+    /// a union, counting its base as the first input.
+    fn remove(expr: &mut MirRelationExpr, index: usize) {
+        match expr {
+            MirRelationExpr::Filter { predicates, .. } => {
+                predicates.remove(index);
+            }
+            MirRelationExpr::Union { base, inputs } => {
+                if index == 0 {
+                    *base = Box::new(inputs.remove(0));
+                } else {
+                    inputs.remove(index - 1);
+                }
+                if inputs.is_empty() {
+                    *expr = base.take_dangerous();
+                }
+            }
+            _ => unreachable!("only filters and unions have parts to remove"),
+        }
+    }
+
+    /// Returns the copies of `contents` that lack one of its rows, or that
+    /// contain one row fewer times.
+    fn smaller_contents(
+        contents: &HashMap<GlobalId, Collection>,
+    ) -> Vec<HashMap<GlobalId, Collection>> {
+        let mut smaller = Vec::new();
+        for (id, rows) in contents {
+            for (index, (_, diff)) in rows.iter().enumerate() {
+                let mut without = contents.clone();
+                without.get_mut(id).unwrap().remove(index);
+                smaller.push(without);
+                if *diff > 1 {
+                    let mut fewer = contents.clone();
+                    fewer.get_mut(id).unwrap()[index].1 -= 1;
+                    smaller.push(fewer);
+                }
+            }
+        }
+        smaller
+    }
+
+    fn report(failure: &Failure, catalog: &TestCatalog, seed: u64) -> String {
+        let (spec, source_defs) =
+            json_to_spec(&serde_json::to_string(&failure.input).unwrap(), catalog);
+        let mut report = format!(
+            "{} changed the result of the plan\n{}\nwhich is written in the test syntax as\n",
+            failure.transform.debug(),
+            generate_explanation(catalog, &failure.input, None),
+        );
+        for def in source_defs {
+            writeln!(report, "{}", def).unwrap();
+        }
+        writeln!(report, "{}\nover the sources", spec).unwrap();
+        let mut contents = failure.contents.iter().collect::<Vec<_>>();
+        contents.sort_by_key(|(id, _)| **id);
+        for (id, rows) in contents {
+            let name = catalog.get_source_name(id).unwrap();
+            writeln!(report, "{}: {:?}", name, rows).unwrap();
+        }
+        writeln!(report, "expected: {:?}", failure.expected).unwrap();
+        writeln!(report, "got: {:?}", failure.got).unwrap();
+        write!(
+            report,
+            "reproduce with MZ_DIFFERENTIAL_SEED={} MZ_DIFFERENTIAL_PLANS=1",
+            seed
+        )
+        .unwrap();
+        report
+    }
+}
@@ -20,6 +20,7 @@ mod tests {
     use std::fmt::Write;
 
     use anyhow::{anyhow, Error};
+    use mz_dataflow_types::CollectionStatistics;
     use mz_expr::{GlobalId, Id, MirRelationExpr};
     use mz_expr_test_util::{
         build_rel, generate_explanation, json_to_spec, MirRelationExprDeserializeContext,
@@ -34,6 +35,7 @@ mod tests {
     // Global options
     const IN: &str = "in";
     const FORMAT: &str = "format";
+    const STATISTICS: &str = "statistics";
     // Values that can be supplied for global options
     const JSON: &str = "json";
     const TEST: &str = "test";
@@ -99,6 +101,32 @@ mod tests {
         build_rel(s, cat).map_err(|e| anyhow!(e))
     }
 
+    /// Parses size estimates from `args[statistics]`.
+    ///
+    /// Each estimate is specified as `name:records`.
+    fn get_statistics(
+        cat: &TestCatalog,
+        args: &HashMap<String, Vec<String>>,
+    ) -> Result<HashMap<GlobalId, CollectionStatistics>, Error> {
+        let mut statistics = HashMap::new();
+        for spec in args.get(STATISTICS).into_iter().flatten() {
+            let (name, records) = spec
+                .split_once(':')
+                .ok_or_else(|| anyhow!("invalid statistics {}, expected name:records", spec))?;
+            let id = cat
+                .get_source_id(name)
+                .ok_or_else(|| anyhow!("no object named {}", name))?;
+            statistics.insert(
+                id,
+                CollectionStatistics {
+                    records: records.parse()?,
+                    distinct_keys: Vec::new(),
+                },
+            );
+        }
+        Ok(statistics)
+    }
+
     /// Converts MirRelationExpr to `format_type`.
     fn convert_rel_to_string(
         rel: &MirRelationExpr,
@@ -124,12 +152,14 @@ mod tests {
         let mut rel = parse_relation(s, cat, args)?;
         let mut id_gen = Default::default();
         let indexes = HashMap::new();
+        let statistics = get_statistics(cat, args)?;
         for t in args.get("apply").cloned().unwrap_or_else(Vec::new).iter() {
             get_transform(t)?.transform(
                 &mut rel,
                 TransformArgs {
                     id_gen: &mut id_gen,
                     indexes: &indexes,
+                    statistics: &statistics,
                 },
             )?;
         }
@@ -144,6 +174,7 @@ mod tests {
                         TransformArgs {
                             id_gen: &mut id_gen,
                             indexes: &indexes,
+                            statistics: &statistics,
                         },
                     )?;
                 }
@@ -168,6 +199,7 @@ mod tests {
                             TransformArgs {
                                 id_gen: &mut id_gen,
                                 indexes: &indexes,
+                                statistics: &statistics,
                             },
                         )?;
 
@@ -237,6 +269,9 @@ mod tests {
                 limit: None,
             })),
             "JoinFusion" => Ok(Box::new(mz_transform::fusion::join::Join)),
+            "JoinImplementation" => Ok(Box::new(
+                mz_transform::join_implementation::JoinImplementation::default(),
+            )),
             "LiteralLifting" => Ok(Box::new(
                 mz_transform::map_lifting::LiteralLifting::default(),
             )),
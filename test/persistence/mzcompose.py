@@ -21,10 +21,14 @@ from materialize.mzcompose.services import (
 
 mz_options = "--persistent-user-tables --persistent-kafka-sources --disable-persistent-system-tables-test"
 
-mz_default = Materialized(options=mz_options)
+# Used by the webhook tests to validate signed requests.
+mz_environment = ["MZ_WEBHOOK_TESTDRIVE_KEY=testdrive-webhook-key"]
+
+mz_default = Materialized(options=mz_options, environment_extra=mz_environment)
 
 mz_logical_compaction_window_off = Materialized(
-    options=f"{mz_options} --logical-compaction-window=off"
+    options=f"{mz_options} --logical-compaction-window=off",
+    environment_extra=mz_environment,
 )
 
 mz_disable_user_indexes = Materialized(
@@ -93,6 +97,8 @@ def workflow_kafka_sources(c: Composition) -> None:
 def workflow_user_tables(c: Composition) -> None:
     seed = round(time.time())
 
+    c.start_and_wait_for_tcp(services=prerequisites)
+
     c.up("materialized")
     c.wait_for_materialized()
 
@@ -63,6 +63,36 @@ def workflow_github_8021(c: Composition) -> None:
     c.kill("materialized")
 
 
+def workflow_refresh_schema(c: Composition) -> None:
+    c.start_and_wait_for_tcp(services=["zookeeper", "kafka", "schema-registry"])
+
+    c.up("materialized")
+    c.wait_for_materialized("materialized")
+    c.run("testdrive-svc", "refresh-schema-before.td")
+
+    # Views are replanned from their definitions on restart.
+    c.kill("materialized")
+    c.up("materialized")
+    c.wait_for_materialized("materialized")
+    c.run("testdrive_no_reset", "refresh-schema-after.td")
+    c.kill("materialized")
+
+
+def workflow_tail_subscription(c: Composition) -> None:
+    c.up("materialized")
+    c.wait_for_materialized("materialized")
+    c.run("testdrive-svc", "tail-subscription-before.td")
+
+    # Tables are not persisted, so their history is lost in the restart.
+    c.kill("materialized")
+    c.up("materialized")
+    c.wait_for_materialized("materialized")
+    c.run("testdrive_no_reset", "tail-subscription-after.td")
+    c.kill("materialized")
+
+
 def workflow_default(c: Composition) -> None:
     workflow_disable_user_indexes(c)
     workflow_github_8021(c)
+    workflow_refresh_schema(c)
+    workflow_tail_subscription(c)